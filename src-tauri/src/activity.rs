@@ -0,0 +1,204 @@
+// Opt-in activity log: periodically sample the foreground application and
+// window title into `activity_samples` to help reconstruct untracked time.
+//
+// Only X11 on Linux is supported for now; other platforms record nothing.
+use std::time::{Duration, Instant};
+
+use crate::db;
+use crate::settings::Settings;
+
+// Away from the computer: stop sampling
+const IDLE_CUTOFF_SECS: u64 = 120;
+const RETENTION_CHECK_INTERVAL: Duration = Duration::from_secs(3600);
+
+#[derive(Clone, PartialEq)]
+pub struct ActiveWindow {
+    pub app: String,
+    pub title: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct ActivitySegment {
+    id: i64,
+    start_time: String,
+    end_time: String,
+    duration: i64,
+    app: String,
+    title: String,
+}
+
+#[cfg(target_os = "linux")]
+pub fn active_window() -> Option<ActiveWindow> {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{AtomEnum, ConnectionExt};
+
+    let (conn, screen_num) = x11rb::connect(None).ok()?;
+    let root = conn.setup().roots.get(screen_num)?.root;
+    let intern = |name: &[u8]| -> Option<u32> { Some(conn.intern_atom(false, name).ok()?.reply().ok()?.atom) };
+    let net_active_window = intern(b"_NET_ACTIVE_WINDOW")?;
+    let net_wm_name = intern(b"_NET_WM_NAME")?;
+    let utf8_string = intern(b"UTF8_STRING")?;
+
+    let active = conn
+        .get_property(false, root, net_active_window, AtomEnum::WINDOW, 0, 1)
+        .ok()?
+        .reply()
+        .ok()?;
+    let window = active.value32()?.next()?;
+    if window == 0 {
+        return None;
+    }
+
+    let read_string = |property: u32, type_: u32| -> Option<String> {
+        let reply = conn.get_property(false, window, property, type_, 0, 1024).ok()?.reply().ok()?;
+        Some(String::from_utf8_lossy(&reply.value).into_owned())
+    };
+    let title = read_string(net_wm_name, utf8_string)
+        .filter(|t| !t.is_empty())
+        .or_else(|| read_string(AtomEnum::WM_NAME.into(), AtomEnum::STRING.into()))
+        .unwrap_or_default();
+    // WM_CLASS holds "instance\0class\0"; the class is the friendlier name
+    let app = read_string(AtomEnum::WM_CLASS.into(), AtomEnum::STRING.into())
+        .and_then(|class| class.split('\0').rfind(|s| !s.is_empty()).map(str::to_string))
+        .unwrap_or_default();
+
+    Some(ActiveWindow { app, title })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn active_window() -> Option<ActiveWindow> {
+    None
+}
+
+/// Whether a window matches any exclusion pattern (case-insensitive substring
+/// of the application or title)
+pub fn is_excluded(window: &ActiveWindow, exclusions: &[String]) -> bool {
+    let app = window.app.to_lowercase();
+    let title = window.title.to_lowercase();
+    exclusions.iter().any(|pattern| {
+        let pattern = pattern.to_lowercase();
+        app.contains(&pattern) || title.contains(&pattern)
+    })
+}
+
+/// Exclusion patterns are stored one per line
+fn read_exclusions(settings: &Settings) -> Vec<String> {
+    settings
+        .activity_exclusions
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+/// Spawn the background sampler. It reads its settings from the database on
+/// every tick, so toggling it in the UI needs no restart.
+pub fn start_sampler(app: tauri::AppHandle) {
+    std::thread::spawn(move || {
+        // Last recorded window and its row id
+        let mut last: Option<(ActiveWindow, i64)> = None;
+        // What the activity rules saw in earlier samples
+        let mut rule_state = crate::rules::RuleState::default();
+        let mut last_retention_check: Option<Instant> = None;
+
+        loop {
+            let Some(conn) = db::open() else {
+                std::thread::sleep(Duration::from_secs(60));
+                continue;
+            };
+            let settings = Settings::load(&conn);
+            let interval_secs = settings.activity_sample_seconds.max(5);
+
+            if !settings.activity_tracking_enabled {
+                last = None;
+                drop(conn);
+                std::thread::sleep(Duration::from_secs(60));
+                continue;
+            }
+
+            if last_retention_check.is_none_or(|t| t.elapsed() >= RETENTION_CHECK_INTERVAL) {
+                let days = settings.activity_retention_days;
+                if let Err(e) = conn.execute(
+                    "DELETE FROM activity_samples WHERE sampled_at < datetime('now', ?1)",
+                    [format!("-{} days", days)],
+                ) {
+                    eprintln!("Failed to prune activity samples: {}", e);
+                }
+                last_retention_check = Some(Instant::now());
+            }
+
+            let away = crate::get_idle_time_seconds().is_some_and(|idle| idle >= IDLE_CUTOFF_SECS);
+            let window = if away { None } else { active_window() };
+            let window = window.filter(|w| !is_excluded(w, &read_exclusions(&settings)));
+
+            // Each sample stands for one interval; consecutive samples of the
+            // same window extend a single row
+            last = match (window, last.take()) {
+                (Some(window), Some((prev, row_id))) if prev == window => {
+                    if let Err(e) = conn.execute(
+                        "UPDATE activity_samples SET duration = duration + ?1 WHERE id = ?2",
+                        rusqlite::params![interval_secs as i64, row_id],
+                    ) {
+                        eprintln!("Failed to record activity sample: {}", e);
+                    }
+                    Some((window, row_id))
+                }
+                (Some(window), _) => match conn.execute(
+                    "INSERT INTO activity_samples (sampled_at, app, title, duration) VALUES (datetime('now'), ?1, ?2, ?3)",
+                    rusqlite::params![window.app, window.title, interval_secs as i64],
+                ) {
+                    Ok(_) => Some((window, conn.last_insert_rowid())),
+                    Err(e) => {
+                        eprintln!("Failed to record activity sample: {}", e);
+                        None
+                    }
+                },
+                (None, _) => None,
+            };
+
+            match &last {
+                Some((window, _)) => crate::rules::apply(&app, &conn, window, &mut rule_state),
+                None => rule_state.interrupt(),
+            }
+
+            drop(conn);
+            std::thread::sleep(Duration::from_secs(interval_secs));
+        }
+    });
+}
+
+/// Activity segments for a local date ("YYYY-MM-DD"), in chronological order
+#[tauri::command]
+pub fn get_activity_timeline(date: String) -> Result<Vec<ActivitySegment>, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, sampled_at, datetime(sampled_at, '+' || duration || ' seconds'), duration, app, title
+             FROM activity_samples
+             WHERE date(sampled_at, 'localtime') = ?1
+             ORDER BY sampled_at",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([date], |row| {
+            Ok(ActivitySegment {
+                id: row.get(0)?,
+                start_time: row.get(1)?,
+                end_time: row.get(2)?,
+                duration: row.get(3)?,
+                app: row.get(4)?,
+                title: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Delete the whole activity log
+#[tauri::command]
+pub fn clear_activity_samples() -> Result<(), String> {
+    let conn = db::open().ok_or("Database not found")?;
+    conn.execute("DELETE FROM activity_samples", [])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
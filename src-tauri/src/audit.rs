@@ -0,0 +1,322 @@
+// Audit trail for time entries. Every journaled change to `time_entries`
+// (including undo and redo) leaves a row in `entry_history` with the old and
+// new values and where the change came from, so reports can show what was
+// originally tracked next to what it was edited to.
+use chrono::NaiveDate;
+use rusqlite::Connection;
+use serde_json::{Map, Value as Json};
+
+use crate::db;
+use crate::zones;
+
+/// Where a change was made
+#[derive(Clone, Copy, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeSource {
+    #[default]
+    Ui,
+    Tray,
+    Cli,
+    Import,
+    /// Background tasks: activity rules, the git watcher, quitting the app
+    Automatic,
+}
+
+impl ChangeSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChangeSource::Ui => "ui",
+            ChangeSource::Tray => "tray",
+            ChangeSource::Cli => "cli",
+            ChangeSource::Import => "import",
+            ChangeSource::Automatic => "automatic",
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct EntryVersion {
+    project_id: Option<i64>,
+    start_time: Option<String>,
+    end_time: Option<String>,
+    duration: Option<i64>,
+}
+
+#[derive(serde::Serialize)]
+pub struct HistoryRecord {
+    id: i64,
+    entry_id: i64,
+    changed_at: String,
+    source: String,
+    action: String, // "insert", "update" or "delete"
+    label: String,
+    old: Option<EntryVersion>,
+    new: Option<EntryVersion>,
+}
+
+#[derive(serde::Serialize)]
+pub struct EditedEntry {
+    entry_id: i64,
+    project_name: String,
+    project_color: String,
+    start_time: String,
+    original_duration: i64,
+    current_duration: i64,
+    edits: i64,
+    last_edited_at: String,
+}
+
+fn version(row: &Map<String, Json>) -> EntryVersion {
+    EntryVersion {
+        project_id: row.get("project_id").and_then(Json::as_i64),
+        start_time: row.get("start_time").and_then(Json::as_str).map(str::to_string),
+        end_time: row.get("end_time").and_then(Json::as_str).map(str::to_string),
+        duration: row.get("duration").and_then(Json::as_i64),
+    }
+}
+
+/// Record one change to a time entry; `before`/`after` are full rows as
+/// snapshotted by the journal, None when the entry did not exist
+pub fn record(
+    conn: &Connection,
+    entry_id: i64,
+    before: Option<&Map<String, Json>>,
+    after: Option<&Map<String, Json>>,
+    source: ChangeSource,
+    label: &str,
+) -> rusqlite::Result<()> {
+    let action = match (before, after) {
+        (None, Some(_)) => "insert",
+        (Some(_), None) => "delete",
+        _ => "update",
+    };
+    let old = before.map(version);
+    let new = after.map(version);
+    conn.execute(
+        "INSERT INTO entry_history
+            (entry_id, changed_at, source, action, label,
+             old_project_id, old_start_time, old_end_time, old_duration,
+             new_project_id, new_start_time, new_end_time, new_duration)
+         VALUES (?1, datetime('now'), ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        rusqlite::params![
+            entry_id,
+            source.as_str(),
+            action,
+            label,
+            old.as_ref().and_then(|v| v.project_id),
+            old.as_ref().and_then(|v| v.start_time.clone()),
+            old.as_ref().and_then(|v| v.end_time.clone()),
+            old.as_ref().and_then(|v| v.duration),
+            new.as_ref().and_then(|v| v.project_id),
+            new.as_ref().and_then(|v| v.start_time.clone()),
+            new.as_ref().and_then(|v| v.end_time.clone()),
+            new.as_ref().and_then(|v| v.duration),
+        ],
+    )?;
+    Ok(())
+}
+
+fn read_version(row: &rusqlite::Row, offset: usize) -> rusqlite::Result<EntryVersion> {
+    Ok(EntryVersion {
+        project_id: row.get(offset)?,
+        start_time: row.get(offset + 1)?,
+        end_time: row.get(offset + 2)?,
+        duration: row.get(offset + 3)?,
+    })
+}
+
+/// Every recorded change to one entry, oldest first
+#[tauri::command]
+pub fn get_entry_history(entry_id: i64) -> Result<Vec<HistoryRecord>, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, entry_id, changed_at, source, action, label,
+                    old_project_id, old_start_time, old_end_time, old_duration,
+                    new_project_id, new_start_time, new_end_time, new_duration
+             FROM entry_history WHERE entry_id = ?1 ORDER BY id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([entry_id], |row| {
+            let action: String = row.get(4)?;
+            Ok(HistoryRecord {
+                id: row.get(0)?,
+                entry_id: row.get(1)?,
+                changed_at: row.get(2)?,
+                source: row.get(3)?,
+                label: row.get(5)?,
+                old: if action == "insert" { None } else { Some(read_version(row, 6)?) },
+                new: if action == "delete" { None } else { Some(read_version(row, 10)?) },
+                action,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Completed entries starting between two dates ("YYYY-MM-DD", inclusive, in
+/// the report zone) that were changed after they were first recorded, with the
+/// duration as originally tracked next to the current one
+#[tauri::command]
+pub fn get_edited_entries(start_date: String, end_date: String) -> Result<Vec<EditedEntry>, String> {
+    let (first_day, last_day) = (zones::parse_date(&start_date)?, zones::parse_date(&end_date)?);
+    let conn = db::open().ok_or("Database not found")?;
+    edited_entries(&conn, first_day, last_day)
+}
+
+fn edited_entries(conn: &Connection, first_day: NaiveDate, last_day: NaiveDate) -> Result<Vec<EditedEntry>, String> {
+    let (from, to) = zones::query_bounds(first_day, last_day);
+    let zone = zones::report_zone(conn);
+    let mut stmt = conn
+        .prepare(
+            "SELECT te.id, p.name, p.color, te.start_time, te.duration,
+                    h.changed_at, h.old_duration, h.new_duration,
+                    CAST(strftime('%s', te.start_time) AS INTEGER), te.timezone
+             FROM time_entries te
+             JOIN projects p ON p.id = te.project_id
+             JOIN entry_history h ON h.entry_id = te.id
+             WHERE te.end_time IS NOT NULL
+               AND te.start_time >= datetime(?1, 'unixepoch') AND te.start_time < datetime(?2, 'unixepoch')
+             ORDER BY te.start_time, te.id, h.id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([from, to], |row| {
+            let day = zone.date_of(row.get(8)?, row.get::<_, Option<String>>(9)?.as_deref());
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, i64>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, Option<i64>>(6)?,
+                row.get::<_, Option<i64>>(7)?,
+                day,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut edited: Vec<EditedEntry> = Vec::new();
+    for (entry_id, project_name, project_color, start_time, duration, changed_at, old_duration, new_duration, day) in rows {
+        if day < first_day || day > last_day {
+            continue;
+        }
+        if edited.last().is_none_or(|e| e.entry_id != entry_id) {
+            edited.push(EditedEntry {
+                entry_id,
+                project_name,
+                project_color,
+                start_time,
+                original_duration: -1,
+                current_duration: duration,
+                edits: 0,
+                last_edited_at: String::new(),
+            });
+        }
+        let entry = edited.last_mut().unwrap();
+        // The first duration an entry had is what was tracked; stopping a running
+        // entry sets it and is not an edit, anything after that is
+        if entry.original_duration < 0 {
+            match (old_duration, new_duration) {
+                (Some(old), _) => entry.original_duration = old,
+                (None, Some(new)) => {
+                    entry.original_duration = new;
+                    continue;
+                }
+                (None, None) => continue,
+            }
+        }
+        entry.edits += 1;
+        entry.last_edited_at = changed_at;
+    }
+    edited.retain(|e| e.edits > 0);
+    Ok(edited)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::Recorder;
+
+    fn setup() -> Connection {
+        let conn = db::testing::memory();
+        conn.execute_batch(
+            "INSERT INTO settings (key, value) VALUES ('report_timezone', 'UTC');
+             INSERT INTO projects (id, name, color) VALUES (1, 'Horalis', '#111');",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn insert(conn: &Connection, start: &str, end: Option<&str>) -> i64 {
+        let mut journal = Recorder::new("Add entry");
+        conn.execute(
+            "INSERT INTO time_entries (project_id, start_time, end_time, duration)
+             VALUES (1, ?1, ?2, CAST(strftime('%s', ?2) AS INTEGER) - CAST(strftime('%s', ?1) AS INTEGER))",
+            rusqlite::params![start, end],
+        )
+        .unwrap();
+        let id = conn.last_insert_rowid();
+        journal.track_new("time_entries", id);
+        journal.finish(conn).unwrap();
+        id
+    }
+
+    fn set_end(conn: &Connection, id: i64, end: &str) {
+        let mut journal = Recorder::new("Edit entry");
+        journal.track(conn, "time_entries", id).unwrap();
+        conn.execute(
+            "UPDATE time_entries SET end_time = ?2,
+                 duration = CAST(strftime('%s', ?2) AS INTEGER) - CAST(strftime('%s', start_time) AS INTEGER)
+             WHERE id = ?1",
+            rusqlite::params![id, end],
+        )
+        .unwrap();
+        journal.finish(conn).unwrap();
+    }
+
+    fn day(text: &str) -> NaiveDate {
+        zones::parse_date(text).unwrap()
+    }
+
+    #[test]
+    fn edited_entries_compare_the_tracked_duration() {
+        let conn = setup();
+        // Stopped at 10:00, then edited twice
+        let stopped = insert(&conn, "2026-10-12 09:00:00", None);
+        set_end(&conn, stopped, "2026-10-12 10:00:00");
+        set_end(&conn, stopped, "2026-10-12 10:30:00");
+        // Same start, with its edit recorded between the other's
+        let added = insert(&conn, "2026-10-12 09:00:00", Some("2026-10-12 09:20:00"));
+        set_end(&conn, added, "2026-10-12 09:10:00");
+        set_end(&conn, stopped, "2026-10-12 10:20:00");
+        // Never edited, and edited but outside the range
+        insert(&conn, "2026-10-13 09:00:00", Some("2026-10-13 09:30:00"));
+        let outside = insert(&conn, "2026-10-20 09:00:00", Some("2026-10-20 09:30:00"));
+        set_end(&conn, outside, "2026-10-20 10:00:00");
+
+        let edited = edited_entries(&conn, day("2026-10-12"), day("2026-10-18")).unwrap();
+        let summary: Vec<(i64, i64, i64, i64)> =
+            edited.iter().map(|e| (e.entry_id, e.original_duration, e.current_duration, e.edits)).collect();
+        assert_eq!(summary, vec![(stopped, 3600, 4800, 2), (added, 1200, 600, 1)]);
+        assert!(edited.iter().all(|e| !e.last_edited_at.is_empty()));
+    }
+
+    #[test]
+    fn running_entries_are_not_edited_entries() {
+        let conn = setup();
+        let running = insert(&conn, "2026-10-12 09:00:00", None);
+        let mut journal = Recorder::new("Edit entry");
+        journal.track(&conn, "time_entries", running).unwrap();
+        conn.execute("UPDATE time_entries SET start_time = '2026-10-12 08:30:00' WHERE id = ?1", [running]).unwrap();
+        journal.finish(&conn).unwrap();
+        assert!(edited_entries(&conn, day("2026-10-12"), day("2026-10-12")).unwrap().is_empty());
+        // Stopping it records the tracked duration, which is not an edit
+        set_end(&conn, running, "2026-10-12 09:30:00");
+        assert!(edited_entries(&conn, day("2026-10-12"), day("2026-10-12")).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,489 @@
+// Automatic backups. Once a day the database is copied with SQLite's online
+// backup API, so the webview can keep writing meanwhile, into the folder
+// chosen under `backup_folder` as horalis-YYYYMMDD-HHMMSS.db (local time).
+// With `backup_passphrase` set the copy is encrypted (see crypto.rs) and gets
+// a .db.enc suffix; the unencrypted copy never touches the backup folder.
+//
+// Retention keeps the newest backup of each of the last `backup_keep_daily`
+// days, `backup_keep_weekly` ISO weeks and `backup_keep_monthly` months, and
+// deletes the rest; a count of 0 keeps none of that kind, but the newest backup
+// always stays. Only files matching the name pattern are ever deleted.
+use chrono::{Datelike, NaiveDateTime, Utc};
+use rusqlite::backup::{Backup, StepResult};
+use rusqlite::{Connection, DatabaseName};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::Emitter;
+
+use crate::crypto;
+use crate::db;
+use crate::encryption;
+use crate::settings::Settings;
+use crate::zones;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+const PREFIX: &str = "horalis-";
+const NAME_FORMAT: &str = "%Y%m%d-%H%M%S";
+// Tables any usable backup has
+const REQUIRED_TABLES: &[&str] = &["projects", "time_entries", "settings"];
+const SWAP_ATTEMPTS: usize = 25;
+
+#[derive(Clone, serde::Serialize)]
+pub struct BackupFile {
+    path: String,
+    file_name: String,
+    created_at: String, // "YYYY-MM-DD HH:MM:SS", local
+    size: u64,
+    encrypted: bool,
+    #[serde(skip)]
+    time: NaiveDateTime,
+}
+
+/// Key of the day, week or month a backup falls in
+type Bucket = fn(&NaiveDateTime) -> (i32, u32);
+
+struct Retention {
+    daily: usize,
+    weekly: usize,
+    monthly: usize,
+}
+
+impl Retention {
+    fn from_settings(settings: &Settings) -> Self {
+        Retention {
+            daily: settings.backup_keep_daily as usize,
+            weekly: settings.backup_keep_weekly as usize,
+            monthly: settings.backup_keep_monthly as usize,
+        }
+    }
+}
+
+fn folder(conn: &Connection) -> Result<PathBuf, String> {
+    let folder = Settings::load(conn).backup_folder;
+    if !Path::new(&folder).is_absolute() {
+        return Err("Choose a full backup folder path".into());
+    }
+    Ok(PathBuf::from(folder))
+}
+
+fn passphrase(conn: &Connection) -> Option<String> {
+    Some(Settings::load(conn).backup_passphrase).filter(|p| !p.is_empty())
+}
+
+fn local_now() -> NaiveDateTime {
+    Utc::now().with_timezone(&zones::system_zone()).naive_local()
+}
+
+/// Backup time from a file name, or None for files that aren't backups
+fn parse_name(name: &str) -> Option<(NaiveDateTime, bool)> {
+    let stem = name.strip_prefix(PREFIX)?;
+    let (stem, encrypted) = match stem.strip_suffix(".db.enc") {
+        Some(stem) => (stem, true),
+        None => (stem.strip_suffix(".db")?, false),
+    };
+    let time = NaiveDateTime::parse_from_str(stem, NAME_FORMAT).ok()?;
+    Some((time, encrypted))
+}
+
+/// Backups in `folder`, newest first
+fn scan(folder: &Path) -> Result<Vec<BackupFile>, String> {
+    let dir = match std::fs::read_dir(folder) {
+        Ok(dir) => dir,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {}", folder.display(), e)),
+    };
+    let mut backups: Vec<BackupFile> = dir
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let file_name = entry.file_name().into_string().ok()?;
+            let (time, encrypted) = parse_name(&file_name)?;
+            Some(BackupFile {
+                path: entry.path().display().to_string(),
+                created_at: time.format("%Y-%m-%d %H:%M:%S").to_string(),
+                size: entry.metadata().ok()?.len(),
+                file_name,
+                encrypted,
+                time,
+            })
+        })
+        .collect();
+    backups.sort_by_key(|b| std::cmp::Reverse(b.time));
+    Ok(backups)
+}
+
+/// Backups the retention policy no longer keeps. `backups` is newest first;
+/// the newest backup is always kept.
+fn expired<'a>(backups: &'a [BackupFile], retention: &Retention) -> Vec<&'a BackupFile> {
+    let mut keep: HashSet<usize> = HashSet::from([0]);
+    let buckets: [(usize, Bucket); 3] = [
+        (retention.daily, |t| (t.year(), t.ordinal())),
+        (retention.weekly, |t| (t.iso_week().year(), t.iso_week().week())),
+        (retention.monthly, |t| (t.year(), t.month())),
+    ];
+    for (count, bucket) in buckets {
+        let mut seen = HashSet::new();
+        for (i, backup) in backups.iter().enumerate() {
+            if seen.len() == count {
+                break;
+            }
+            // The first backup seen in a bucket is its newest
+            if seen.insert(bucket(&backup.time)) {
+                keep.insert(i);
+            }
+        }
+    }
+    backups.iter().enumerate().filter(|(i, _)| !keep.contains(i)).map(|(_, b)| b).collect()
+}
+
+fn prune(folder: &Path, retention: &Retention) -> Result<(), String> {
+    let backups = scan(folder)?;
+    for backup in expired(&backups, retention) {
+        if let Err(e) = std::fs::remove_file(&backup.path) {
+            eprintln!("Failed to delete old backup {}: {}", backup.path, e);
+        }
+    }
+    Ok(())
+}
+
+/// Scratch file next to the live database, outside the backup folder
+fn scratch_path(name: &str) -> Result<PathBuf, String> {
+    let path = db::database_path().ok_or("Database not found")?;
+    Ok(path.with_file_name(name))
+}
+
+/// Copy the database into the backup folder, encrypted when a passphrase is
+/// set, then apply the retention policy
+pub fn create_backup(conn: &Connection) -> Result<PathBuf, String> {
+    let folder = folder(conn)?;
+    std::fs::create_dir_all(&folder).map_err(|e| format!("Failed to create {}: {}", folder.display(), e))?;
+
+    let copy = scratch_path("horalis-backup.tmp")?;
+    let _ = std::fs::remove_file(&copy);
+    conn.backup(DatabaseName::Main, &copy, None).map_err(|e| format!("Backup failed: {}", e))?;
+    let contents = std::fs::read(&copy).map_err(|e| e.to_string());
+    let _ = std::fs::remove_file(&copy);
+    let contents = contents?;
+
+    let (extension, contents) = match passphrase(conn) {
+        Some(passphrase) => ("db.enc", crypto::encrypt(&passphrase, &contents)?),
+        None => ("db", contents),
+    };
+    // Never replace a backup, e.g. one being restored from within the same second
+    let mut time = local_now();
+    let path = loop {
+        let path = folder.join(format!("{}{}.{}", PREFIX, time.format(NAME_FORMAT), extension));
+        if !path.exists() {
+            break path;
+        }
+        time += chrono::Duration::seconds(1);
+    };
+    // Written under another name first, so a half-written file never looks like a backup
+    let partial = path.with_extension("partial");
+    std::fs::write(&partial, contents).map_err(|e| format!("Failed to write {}: {}", partial.display(), e))?;
+    std::fs::rename(&partial, &path).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    prune(&folder, &Retention::from_settings(&Settings::load(conn)))?;
+    Ok(path)
+}
+
+/// Whether today's backup is still to be made
+fn backup_due(conn: &Connection) -> Result<bool, String> {
+    let newest = scan(&folder(conn)?)?.into_iter().next();
+    Ok(newest.is_none_or(|b| b.time.date() < local_now().date()))
+}
+
+/// Spawn the scheduler. Settings are read on every tick, so changes apply
+/// without a restart; after the app was closed a backup is made on the next tick.
+pub fn start_scheduler(app: tauri::AppHandle) {
+    std::thread::spawn(move || {
+        // Failures are retried every tick but only shown once a day
+        let mut notified_on = None;
+        loop {
+            std::thread::sleep(CHECK_INTERVAL);
+            let Some(conn) = db::open() else {
+                continue;
+            };
+            if !Settings::load(&conn).backup_enabled {
+                continue;
+            }
+            let result = backup_due(&conn).and_then(|due| if due { create_backup(&conn).map(|_| ()) } else { Ok(()) });
+            if let Err(e) = result {
+                eprintln!("Failed to back up: {}", e);
+                let today = local_now().date();
+                if notified_on != Some(today) {
+                    notified_on = Some(today);
+                    crate::notify::show_plain(&app, "Backup failed", &e);
+                }
+            }
+        }
+    });
+}
+
+/// Open a restored copy and check it is an intact Horalis database
+fn validate(conn: &Connection) -> Result<(), String> {
+    let integrity: String = conn
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .map_err(|_| "Not a Horalis backup")?;
+    if integrity != "ok" {
+        return Err(format!("The backup is damaged: {}", integrity));
+    }
+    for table in REQUIRED_TABLES {
+        let exists: bool = conn
+            .query_row("SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)", [table], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        if !exists {
+            return Err("Not a Horalis backup".into());
+        }
+    }
+    Ok(())
+}
+
+/// Decrypt a backup's `contents` if needed, write them to `scratch` and open
+/// the copy once it validates. `keyed` says whether the scratch copy gets the
+/// database key (see encryption.rs).
+fn open_backup(contents: Vec<u8>, passphrase: Option<String>, scratch: &Path, keyed: bool) -> Result<Connection, String> {
+    let contents = if crypto::is_encrypted(&contents) {
+        let passphrase = passphrase.ok_or("This backup is encrypted; enter its passphrase")?;
+        crypto::decrypt(&passphrase, &contents)?
+    } else {
+        contents
+    };
+    if keyed && encryption::is_plain_database(&contents) {
+        return export_plain(&contents, scratch);
+    }
+    std::fs::write(scratch, contents).map_err(|e| e.to_string())?;
+    let restored = Connection::open(scratch).map_err(|e| e.to_string())?;
+    validate(&restored)?;
+    Ok(restored)
+}
+
+/// Copy a plain SQLite backup, made before the database was encrypted, into
+/// the keyed `scratch` copy. It is opened outside the database folder, where
+/// no key is applied.
+fn export_plain(contents: &[u8], scratch: &Path) -> Result<Connection, String> {
+    let plain_path = std::env::temp_dir().join(format!("horalis-restore-{}.db", std::process::id()));
+    std::fs::write(&plain_path, contents).map_err(|e| e.to_string())?;
+    let result = (|| {
+        validate(&Connection::open(&plain_path).map_err(|e| e.to_string())?)?;
+        let _ = std::fs::remove_file(scratch);
+        let restored = Connection::open(scratch).map_err(|e| e.to_string())?;
+        restored
+            .execute("ATTACH DATABASE ?1 AS plain KEY ''", [plain_path.to_string_lossy()])
+            .map_err(|e| e.to_string())?;
+        restored
+            .query_row("SELECT sqlcipher_export('main', 'plain')", [], |_| Ok(()))
+            .map_err(|e| format!("Restore failed: {}", e))?;
+        let version: i64 = restored
+            .pragma_query_value(Some(DatabaseName::Attached("plain")), "user_version", |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        restored.pragma_update(None, "user_version", version).map_err(|e| e.to_string())?;
+        restored.execute_batch("DETACH DATABASE plain").map_err(|e| e.to_string())?;
+        validate(&restored)?;
+        Ok(restored)
+    })();
+    let _ = std::fs::remove_file(&plain_path);
+    result
+}
+
+/// Replace the live database with `source`, in one step so other connections
+/// see either the old or the new contents
+fn swap_in(source: &Connection) -> Result<(), String> {
+    let mut live = db::open().ok_or("Database not found")?;
+    let backup = Backup::new(source, &mut live).map_err(|e| e.to_string())?;
+    for _ in 0..SWAP_ATTEMPTS {
+        match backup.step(-1).map_err(|e| format!("Restore failed: {}", e))? {
+            StepResult::Done => return Ok(()),
+            // Another connection holds a lock; nothing has been copied yet
+            _ => std::thread::sleep(Duration::from_millis(200)),
+        }
+    }
+    Err("Restore failed: the database is busy".into())
+}
+
+#[tauri::command]
+pub fn list_backups() -> Result<Vec<BackupFile>, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    let Ok(folder) = folder(&conn) else {
+        return Ok(Vec::new());
+    };
+    scan(&folder)
+}
+
+/// Make a backup now. Returns the file path.
+#[tauri::command]
+pub fn backup_now() -> Result<String, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    Ok(create_backup(&conn)?.display().to_string())
+}
+
+/// Restore a backup. Encrypted backups are opened with `passphrase`, or the
+/// configured one. The file is decrypted and validated before anything is
+/// replaced, and the current data is backed up first when a folder is set.
+#[tauri::command]
+pub fn restore_backup(app: tauri::AppHandle, path: String, passphrase: Option<String>) -> Result<(), String> {
+    let conn = db::open().ok_or("Database not found")?;
+    let contents = std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let passphrase = passphrase.filter(|p| !p.is_empty()).or_else(|| self::passphrase(&conn));
+
+    let restored_path = scratch_path("horalis-restore.tmp")?;
+    let keyed = db::database_path().is_some_and(|p| encryption::is_encrypted(&p));
+    let result = open_backup(contents, passphrase, &restored_path, keyed).and_then(|restored| {
+        if folder(&conn).is_ok() {
+            create_backup(&conn).map_err(|e| format!("Could not back up the current data first: {}", e))?;
+        }
+        drop(conn);
+        swap_in(&restored)
+    });
+    let _ = std::fs::remove_file(&restored_path);
+    result?;
+
+    let _ = app.emit("entries-changed", ());
+    let _ = app.emit("timer-changed", ());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backups(times: &[&str]) -> Vec<BackupFile> {
+        let mut backups: Vec<BackupFile> = times
+            .iter()
+            .map(|text| {
+                let time = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap();
+                BackupFile {
+                    path: String::new(),
+                    file_name: format!("{}{}.db", PREFIX, time.format(NAME_FORMAT)),
+                    created_at: time.format("%Y-%m-%d %H:%M:%S").to_string(),
+                    size: 0,
+                    encrypted: false,
+                    time,
+                }
+            })
+            .collect();
+        backups.sort_by_key(|b| std::cmp::Reverse(b.time));
+        backups
+    }
+
+    fn expired_names(times: &[&str], daily: usize, weekly: usize, monthly: usize) -> Vec<String> {
+        let backups = backups(times);
+        let expired = expired(&backups, &Retention { daily, weekly, monthly });
+        expired.into_iter().map(|b| b.created_at[..16].to_string()).collect()
+    }
+
+    #[test]
+    fn daily_keeps_newest_of_each_day() {
+        let times = ["2026-10-16 09:00", "2026-10-17 09:00", "2026-10-17 18:00", "2026-10-18 08:00", "2026-10-18 12:00"];
+        assert_eq!(expired_names(&times, 2, 0, 0), ["2026-10-18 08:00", "2026-10-17 09:00", "2026-10-16 09:00"]);
+    }
+
+    #[test]
+    fn weekly_keeps_newest_of_each_iso_week() {
+        // 2026-10-12 and 2026-10-18 are the Monday and Sunday of ISO week 42
+        let times = ["2026-10-04 10:00", "2026-10-09 10:00", "2026-10-12 10:00", "2026-10-18 10:00"];
+        assert_eq!(expired_names(&times, 0, 2, 0), ["2026-10-12 10:00", "2026-10-04 10:00"]);
+        // Week 1 of 2027 starts on 2027-01-04, so 2027-01-02 falls in 2026's week 53
+        let times = ["2026-12-28 10:00", "2027-01-02 10:00", "2027-01-04 10:00"];
+        assert_eq!(expired_names(&times, 0, 2, 0), ["2026-12-28 10:00"]);
+    }
+
+    #[test]
+    fn monthly_keeps_newest_of_each_month() {
+        let times = ["2026-07-31 10:00", "2026-08-01 10:00", "2026-08-31 10:00", "2026-09-15 10:00", "2026-10-01 10:00"];
+        assert_eq!(expired_names(&times, 0, 0, 3), ["2026-08-01 10:00", "2026-07-31 10:00"]);
+    }
+
+    #[test]
+    fn buckets_keep_the_union() {
+        let times = ["2026-08-20 10:00", "2026-09-30 10:00", "2026-10-10 10:00", "2026-10-17 10:00", "2026-10-18 10:00"];
+        // Days keep 10-18 and 10-17, weeks add 10-10, months add 09-30
+        assert_eq!(expired_names(&times, 2, 3, 2), ["2026-08-20 10:00"]);
+    }
+
+    #[test]
+    fn newest_is_always_kept() {
+        let times = ["2026-10-16 09:00", "2026-10-17 09:00", "2026-10-18 09:00"];
+        assert_eq!(expired_names(&times, 0, 0, 0), ["2026-10-17 09:00", "2026-10-16 09:00"]);
+        assert!(expired_names(&["2026-10-18 09:00"], 0, 0, 0).is_empty());
+        assert!(expired_names(&[], 1, 1, 1).is_empty());
+    }
+
+    /// A small Horalis database's file contents
+    fn database_file(dir: &Path) -> Vec<u8> {
+        let path = dir.join("source.db");
+        let conn = Connection::open(&path).unwrap();
+        db::testing::init(&conn);
+        conn.execute("INSERT INTO projects (name, color) VALUES ('Horalis', '#111')", []).unwrap();
+        drop(conn);
+        std::fs::read(path).unwrap()
+    }
+
+    #[test]
+    fn opens_plain_and_encrypted_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let contents = database_file(dir.path());
+        let scratch = dir.path().join("restore.tmp");
+
+        let restored = open_backup(contents.clone(), None, &scratch, false).unwrap();
+        let name: String = restored.query_row("SELECT name FROM projects", [], |row| row.get(0)).unwrap();
+        assert_eq!(name, "Horalis");
+        drop(restored);
+
+        let encrypted = crypto::encrypt("correct horse", &contents).unwrap();
+        let restored = open_backup(encrypted, Some("correct horse".into()), &scratch, false).unwrap();
+        let count: i64 = restored.query_row("SELECT count(*) FROM projects", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn exports_plain_backups_into_a_keyed_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let contents = database_file(dir.path());
+        let scratch = dir.path().join("restore.tmp");
+        std::fs::write(&scratch, b"left over").unwrap();
+
+        let restored = open_backup(contents, None, &scratch, true).unwrap();
+        let name: String = restored.query_row("SELECT name FROM projects", [], |row| row.get(0)).unwrap();
+        assert_eq!(name, "Horalis");
+        let version: i64 = restored.pragma_query_value(None, "user_version", |row| row.get(0)).unwrap();
+        let source: i64 = Connection::open(dir.path().join("source.db"))
+            .unwrap()
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, source);
+        assert!(open_backup(b"SQLite format 3\0 but cut short".to_vec(), None, &scratch, true).is_err());
+    }
+
+    #[test]
+    fn refuses_wrong_or_missing_passphrase() {
+        let dir = tempfile::tempdir().unwrap();
+        let encrypted = crypto::encrypt("correct horse", &database_file(dir.path())).unwrap();
+        let scratch = dir.path().join("restore.tmp");
+
+        let error = open_backup(encrypted.clone(), Some("battery staple".into()), &scratch, false).err().unwrap();
+        assert_eq!(error, "Wrong passphrase, or the file is damaged");
+        let error = open_backup(encrypted, None, &scratch, false).err().unwrap();
+        assert_eq!(error, "This backup is encrypted; enter its passphrase");
+        assert!(!scratch.exists());
+    }
+
+    #[test]
+    fn refuses_truncated_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let contents = database_file(dir.path());
+        let encrypted = crypto::encrypt("correct horse", &contents).unwrap();
+        let scratch = dir.path().join("restore.tmp");
+
+        // Cut inside the ciphertext: the tag no longer matches
+        let error = open_backup(encrypted[..encrypted.len() / 2].to_vec(), Some("correct horse".into()), &scratch, false).err().unwrap();
+        assert_eq!(error, "Wrong passphrase, or the file is damaged");
+        // Cut inside the header
+        let error = open_backup(encrypted[..20].to_vec(), Some("correct horse".into()), &scratch, false).err().unwrap();
+        assert_eq!(error, "The file is truncated");
+        assert!(!scratch.exists());
+
+        // A plain copy cut short opens, but doesn't validate
+        assert!(open_backup(contents[..contents.len() / 2].to_vec(), None, &scratch, false).is_err());
+    }
+}
@@ -0,0 +1,40 @@
+// Reference sync server, see docs/sync-protocol.md and sync_service.rs.
+//
+//   horalis-sync-server --listen 127.0.0.1:8787 --data sync.jsonl [--token secret]
+//
+// The token can also come from HORALIS_SYNC_TOKEN.
+use horalis_lib::sync_service::{self, Store};
+use std::net::TcpListener;
+use std::path::PathBuf;
+
+fn main() {
+    let mut listen = "127.0.0.1:8787".to_string();
+    let mut data = PathBuf::from("horalis-sync.jsonl");
+    let mut token = std::env::var("HORALIS_SYNC_TOKEN").ok().filter(|t| !t.is_empty());
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--listen", Some(value)) => listen = value,
+            ("--data", Some(value)) => data = PathBuf::from(value),
+            ("--token", Some(value)) => token = Some(value),
+            _ => {
+                eprintln!("Usage: horalis-sync-server [--listen ADDR] [--data FILE] [--token TOKEN]");
+                std::process::exit(2);
+            }
+        }
+    }
+
+    let mut store = Store::open(&data).unwrap_or_else(|e| {
+        eprintln!("Failed to open {}: {}", data.display(), e);
+        std::process::exit(1);
+    });
+    let listener = TcpListener::bind(&listen).unwrap_or_else(|e| {
+        eprintln!("Failed to listen on {}: {}", listen, e);
+        std::process::exit(1);
+    });
+    println!("Serving {} changes on http://{}", store.len(), listen);
+
+    for stream in listener.incoming().flatten() {
+        sync_service::serve(stream, &mut store, token.as_deref());
+    }
+}
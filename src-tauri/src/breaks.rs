@@ -0,0 +1,217 @@
+// Break reminders: nag the user after a long stretch of continuous work
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::Manager;
+use tokio::sync::watch;
+
+use crate::settings::Settings;
+use crate::{get_idle_time_seconds, NativeTimerState};
+
+/// What counts as "working" when measuring a continuous stretch
+#[derive(Clone, Copy, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BreakMode {
+    /// A timer is running (and the user is not idle)
+    #[default]
+    Tracked,
+    /// The user is active at the computer, tracked or not
+    Activity,
+}
+
+// Break reminder state for background task
+pub struct BreakState {
+    pub stop_tx: Mutex<Option<watch::Sender<bool>>>,
+    // Unix timestamps in seconds
+    pub snoozed_until: Mutex<Option<u64>>,
+    pub default_snooze_minutes: Mutex<u32>,
+}
+
+impl BreakState {
+    pub fn new() -> Self {
+        Self {
+            stop_tx: Mutex::new(None),
+            snoozed_until: Mutex::new(None),
+            default_snooze_minutes: Mutex::new(10),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct BreakStats {
+    breaks_taken: i64,
+    total_break_seconds: i64,
+    longest_break_seconds: i64,
+    reminders_sent: i64,
+    reminders_snoozed: i64,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Append an entry to the break log (kind is "break", "reminder" or "snooze")
+fn log_break_event(kind: &str, start_secs: u64, end_secs: Option<u64>) {
+    let Some(conn) = crate::db::open() else {
+        return;
+    };
+    let result = conn.execute(
+        "INSERT INTO break_log (kind, start_time, end_time, duration)
+         VALUES (?1, datetime(?2, 'unixepoch'), datetime(?3, 'unixepoch'), ?4)",
+        rusqlite::params![
+            kind,
+            start_secs as i64,
+            end_secs.map(|s| s as i64),
+            end_secs.map(|s| s.saturating_sub(start_secs) as i64),
+        ],
+    );
+    if let Err(e) = result {
+        eprintln!("Failed to log break event: {}", e);
+    }
+}
+
+/// Stop the break reminder task if one is running
+pub fn stop(app: &tauri::AppHandle) {
+    let break_state = app.state::<BreakState>();
+    let mut stop_tx_guard = break_state.stop_tx.lock().unwrap();
+    if let Some(tx) = stop_tx_guard.take() {
+        let _ = tx.send(true);
+    }
+}
+
+/// (Re)start the break reminder task with the given settings
+pub fn start(app: &tauri::AppHandle, settings: &Settings) {
+    stop(app);
+    if !settings.reminder_break_enabled {
+        return;
+    }
+
+    let break_state = app.state::<BreakState>();
+    *break_state.default_snooze_minutes.lock().unwrap() = settings.reminder_break_snooze_minutes;
+
+    let (stop_tx, mut stop_rx) = watch::channel(false);
+    *break_state.stop_tx.lock().unwrap() = Some(stop_tx);
+
+    let app_handle = app.clone();
+    let mode = settings.reminder_break_mode;
+    let work_secs = (settings.reminder_break_after_minutes as u64) * 60;
+    let min_break_secs = (settings.reminder_break_min_minutes as u64) * 60;
+    let renotify_secs = (settings.reminder_break_snooze_minutes as u64) * 60;
+
+    tauri::async_runtime::spawn(async move {
+        // Start of the current working stretch, if the user is working
+        let mut active_since: Option<u64> = None;
+        // Start of the current pause, if the user is away
+        let mut inactive_since: Option<u64> = None;
+        let mut last_notified: Option<u64> = None;
+
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(std::time::Duration::from_secs(60)) => {}
+                _ = stop_rx.changed() => {
+                    if *stop_rx.borrow() {
+                        return;
+                    }
+                }
+            }
+
+            if *stop_rx.borrow() {
+                return;
+            }
+
+            let now = now_secs();
+            let idle_secs = get_idle_time_seconds().unwrap_or(0);
+            let is_idle = idle_secs >= min_break_secs;
+            let active = match mode {
+                BreakMode::Tracked => {
+                    let timer_state = app_handle.state::<NativeTimerState>();
+                    let running = timer_state.start_time_ms.lock().unwrap().is_some();
+                    running && !is_idle
+                }
+                BreakMode::Activity => !is_idle,
+            };
+
+            if !active {
+                if inactive_since.is_none() {
+                    // Idle time tells us when the user actually walked away
+                    inactive_since = Some(if is_idle { now.saturating_sub(idle_secs) } else { now });
+                }
+                continue;
+            }
+
+            // Coming back from a pause: count it as a break if it was long enough
+            if let Some(paused_at) = inactive_since.take() {
+                if now.saturating_sub(paused_at) >= min_break_secs {
+                    log_break_event("break", paused_at, Some(now));
+                    active_since = None;
+                    last_notified = None;
+                }
+            }
+
+            let since = *active_since.get_or_insert(now);
+            if now.saturating_sub(since) < work_secs {
+                continue;
+            }
+
+            let break_state = app_handle.state::<BreakState>();
+            {
+                let mut snoozed_until = break_state.snoozed_until.lock().unwrap();
+                match *snoozed_until {
+                    Some(until) if now < until => continue,
+                    Some(_) => *snoozed_until = None,
+                    None => {}
+                }
+            }
+            if let Some(last) = last_notified {
+                if now.saturating_sub(last) < renotify_secs {
+                    continue;
+                }
+            }
+
+            let snooze_mins = *break_state.default_snooze_minutes.lock().unwrap();
+            crate::notify::show_break_reminder(&app_handle, now.saturating_sub(since) / 60, snooze_mins);
+            log_break_event("reminder", now, None);
+            last_notified = Some(now);
+        }
+    });
+}
+
+/// Postpone the next break reminder (defaults to the configured snooze duration)
+#[tauri::command]
+pub fn snooze_break_reminder(app: tauri::AppHandle, minutes: Option<u32>) {
+    let break_state = app.state::<BreakState>();
+    let minutes = minutes.unwrap_or(*break_state.default_snooze_minutes.lock().unwrap());
+    let now = now_secs();
+    *break_state.snoozed_until.lock().unwrap() = Some(now + (minutes as u64) * 60);
+    log_break_event("snooze", now, Some(now + (minutes as u64) * 60));
+}
+
+/// Summarize breaks and break reminders over the last `days` days (default 7)
+#[tauri::command]
+pub fn get_break_stats(days: Option<u32>) -> Result<BreakStats, String> {
+    let conn = crate::db::open().ok_or("Database not found")?;
+    let modifier = format!("-{} days", days.unwrap_or(7));
+    conn.query_row(
+        "SELECT
+            COALESCE(SUM(kind = 'break'), 0),
+            COALESCE(SUM(CASE WHEN kind = 'break' THEN duration END), 0),
+            COALESCE(MAX(CASE WHEN kind = 'break' THEN duration END), 0),
+            COALESCE(SUM(kind = 'reminder'), 0),
+            COALESCE(SUM(kind = 'snooze'), 0)
+         FROM break_log
+         WHERE start_time >= datetime('now', ?1)",
+        [modifier],
+        |row| {
+            Ok(BreakStats {
+                breaks_taken: row.get(0)?,
+                total_break_seconds: row.get(1)?,
+                longest_break_seconds: row.get(2)?,
+                reminders_sent: row.get(3)?,
+                reminders_snoozed: row.get(4)?,
+            })
+        },
+    )
+    .map_err(|e| e.to_string())
+}
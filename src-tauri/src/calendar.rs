@@ -0,0 +1,361 @@
+// Days off (vacations, holidays, muted days) on which reminders stay quiet
+use crate::journal::Recorder;
+use crate::schedule::{self, ReminderSchedule};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use rusqlite::Connection;
+
+#[derive(serde::Serialize)]
+pub struct DayOff {
+    date: String, // "YYYY-MM-DD"
+    label: String,
+    source: String, // "manual", "ics" or "mute"
+}
+
+// Guard against runaway events (e.g. a DTEND years after DTSTART)
+const MAX_EVENT_DAYS: i64 = 366;
+
+/// Whether `date` is marked as a day off
+pub fn is_day_off(conn: &Connection, date: NaiveDate) -> bool {
+    conn.query_row(
+        "SELECT COUNT(*) FROM days_off WHERE date = ?1",
+        [date.format("%Y-%m-%d").to_string()],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+    .unwrap_or(false)
+}
+
+// Existing days keep their label and source, so muting an imported holiday
+// does not turn it into "Not working today". New rows are added to `journal`.
+fn insert_day_off(conn: &Connection, journal: &mut Recorder, date: NaiveDate, label: &str, source: &str) -> rusqlite::Result<usize> {
+    let rows = conn.execute(
+        "INSERT OR IGNORE INTO days_off (date, label, source) VALUES (?1, ?2, ?3)",
+        rusqlite::params![date.format("%Y-%m-%d").to_string(), label, source],
+    )?;
+    if rows > 0 {
+        journal.track_new("days_off", conn.last_insert_rowid());
+    }
+    Ok(rows)
+}
+
+/// Insert days off as one undoable step
+fn add_days_off(label: &str, days: &[(NaiveDate, String)], source: &str) -> Result<usize, String> {
+    let mut conn = crate::db::open().ok_or("Database not found")?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut journal = Recorder::new(label);
+    let mut added = 0;
+    for (date, day_label) in days {
+        added += insert_day_off(&tx, &mut journal, *date, day_label, source).map_err(|e| e.to_string())?;
+    }
+    journal.finish(&tx).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(added)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum IcsTime {
+    /// An all-day value (`VALUE=DATE`)
+    Date(NaiveDate),
+    At(DateTime<Utc>),
+}
+
+/// Parse an ICS DTSTART/DTEND value such as "20261224", "20261224T090000Z" or
+/// "20261224T090000" with a `TZID` parameter; floating times are local
+fn parse_ics_time(params: &[&str], value: &str) -> Option<IcsTime> {
+    let all_day = params.iter().any(|p| p.eq_ignore_ascii_case("VALUE=DATE"));
+    if all_day || value.len() == 8 {
+        return NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok().map(IcsTime::Date);
+    }
+    let naive = NaiveDateTime::parse_from_str(value.get(..15)?, "%Y%m%dT%H%M%S").ok()?;
+    if value.ends_with('Z') {
+        return Some(IcsTime::At(naive.and_utc()));
+    }
+    let tzid = params.iter().find_map(|p| p.strip_prefix("TZID="));
+    let at = match tzid.and_then(|name| name.trim_matches('"').parse::<Tz>().ok()) {
+        Some(tz) => tz.from_local_datetime(&naive).earliest()?.with_timezone(&Utc),
+        None => Local.from_local_datetime(&naive).earliest()?.with_timezone(&Utc),
+    };
+    Some(IcsTime::At(at))
+}
+
+fn local_instant<T: TimeZone>(tz: &T, date: NaiveDate, (hour, minute): (u32, u32)) -> Option<DateTime<Utc>> {
+    let naive = date.and_hms_opt(hour, minute, 0)?;
+    Some(tz.from_local_datetime(&naive).earliest()?.with_timezone(&Utc))
+}
+
+/// The working windows starting on `day`, as instants. Each window uses its own
+/// time zone, or the system one when unset.
+fn working_windows(windows: &[ReminderSchedule], day: NaiveDate) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let weekday = schedule::ui_weekday(day.weekday());
+    windows
+        .iter()
+        .filter(|w| w.weekdays.contains(&weekday))
+        .filter_map(|w| {
+            let start = schedule::parse_time_string(&w.start_time)?;
+            let end = schedule::parse_time_string(&w.end_time)?;
+            // An end at or before the start means the window ends the next day
+            let end_day = if end <= start { day.succ_opt()? } else { day };
+            match w.timezone.as_deref().filter(|tz| !tz.is_empty()) {
+                Some(name) => {
+                    let tz = name.parse::<Tz>().ok()?;
+                    Some((local_instant(&tz, day, start)?, local_instant(&tz, end_day, end)?))
+                }
+                None => Some((local_instant(&Local, day, start)?, local_instant(&Local, end_day, end)?)),
+            }
+        })
+        .collect()
+}
+
+/// Days on which a timed event covers every working window. Days without
+/// working hours are skipped, since reminders are quiet there anyway.
+fn timed_event_days(windows: &[ReminderSchedule], start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<NaiveDate> {
+    let mut days = Vec::new();
+    // A day before and after the event, so windows in other zones are not missed
+    let mut day = start.date_naive() - Duration::days(1);
+    let last = end.date_naive() + Duration::days(1);
+    while day <= last && days.len() < MAX_EVENT_DAYS as usize {
+        let ranges = working_windows(windows, day);
+        if !ranges.is_empty() && ranges.iter().all(|(from, to)| start <= *from && *to <= end) {
+            days.push(day);
+        }
+        day += Duration::days(1);
+    }
+    days
+}
+
+/// Extract the days off described by the VEVENTs of an iCalendar file, with
+/// the event summary as label. All-day events cover each of their days; timed
+/// events only count for days whose working windows they cover entirely, so a
+/// one-hour appointment does not become a day off. Recurrence rules are not
+/// expanded.
+pub fn parse_ics_days(contents: &str, windows: &[ReminderSchedule]) -> Vec<(NaiveDate, String)> {
+    // Unfold continuation lines (RFC 5545 section 3.1)
+    let mut lines: Vec<String> = Vec::new();
+    for raw in contents.lines() {
+        if let Some(rest) = raw.strip_prefix(' ').or_else(|| raw.strip_prefix('\t')) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(rest.trim_end_matches('\r'));
+                continue;
+            }
+        }
+        lines.push(raw.trim_end_matches('\r').to_string());
+    }
+
+    let mut days = Vec::new();
+    let mut in_event = false;
+    let mut start: Option<IcsTime> = None;
+    // Exclusive end
+    let mut end: Option<IcsTime> = None;
+    let mut summary = String::new();
+
+    for line in &lines {
+        if line == "BEGIN:VEVENT" {
+            in_event = true;
+            start = None;
+            end = None;
+            summary.clear();
+            continue;
+        }
+        if !in_event {
+            continue;
+        }
+        if line == "END:VEVENT" {
+            in_event = false;
+            let covered = match (start, end) {
+                (Some(IcsTime::Date(first)), end) => {
+                    let last = match end {
+                        Some(IcsTime::Date(last)) if last > first => last,
+                        _ => first + Duration::days(1),
+                    };
+                    let mut covered = Vec::new();
+                    let mut day = first;
+                    while day < last && (day - first).num_days() < MAX_EVENT_DAYS {
+                        covered.push(day);
+                        day += Duration::days(1);
+                    }
+                    covered
+                }
+                (Some(IcsTime::At(from)), Some(IcsTime::At(to))) => timed_event_days(windows, from, to),
+                // Timed events without an end (or with a mismatched one) cover no working time
+                _ => Vec::new(),
+            };
+            days.extend(covered.into_iter().map(|day| (day, summary.clone())));
+            continue;
+        }
+
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        // Parameters such as ";VALUE=DATE" or ";TZID=Europe/Oslo"
+        let mut parts = name.split(';');
+        let name = parts.next().unwrap_or(name);
+        let params: Vec<&str> = parts.collect();
+        match name {
+            "DTSTART" => start = parse_ics_time(&params, value),
+            "DTEND" => end = parse_ics_time(&params, value),
+            "SUMMARY" => summary = value.replace("\\,", ",").replace("\\;", ";"),
+            _ => {}
+        }
+    }
+
+    days
+}
+
+/// Days off from today onwards
+#[tauri::command]
+pub fn list_days_off() -> Result<Vec<DayOff>, String> {
+    let conn = crate::db::open().ok_or("Database not found")?;
+    let mut stmt = conn
+        .prepare("SELECT date, label, source FROM days_off WHERE date >= date('now', 'localtime') ORDER BY date")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(DayOff { date: row.get(0)?, label: row.get(1)?, source: row.get(2)? })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn add_day_off(date: String, label: Option<String>) -> Result<(), String> {
+    let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| e.to_string())?;
+    add_days_off("Add day off", &[(date, label.unwrap_or_default())], "manual")?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn remove_day_off(date: String) -> Result<(), String> {
+    let mut conn = crate::db::open().ok_or("Database not found")?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut journal = Recorder::new("Remove day off");
+    journal.track_where(&tx, "days_off", "date = ?1", [&date]).map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM days_off WHERE date = ?1", [&date])
+        .map_err(|e| e.to_string())?;
+    journal.finish(&tx).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Silence reminders for the rest of today
+#[tauri::command]
+pub fn mute_reminders_today() -> Result<(), String> {
+    add_days_off("Mute reminders today", &[(Local::now().date_naive(), "Not working today".into())], "mute")?;
+    Ok(())
+}
+
+/// Import the days off from a local .ics file, returning the number of days found
+#[tauri::command]
+pub fn import_days_off_ics(path: String) -> Result<usize, String> {
+    let contents = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let windows = {
+        let conn = crate::db::open().ok_or("Database not found")?;
+        crate::settings::Settings::load(&conn).workday_windows()
+    };
+    let days = parse_ics_days(&contents, &windows);
+    add_days_off("Import days off", &days, "ics")?;
+    Ok(days.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAYS_OFF: &str = include_str!("../tests/fixtures/days_off.ics");
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    fn at(text: &str) -> DateTime<Utc> {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S").unwrap().and_utc()
+    }
+
+    fn window(weekdays: Vec<u32>, start: &str, end: &str, timezone: &str) -> ReminderSchedule {
+        ReminderSchedule { weekdays, start_time: start.into(), end_time: end.into(), timezone: Some(timezone.into()) }
+    }
+
+    /// 09:00-18:00 on weekdays in Berlin
+    fn office() -> Vec<ReminderSchedule> {
+        vec![window(vec![1, 2, 3, 4, 5], "09:00", "18:00", "Europe/Berlin")]
+    }
+
+    #[test]
+    fn ics_times() {
+        let cases: [(&[&str], &str, Option<IcsTime>); 8] = [
+            (&[], "20261224", Some(IcsTime::Date(date("2026-12-24")))),
+            (&["VALUE=DATE"], "20261224", Some(IcsTime::Date(date("2026-12-24")))),
+            (&[], "20261224T090000Z", Some(IcsTime::At(at("2026-12-24 09:00:00")))),
+            (&["TZID=Europe/Berlin"], "20261224T090000", Some(IcsTime::At(at("2026-12-24 08:00:00")))),
+            (&["TZID=\"America/New_York\""], "20260701T090000", Some(IcsTime::At(at("2026-07-01 13:00:00")))),
+            // Twice on the night the clocks go back: the first one
+            (&["TZID=Europe/Berlin"], "20261025T023000", Some(IcsTime::At(at("2026-10-25 00:30:00")))),
+            (&[], "20261324", None),
+            (&[], "2026122", None),
+        ];
+        for (params, value, expected) in cases {
+            assert_eq!(parse_ics_time(params, value), expected, "{}", value);
+        }
+        let floating = Local.from_local_datetime(&date("2026-12-24").and_hms_opt(9, 0, 0).unwrap()).earliest().unwrap();
+        assert_eq!(parse_ics_time(&[], "20261224T090000"), Some(IcsTime::At(floating.with_timezone(&Utc))));
+    }
+
+    #[test]
+    fn ics_fixture_days() {
+        let days = parse_ics_days(DAYS_OFF, &office());
+        let days: Vec<(String, &str)> = days.iter().map(|(day, label)| (day.to_string(), label.as_str())).collect();
+        let closed = "Office closed, between the holidays";
+        assert_eq!(
+            days,
+            [
+                ("2026-12-24".to_string(), "Christmas Eve"),
+                ("2026-12-28".to_string(), closed),
+                ("2026-12-29".to_string(), closed),
+                ("2026-12-30".to_string(), closed),
+                ("2026-12-31".to_string(), closed),
+                ("2027-01-01".to_string(), closed),
+                ("2026-10-19".to_string(), "Founders day"),
+                ("2026-10-20".to_string(), "Offsite"),
+                ("2026-10-21".to_string(), "Conference"),
+                ("2026-10-22".to_string(), "Conference"),
+                ("2026-10-23".to_string(), "Conference"),
+            ]
+        );
+    }
+
+    #[test]
+    fn crlf_and_tab_folded_lines() {
+        let contents = "BEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20261102\r\nSUMMARY:All\r\n\tSaints\r\nEND:VEVENT\r\n";
+        assert_eq!(parse_ics_days(contents, &[]), [(date("2026-11-02"), "AllSaints".to_string())]);
+    }
+
+    #[test]
+    fn timed_events_must_cover_every_window() {
+        let split = vec![
+            window(vec![1], "09:00", "12:00", "Europe/Berlin"),
+            window(vec![1], "13:00", "17:00", "Europe/Berlin"),
+        ];
+        // Monday 2026-10-26, CET
+        let cases = [
+            ("2026-10-26 08:00:00", "2026-10-26 16:00:00", vec![date("2026-10-26")]),
+            ("2026-10-26 08:00:00", "2026-10-26 11:30:00", vec![]),
+            ("2026-10-26 12:00:00", "2026-10-26 16:00:00", vec![]),
+            ("2026-10-25 12:00:00", "2026-10-27 12:00:00", vec![date("2026-10-26")]),
+        ];
+        for (start, end, expected) in cases {
+            assert_eq!(timed_event_days(&split, at(start), at(end)), expected, "{} - {}", start, end);
+        }
+    }
+
+    #[test]
+    fn timed_events_use_the_window_zone_and_day() {
+        // Tokyo's Monday morning is Sunday evening in UTC
+        let tokyo = vec![window(vec![1], "09:00", "18:00", "Asia/Tokyo")];
+        assert_eq!(timed_event_days(&tokyo, at("2026-10-18 23:00:00"), at("2026-10-19 10:00:00")), [date("2026-10-19")]);
+        assert!(timed_event_days(&tokyo, at("2026-10-19 01:00:00"), at("2026-10-19 10:00:00")).is_empty());
+
+        // Overnight shift from Monday 22:00 to Tuesday 06:00
+        let night = vec![window(vec![1], "22:00", "06:00", "UTC")];
+        assert_eq!(timed_event_days(&night, at("2026-10-19 21:00:00"), at("2026-10-20 07:00:00")), [date("2026-10-19")]);
+        assert!(timed_event_days(&night, at("2026-10-19 21:00:00"), at("2026-10-20 05:00:00")).is_empty());
+    }
+}
@@ -0,0 +1,57 @@
+// Passphrase encryption for files written outside the app's own folder, such
+// as backups. The key is derived from the passphrase and a random salt with
+// Argon2id and the data is sealed with XChaCha20-Poly1305, so a wrong
+// passphrase or a damaged file is reported instead of producing garbage.
+//
+// Layout: MAGIC, 16-byte salt, 24-byte nonce, then the ciphertext with its tag.
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+
+const MAGIC: &[u8] = b"HORALIS-ENC1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key, String> {
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Failed to derive key: {}", e))?;
+    Ok(key)
+}
+
+/// Whether `data` was written by `encrypt`
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+pub fn encrypt(passphrase: &str, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let key = derive_key(passphrase, &salt)?;
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = XChaCha20Poly1305::new(&key)
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| "Encryption failed".to_string())?;
+
+    let mut out = Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&nonce);
+    out.extend(ciphertext);
+    Ok(out)
+}
+
+pub fn decrypt(passphrase: &str, data: &[u8]) -> Result<Vec<u8>, String> {
+    let body = data.strip_prefix(MAGIC).ok_or("Not an encrypted file")?;
+    if body.len() < SALT_LEN + NONCE_LEN {
+        return Err("The file is truncated".into());
+    }
+    let (salt, rest) = body.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let key = derive_key(passphrase, salt)?;
+    XChaCha20Poly1305::new(&key)
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Wrong passphrase, or the file is damaged".to_string())
+}
@@ -0,0 +1,354 @@
+// Direct SQLite access for the Rust backend.
+//
+// The schema for projects, time entries and settings is owned by the frontend
+// (see src/lib/db.ts). Tables that only the backend writes to are created here.
+use rusqlite::{Connection, OptionalExtension};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::audit::ChangeSource;
+
+/// Locate horalis.db on disk, checking every place tauri-plugin-sql may have put it
+pub fn database_path() -> Option<PathBuf> {
+    let mut candidates: Vec<PathBuf> = Vec::new();
+
+    // tauri-plugin-sql resolves sqlite: URLs against the app config dir
+    if let Some(config_dir) = crate::dirs::config_dir() {
+        candidates.push(config_dir.join("com.horalis.app").join("horalis.db"));
+    }
+    if let Some(proj_dirs) = directories::ProjectDirs::from("", "", "com.horalis.app") {
+        candidates.push(proj_dirs.data_dir().join("horalis.db"));
+    }
+    // Also check the old-style path that tauri-plugin-sql might use
+    if let Some(data_dir) = crate::dirs::data_dir() {
+        candidates.push(data_dir.join("com.horalis.app").join("horalis.db"));
+    }
+
+    candidates.into_iter().find(|p| p.exists())
+}
+
+/// Open the database and make sure backend-owned tables exist
+pub fn open() -> Option<Connection> {
+    let path = database_path()?;
+    if crate::encryption::is_locked(&path) {
+        return None;
+    }
+    let conn = Connection::open(&path).ok()?;
+    // The webview holds its own connection through tauri-plugin-sql
+    let _ = conn.busy_timeout(Duration::from_secs(5));
+    if let Err(e) = init_schema(&conn) {
+        eprintln!("Failed to initialize backend schema: {}", e);
+    }
+    Some(conn)
+}
+
+fn init_schema(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS break_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind TEXT NOT NULL,
+            start_time DATETIME NOT NULL,
+            end_time DATETIME,
+            duration INTEGER
+        );
+        CREATE TABLE IF NOT EXISTS days_off (
+            date TEXT PRIMARY KEY,
+            label TEXT NOT NULL DEFAULT '',
+            source TEXT NOT NULL DEFAULT 'manual'
+        );
+        CREATE TABLE IF NOT EXISTS activity_samples (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            sampled_at DATETIME NOT NULL,
+            app TEXT NOT NULL,
+            title TEXT NOT NULL,
+            duration INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_activity_samples_sampled_at ON activity_samples(sampled_at);
+        CREATE TABLE IF NOT EXISTS activity_rules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            field TEXT NOT NULL,
+            operator TEXT NOT NULL,
+            pattern TEXT NOT NULL,
+            project_id INTEGER,
+            tag TEXT,
+            mode TEXT NOT NULL DEFAULT 'suggest',
+            enabled INTEGER NOT NULL DEFAULT 1,
+            priority INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE TABLE IF NOT EXISTS entry_tags (
+            entry_id INTEGER NOT NULL,
+            tag TEXT NOT NULL,
+            PRIMARY KEY (entry_id, tag)
+        );
+        CREATE TABLE IF NOT EXISTS project_repos (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_id INTEGER NOT NULL,
+            path TEXT NOT NULL UNIQUE
+        );
+        CREATE TABLE IF NOT EXISTS entry_commits (
+            entry_id INTEGER NOT NULL,
+            hash TEXT NOT NULL,
+            message TEXT NOT NULL,
+            committed_at DATETIME NOT NULL,
+            repo_path TEXT NOT NULL,
+            PRIMARY KEY (entry_id, hash)
+        );
+        CREATE TABLE IF NOT EXISTS journal (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            label TEXT NOT NULL,
+            changes TEXT NOT NULL,
+            created_at DATETIME NOT NULL,
+            undone INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE TABLE IF NOT EXISTS entry_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            entry_id INTEGER NOT NULL,
+            changed_at DATETIME NOT NULL,
+            source TEXT NOT NULL,
+            action TEXT NOT NULL,
+            label TEXT NOT NULL,
+            old_project_id INTEGER,
+            old_start_time DATETIME,
+            old_end_time DATETIME,
+            old_duration INTEGER,
+            new_project_id INTEGER,
+            new_start_time DATETIME,
+            new_end_time DATETIME,
+            new_duration INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_entry_history_entry ON entry_history(entry_id);
+        CREATE TABLE IF NOT EXISTS report_jobs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            spec TEXT NOT NULL,
+            format TEXT NOT NULL,
+            schedule TEXT NOT NULL,
+            weekday INTEGER NOT NULL DEFAULT 5,
+            time TEXT NOT NULL,
+            folder TEXT NOT NULL,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            last_run_at DATETIME,
+            created_at DATETIME NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS timesheets (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            start_date TEXT NOT NULL,
+            end_date TEXT NOT NULL,
+            start_at DATETIME NOT NULL,
+            end_at DATETIME NOT NULL,
+            submitted_at DATETIME NOT NULL,
+            totals TEXT NOT NULL,
+            total INTEGER NOT NULL,
+            file_path TEXT,
+            unlocked_at DATETIME,
+            unlock_reason TEXT
+        );
+        CREATE TABLE IF NOT EXISTS sync_state (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS sync_rows (
+            tbl TEXT NOT NULL,
+            uid TEXT NOT NULL,
+            fingerprint TEXT,
+            version_at INTEGER NOT NULL,
+            version_device TEXT NOT NULL,
+            PRIMARY KEY (tbl, uid)
+        );
+        CREATE TABLE IF NOT EXISTS sync_outbox (
+            seq INTEGER PRIMARY KEY,
+            change TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS sync_peers (
+            device TEXT PRIMARY KEY,
+            read_offset INTEGER NOT NULL DEFAULT 0,
+            seq INTEGER NOT NULL DEFAULT 0,
+            synced_at DATETIME
+        );
+        CREATE TABLE IF NOT EXISTS sync_conflicts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            tbl TEXT NOT NULL,
+            uid TEXT NOT NULL,
+            device TEXT NOT NULL,
+            kept TEXT,
+            discarded TEXT,
+            detected_at DATETIME NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS sync_edits (
+            tbl TEXT NOT NULL,
+            row_id INTEGER NOT NULL,
+            uid TEXT,
+            at INTEGER NOT NULL,
+            PRIMARY KEY (tbl, row_id)
+        );",
+    )?;
+
+    // Backend columns on the frontend's tables, added once those tables exist
+    add_column(conn, "time_entries", "timezone", "TEXT")?; // Zone the entry was recorded in
+    add_column(conn, "projects", "client", "TEXT")?;
+    add_column(conn, "time_entries", "locked_by", "INTEGER")?; // Submitted timesheet, see timesheets.rs
+    add_column(conn, "projects", "uid", "TEXT")?; // Identity across devices, see sync.rs
+    add_column(conn, "time_entries", "uid", "TEXT")?;
+    Ok(())
+}
+
+fn add_column(conn: &Connection, table: &str, column: &str, definition: &str) -> rusqlite::Result<()> {
+    let columns: Vec<String> = conn
+        .prepare(&format!("SELECT name FROM pragma_table_info('{}')", table))?
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    if !columns.is_empty() && !columns.iter().any(|c| c == column) {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(())
+}
+
+/// IANA name of the zone new entries are recorded in
+pub fn current_zone_name() -> &'static str {
+    crate::zones::system_zone().name()
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct ProjectRow {
+    pub id: i64,
+    pub name: String,
+    pub color: String,
+}
+
+pub fn get_project(conn: &Connection, id: i64) -> Option<ProjectRow> {
+    conn.query_row(
+        "SELECT id, name, color FROM projects WHERE id = ?1",
+        [id],
+        |row| Ok(ProjectRow { id: row.get(0)?, name: row.get(1)?, color: row.get(2)? }),
+    )
+    .optional()
+    .ok()
+    .flatten()
+}
+
+/// The project of the most recently started time entry
+pub fn last_used_project(conn: &Connection) -> Option<ProjectRow> {
+    let id: i64 = conn
+        .query_row(
+            "SELECT project_id FROM time_entries ORDER BY start_time DESC LIMIT 1",
+            [],
+            |row| row.get(0),
+        )
+        .optional()
+        .ok()
+        .flatten()?;
+    get_project(conn, id)
+}
+
+/// All projects, ordered by name as in the project lists
+pub fn list_projects(conn: &Connection) -> rusqlite::Result<Vec<ProjectRow>> {
+    let mut stmt = conn.prepare("SELECT id, name, color FROM projects ORDER BY name")?;
+    let rows = stmt.query_map([], |row| Ok(ProjectRow { id: row.get(0)?, name: row.get(1)?, color: row.get(2)? }))?;
+    rows.collect()
+}
+
+/// Project and start (Unix seconds) of the running time entry, if any
+pub fn running_entry(conn: &Connection) -> Option<(ProjectRow, i64)> {
+    let (project_id, start): (i64, i64) = conn
+        .query_row(
+            "SELECT project_id, CAST(strftime('%s', start_time) AS INTEGER)
+             FROM time_entries WHERE end_time IS NULL ORDER BY start_time DESC LIMIT 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .ok()
+        .flatten()?;
+    Some((get_project(conn, project_id)?, start))
+}
+
+/// Id of the running time entry, if any
+pub fn running_entry_id(conn: &Connection) -> Option<i64> {
+    conn.query_row(
+        "SELECT id FROM time_entries WHERE end_time IS NULL LIMIT 1",
+        [],
+        |row| row.get(0),
+    )
+    .optional()
+    .ok()
+    .flatten()
+}
+
+/// Stop whatever is running and start a new entry for `project_id`
+pub fn start_time_entry(conn: &Connection, project_id: i64, source: ChangeSource) -> rusqlite::Result<i64> {
+    let tx = conn.unchecked_transaction()?;
+    let mut journal = crate::journal::Recorder::new("Start timer").source(source);
+    journal.track_where(&tx, "time_entries", "end_time IS NULL", [])?;
+    tx.execute(
+        "UPDATE time_entries
+         SET end_time = datetime('now'),
+             duration = CAST((julianday(datetime('now')) - julianday(start_time)) * 86400 AS INTEGER)
+         WHERE end_time IS NULL",
+        [],
+    )?;
+    tx.execute(
+        "INSERT INTO time_entries (project_id, start_time, timezone) VALUES (?1, datetime('now'), ?2)",
+        rusqlite::params![project_id, current_zone_name()],
+    )?;
+    let entry_id = tx.last_insert_rowid();
+    journal.track_new("time_entries", entry_id);
+    journal.finish(&tx)?;
+    tx.commit()?;
+    Ok(entry_id)
+}
+
+/// Move the end of a time entry to `end_secs` (Unix seconds), e.g. to drop idle time
+pub fn trim_time_entry_end(conn: &Connection, entry_id: i64, end_secs: u64, source: ChangeSource) -> rusqlite::Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+    let mut journal = crate::journal::Recorder::new("Discard idle time").source(source);
+    journal.track(&tx, "time_entries", entry_id)?;
+    let rows = tx.execute(
+        "UPDATE time_entries
+         SET end_time = MAX(start_time, datetime(?1, 'unixepoch')),
+             duration = MAX(0, CAST((julianday(datetime(?1, 'unixepoch')) - julianday(start_time)) * 86400 AS INTEGER))
+         WHERE id = ?2",
+        rusqlite::params![end_secs as i64, entry_id],
+    )?;
+    journal.finish(&tx)?;
+    tx.commit()?;
+    Ok(rows)
+}
+
+/// Databases for unit tests
+#[cfg(test)]
+pub mod testing {
+    use rusqlite::Connection;
+
+    /// Create the frontend's tables (see src/lib/db.ts), then the backend schema
+    pub fn init(conn: &Connection) {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS projects (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                color TEXT DEFAULT '#3B82F6',
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            );
+            CREATE TABLE IF NOT EXISTS time_entries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id INTEGER NOT NULL,
+                start_time DATETIME NOT NULL,
+                end_time DATETIME,
+                duration INTEGER,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (project_id) REFERENCES projects(id)
+            );
+            CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );",
+        )
+        .unwrap();
+        super::init_schema(conn).unwrap();
+    }
+
+    pub fn memory() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init(&conn);
+        conn
+    }
+}
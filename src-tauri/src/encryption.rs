@@ -0,0 +1,237 @@
+// At-rest encryption of horalis.db with SQLCipher. The webview's tauri-plugin-sql
+// and rusqlite link the same SQLite, so a hook registered with
+// sqlite3_auto_extension hands the key to every connection either of them opens
+// on a file in the database folder. That covers the scratch copies made by
+// backups too, which then stay encrypted. Backups written before the database
+// was encrypted are still plain SQLite; restoring one exports it into such a
+// scratch copy (see backups.rs).
+//
+// Until the passphrase is entered (or read from the OS keyring) the database
+// is locked: db::open() returns None, background tasks skip their work and the
+// tray shows no project names.
+use rusqlite::{ffi, Connection};
+use std::ffi::{c_char, c_int, c_void, CStr};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Emitter;
+
+use crate::db;
+
+const KEYRING_SERVICE: &str = "com.horalis.app";
+const KEYRING_USER: &str = "database";
+const PLAIN_HEADER: &[u8] = b"SQLite format 3\0";
+const MIN_PASSPHRASE: usize = 8;
+
+struct Key {
+    folder: PathBuf,
+    passphrase: String,
+}
+
+static KEY: Mutex<Option<Key>> = Mutex::new(None);
+
+#[derive(Clone, serde::Serialize)]
+pub struct DatabaseLock {
+    encrypted: bool,
+    locked: bool,
+    remembered: bool,
+}
+
+/// Keys new connections to files in the database folder while unlocked
+unsafe extern "C" fn apply_key(db: *mut ffi::sqlite3, _: *mut *mut c_char, _: *const ffi::sqlite3_api_routines) -> c_int {
+    let Ok(key) = KEY.lock() else {
+        return ffi::SQLITE_OK;
+    };
+    let Some(key) = key.as_ref() else {
+        return ffi::SQLITE_OK;
+    };
+    let name = ffi::sqlite3_db_filename(db, c"main".as_ptr());
+    if name.is_null() {
+        return ffi::SQLITE_OK;
+    }
+    let Ok(name) = CStr::from_ptr(name).to_str() else {
+        return ffi::SQLITE_OK;
+    };
+    if Path::new(name).parent() == Some(key.folder.as_path()) {
+        ffi::sqlite3_key(db, key.passphrase.as_ptr() as *const c_void, key.passphrase.len() as c_int);
+    }
+    ffi::SQLITE_OK
+}
+
+/// Register the key hook and unlock with a remembered passphrase. Runs before
+/// any connection is opened.
+pub fn init() {
+    unsafe {
+        ffi::sqlite3_auto_extension(Some(apply_key));
+    }
+    let Some(path) = db::database_path().filter(|p| is_encrypted(p)) else {
+        return;
+    };
+    if let Some(passphrase) = remembered() {
+        match check_passphrase(&path, &passphrase) {
+            Ok(()) => set_key(&path, Some(passphrase)),
+            Err(e) => eprintln!("Remembered database passphrase no longer works: {}", e),
+        }
+    }
+}
+
+/// Whether `path` is an SQLCipher database rather than plain SQLite
+pub fn is_encrypted(path: &Path) -> bool {
+    let mut header = [0u8; 16];
+    match std::fs::File::open(path).and_then(|mut f| std::io::Read::read_exact(&mut f, &mut header)) {
+        Ok(()) => header != PLAIN_HEADER,
+        Err(_) => false,
+    }
+}
+
+/// Whether `data` begins like a plain SQLite database
+pub fn is_plain_database(data: &[u8]) -> bool {
+    data.starts_with(PLAIN_HEADER)
+}
+
+/// Whether `path` is encrypted and no passphrase has been entered yet
+pub fn is_locked(path: &Path) -> bool {
+    KEY.lock().map(|k| k.is_none()).unwrap_or(true) && is_encrypted(path)
+}
+
+/// Whether the database exists and is locked
+pub fn database_locked() -> bool {
+    db::database_path().is_some_and(|p| is_locked(&p))
+}
+
+fn set_key(path: &Path, passphrase: Option<String>) {
+    let folder = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    *KEY.lock().unwrap() = passphrase.map(|passphrase| Key { folder, passphrase });
+}
+
+fn keyring_entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER).map_err(|e| format!("System keyring unavailable: {}", e))
+}
+
+fn remembered() -> Option<String> {
+    keyring_entry().ok()?.get_password().ok()
+}
+
+fn remember(passphrase: &str) -> Result<(), String> {
+    keyring_entry()?.set_password(passphrase).map_err(|e| format!("Failed to store the passphrase: {}", e))
+}
+
+fn forget() -> Result<(), String> {
+    match keyring_entry()?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("Failed to remove the passphrase: {}", e)),
+    }
+}
+
+/// Open `path` with `passphrase` and read from it
+fn check_passphrase(path: &Path, passphrase: &str) -> Result<(), String> {
+    let conn = Connection::open(path).map_err(|e| e.to_string())?;
+    conn.pragma_update(None, "key", passphrase).map_err(|e| e.to_string())?;
+    conn.query_row("SELECT count(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))
+        .map_err(|_| "Wrong passphrase".to_string())?;
+    Ok(())
+}
+
+fn lock_state() -> DatabaseLock {
+    let path = db::database_path();
+    DatabaseLock {
+        encrypted: path.as_deref().is_some_and(is_encrypted),
+        locked: path.as_deref().is_some_and(is_locked),
+        remembered: remembered().is_some(),
+    }
+}
+
+fn notify_changed(app: &tauri::AppHandle) {
+    let _ = app.emit("database-lock-changed", lock_state());
+}
+
+#[tauri::command]
+pub fn get_database_lock() -> DatabaseLock {
+    lock_state()
+}
+
+/// Unlock with `passphrase`, optionally keeping it in the OS keyring so the
+/// next launch unlocks on its own
+#[tauri::command]
+pub fn unlock_database(app: tauri::AppHandle, passphrase: String, remember_passphrase: bool) -> Result<(), String> {
+    let path = db::database_path().ok_or("Database not found")?;
+    check_passphrase(&path, &passphrase)?;
+    if remember_passphrase {
+        remember(&passphrase)?;
+    }
+    set_key(&path, Some(passphrase));
+    crate::restore_session(&app);
+    notify_changed(&app);
+    Ok(())
+}
+
+/// Forget the passphrase until it is entered again. Windows close their
+/// connections when they get the event.
+#[tauri::command]
+pub fn lock_database(app: tauri::AppHandle) -> Result<(), String> {
+    let path = db::database_path().ok_or("Database not found")?;
+    if !is_encrypted(&path) {
+        return Err("The database is not encrypted".into());
+    }
+    set_key(&path, None);
+    crate::hide_tray_details(&app);
+    notify_changed(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn forget_database_passphrase() -> Result<(), String> {
+    forget()
+}
+
+/// Convert the plaintext database into an encrypted one. The webview closes
+/// its connection first; the plaintext file is replaced, not kept.
+#[tauri::command]
+pub fn encrypt_database(app: tauri::AppHandle, passphrase: String, remember_passphrase: bool) -> Result<(), String> {
+    if passphrase.chars().count() < MIN_PASSPHRASE {
+        return Err(format!("Use a passphrase of at least {} characters", MIN_PASSPHRASE));
+    }
+    let path = db::database_path().ok_or("Database not found")?;
+    if is_encrypted(&path) {
+        return Err("The database is already encrypted".into());
+    }
+    let target = path.with_file_name("horalis.db.encrypting");
+    let _ = std::fs::remove_file(&target);
+
+    let result = (|| {
+        let conn = Connection::open(&path).map_err(|e| e.to_string())?;
+        let _ = conn.busy_timeout(Duration::from_secs(5));
+        let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0)).map_err(|e| e.to_string())?;
+        conn.execute("ATTACH DATABASE ?1 AS encrypted KEY ?2", rusqlite::params![target.to_string_lossy(), passphrase])
+            .map_err(|e| e.to_string())?;
+        conn.query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))
+            .map_err(|e| format!("Encryption failed: {}", e))?;
+        conn.pragma_update(Some(rusqlite::DatabaseName::Attached("encrypted")), "user_version", version)
+            .map_err(|e| e.to_string())?;
+        conn.execute_batch("DETACH DATABASE encrypted").map_err(|e| e.to_string())?;
+        // Folds the write-ahead log into the old file so none of it is left over
+        conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE)").map_err(|e| e.to_string())?;
+        drop(conn);
+        check_passphrase(&target, &passphrase)
+    })();
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&target);
+        return Err(e);
+    }
+
+    // A log next to the new file would be replayed into it
+    for suffix in ["-wal", "-shm"] {
+        let mut side = path.clone().into_os_string();
+        side.push(suffix);
+        let _ = std::fs::remove_file(side);
+    }
+    std::fs::rename(&target, &path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))?;
+    set_key(&path, Some(passphrase.clone()));
+    if remember_passphrase {
+        if let Err(e) = remember(&passphrase) {
+            eprintln!("{}", e);
+        }
+    }
+    notify_changed(&app);
+    Ok(())
+}
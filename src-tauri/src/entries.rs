@@ -0,0 +1,764 @@
+// Time entry validation and editing: entries may not end before they start
+// or overlap each other. Edits go through `update_time_entry` and the split,
+// merge and duplicate commands, which reject both; entries that are already
+// inconsistent (older edits, imports) are listed by `list_entry_conflicts`
+// and fixed with `resolve_entry_conflict`.
+use chrono::{NaiveDate, NaiveDateTime, TimeZone};
+use rusqlite::{Connection, OptionalExtension};
+use tauri::Emitter;
+
+use crate::audit::ChangeSource;
+use crate::db;
+use crate::journal::Recorder;
+use crate::zones;
+
+#[derive(Clone, serde::Serialize)]
+pub struct EntrySummary {
+    id: i64,
+    project_id: i64,
+    project_name: String,
+    project_color: String,
+    start: i64, // Unix seconds
+    end: Option<i64>,
+    timezone: Option<String>, // IANA zone the entry was recorded in
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    Overlap,
+    NegativeDuration,
+}
+
+#[derive(serde::Serialize)]
+pub struct Conflict {
+    kind: ConflictKind,
+    first: EntrySummary,
+    /// The later of two overlapping entries
+    second: Option<EntrySummary>,
+    overlap_secs: i64,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resolution {
+    /// End the first entry where the second starts
+    TrimFirst,
+    /// Start the second entry where the first ends
+    TrimSecond,
+    /// Cut the first entry around the second one it contains
+    Split,
+    /// Combine both into the first entry
+    Merge,
+    /// Swap start and end of an entry that ends before it starts
+    Swap,
+}
+
+/// Parse a timestamp as stored by the frontend ("YYYY-MM-DD HH:MM:SS", UTC)
+pub fn parse_db_time(value: &str) -> Result<i64, String> {
+    let value = value.trim().trim_end_matches('Z').replace('T', " ");
+    let value = value.split('.').next().unwrap_or_default();
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .map(|t| t.and_utc().timestamp())
+        .map_err(|_| format!("Invalid time: {}", value))
+}
+
+const SUMMARY_SELECT: &str = "SELECT te.id, te.project_id, p.name, p.color,
+        CAST(strftime('%s', te.start_time) AS INTEGER),
+        CAST(strftime('%s', te.end_time) AS INTEGER), te.timezone
+     FROM time_entries te
+     JOIN projects p ON p.id = te.project_id";
+
+fn summary_from_row(row: &rusqlite::Row) -> rusqlite::Result<EntrySummary> {
+    Ok(EntrySummary {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        project_name: row.get(2)?,
+        project_color: row.get(3)?,
+        start: row.get(4)?,
+        end: row.get(5)?,
+        timezone: row.get(6)?,
+    })
+}
+
+pub fn load_entry(conn: &Connection, id: i64) -> rusqlite::Result<Option<EntrySummary>> {
+    conn.query_row(&format!("{} WHERE te.id = ?1", SUMMARY_SELECT), [id], summary_from_row)
+        .optional()
+}
+
+/// Check an entry's interval before saving it. `exclude` holds the entries
+/// being edited; a running entry (no end) counts as lasting until now.
+pub fn validate(conn: &Connection, exclude: &[i64], start: i64, end: Option<i64>) -> Result<(), String> {
+    if end.is_some_and(|end| end < start) {
+        return Err("An entry cannot end before it starts".into());
+    }
+    let end = end.unwrap_or_else(|| chrono::Utc::now().timestamp());
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE te.start_time < datetime(?2, 'unixepoch')
+               AND COALESCE(te.end_time, datetime('now')) > datetime(?1, 'unixepoch')
+             ORDER BY te.start_time",
+            SUMMARY_SELECT
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([start, end], summary_from_row).map_err(|e| e.to_string())?;
+    for other in rows {
+        let other = other.map_err(|e| e.to_string())?;
+        if !exclude.contains(&other.id) {
+            return Err(format!("Overlaps with a {} entry (#{})", other.project_name, other.id));
+        }
+    }
+    Ok(())
+}
+
+/// Write an entry's project and interval, keeping `duration` in step with them
+pub fn write_entry(conn: &Connection, id: i64, project_id: i64, start: i64, end: Option<i64>) -> rusqlite::Result<usize> {
+    conn.execute(
+        "UPDATE time_entries
+         SET project_id = ?1,
+             start_time = datetime(?2, 'unixepoch'),
+             end_time = datetime(?3, 'unixepoch'),
+             duration = ?3 - ?2
+         WHERE id = ?4",
+        rusqlite::params![project_id, start, end, id],
+    )
+}
+
+/// Insert a finished entry and return its id. `timezone` is the zone it
+/// counts as recorded in, the system zone when None.
+pub fn insert_entry(conn: &Connection, project_id: i64, start: i64, end: i64, timezone: Option<&str>) -> rusqlite::Result<i64> {
+    conn.execute(
+        "INSERT INTO time_entries (project_id, start_time, end_time, duration, timezone)
+         VALUES (?1, datetime(?2, 'unixepoch'), datetime(?3, 'unixepoch'), ?3 - ?2, ?4)",
+        rusqlite::params![project_id, start, end, timezone.unwrap_or(db::current_zone_name())],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Validated edit of a time entry; times are UTC "YYYY-MM-DD HH:MM:SS"
+#[tauri::command]
+pub fn update_time_entry(app: tauri::AppHandle, id: i64, project_id: i64, start_time: String, end_time: String) -> Result<(), String> {
+    let start = parse_db_time(&start_time)?;
+    let end = parse_db_time(&end_time)?;
+    let mut conn = db::open().ok_or("Database not found")?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    validate(&tx, &[id], start, Some(end))?;
+    let mut journal = Recorder::new("Edit entry");
+    journal.track(&tx, "time_entries", id).map_err(|e| e.to_string())?;
+    write_entry(&tx, id, project_id, start, Some(end)).map_err(|e| e.to_string())?;
+    journal.finish(&tx).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    let _ = app.emit("entries-changed", id);
+    Ok(())
+}
+
+/// Entries that end before they start and pairs of entries that overlap,
+/// among entries started in the last `days` days (default 90)
+#[tauri::command]
+pub fn list_entry_conflicts(days: Option<u32>) -> Result<Vec<Conflict>, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    let now = chrono::Utc::now().timestamp();
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE te.start_time >= datetime('now', ?1) ORDER BY te.start_time, te.id",
+            SUMMARY_SELECT
+        ))
+        .map_err(|e| e.to_string())?;
+    let entries = stmt
+        .query_map([format!("-{} days", days.unwrap_or(90))], summary_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut conflicts = Vec::new();
+    for (i, first) in entries.iter().enumerate() {
+        let first_end = first.end.unwrap_or(now);
+        if first_end < first.start {
+            conflicts.push(Conflict {
+                kind: ConflictKind::NegativeDuration,
+                first: first.clone(),
+                second: None,
+                overlap_secs: 0,
+            });
+            continue;
+        }
+        // Sorted by start, so only later entries starting before this one ends can overlap
+        for second in entries[i + 1..].iter().take_while(|e| e.start < first_end) {
+            let second_end = second.end.unwrap_or(now);
+            if second_end < second.start {
+                continue;
+            }
+            conflicts.push(Conflict {
+                kind: ConflictKind::Overlap,
+                first: first.clone(),
+                second: Some(second.clone()),
+                overlap_secs: first_end.min(second_end) - second.start,
+            });
+        }
+    }
+    Ok(conflicts)
+}
+
+/// Move `from`'s tags and commit links to `to`. Rows `to` already has are
+/// dropped rather than duplicated.
+fn move_links(conn: &Connection, journal: &mut Recorder, from: i64, to: i64) -> Result<(), String> {
+    for table in ["entry_tags", "entry_commits"] {
+        journal.track_where(conn, table, "entry_id = ?1", [from]).map_err(|e| e.to_string())?;
+        conn.execute(&format!("UPDATE OR IGNORE {} SET entry_id = ?1 WHERE entry_id = ?2", table), [to, from])
+            .map_err(|e| e.to_string())?;
+        conn.execute(&format!("DELETE FROM {} WHERE entry_id = ?1", table), [from])
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Give the new entry `to` the tags and commit links of `from`
+fn copy_links(conn: &Connection, journal: &mut Recorder, from: i64, to: i64) -> Result<(), String> {
+    let copies = [
+        ("entry_tags", "INSERT OR IGNORE INTO entry_tags (entry_id, tag) SELECT ?1, tag FROM entry_tags WHERE entry_id = ?2"),
+        (
+            "entry_commits",
+            "INSERT OR IGNORE INTO entry_commits (entry_id, hash, message, committed_at, repo_path)
+             SELECT ?1, hash, message, committed_at, repo_path FROM entry_commits WHERE entry_id = ?2",
+        ),
+    ];
+    for (table, sql) in copies {
+        conn.execute(sql, [to, from]).map_err(|e| e.to_string())?;
+        let rowids = conn
+            .prepare(&format!("SELECT rowid FROM {} WHERE entry_id = ?1", table))
+            .and_then(|mut stmt| stmt.query_map([to], |row| row.get::<_, i64>(0))?.collect::<rusqlite::Result<Vec<_>>>())
+            .map_err(|e| e.to_string())?;
+        for rowid in rowids {
+            journal.track_new(table, rowid);
+        }
+    }
+    Ok(())
+}
+
+fn resolve(conn: &Connection, first_id: i64, second_id: Option<i64>, resolution: Resolution) -> Result<(), String> {
+    let first = load_entry(conn, first_id).map_err(|e| e.to_string())?.ok_or("Entry not found")?;
+    let mut journal = Recorder::new("Resolve conflict");
+    for id in std::iter::once(first_id).chain(second_id) {
+        journal.track(conn, "time_entries", id).map_err(|e| e.to_string())?;
+    }
+
+    // The two entries of an overlap, earlier one first
+    let pair = || -> Result<(EntrySummary, EntrySummary), String> {
+        let second_id = second_id.ok_or("This resolution needs two entries")?;
+        let second = load_entry(conn, second_id).map_err(|e| e.to_string())?.ok_or("Entry not found")?;
+        Ok(if second.start < first.start { (second, first.clone()) } else { (first.clone(), second) })
+    };
+
+    match resolution {
+        Resolution::Swap => {
+            let end = first.end.ok_or("A running entry has no end to swap")?;
+            write_entry(conn, first.id, first.project_id, end.min(first.start), Some(end.max(first.start)))
+                .map_err(|e| e.to_string())?;
+        }
+        Resolution::TrimFirst => {
+            let (first, second) = pair()?;
+            let first_end = first.end.ok_or("Stop the running entry before trimming it")?;
+            // Trimming must not lengthen the first entry
+            if second.start >= first_end {
+                return Err("These entries do not overlap".into());
+            }
+            write_entry(conn, first.id, first.project_id, first.start, Some(second.start))
+                .map_err(|e| e.to_string())?;
+        }
+        Resolution::TrimSecond => {
+            let (first, second) = pair()?;
+            let first_end = first.end.ok_or("The earlier entry is still running")?;
+            if second.end.is_some_and(|end| end <= first_end) {
+                return Err("The second entry lies inside the first; split or merge instead".into());
+            }
+            write_entry(conn, second.id, second.project_id, first_end, second.end)
+                .map_err(|e| e.to_string())?;
+        }
+        Resolution::Split => {
+            let (first, second) = pair()?;
+            let first_end = first.end.ok_or("Stop the running entry before splitting it")?;
+            let second_end = second.end.ok_or("The inner entry is still running")?;
+            if second_end > first_end {
+                return Err("Splitting needs one entry inside the other".into());
+            }
+            write_entry(conn, first.id, first.project_id, first.start, Some(second.start))
+                .map_err(|e| e.to_string())?;
+            if second_end < first_end {
+                let tail_id = insert_entry(conn, first.project_id, second_end, first_end, first.timezone.as_deref()).map_err(|e| e.to_string())?;
+                journal.track_new("time_entries", tail_id);
+                copy_links(conn, &mut journal, first.id, tail_id)?;
+            }
+        }
+        Resolution::Merge => {
+            let (first, second) = pair()?;
+            // A running entry keeps the merged entry running
+            let end = match (first.end, second.end) {
+                (Some(a), Some(b)) => Some(a.max(b)),
+                _ => None,
+            };
+            validate(conn, &[first.id, second.id], first.start, end)?;
+            move_links(conn, &mut journal, second.id, first.id)?;
+            conn.execute("DELETE FROM time_entries WHERE id = ?1", [second.id])
+                .map_err(|e| e.to_string())?;
+            write_entry(conn, first.id, first.project_id, first.start, end).map_err(|e| e.to_string())?;
+        }
+    }
+
+    journal.finish(conn).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn resolve_entry_conflict(app: tauri::AppHandle, first_id: i64, second_id: Option<i64>, resolution: Resolution) -> Result<(), String> {
+    let mut conn = db::open().ok_or("Database not found")?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    resolve(&tx, first_id, second_id, resolution)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    let _ = app.emit("entries-changed", first_id);
+    Ok(())
+}
+
+/// Split an entry at `at` (UTC "YYYY-MM-DD HH:MM:SS"). The second part goes to
+/// `project_id`, or stays with the entry's project. Splitting a running entry
+/// keeps the second part running. Returns the id of the second part.
+#[tauri::command]
+pub fn split_time_entry(app: tauri::AppHandle, id: i64, at: String, project_id: Option<i64>) -> Result<i64, String> {
+    let at = parse_db_time(&at)?;
+    let mut conn = db::open().ok_or("Database not found")?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let entry = load_entry(&tx, id).map_err(|e| e.to_string())?.ok_or("Entry not found")?;
+    let second_id = split(&tx, &entry, at, project_id)?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    if entry.end.is_none() {
+        let _ = app.emit("timer-changed", ());
+    }
+    let _ = app.emit("entries-changed", second_id);
+    Ok(second_id)
+}
+
+fn split(conn: &Connection, entry: &EntrySummary, at: i64, project_id: Option<i64>) -> Result<i64, String> {
+    let end = entry.end.unwrap_or_else(|| chrono::Utc::now().timestamp());
+    if at <= entry.start || at >= end {
+        return Err("The split time must fall inside the entry".into());
+    }
+
+    let mut journal = Recorder::new("Split entry");
+    journal.track(conn, "time_entries", entry.id).map_err(|e| e.to_string())?;
+    write_entry(conn, entry.id, entry.project_id, entry.start, Some(at)).map_err(|e| e.to_string())?;
+    let second_project = project_id.unwrap_or(entry.project_id);
+    let second_id = match entry.end {
+        Some(end) => insert_entry(conn, second_project, at, end, entry.timezone.as_deref()),
+        None => conn
+            .execute(
+                "INSERT INTO time_entries (project_id, start_time, timezone) VALUES (?1, datetime(?2, 'unixepoch'), ?3)",
+                rusqlite::params![second_project, at, entry.timezone.as_deref().unwrap_or(db::current_zone_name())],
+            )
+            .map(|_| conn.last_insert_rowid()),
+    }
+    .map_err(|e| e.to_string())?;
+    journal.track_new("time_entries", second_id);
+    journal.finish(conn).map_err(|e| e.to_string())?;
+    Ok(second_id)
+}
+
+/// Largest pause between two entries that `merge_time_entries` bridges
+pub const MAX_MERGE_GAP_SECS: i64 = 5 * 60;
+
+/// Merge two entries of the same project that touch or are at most
+/// `MAX_MERGE_GAP_SECS` apart into the earlier one, taking over the later
+/// entry's tags and commits. Returns the id of the merged entry.
+#[tauri::command]
+pub fn merge_time_entries(app: tauri::AppHandle, first_id: i64, second_id: i64) -> Result<i64, String> {
+    let mut conn = db::open().ok_or("Database not found")?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let merged = merge(&tx, first_id, second_id)?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    let _ = app.emit("entries-changed", merged);
+    Ok(merged)
+}
+
+fn merge(conn: &Connection, first_id: i64, second_id: i64) -> Result<i64, String> {
+    let a = load_entry(conn, first_id).map_err(|e| e.to_string())?.ok_or("Entry not found")?;
+    let b = load_entry(conn, second_id).map_err(|e| e.to_string())?.ok_or("Entry not found")?;
+    if a.id == b.id {
+        return Err("Pick two different entries".into());
+    }
+    if a.project_id != b.project_id {
+        return Err("Only entries of the same project can be merged".into());
+    }
+    let (first, second) = if b.start < a.start { (b, a) } else { (a, b) };
+    // Merging would otherwise silently book the pause between them
+    if first.end.is_some_and(|end| second.start - end > MAX_MERGE_GAP_SECS) {
+        return Err(format!(
+            "Only entries at most {} minutes apart can be merged",
+            MAX_MERGE_GAP_SECS / 60
+        ));
+    }
+    // A running entry keeps the merged entry running
+    let end = match (first.end, second.end) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        _ => None,
+    };
+    validate(conn, &[first.id, second.id], first.start, end)
+        .map_err(|_| "Another entry lies between these entries".to_string())?;
+
+    let mut journal = Recorder::new("Merge entries");
+    journal.track(conn, "time_entries", first.id).map_err(|e| e.to_string())?;
+    journal.track(conn, "time_entries", second.id).map_err(|e| e.to_string())?;
+    move_links(conn, &mut journal, second.id, first.id)?;
+    conn.execute("DELETE FROM time_entries WHERE id = ?1", [second.id])
+        .map_err(|e| e.to_string())?;
+    write_entry(conn, first.id, first.project_id, first.start, end).map_err(|e| e.to_string())?;
+    journal.finish(conn).map_err(|e| e.to_string())?;
+    Ok(first.id)
+}
+
+/// Copy a finished entry to the same local time, in the zone it was recorded
+/// in, on another date ("YYYY-MM-DD"). Returns the id of the copy.
+#[tauri::command]
+pub fn duplicate_time_entry(app: tauri::AppHandle, id: i64, date: String) -> Result<i64, String> {
+    let target = NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| e.to_string())?;
+    let mut conn = db::open().ok_or("Database not found")?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let new_id = duplicate(&tx, id, target)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    let _ = app.emit("entries-changed", new_id);
+    Ok(new_id)
+}
+
+fn duplicate(conn: &Connection, id: i64, target: NaiveDate) -> Result<i64, String> {
+    let entry = load_entry(conn, id).map_err(|e| e.to_string())?.ok_or("Entry not found")?;
+    let end = entry.end.ok_or("A running entry cannot be duplicated")?;
+
+    let tz = zones::entry_zone(entry.timezone.as_deref());
+    let start_local = tz
+        .timestamp_opt(entry.start, 0)
+        .single()
+        .ok_or("Invalid entry start")?
+        .naive_local();
+    let start = tz
+        .from_local_datetime(&target.and_time(start_local.time()))
+        .earliest()
+        .ok_or("That time does not exist on the chosen date")?
+        .timestamp();
+    let end = start + (end - entry.start);
+
+    validate(conn, &[], start, Some(end))?;
+    let new_id = insert_entry(conn, entry.project_id, start, end, entry.timezone.as_deref()).map_err(|e| e.to_string())?;
+    let mut journal = Recorder::new("Duplicate entry");
+    journal.track_new("time_entries", new_id);
+    journal.finish(conn).map_err(|e| e.to_string())?;
+    Ok(new_id)
+}
+
+/// Start tracking a project, stopping whatever is running
+#[tauri::command]
+pub fn start_time_entry(project_id: i64, source: Option<ChangeSource>) -> Result<i64, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    db::start_time_entry(&conn, project_id, source.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn stop_time_entry(id: i64, source: Option<ChangeSource>) -> Result<(), String> {
+    let mut conn = db::open().ok_or("Database not found")?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut journal = Recorder::new("Stop timer").source(source.unwrap_or_default());
+    journal.track(&tx, "time_entries", id).map_err(|e| e.to_string())?;
+    tx.execute(
+        "UPDATE time_entries
+         SET end_time = datetime('now'),
+             duration = CAST((julianday(datetime('now')) - julianday(start_time)) * 86400 AS INTEGER)
+         WHERE id = ?1 AND end_time IS NULL",
+        [id],
+    )
+    .map_err(|e| e.to_string())?;
+    journal.finish(&tx).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_time_entry(app: tauri::AppHandle, id: i64) -> Result<(), String> {
+    let mut conn = db::open().ok_or("Database not found")?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut journal = Recorder::new("Delete entry");
+    journal.track(&tx, "time_entries", id).map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM time_entries WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    journal.finish(&tx).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    let _ = app.emit("entries-changed", id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> i64 {
+        parse_db_time(text).unwrap()
+    }
+
+    fn setup() -> Connection {
+        let conn = db::testing::memory();
+        conn.execute_batch("INSERT INTO projects (id, name, color) VALUES (1, 'Horalis', '#111'), (2, 'Billing', '#222');")
+            .unwrap();
+        conn
+    }
+
+    fn add(conn: &Connection, project_id: i64, start: &str, end: Option<&str>) -> i64 {
+        conn.execute(
+            "INSERT INTO time_entries (project_id, start_time, end_time, duration, timezone)
+             VALUES (?1, ?2, ?3, strftime('%s', ?3) - strftime('%s', ?2), 'Europe/Berlin')",
+            rusqlite::params![project_id, start, end],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn link(conn: &Connection, entry_id: i64, tag: &str, hash: &str) {
+        conn.execute("INSERT INTO entry_tags (entry_id, tag) VALUES (?1, ?2)", rusqlite::params![entry_id, tag]).unwrap();
+        conn.execute(
+            "INSERT INTO entry_commits (entry_id, hash, message, committed_at, repo_path)
+             VALUES (?1, ?2, 'Fix', '2026-10-19 09:30:00', '/repo')",
+            rusqlite::params![entry_id, hash],
+        )
+        .unwrap();
+    }
+
+    /// (start, end, duration) of an entry, None once deleted
+    fn interval(conn: &Connection, id: i64) -> Option<(String, Option<String>, Option<i64>)> {
+        conn.query_row("SELECT start_time, end_time, duration FROM time_entries WHERE id = ?1", [id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })
+        .optional()
+        .unwrap()
+    }
+
+    fn finished(start: &str, end: &str, duration: i64) -> Option<(String, Option<String>, Option<i64>)> {
+        Some((start.to_string(), Some(end.to_string()), Some(duration)))
+    }
+
+    fn links(conn: &Connection, entry_id: i64) -> (Vec<String>, Vec<String>) {
+        let list = |sql: &str| -> Vec<String> {
+            conn.prepare(sql).unwrap().query_map([entry_id], |row| row.get(0)).unwrap().collect::<rusqlite::Result<_>>().unwrap()
+        };
+        (
+            list("SELECT tag FROM entry_tags WHERE entry_id = ?1 ORDER BY tag"),
+            list("SELECT hash FROM entry_commits WHERE entry_id = ?1 ORDER BY hash"),
+        )
+    }
+
+    fn count(conn: &Connection, sql: &str) -> i64 {
+        conn.query_row(sql, [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn validate_refuses_backwards_and_overlapping_entries() {
+        let conn = setup();
+        let id = add(&conn, 1, "2026-10-19 09:00:00", Some("2026-10-19 10:00:00"));
+        let check = |exclude: &[i64], start: &str, end: &str| validate(&conn, exclude, at(start), Some(at(end)));
+
+        assert_eq!(check(&[], "2026-10-19 12:00:00", "2026-10-19 11:00:00").unwrap_err(), "An entry cannot end before it starts");
+        assert_eq!(check(&[], "2026-10-19 09:30:00", "2026-10-19 11:00:00").unwrap_err(), format!("Overlaps with a Horalis entry (#{})", id));
+        assert!(check(&[id], "2026-10-19 09:30:00", "2026-10-19 11:00:00").is_ok());
+        // Touching is fine
+        assert!(check(&[], "2026-10-19 10:00:00", "2026-10-19 11:00:00").is_ok());
+        assert!(check(&[], "2026-10-19 08:00:00", "2026-10-19 09:00:00").is_ok());
+    }
+
+    #[test]
+    fn validate_counts_running_entries_until_now() {
+        let conn = setup();
+        let now = chrono::Utc::now().timestamp();
+        conn.execute(
+            "INSERT INTO time_entries (project_id, start_time) VALUES (1, datetime(?1, 'unixepoch'))",
+            [now - 3600],
+        )
+        .unwrap();
+        assert!(validate(&conn, &[], now - 600, Some(now - 300)).is_err());
+        assert!(validate(&conn, &[], now - 7200, Some(now - 3600)).is_ok());
+        // A new running entry reaches up to now as well
+        assert!(validate(&conn, &[], now - 7200, None).is_err());
+    }
+
+    #[test]
+    fn split_cuts_an_entry_in_two() {
+        let conn = setup();
+        let id = add(&conn, 1, "2026-10-19 09:00:00", Some("2026-10-19 11:00:00"));
+        let entry = load_entry(&conn, id).unwrap().unwrap();
+        assert!(split(&conn, &entry, at("2026-10-19 09:00:00"), None).is_err());
+        assert!(split(&conn, &entry, at("2026-10-19 11:00:00"), None).is_err());
+
+        let second = split(&conn, &entry, at("2026-10-19 10:15:00"), Some(2)).unwrap();
+        assert_eq!(interval(&conn, id), finished("2026-10-19 09:00:00", "2026-10-19 10:15:00", 4500));
+        assert_eq!(interval(&conn, second), finished("2026-10-19 10:15:00", "2026-10-19 11:00:00", 2700));
+        let project: i64 = conn.query_row("SELECT project_id FROM time_entries WHERE id = ?1", [second], |row| row.get(0)).unwrap();
+        assert_eq!(project, 2);
+    }
+
+    #[test]
+    fn split_keeps_the_second_part_running() {
+        let conn = setup();
+        let now = chrono::Utc::now().timestamp();
+        conn.execute("INSERT INTO time_entries (project_id, start_time) VALUES (1, datetime(?1, 'unixepoch'))", [now - 3600])
+            .unwrap();
+        let entry = load_entry(&conn, conn.last_insert_rowid()).unwrap().unwrap();
+        let second = split(&conn, &entry, now - 1800, None).unwrap();
+        assert_eq!(load_entry(&conn, entry.id).unwrap().unwrap().end, Some(now - 1800));
+        assert_eq!(load_entry(&conn, second).unwrap().unwrap().end, None);
+    }
+
+    #[test]
+    fn merge_takes_over_links_and_refuses_wide_gaps() {
+        let conn = setup();
+        let first = add(&conn, 1, "2026-10-19 09:00:00", Some("2026-10-19 10:00:00"));
+        let second = add(&conn, 1, "2026-10-19 10:04:00", Some("2026-10-19 10:30:00"));
+        let far = add(&conn, 1, "2026-10-19 10:40:00", Some("2026-10-19 11:00:00"));
+        let other = add(&conn, 2, "2026-10-19 11:00:00", Some("2026-10-19 11:10:00"));
+        link(&conn, first, "meeting", "aaa");
+        link(&conn, second, "meeting", "bbb");
+
+        assert_eq!(merge(&conn, first, first).unwrap_err(), "Pick two different entries");
+        assert_eq!(merge(&conn, far, other).unwrap_err(), "Only entries of the same project can be merged");
+        assert_eq!(merge(&conn, second, far).unwrap_err(), "Only entries at most 5 minutes apart can be merged");
+
+        assert_eq!(merge(&conn, second, first).unwrap(), first);
+        assert_eq!(interval(&conn, first), finished("2026-10-19 09:00:00", "2026-10-19 10:30:00", 5400));
+        assert_eq!(interval(&conn, second), None);
+        assert_eq!(links(&conn, first), (vec!["meeting".to_string()], vec!["aaa".to_string(), "bbb".to_string()]));
+        assert_eq!(count(&conn, &format!("SELECT count(*) FROM entry_tags WHERE entry_id = {}", second)), 0);
+    }
+
+    #[test]
+    fn duplicate_keeps_the_local_time() {
+        let conn = setup();
+        // 09:00-10:30 in Berlin (CEST)
+        let id = add(&conn, 1, "2026-10-19 07:00:00", Some("2026-10-19 08:30:00"));
+        // After the switch to CET the same local time is an hour later in UTC
+        let copy = duplicate(&conn, id, NaiveDate::from_ymd_opt(2026, 10, 26).unwrap()).unwrap();
+        assert_eq!(interval(&conn, copy), finished("2026-10-26 08:00:00", "2026-10-26 09:30:00", 5400));
+        // The copy is there now
+        assert!(duplicate(&conn, id, NaiveDate::from_ymd_opt(2026, 10, 26).unwrap()).unwrap_err().starts_with("Overlaps"));
+
+        conn.execute("INSERT INTO time_entries (project_id, start_time) VALUES (1, '2026-10-20 09:00:00')", []).unwrap();
+        let running = conn.last_insert_rowid();
+        assert_eq!(
+            duplicate(&conn, running, NaiveDate::from_ymd_opt(2026, 10, 27).unwrap()).unwrap_err(),
+            "A running entry cannot be duplicated"
+        );
+    }
+
+    /// Two overlapping entries: 09:00-10:00 and 09:40-10:20
+    fn overlap(conn: &Connection) -> (i64, i64) {
+        (
+            add(conn, 1, "2026-10-19 09:00:00", Some("2026-10-19 10:00:00")),
+            add(conn, 2, "2026-10-19 09:40:00", Some("2026-10-19 10:20:00")),
+        )
+    }
+
+    #[test]
+    fn trim_first_ends_it_where_the_second_starts() {
+        let conn = setup();
+        let (first, second) = overlap(&conn);
+        // Either order
+        resolve(&conn, second, Some(first), Resolution::TrimFirst).unwrap();
+        assert_eq!(interval(&conn, first), finished("2026-10-19 09:00:00", "2026-10-19 09:40:00", 2400));
+        assert_eq!(interval(&conn, second), finished("2026-10-19 09:40:00", "2026-10-19 10:20:00", 2400));
+    }
+
+    #[test]
+    fn trim_first_refuses_entries_that_do_not_overlap() {
+        let conn = setup();
+        let first = add(&conn, 1, "2026-10-19 09:00:00", Some("2026-10-19 10:00:00"));
+        let second = add(&conn, 2, "2026-10-19 11:00:00", Some("2026-10-19 12:00:00"));
+        assert_eq!(resolve(&conn, first, Some(second), Resolution::TrimFirst).unwrap_err(), "These entries do not overlap");
+        assert_eq!(interval(&conn, first), finished("2026-10-19 09:00:00", "2026-10-19 10:00:00", 3600));
+    }
+
+    #[test]
+    fn trim_first_refuses_a_running_entry() {
+        let conn = setup();
+        let first = add(&conn, 1, "2026-10-19 09:00:00", None);
+        let second = add(&conn, 2, "2026-10-19 09:40:00", Some("2026-10-19 10:20:00"));
+        assert_eq!(resolve(&conn, first, Some(second), Resolution::TrimFirst).unwrap_err(), "Stop the running entry before trimming it");
+        assert_eq!(resolve(&conn, first, None, Resolution::TrimFirst).unwrap_err(), "This resolution needs two entries");
+    }
+
+    #[test]
+    fn trim_second_starts_it_where_the_first_ends() {
+        let conn = setup();
+        let (first, second) = overlap(&conn);
+        resolve(&conn, first, Some(second), Resolution::TrimSecond).unwrap();
+        assert_eq!(interval(&conn, second), finished("2026-10-19 10:00:00", "2026-10-19 10:20:00", 1200));
+
+        let conn = setup();
+        let outer = add(&conn, 1, "2026-10-19 09:00:00", Some("2026-10-19 11:00:00"));
+        let inner = add(&conn, 2, "2026-10-19 09:30:00", Some("2026-10-19 10:00:00"));
+        assert!(resolve(&conn, outer, Some(inner), Resolution::TrimSecond).unwrap_err().contains("inside the first"));
+        let running = add(&conn, 1, "2026-10-19 12:00:00", None);
+        let later = add(&conn, 2, "2026-10-19 12:30:00", Some("2026-10-19 13:00:00"));
+        assert_eq!(resolve(&conn, running, Some(later), Resolution::TrimSecond).unwrap_err(), "The earlier entry is still running");
+    }
+
+    #[test]
+    fn split_resolution_gives_the_tail_the_links() {
+        let conn = setup();
+        let outer = add(&conn, 1, "2026-10-19 09:00:00", Some("2026-10-19 11:00:00"));
+        let inner = add(&conn, 2, "2026-10-19 09:30:00", Some("2026-10-19 10:00:00"));
+        link(&conn, outer, "client", "aaa");
+        resolve(&conn, outer, Some(inner), Resolution::Split).unwrap();
+
+        assert_eq!(interval(&conn, outer), finished("2026-10-19 09:00:00", "2026-10-19 09:30:00", 1800));
+        let tail: i64 = conn.query_row("SELECT id FROM time_entries WHERE start_time = '2026-10-19 10:00:00' AND project_id = 1", [], |row| row.get(0)).unwrap();
+        assert_eq!(interval(&conn, tail), finished("2026-10-19 10:00:00", "2026-10-19 11:00:00", 3600));
+        assert_eq!(links(&conn, tail), links(&conn, outer));
+        assert_eq!(links(&conn, tail).0, ["client"]);
+    }
+
+    #[test]
+    fn split_resolution_refuses_running_or_partial_overlaps() {
+        let conn = setup();
+        let (first, second) = overlap(&conn);
+        assert_eq!(resolve(&conn, first, Some(second), Resolution::Split).unwrap_err(), "Splitting needs one entry inside the other");
+        let outer = add(&conn, 1, "2026-10-19 12:00:00", None);
+        let inner = add(&conn, 2, "2026-10-19 12:30:00", Some("2026-10-19 13:00:00"));
+        assert_eq!(resolve(&conn, outer, Some(inner), Resolution::Split).unwrap_err(), "Stop the running entry before splitting it");
+    }
+
+    #[test]
+    fn merge_resolution_moves_links_and_validates() {
+        let conn = setup();
+        let (first, second) = overlap(&conn);
+        link(&conn, second, "review", "bbb");
+        resolve(&conn, first, Some(second), Resolution::Merge).unwrap();
+        assert_eq!(interval(&conn, first), finished("2026-10-19 09:00:00", "2026-10-19 10:20:00", 4800));
+        assert_eq!(interval(&conn, second), None);
+        assert_eq!(links(&conn, first), (vec!["review".to_string()], vec!["bbb".to_string()]));
+        assert_eq!(count(&conn, "SELECT count(*) FROM entry_commits WHERE entry_id != 1"), 0);
+
+        // The merged entry would cover a third one
+        let conn = setup();
+        let (first, second) = overlap(&conn);
+        conn.execute("UPDATE time_entries SET end_time = '2026-10-19 12:00:00', duration = 8400 WHERE id = ?1", [second]).unwrap();
+        add(&conn, 1, "2026-10-19 11:00:00", Some("2026-10-19 11:30:00"));
+        assert!(resolve(&conn, first, Some(second), Resolution::Merge).unwrap_err().starts_with("Overlaps"));
+        assert!(interval(&conn, second).is_some());
+    }
+
+    #[test]
+    fn swap_fixes_backwards_entries() {
+        let conn = setup();
+        conn.execute(
+            "INSERT INTO time_entries (project_id, start_time, end_time, duration) VALUES (1, '2026-10-19 10:00:00', '2026-10-19 09:00:00', -3600)",
+            [],
+        )
+        .unwrap();
+        let id = conn.last_insert_rowid();
+        resolve(&conn, id, None, Resolution::Swap).unwrap();
+        assert_eq!(interval(&conn, id), finished("2026-10-19 09:00:00", "2026-10-19 10:00:00", 3600));
+        let running = add(&conn, 1, "2026-10-19 12:00:00", None);
+        assert_eq!(resolve(&conn, running, None, Resolution::Swap).unwrap_err(), "A running entry has no end to swap");
+    }
+}
@@ -0,0 +1,400 @@
+// Gap filler: find untracked stretches inside working hours for a day and
+// fill them by extending a neighbouring entry, adding an entry for a project
+// or recording a break. Each fill is applied in one transaction and is a
+// single step in the undo journal.
+use chrono::{NaiveDate, TimeZone, Utc};
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::db;
+use crate::entries;
+use crate::journal::Recorder;
+use crate::schedule::{self, ReminderSchedule};
+use crate::settings::Settings;
+use crate::zones;
+
+// Short gaps between two different projects are most likely a pause
+const BREAK_SUGGESTION_SECS: i64 = 15 * 60;
+
+#[derive(Clone, serde::Serialize)]
+pub struct Neighbor {
+    entry_id: i64,
+    project_id: i64,
+    project_name: String,
+    project_color: String,
+}
+
+/// How to fill one gap
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum FillAction {
+    /// Stretch an adjacent entry over the gap
+    Extend { entry_id: i64 },
+    /// Add a new entry for a project
+    Assign { project_id: i64 },
+    /// Record the gap as a break
+    Break,
+    Skip,
+}
+
+#[derive(serde::Serialize)]
+pub struct Gap {
+    start: i64, // Unix seconds
+    end: i64,
+    duration: i64,
+    previous: Option<Neighbor>,
+    next: Option<Neighbor>,
+    suggestion: FillAction,
+}
+
+#[derive(serde::Deserialize)]
+pub struct GapFill {
+    start: i64,
+    end: i64,
+    #[serde(flatten)]
+    action: FillAction,
+}
+
+/// Working intervals within [day_start, day_end), at minute resolution so
+/// overnight windows, time zones and DST all follow `schedule::is_active`. A
+/// minute counts when the schedules cover both its start and its end, as
+/// windows include their end minute.
+fn working_intervals(schedules: &[ReminderSchedule], day_start: i64, day_end: i64) -> Vec<(i64, i64)> {
+    let active = |minute: i64| Utc.timestamp_opt(minute, 0).single().is_some_and(|t| schedule::is_active(schedules, t));
+    let mut intervals: Vec<(i64, i64)> = Vec::new();
+    let mut minute = day_start;
+    let mut starts_active = active(minute);
+    while minute + 60 <= day_end {
+        let ends_active = active(minute + 60);
+        if starts_active && ends_active {
+            match intervals.last_mut() {
+                Some(last) if last.1 == minute => last.1 = minute + 60,
+                _ => intervals.push((minute, minute + 60)),
+            }
+        }
+        starts_active = ends_active;
+        minute += 60;
+    }
+    intervals
+}
+
+/// Subtract sorted `covered` intervals from sorted `intervals`
+fn subtract(intervals: &[(i64, i64)], covered: &[(i64, i64)]) -> Vec<(i64, i64)> {
+    let mut result = Vec::new();
+    for &(start, end) in intervals {
+        let mut cursor = start;
+        for &(c_start, c_end) in covered {
+            if c_end <= cursor || c_start >= end {
+                continue;
+            }
+            if c_start > cursor {
+                result.push((cursor, c_start));
+            }
+            cursor = cursor.max(c_end);
+            if cursor >= end {
+                break;
+            }
+        }
+        if cursor < end {
+            result.push((cursor, end));
+        }
+    }
+    result
+}
+
+/// Entries overlapping [from, to) with their project, running entries ending now
+fn entries_between(conn: &Connection, from: i64, to: i64) -> rusqlite::Result<Vec<(i64, i64, Neighbor)>> {
+    let mut stmt = conn.prepare(
+        "SELECT te.id, te.project_id, p.name, p.color,
+                CAST(strftime('%s', te.start_time) AS INTEGER),
+                CAST(strftime('%s', COALESCE(te.end_time, datetime('now'))) AS INTEGER)
+         FROM time_entries te
+         JOIN projects p ON p.id = te.project_id
+         WHERE te.start_time < datetime(?2, 'unixepoch')
+           AND COALESCE(te.end_time, datetime('now')) > datetime(?1, 'unixepoch')
+         ORDER BY te.start_time",
+    )?;
+    let rows = stmt.query_map([from, to], |row| {
+        Ok((
+            row.get(4)?,
+            row.get(5)?,
+            Neighbor { entry_id: row.get(0)?, project_id: row.get(1)?, project_name: row.get(2)?, project_color: row.get(3)? },
+        ))
+    })?;
+    rows.collect()
+}
+
+fn breaks_between(conn: &Connection, from: i64, to: i64) -> rusqlite::Result<Vec<(i64, i64)>> {
+    let mut stmt = conn.prepare(
+        "SELECT CAST(strftime('%s', start_time) AS INTEGER), CAST(strftime('%s', end_time) AS INTEGER)
+         FROM break_log
+         WHERE kind = 'break' AND end_time IS NOT NULL
+           AND start_time < datetime(?2, 'unixepoch') AND end_time > datetime(?1, 'unixepoch')
+         ORDER BY start_time",
+    )?;
+    let rows = stmt.query_map([from, to], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+fn suggest(previous: &Option<Neighbor>, next: &Option<Neighbor>, duration: i64, fallback: Option<i64>) -> FillAction {
+    match (previous, next) {
+        (Some(p), Some(n)) if p.project_id == n.project_id => FillAction::Extend { entry_id: p.entry_id },
+        (Some(_), Some(_)) if duration <= BREAK_SUGGESTION_SECS => FillAction::Break,
+        (Some(p), _) => FillAction::Extend { entry_id: p.entry_id },
+        (None, Some(n)) => FillAction::Extend { entry_id: n.entry_id },
+        (None, None) => match fallback {
+            Some(project_id) => FillAction::Assign { project_id },
+            None => FillAction::Skip,
+        },
+    }
+}
+
+/// Untracked gaps of at least `min_minutes` (default 5) inside working hours on a
+/// date in the report zone ("YYYY-MM-DD"), each with a suggested way to fill it
+#[tauri::command]
+pub fn find_gaps(date: String, min_minutes: Option<u32>) -> Result<Vec<Gap>, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    gaps_on(&conn, zones::parse_date(&date)?, min_minutes, Utc::now().timestamp())
+}
+
+/// Gaps on `date`, a day in the report zone, up to `now`
+fn gaps_on(conn: &Connection, date: NaiveDate, min_minutes: Option<u32>, now: i64) -> Result<Vec<Gap>, String> {
+    let (day_start, day_end) = zones::day_bounds(date, zones::report_zone(conn).tz());
+    let min_secs = min_minutes.unwrap_or(5) as i64 * 60;
+
+    let working = working_intervals(&Settings::load(conn).workday_windows(), day_start, day_end.min(now));
+    let entries = entries_between(conn, day_start, day_end).map_err(|e| e.to_string())?;
+    let mut covered: Vec<(i64, i64)> = entries.iter().map(|(start, end, _)| (*start, *end)).collect();
+    covered.extend(breaks_between(conn, day_start, day_end).map_err(|e| e.to_string())?);
+    covered.sort();
+    let fallback = db::last_used_project(conn).map(|p| p.id);
+
+    let gaps = subtract(&working, &covered)
+        .into_iter()
+        .filter(|(start, end)| end - start >= min_secs)
+        .map(|(start, end)| {
+            // Gap boundaries are taken from entry boundaries, so neighbours touch exactly
+            let previous = entries.iter().find(|(_, e, _)| *e == start).map(|(_, _, n)| n.clone());
+            let next = entries.iter().find(|(s, _, _)| *s == end).map(|(_, _, n)| n.clone());
+            let suggestion = suggest(&previous, &next, end - start, fallback);
+            Gap { start, end, duration: end - start, previous, next, suggestion }
+        })
+        .collect();
+    Ok(gaps)
+}
+
+/// Apply the fills in one transaction, undone together by `undo`
+#[tauri::command]
+pub fn apply_gap_fills(app: tauri::AppHandle, fills: Vec<GapFill>) -> Result<(), String> {
+    use tauri::Emitter;
+
+    let mut conn = db::open().ok_or("Database not found")?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut journal = Recorder::new("Fill gaps");
+
+    for fill in &fills {
+        if fill.end <= fill.start {
+            return Err("A gap must end after it starts".into());
+        }
+        if matches!(fill.action, FillAction::Skip) {
+            continue;
+        }
+        // Refuse to double-book if entries changed since the gaps were computed
+        entries::validate(&tx, &[], fill.start, Some(fill.end))
+            .map_err(|e| format!("Entries changed since the gaps were found; please look again ({})", e))?;
+
+        match &fill.action {
+            FillAction::Extend { entry_id } => {
+                let (start, end): (i64, Option<i64>) = tx
+                    .query_row(
+                        "SELECT CAST(strftime('%s', start_time) AS INTEGER), CAST(strftime('%s', end_time) AS INTEGER)
+                         FROM time_entries WHERE id = ?1",
+                        [entry_id],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )
+                    .optional()
+                    .map_err(|e| e.to_string())?
+                    .ok_or("Entry not found")?;
+                // Only a neighbour may be stretched, or the entry would swallow
+                // whatever lies between it and the gap
+                if fill.end != start && end != Some(fill.start) {
+                    return Err("The gap does not touch the entry to extend".into());
+                }
+                let new_start = start.min(fill.start);
+                // A running entry keeps running
+                let new_end = end.map(|end| end.max(fill.end));
+                entries::validate(&tx, &[*entry_id], new_start, new_end)?;
+                journal.track(&tx, "time_entries", *entry_id).map_err(|e| e.to_string())?;
+                tx.execute(
+                    "UPDATE time_entries
+                     SET start_time = datetime(?1, 'unixepoch'),
+                         end_time = datetime(?2, 'unixepoch'),
+                         duration = ?2 - ?1
+                     WHERE id = ?3",
+                    rusqlite::params![new_start, new_end, entry_id],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+            FillAction::Assign { project_id } => {
+                let id = entries::insert_entry(&tx, *project_id, fill.start, fill.end, None).map_err(|e| e.to_string())?;
+                journal.track_new("time_entries", id);
+            }
+            FillAction::Break => {
+                tx.execute(
+                    "INSERT INTO break_log (kind, start_time, end_time, duration)
+                     VALUES ('break', datetime(?1, 'unixepoch'), datetime(?2, 'unixepoch'), ?2 - ?1)",
+                    [fill.start, fill.end],
+                )
+                .map_err(|e| e.to_string())?;
+                journal.track_new("break_log", tx.last_insert_rowid());
+            }
+            FillAction::Skip => {}
+        }
+    }
+
+    journal.finish(&tx).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    let _ = app.emit("entries-changed", ());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> i64 {
+        entries::parse_db_time(text).unwrap()
+    }
+
+    fn window(weekdays: Vec<u32>, start: &str, end: &str, timezone: &str) -> ReminderSchedule {
+        ReminderSchedule { weekdays, start_time: start.into(), end_time: end.into(), timezone: Some(timezone.into()) }
+    }
+
+    fn intervals(items: &[(&str, &str)]) -> Vec<(i64, i64)> {
+        items.iter().map(|(start, end)| (at(start), at(end))).collect()
+    }
+
+    #[test]
+    fn working_intervals_end_with_the_window() {
+        // Monday 2026-10-19
+        let day = (at("2026-10-19 00:00:00"), at("2026-10-20 00:00:00"));
+        let office = [window(vec![1], "09:00", "18:00", "UTC")];
+        assert_eq!(working_intervals(&office, day.0, day.1), intervals(&[("2026-10-19 09:00:00", "2026-10-19 18:00:00")]));
+        // Cut short by the end of the range, e.g. now
+        assert_eq!(
+            working_intervals(&office, day.0, at("2026-10-19 12:30:00")),
+            intervals(&[("2026-10-19 09:00:00", "2026-10-19 12:30:00")])
+        );
+        assert!(working_intervals(&office, at("2026-10-20 00:00:00"), at("2026-10-21 00:00:00")).is_empty());
+
+        let split = [window(vec![1], "09:00", "12:00", "UTC"), window(vec![1], "13:00", "17:30", "UTC")];
+        assert_eq!(
+            working_intervals(&split, day.0, day.1),
+            intervals(&[("2026-10-19 09:00:00", "2026-10-19 12:00:00"), ("2026-10-19 13:00:00", "2026-10-19 17:30:00")])
+        );
+    }
+
+    #[test]
+    fn working_intervals_follow_overnight_windows_and_zones() {
+        let night = [window(vec![1], "22:00", "02:00", "UTC")];
+        assert_eq!(
+            working_intervals(&night, at("2026-10-19 00:00:00"), at("2026-10-20 00:00:00")),
+            intervals(&[("2026-10-19 22:00:00", "2026-10-20 00:00:00")])
+        );
+        assert_eq!(
+            working_intervals(&night, at("2026-10-20 00:00:00"), at("2026-10-21 00:00:00")),
+            intervals(&[("2026-10-20 00:00:00", "2026-10-20 02:00:00")])
+        );
+        // 09:00-17:00 in Berlin on the day the clocks go back (CEST until 03:00)
+        let berlin = [window(vec![0], "09:00", "17:00", "Europe/Berlin")];
+        assert_eq!(
+            working_intervals(&berlin, at("2026-10-24 22:00:00"), at("2026-10-25 23:00:00")),
+            intervals(&[("2026-10-25 08:00:00", "2026-10-25 16:00:00")])
+        );
+    }
+
+    type Spans<'a> = &'a [(&'a str, &'a str)];
+
+    #[test]
+    fn subtract_leaves_the_uncovered_parts() {
+        let working = intervals(&[("2026-10-19 09:00:00", "2026-10-19 12:00:00"), ("2026-10-19 13:00:00", "2026-10-19 18:00:00")]);
+        let cases: [(Spans, Spans); 4] = [
+            (&[], &[("2026-10-19 09:00:00", "2026-10-19 12:00:00"), ("2026-10-19 13:00:00", "2026-10-19 18:00:00")]),
+            // Overlapping covers, one reaching into the lunch break
+            (
+                &[("2026-10-19 08:00:00", "2026-10-19 10:00:00"), ("2026-10-19 09:30:00", "2026-10-19 11:00:00"), ("2026-10-19 11:30:00", "2026-10-19 14:00:00")],
+                &[("2026-10-19 11:00:00", "2026-10-19 11:30:00"), ("2026-10-19 14:00:00", "2026-10-19 18:00:00")],
+            ),
+            (&[("2026-10-19 10:00:00", "2026-10-19 10:30:00")], &[
+                ("2026-10-19 09:00:00", "2026-10-19 10:00:00"),
+                ("2026-10-19 10:30:00", "2026-10-19 12:00:00"),
+                ("2026-10-19 13:00:00", "2026-10-19 18:00:00"),
+            ]),
+            (&[("2026-10-19 07:00:00", "2026-10-19 19:00:00")], &[]),
+        ];
+        for (covered, expected) in cases {
+            assert_eq!(subtract(&working, &intervals(covered)), intervals(expected));
+        }
+    }
+
+    /// Office hours 09:00-18:00 on Mondays in Tokyo, which is also the report zone
+    fn setup() -> Connection {
+        let conn = db::testing::memory();
+        conn.execute_batch(
+            r#"INSERT INTO settings (key, value) VALUES
+                   ('report_timezone', 'Asia/Tokyo'),
+                   ('reminder_weekdays', ''),
+                   ('reminder_schedules', '[{"weekdays":[1],"start_time":"09:00","end_time":"18:00","timezone":"Asia/Tokyo"}]');
+               INSERT INTO projects (id, name, color) VALUES (1, 'Horalis', '#111'), (2, 'Billing', '#222');"#,
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn gaps_cover_the_report_zone_day() {
+        let conn = setup();
+        // 09:00-10:00 and 12:00-13:00 Tokyo time, a break 14:00-14:30
+        conn.execute_batch(
+            "INSERT INTO time_entries (id, project_id, start_time, end_time, duration) VALUES
+                 (1, 1, '2026-10-19 00:00:00', '2026-10-19 01:00:00', 3600),
+                 (2, 1, '2026-10-19 03:00:00', '2026-10-19 04:00:00', 3600);
+             INSERT INTO break_log (kind, start_time, end_time, duration) VALUES ('break', '2026-10-19 05:00:00', '2026-10-19 05:30:00', 1800);",
+        )
+        .unwrap();
+        let date = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let gaps = gaps_on(&conn, date, None, at("2026-10-20 12:00:00")).unwrap();
+        let spans: Vec<(i64, i64)> = gaps.iter().map(|g| (g.start, g.end)).collect();
+        assert_eq!(
+            spans,
+            intervals(&[
+                ("2026-10-19 01:00:00", "2026-10-19 03:00:00"),
+                ("2026-10-19 04:00:00", "2026-10-19 05:00:00"),
+                ("2026-10-19 05:30:00", "2026-10-19 09:00:00"),
+            ])
+        );
+        // Between two entries of one project: stretch the earlier one
+        assert!(matches!(gaps[0].suggestion, FillAction::Extend { entry_id: 1 }));
+        assert!(matches!(gaps[1].suggestion, FillAction::Extend { entry_id: 2 }));
+        assert_eq!(gaps[2].duration, 3 * 3600 + 1800);
+    }
+
+    #[test]
+    fn gaps_stop_at_now_and_skip_short_ones() {
+        let conn = setup();
+        conn.execute(
+            "INSERT INTO time_entries (id, project_id, start_time, end_time, duration) VALUES (1, 2, '2026-10-19 00:03:00', '2026-10-19 02:00:00', 6900)",
+            [],
+        )
+        .unwrap();
+        let date = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let gaps = gaps_on(&conn, date, None, at("2026-10-19 03:00:00")).unwrap();
+        let spans: Vec<(i64, i64)> = gaps.iter().map(|g| (g.start, g.end)).collect();
+        assert_eq!(spans, intervals(&[("2026-10-19 02:00:00", "2026-10-19 03:00:00")]));
+        let gaps = gaps_on(&conn, date, Some(2), at("2026-10-19 03:00:00")).unwrap();
+        assert_eq!(gaps.len(), 2);
+        // Only an entry after the three minute gap, so it starts earlier
+        assert!(matches!(gaps[0].suggestion, FillAction::Extend { entry_id: 1 }));
+        assert!(matches!(gaps[1].suggestion, FillAction::Extend { entry_id: 1 }));
+    }
+}
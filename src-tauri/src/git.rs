@@ -0,0 +1,293 @@
+// Git-aware tracking: projects map to local repositories. Commits made while
+// an entry was running are attached to it, and an optional watcher switches
+// the running project when a mapped repository sees a commit or checkout.
+//
+// Repositories are read with the `git` executable, so it must be on PATH.
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use rusqlite::Connection;
+
+use crate::db;
+use crate::settings::Settings;
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(15);
+// How far back the watcher re-attaches commits after a change
+const WATCH_SYNC_DAYS: u32 = 1;
+
+#[derive(serde::Serialize)]
+pub struct ProjectRepo {
+    id: i64,
+    project_id: i64,
+    path: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct Commit {
+    hash: String,
+    message: String,
+    committed_at: i64,
+    repo_path: String,
+}
+
+/// Run git in `repo` and return trimmed stdout, or None if it failed
+fn git(repo: &str, args: &[&str]) -> Option<String> {
+    let mut command = Command::new("git");
+    command.arg("-C").arg(repo).args(args);
+    // The watcher runs git every few seconds; keep it from flashing a console window
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = command.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Escape the metacharacters of a POSIX extended regex, e.g. the dots and a
+/// plus sign in "first.last+work@example.com"
+fn escape_regex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\.^$|?*+()[]{}".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Commits in `repo` between two Unix timestamps, limited to the configured
+/// git user so teammates' work is not attached to our entries
+pub fn read_commits(repo: &str, since_secs: i64, until_secs: i64) -> Vec<Commit> {
+    let since = format!("--since=@{}", since_secs);
+    let until = format!("--until=@{}", until_secs);
+    let mut args = vec!["log", "--all", "--no-merges", "--format=%H%x1f%ct%x1f%s", since.as_str(), until.as_str()];
+    let author = git(repo, &["config", "user.email"]).filter(|email| !email.is_empty());
+    // --author is a regex over "Name <email>"; match the bracketed address literally
+    let author_arg = author.map(|email| format!("--author=<{}>", escape_regex(&email)));
+    if let Some(arg) = &author_arg {
+        args.push("--extended-regexp");
+        args.push(arg);
+    }
+
+    let Some(log) = git(repo, &args) else {
+        return Vec::new();
+    };
+    log.lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\u{1f}');
+            let hash = parts.next()?.to_string();
+            let committed_at = parts.next()?.parse().ok()?;
+            let message = parts.next().unwrap_or_default().to_string();
+            Some(Commit { hash, message, committed_at, repo_path: repo.to_string() })
+        })
+        .collect()
+}
+
+fn load_repos(conn: &Connection) -> rusqlite::Result<Vec<ProjectRepo>> {
+    // Mappings of deleted projects are skipped
+    let mut stmt = conn.prepare(
+        "SELECT r.id, r.project_id, r.path
+         FROM project_repos r
+         JOIN projects p ON p.id = r.project_id
+         ORDER BY r.project_id, r.path",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(ProjectRepo { id: row.get(0)?, project_id: row.get(1)?, path: row.get(2)? })
+    })?;
+    rows.collect()
+}
+
+/// Attach commits from each project's repositories to that project's entries
+/// that started in the last `days` days. Returns the number of new links.
+pub fn sync_commits(conn: &Connection, days: u32) -> rusqlite::Result<usize> {
+    let repos = load_repos(conn)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, CAST(strftime('%s', start_time) AS INTEGER),
+                CAST(strftime('%s', COALESCE(end_time, datetime('now'))) AS INTEGER)
+         FROM time_entries
+         WHERE project_id = ?1 AND start_time >= datetime('now', ?2)
+         ORDER BY start_time",
+    )?;
+
+    let mut attached = 0;
+    for repo in &repos {
+        let entries = stmt
+            .query_map(rusqlite::params![repo.project_id, format!("-{} days", days)], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let (Some(first), Some(last)) = (entries.first(), entries.iter().map(|e| e.2).max()) else {
+            continue;
+        };
+
+        // One git call per repository, then match commits to entries in memory
+        for commit in read_commits(&repo.path, first.1, last) {
+            let entry = entries
+                .iter()
+                .find(|(_, start, end)| commit.committed_at >= *start && commit.committed_at <= *end);
+            if let Some((entry_id, _, _)) = entry {
+                attached += conn.execute(
+                    "INSERT OR IGNORE INTO entry_commits (entry_id, hash, message, committed_at, repo_path)
+                     VALUES (?1, ?2, ?3, datetime(?4, 'unixepoch'), ?5)",
+                    rusqlite::params![entry_id, commit.hash, commit.message, commit.committed_at, commit.repo_path],
+                )?;
+            }
+        }
+    }
+    Ok(attached)
+}
+
+/// Current HEAD of a repository: the commit hash plus the checked-out ref, so
+/// both new commits and branch switches register as a change
+fn head_state(repo: &str) -> Option<String> {
+    git(repo, &["rev-parse", "HEAD", "--symbolic-full-name", "HEAD"])
+}
+
+/// Spawn the repository watcher. Like the activity sampler it reads its
+/// setting on every tick, so it can be toggled without a restart.
+pub fn start_watcher(app: tauri::AppHandle) {
+    std::thread::spawn(move || {
+        let mut heads: HashMap<String, String> = HashMap::new();
+
+        loop {
+            std::thread::sleep(WATCH_INTERVAL);
+            let Some(conn) = db::open() else {
+                continue;
+            };
+            if !Settings::load(&conn).git_watch_enabled {
+                heads.clear();
+                continue;
+            }
+            let repos = match load_repos(&conn) {
+                Ok(repos) => repos,
+                Err(e) => {
+                    eprintln!("Failed to load project repositories: {}", e);
+                    continue;
+                }
+            };
+
+            let mut changed_project = None;
+            for repo in &repos {
+                let Some(state) = head_state(&repo.path) else {
+                    continue;
+                };
+                // The first observation only records a baseline
+                if let Some(previous) = heads.insert(repo.path.clone(), state.clone()) {
+                    if previous != state {
+                        changed_project = Some(repo.project_id);
+                    }
+                }
+            }
+            let Some(project_id) = changed_project else {
+                continue;
+            };
+
+            // Only a running timer is switched; nothing starts on its own
+            let running_project: Option<i64> = conn
+                .query_row("SELECT project_id FROM time_entries WHERE end_time IS NULL LIMIT 1", [], |row| row.get(0))
+                .ok();
+            if running_project.is_some_and(|running| running != project_id) {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = crate::start_project_timer_native(&app, Some(project_id), crate::audit::ChangeSource::Automatic).await {
+                        eprintln!("Failed to switch project from repository: {}", e);
+                    }
+                });
+            }
+            if let Err(e) = sync_commits(&conn, WATCH_SYNC_DAYS) {
+                eprintln!("Failed to attach commits: {}", e);
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn list_project_repos() -> Result<Vec<ProjectRepo>, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    load_repos(&conn).map_err(|e| e.to_string())
+}
+
+/// Map a repository to a project. Any path inside the working tree is accepted
+/// and stored as the repository root.
+#[tauri::command]
+pub fn add_project_repo(project_id: i64, path: String) -> Result<ProjectRepo, String> {
+    if !Path::new(&path).is_dir() {
+        return Err(format!("{} is not a directory", path));
+    }
+    let root = git(&path, &["rev-parse", "--show-toplevel"]).ok_or_else(|| format!("{} is not a git repository", path))?;
+
+    let mut conn = db::open().ok_or("Database not found")?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "INSERT INTO project_repos (project_id, path) VALUES (?1, ?2)",
+        rusqlite::params![project_id, root],
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::SqliteFailure(err, _) if err.code == rusqlite::ErrorCode::ConstraintViolation => {
+            format!("{} is already mapped to a project", root)
+        }
+        e => e.to_string(),
+    })?;
+    let id = tx.last_insert_rowid();
+    let mut journal = crate::journal::Recorder::new("Add repository");
+    journal.track_new("project_repos", id);
+    journal.finish(&tx).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(ProjectRepo { id, project_id, path: root })
+}
+
+#[tauri::command]
+pub fn remove_project_repo(id: i64) -> Result<(), String> {
+    let mut conn = db::open().ok_or("Database not found")?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut journal = crate::journal::Recorder::new("Remove repository");
+    journal.track(&tx, "project_repos", id).map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM project_repos WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    journal.finish(&tx).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Attach commits to entries from the last `days` days (default 30)
+#[tauri::command]
+pub fn sync_git_commits(days: Option<u32>) -> Result<usize, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    sync_commits(&conn, days.unwrap_or(30)).map_err(|e| e.to_string())
+}
+
+/// Commits attached to the given entries, keyed by entry id
+#[tauri::command]
+pub fn get_entry_commits(entry_ids: Vec<i64>) -> Result<HashMap<i64, Vec<Commit>>, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT hash, message, CAST(strftime('%s', committed_at) AS INTEGER), repo_path
+             FROM entry_commits
+             WHERE entry_id = ?1
+             ORDER BY committed_at",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut commits: HashMap<i64, Vec<Commit>> = HashMap::new();
+    for entry_id in entry_ids {
+        let rows = stmt
+            .query_map([entry_id], |row| {
+                Ok(Commit { hash: row.get(0)?, message: row.get(1)?, committed_at: row.get(2)?, repo_path: row.get(3)? })
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        if !rows.is_empty() {
+            commits.insert(entry_id, rows);
+        }
+    }
+    Ok(commits)
+}
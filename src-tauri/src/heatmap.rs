@@ -0,0 +1,106 @@
+// When time gets tracked: a weekday x hour-of-day matrix and per-project
+// distributions over hours and weekdays, for the heatmap on the Reports page.
+// Entries are split at local hour (and so day) boundaries by the report
+// engine; see report.rs.
+use crate::db;
+use crate::periods::Calendar;
+use crate::report::{self, Dimension, ReportQuery, ReportSpec};
+
+#[derive(serde::Serialize)]
+pub struct ProjectDistribution {
+    project_id: i64,
+    project_name: String,
+    project_color: String,
+    hours: [i64; 24],   // Minutes per local hour
+    weekdays: [i64; 7], // Minutes per weekday, 0 = Sunday
+    total: i64,         // Minutes
+}
+
+#[derive(serde::Serialize)]
+pub struct Heatmap {
+    /// First day of the week, so rows can be shown in the configured order
+    week_start: u32,
+    /// Minutes by weekday (0 = Sunday) and local hour
+    matrix: [[i64; 24]; 7],
+    hours: [i64; 24],
+    weekdays: [i64; 7],
+    /// Largest cell of `matrix`, to scale colors
+    max: i64,
+    total: i64,
+    projects: Vec<ProjectDistribution>,
+}
+
+/// Seconds to whole minutes, rounded to nearest
+fn minutes(seconds: i64) -> i64 {
+    (seconds + 30) / 60
+}
+
+/// Tracked minutes between two dates ("YYYY-MM-DD", inclusive, in the report
+/// zone) by weekday and hour, overall and per project
+#[tauri::command]
+pub fn get_heatmap(start_date: String, end_date: String) -> Result<Heatmap, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    let query = ReportQuery {
+        start_date,
+        end_date,
+        spec: ReportSpec {
+            group_by: vec![Dimension::Project, Dimension::Weekday, Dimension::Hour],
+            ..ReportSpec::default()
+        },
+    };
+    let report = report::run(&conn, &query)?;
+
+    // Add up seconds and round each sum once, so cells and totals agree
+    let mut matrix = [[0i64; 24]; 7];
+    let mut projects: Vec<(ProjectDistribution, [[i64; 24]; 7])> = Vec::new();
+    for row in &report.rows {
+        let (Some(project_id), Some(weekday), Some(hour)) = (row.project_id, row.weekday, row.hour) else {
+            continue;
+        };
+        let (weekday, hour) = (weekday as usize, hour as usize);
+        matrix[weekday][hour] += row.duration;
+        // Rows come grouped by project
+        if projects.last().is_none_or(|(p, _)| p.project_id != project_id) {
+            projects.push((
+                ProjectDistribution {
+                    project_id,
+                    project_name: row.project_name.clone().unwrap_or_default(),
+                    project_color: row.project_color.clone().unwrap_or_default(),
+                    hours: [0; 24],
+                    weekdays: [0; 7],
+                    total: 0,
+                },
+                [[0; 24]; 7],
+            ));
+        }
+        projects.last_mut().unwrap().1[weekday][hour] += row.duration;
+    }
+
+    let hours = |cells: &[[i64; 24]; 7]| std::array::from_fn(|hour| minutes(cells.iter().map(|day| day[hour]).sum()));
+    let weekdays = |cells: &[[i64; 24]; 7]| std::array::from_fn(|weekday| minutes(cells[weekday].iter().sum()));
+    let total = |cells: &[[i64; 24]; 7]| minutes(cells.iter().flatten().sum());
+    let mut projects: Vec<ProjectDistribution> = projects
+        .into_iter()
+        .map(|(mut project, cells)| {
+            project.hours = hours(&cells);
+            project.weekdays = weekdays(&cells);
+            project.total = total(&cells);
+            project
+        })
+        .collect();
+    projects.sort_by_key(|p| std::cmp::Reverse(p.total));
+
+    let heatmap_hours = hours(&matrix);
+    let heatmap_weekdays = weekdays(&matrix);
+    let heatmap_total = total(&matrix);
+    let matrix = matrix.map(|day| day.map(minutes));
+    Ok(Heatmap {
+        week_start: Calendar::from_settings(&conn).week_start(),
+        max: matrix.iter().flatten().copied().max().unwrap_or(0),
+        matrix,
+        hours: heatmap_hours,
+        weekdays: heatmap_weekdays,
+        total: heatmap_total,
+        projects,
+    })
+}
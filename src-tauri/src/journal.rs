@@ -0,0 +1,409 @@
+// Undo/redo journal. Every mutation of projects, time entries and the
+// backend-owned configuration tables records the affected rows before and
+// after the change; `undo` writes the "before" rows back and `redo` the
+// "after" rows. Automatic bookkeeping (activity samples, break reminders,
+// tags and commit links) is not journaled.
+//
+// Rows are addressed by rowid, so tables without an integer id work as well.
+// Changes to time entries are also written to the audit trail (see audit.rs),
+// and refused for entries of a submitted timesheet (see timesheets.rs).
+use rusqlite::types::Value;
+use rusqlite::{Connection, OptionalExtension};
+use serde_json::{Map, Value as Json};
+use tauri::Emitter;
+
+use crate::audit::{self, ChangeSource};
+use crate::db;
+use crate::timesheets;
+
+// Oldest operations are dropped beyond this
+const MAX_JOURNAL_ENTRIES: i64 = 200;
+
+type Row = Map<String, Json>;
+
+#[derive(serde::Deserialize, serde::Serialize)]
+struct RowChange {
+    table: String,
+    rowid: i64,
+    before: Option<Row>,
+    after: Option<Row>,
+}
+
+#[derive(serde::Serialize)]
+pub struct JournalState {
+    undo: Option<String>, // Label of the operation `undo` would revert
+    redo: Option<String>,
+}
+
+pub fn to_json(value: Value) -> Json {
+    match value {
+        Value::Null => Json::Null,
+        Value::Integer(i) => Json::from(i),
+        Value::Real(f) => Json::from(f),
+        Value::Text(s) => Json::from(s),
+        Value::Blob(b) => Json::from(b),
+    }
+}
+
+pub fn to_sql(value: &Json) -> Value {
+    match value {
+        Json::Null => Value::Null,
+        Json::Bool(b) => Value::Integer(*b as i64),
+        Json::Number(n) => n.as_i64().map(Value::Integer).unwrap_or_else(|| Value::Real(n.as_f64().unwrap_or_default())),
+        Json::String(s) => Value::Text(s.clone()),
+        Json::Array(items) => Value::Blob(items.iter().filter_map(|v| v.as_u64().map(|b| b as u8)).collect()),
+        Json::Object(_) => Value::Null,
+    }
+}
+
+/// Current contents of a row, or None if it does not exist
+pub fn snapshot(conn: &Connection, table: &str, rowid: i64) -> rusqlite::Result<Option<Row>> {
+    let mut stmt = conn.prepare(&format!("SELECT * FROM {} WHERE rowid = ?1", table))?;
+    let names: Vec<String> = stmt.column_names().into_iter().map(str::to_string).collect();
+    stmt.query_row([rowid], |row| {
+        let mut map = Row::new();
+        for (i, name) in names.iter().enumerate() {
+            map.insert(name.clone(), to_json(row.get(i)?));
+        }
+        Ok(map)
+    })
+    .optional()
+}
+
+/// Make a row match `state`: delete it for None, insert or replace otherwise
+fn restore(conn: &Connection, table: &str, rowid: i64, state: &Option<Row>) -> rusqlite::Result<()> {
+    match state {
+        None => {
+            conn.execute(&format!("DELETE FROM {} WHERE rowid = ?1", table), [rowid])?;
+        }
+        Some(row) => {
+            let columns: Vec<&String> = row.keys().collect();
+            let placeholders: Vec<String> = (0..=columns.len()).map(|i| format!("?{}", i + 1)).collect();
+            let sql = format!(
+                "INSERT OR REPLACE INTO {} (rowid, {}) VALUES ({})",
+                table,
+                columns.iter().map(|c| c.as_str()).collect::<Vec<_>>().join(", "),
+                placeholders.join(", ")
+            );
+            let mut values = vec![Value::Integer(rowid)];
+            values.extend(row.values().map(to_sql));
+            conn.execute(&sql, rusqlite::params_from_iter(values))?;
+        }
+    }
+    Ok(())
+}
+
+/// Collects the rows an operation touches. Call `track` before changing an
+/// existing row and `track_new` after inserting one, then `finish` in the same
+/// transaction as the change.
+pub struct Recorder {
+    label: String,
+    source: ChangeSource,
+    changes: Vec<RowChange>,
+}
+
+impl Recorder {
+    pub fn new(label: &str) -> Self {
+        Recorder { label: label.to_string(), source: ChangeSource::Ui, changes: Vec::new() }
+    }
+
+    /// Where the change comes from, for the audit trail (defaults to the UI)
+    pub fn source(mut self, source: ChangeSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    fn is_tracked(&self, table: &str, rowid: i64) -> bool {
+        self.changes.iter().any(|c| c.table == table && c.rowid == rowid)
+    }
+
+    pub fn track(&mut self, conn: &Connection, table: &str, rowid: i64) -> rusqlite::Result<()> {
+        if !self.is_tracked(table, rowid) {
+            let before = snapshot(conn, table, rowid)?;
+            self.changes.push(RowChange { table: table.to_string(), rowid, before, after: None });
+        }
+        Ok(())
+    }
+
+    /// Track every row of `table` matching `condition`, e.g. all entries of a project
+    pub fn track_where<P: rusqlite::Params>(&mut self, conn: &Connection, table: &str, condition: &str, params: P) -> rusqlite::Result<()> {
+        let rowids = conn
+            .prepare(&format!("SELECT rowid FROM {} WHERE {}", table, condition))?
+            .query_map(params, |row| row.get::<_, i64>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        for rowid in rowids {
+            self.track(conn, table, rowid)?;
+        }
+        Ok(())
+    }
+
+    pub fn track_new(&mut self, table: &str, rowid: i64) {
+        if !self.is_tracked(table, rowid) {
+            self.changes.push(RowChange { table: table.to_string(), rowid, before: None, after: None });
+        }
+    }
+
+    /// Record the operation. A new operation discards anything that could be redone.
+    pub fn finish(mut self, conn: &Connection) -> rusqlite::Result<Option<i64>> {
+        for change in &mut self.changes {
+            change.after = snapshot(conn, &change.table, change.rowid)?;
+        }
+        self.changes.retain(|c| c.before != c.after);
+        if self.changes.is_empty() {
+            return Ok(None);
+        }
+        for change in self.changes.iter().filter(|c| c.table == "time_entries") {
+            timesheets::check_unlocked(conn, change.before.as_ref(), change.after.as_ref())
+                .map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?;
+        }
+
+        record_history(conn, &self.changes, self.source, &self.label)?;
+        let changes = serde_json::to_string(&self.changes).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        conn.execute("DELETE FROM journal WHERE undone = 1", [])?;
+        conn.execute(
+            "INSERT INTO journal (label, changes, created_at, undone) VALUES (?1, ?2, datetime('now'), 0)",
+            rusqlite::params![self.label, changes],
+        )?;
+        let id = conn.last_insert_rowid();
+        conn.execute("DELETE FROM journal WHERE id <= ?1", [id - MAX_JOURNAL_ENTRIES])?;
+        Ok(Some(id))
+    }
+}
+
+fn record_history(conn: &Connection, changes: &[RowChange], source: ChangeSource, label: &str) -> rusqlite::Result<()> {
+    for change in changes.iter().filter(|c| c.table == "time_entries") {
+        audit::record(conn, change.rowid, change.before.as_ref(), change.after.as_ref(), source, label)?;
+    }
+    Ok(())
+}
+
+/// Apply one journal entry in either direction. Refuses when a row was changed
+/// outside the journal since, rather than overwriting that change.
+fn apply(conn: &mut Connection, undo: bool) -> Result<Option<String>, String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let sql = if undo {
+        "SELECT id, label, changes FROM journal WHERE undone = 0 ORDER BY id DESC LIMIT 1"
+    } else {
+        "SELECT id, label, changes FROM journal WHERE undone = 1 ORDER BY id ASC LIMIT 1"
+    };
+    let Some((id, label, changes)) = tx
+        .query_row(sql, [], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))
+        .optional()
+        .map_err(|e| e.to_string())?
+    else {
+        return Ok(None);
+    };
+    let changes: Vec<RowChange> = serde_json::from_str(&changes).map_err(|e| e.to_string())?;
+
+    let ordered: Box<dyn Iterator<Item = &RowChange>> = if undo { Box::new(changes.iter().rev()) } else { Box::new(changes.iter()) };
+    for change in ordered {
+        let (expected, target) = if undo { (&change.after, &change.before) } else { (&change.before, &change.after) };
+        let current = snapshot(&tx, &change.table, change.rowid).map_err(|e| e.to_string())?;
+        if &current != expected {
+            return Err(format!("\"{}\" can no longer be {}: the data changed since", label, if undo { "undone" } else { "redone" }));
+        }
+        if change.table == "time_entries" {
+            timesheets::check_unlocked(&tx, current.as_ref(), target.as_ref())?;
+        }
+        restore(&tx, &change.table, change.rowid, target).map_err(|e| e.to_string())?;
+        if change.table == "time_entries" {
+            let action = format!("{}: {}", if undo { "Undo" } else { "Redo" }, label);
+            audit::record(&tx, change.rowid, expected.as_ref(), target.as_ref(), ChangeSource::Ui, &action)
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    tx.execute("UPDATE journal SET undone = ?1 WHERE id = ?2", rusqlite::params![undo, id])
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(Some(label))
+}
+
+fn notify_changed(app: &tauri::AppHandle) {
+    let _ = app.emit("entries-changed", ());
+    // The running entry may have been touched as well; the tray timer runs
+    // natively and does not listen to events
+    let _ = app.emit("timer-changed", ());
+    if let Some(conn) = db::open() {
+        crate::sync_tray(app, &conn, &crate::settings::Settings::load(&conn));
+    }
+}
+
+/// Revert the latest operation. Returns its label, or None if there is nothing to undo.
+#[tauri::command]
+pub fn undo(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    let mut conn = db::open().ok_or("Database not found")?;
+    let label = apply(&mut conn, true)?;
+    if label.is_some() {
+        notify_changed(&app);
+    }
+    Ok(label)
+}
+
+/// Repeat the latest undone operation
+#[tauri::command]
+pub fn redo(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    let mut conn = db::open().ok_or("Database not found")?;
+    let label = apply(&mut conn, false)?;
+    if label.is_some() {
+        notify_changed(&app);
+    }
+    Ok(label)
+}
+
+/// What undo and redo would do next, for menus and tooltips
+#[tauri::command]
+pub fn get_journal_state() -> Result<JournalState, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    let label = |sql: &str| -> Result<Option<String>, String> {
+        conn.query_row(sql, [], |row| row.get(0)).optional().map_err(|e| e.to_string())
+    };
+    Ok(JournalState {
+        undo: label("SELECT label FROM journal WHERE undone = 0 ORDER BY id DESC LIMIT 1")?,
+        redo: label("SELECT label FROM journal WHERE undone = 1 ORDER BY id ASC LIMIT 1")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Connection {
+        let conn = db::testing::memory();
+        conn.execute_batch(
+            "INSERT INTO projects (id, name, color) VALUES (1, 'Horalis', '#111');
+             INSERT INTO time_entries (id, project_id, start_time, end_time, duration)
+             VALUES (1, 1, '2026-10-19 09:00:00', '2026-10-19 10:00:00', 3600);",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn set_duration(conn: &Connection, id: i64, duration: i64) {
+        let mut journal = Recorder::new(&format!("Set duration to {}", duration));
+        journal.track(conn, "time_entries", id).unwrap();
+        conn.execute("UPDATE time_entries SET duration = ?2 WHERE id = ?1", [id, duration]).unwrap();
+        journal.finish(conn).unwrap();
+    }
+
+    fn add_entry(conn: &Connection, start: &str) -> i64 {
+        let mut journal = Recorder::new("Add entry");
+        conn.execute(
+            "INSERT INTO time_entries (project_id, start_time, end_time, duration) VALUES (1, ?1, datetime(?1, '+1 hour'), 3600)",
+            [start],
+        )
+        .unwrap();
+        let id = conn.last_insert_rowid();
+        journal.track_new("time_entries", id);
+        journal.finish(conn).unwrap();
+        id
+    }
+
+    fn duration(conn: &Connection, id: i64) -> Option<i64> {
+        conn.query_row("SELECT duration FROM time_entries WHERE id = ?1", [id], |row| row.get(0)).optional().unwrap()
+    }
+
+    #[test]
+    fn undo_and_redo_round_trip() {
+        let mut conn = setup();
+        set_duration(&conn, 1, 1800);
+        let added = add_entry(&conn, "2026-10-19 11:00:00");
+
+        assert_eq!(apply(&mut conn, true).unwrap().as_deref(), Some("Add entry"));
+        assert_eq!(duration(&conn, added), None);
+        assert_eq!(apply(&mut conn, true).unwrap().as_deref(), Some("Set duration to 1800"));
+        assert_eq!(duration(&conn, 1), Some(3600));
+        assert_eq!(apply(&mut conn, true).unwrap(), None);
+
+        assert_eq!(apply(&mut conn, false).unwrap().as_deref(), Some("Set duration to 1800"));
+        assert_eq!(duration(&conn, 1), Some(1800));
+        assert_eq!(apply(&mut conn, false).unwrap().as_deref(), Some("Add entry"));
+        assert_eq!(duration(&conn, added), Some(3600));
+        assert_eq!(apply(&mut conn, false).unwrap(), None);
+
+        let labels: Vec<String> = conn
+            .prepare("SELECT label FROM entry_history WHERE entry_id = 1 ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(labels, ["Set duration to 1800", "Undo: Set duration to 1800", "Redo: Set duration to 1800"]);
+    }
+
+    #[test]
+    fn no_op_changes_are_not_recorded() {
+        let conn = setup();
+        set_duration(&conn, 1, 3600);
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM journal", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn rows_changed_since_are_not_overwritten() {
+        let mut conn = setup();
+        set_duration(&conn, 1, 1800);
+        conn.execute("UPDATE time_entries SET duration = 2700 WHERE id = 1", []).unwrap();
+        assert_eq!(apply(&mut conn, true).unwrap_err(), "\"Set duration to 1800\" can no longer be undone: the data changed since");
+        assert_eq!(duration(&conn, 1), Some(2700));
+
+        // Put back as recorded, the step can be undone after all
+        conn.execute("UPDATE time_entries SET duration = 1800 WHERE id = 1", []).unwrap();
+        apply(&mut conn, true).unwrap();
+        conn.execute("UPDATE time_entries SET duration = 900 WHERE id = 1", []).unwrap();
+        assert_eq!(apply(&mut conn, false).unwrap_err(), "\"Set duration to 1800\" can no longer be redone: the data changed since");
+        assert_eq!(duration(&conn, 1), Some(900));
+    }
+
+    #[test]
+    fn submitted_weeks_are_not_changed() {
+        let mut conn = setup();
+        let added = add_entry(&conn, "2026-10-20 09:00:00");
+        apply(&mut conn, true).unwrap();
+        conn.execute(
+            "INSERT INTO timesheets (start_date, end_date, start_at, end_at, submitted_at, totals, total)
+             VALUES ('2026-10-19', '2026-10-25', '2026-10-19 00:00:00', '2026-10-26 00:00:00', datetime('now'), '[]', 0)",
+            [],
+        )
+        .unwrap();
+        assert_eq!(apply(&mut conn, false).unwrap_err(), "The week of 2026-10-19 has been submitted. Unlock it to add time.");
+        assert_eq!(duration(&conn, added), None);
+
+        // And nothing new is journaled inside it either
+        let mut journal = Recorder::new("Edit entry");
+        journal.track(&conn, "time_entries", 1).unwrap();
+        conn.execute("UPDATE time_entries SET duration = 60 WHERE id = 1", []).unwrap();
+        assert!(journal.finish(&conn).is_err());
+    }
+
+    #[test]
+    fn a_new_change_clears_redo() {
+        let mut conn = setup();
+        set_duration(&conn, 1, 1800);
+        apply(&mut conn, true).unwrap();
+        set_duration(&conn, 1, 1200);
+        assert_eq!(apply(&mut conn, false).unwrap(), None);
+        assert_eq!(apply(&mut conn, true).unwrap().as_deref(), Some("Set duration to 1200"));
+        assert_eq!(duration(&conn, 1), Some(3600));
+        assert_eq!(apply(&mut conn, true).unwrap(), None);
+    }
+
+    #[test]
+    fn journal_keeps_the_latest_operations() {
+        let mut conn = setup();
+        for duration in 1..=MAX_JOURNAL_ENTRIES + 5 {
+            set_duration(&conn, 1, duration);
+        }
+        let (count, oldest): (i64, String) = conn
+            .query_row("SELECT COUNT(*), (SELECT label FROM journal ORDER BY id LIMIT 1) FROM journal", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!((count, oldest.as_str()), (MAX_JOURNAL_ENTRIES, "Set duration to 6"));
+        for _ in 0..MAX_JOURNAL_ENTRIES {
+            apply(&mut conn, true).unwrap().unwrap();
+        }
+        assert_eq!(apply(&mut conn, true).unwrap(), None);
+        assert_eq!(duration(&conn, 1), Some(5));
+    }
+}
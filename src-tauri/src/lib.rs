@@ -12,8 +12,38 @@ use std::sync::LazyLock;
 use ab_glyph::{FontRef, PxScale, Font};
 use tokio::sync::watch;
 use std::time::{SystemTime, UNIX_EPOCH};
-use chrono::{Local, Timelike, Datelike};
-use directories::ProjectDirs;
+use chrono::{Local, Utc};
+
+use audit::ChangeSource;
+
+mod activity;
+mod audit;
+mod backups;
+mod breaks;
+mod calendar;
+mod crypto;
+mod db;
+mod encryption;
+mod entries;
+mod gaps;
+mod git;
+mod heatmap;
+mod journal;
+mod notify;
+mod periods;
+mod projects;
+mod render;
+mod report;
+mod report_jobs;
+mod rules;
+mod schedule;
+mod settings;
+mod sync;
+mod sync_server;
+pub mod sync_service;
+mod timesheets;
+mod untracked;
+mod zones;
 
 /// Get idle time in seconds using system-idle-time crate
 fn get_idle_time_seconds() -> Option<u64> {
@@ -23,39 +53,41 @@ fn get_idle_time_seconds() -> Option<u64> {
     }
 }
 
-/// Stop any running time entries in the database (called on app exit)
+/// Stop any running time entries in the database (called on app exit). The
+/// stop shows up in the entry history but is not an undoable step: undoing it
+/// on the next launch would restart a timer that was never running meanwhile.
+/// Timers synced from another device keep running there, so they are left alone.
 fn stop_running_time_entries() {
-    // Get the app data directory where tauri-plugin-sql stores the database
-    if let Some(proj_dirs) = ProjectDirs::from("", "", "com.horalis.app") {
-        let db_path = proj_dirs.data_dir().join("horalis.db");
-
-        // Also check the old-style path that tauri-plugin-sql might use
-        let alt_db_path = dirs::data_dir()
-            .map(|p| p.join("com.horalis.app").join("horalis.db"));
-
-        // Try both paths
-        let paths_to_try: Vec<std::path::PathBuf> = vec![
-            db_path,
-            alt_db_path.unwrap_or_default(),
-        ];
-
-        for db_path in paths_to_try {
-            if db_path.exists() {
-                if let Ok(conn) = rusqlite::Connection::open(&db_path) {
-                    let result = conn.execute(
-                        "UPDATE time_entries
-                         SET end_time = datetime('now'),
-                             duration = CAST((julianday(datetime('now')) - julianday(start_time)) * 86400 AS INTEGER)
-                         WHERE end_time IS NULL",
-                        [],
-                    );
-                    if let Ok(rows) = result {
-                        if rows > 0 {
-                            eprintln!("Stopped {} running time entry(ies) on app exit", rows);
-                        }
-                    }
-                    return;
+    if let Some(conn) = db::open() {
+        let result = (|| -> rusqlite::Result<usize> {
+            let tx = conn.unchecked_transaction()?;
+            let ids = tx
+                .prepare("SELECT id FROM time_entries WHERE end_time IS NULL")?
+                .query_map([], |row| row.get::<_, i64>(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            let mut stopped = 0;
+            for id in &ids {
+                if sync::changed_elsewhere(&tx, *id)? {
+                    continue;
                 }
+                let before = journal::snapshot(&tx, "time_entries", *id)?;
+                tx.execute(
+                    "UPDATE time_entries
+                     SET end_time = datetime('now'),
+                         duration = CAST((julianday(datetime('now')) - julianday(start_time)) * 86400 AS INTEGER)
+                     WHERE id = ?1",
+                    [id],
+                )?;
+                let after = journal::snapshot(&tx, "time_entries", *id)?;
+                audit::record(&tx, *id, before.as_ref(), after.as_ref(), ChangeSource::Automatic, "Stop timer on quit")?;
+                stopped += 1;
+            }
+            tx.commit()?;
+            Ok(stopped)
+        })();
+        if let Ok(rows) = result {
+            if rows > 0 {
+                eprintln!("Stopped {} running time entry(ies) on app exit", rows);
             }
         }
     }
@@ -86,6 +118,20 @@ mod dirs {
             None
         }
     }
+
+    pub fn config_dir() -> Option<PathBuf> {
+        #[cfg(target_os = "linux")]
+        {
+            std::env::var("XDG_CONFIG_HOME")
+                .ok()
+                .map(PathBuf::from)
+                .or_else(|| std::env::var("HOME").ok().map(|h| PathBuf::from(h).join(".config")))
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            data_dir()
+        }
+    }
 }
 
 // Load bold system font for rendering letters (platform-specific paths)
@@ -136,20 +182,25 @@ struct NativeTimerState {
     stop_tx: Mutex<Option<watch::Sender<bool>>>,
 }
 
-// Reminder configuration
-#[derive(Clone, serde::Deserialize)]
-struct ReminderConfig {
-    enabled: bool,
-    interval_minutes: u32,
-    start_time: String,  // "HH:MM" format
-    end_time: String,    // "HH:MM" format
-    weekdays: Vec<u32>,  // 0=Sun, 1=Mon, ..., 6=Sat
-}
-
 // Reminder state for background task
 struct ReminderState {
     stop_tx: Mutex<Option<watch::Sender<bool>>>,
     last_notification_time: Mutex<Option<u64>>, // Unix timestamp in seconds
+    snoozed_until: Mutex<Option<u64>>,          // Unix timestamp in seconds
+    // Start of the current stretch of activity without a running timer
+    untracked_active_since: Mutex<Option<u64>>,
+    // Untracked (start, end) period waiting for the user to add or dismiss it
+    pending_backfill: Mutex<Option<(u64, u64)>>,
+}
+
+/// Suppress tracking reminders for the next `minutes`
+fn snooze_reminders(app: &tauri::AppHandle, minutes: u32) {
+    let now_secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let reminder_state = app.state::<ReminderState>();
+    *reminder_state.snoozed_until.lock().unwrap() = Some(now_secs + (minutes as u64) * 60);
 }
 
 #[derive(serde::Deserialize, Clone)]
@@ -278,46 +329,12 @@ fn format_tray_time(elapsed_secs: u64) -> String {
     format!("{}:{:02}", h, m)
 }
 
-/// Parse "HH:MM" time string into (hour, minute)
-fn parse_time_string(time_str: &str) -> Option<(u32, u32)> {
-    let parts: Vec<&str> = time_str.split(':').collect();
-    if parts.len() != 2 {
-        return None;
+/// Check if today is marked as a day off in the vacation calendar
+fn is_day_off_today() -> bool {
+    match db::open() {
+        Some(conn) => calendar::is_day_off(&conn, Local::now().date_naive()),
+        None => false,
     }
-    let hour = parts[0].parse::<u32>().ok()?;
-    let minute = parts[1].parse::<u32>().ok()?;
-    if hour > 23 || minute > 59 {
-        return None;
-    }
-    Some((hour, minute))
-}
-
-/// Check if current time is within the reminder time window
-fn is_within_time_window(start_time: &str, end_time: &str) -> bool {
-    let now = Local::now();
-    let current_minutes = now.hour() * 60 + now.minute();
-
-    let Some((start_h, start_m)) = parse_time_string(start_time) else {
-        return false;
-    };
-    let Some((end_h, end_m)) = parse_time_string(end_time) else {
-        return false;
-    };
-
-    let start_minutes = start_h * 60 + start_m;
-    let end_minutes = end_h * 60 + end_m;
-
-    current_minutes >= start_minutes && current_minutes <= end_minutes
-}
-
-/// Check if current day is in the allowed weekdays (0=Sun, 1=Mon, ..., 6=Sat)
-fn is_allowed_weekday(weekdays: &[u32]) -> bool {
-    let now = Local::now();
-    // chrono: 0=Mon, 1=Tue, ..., 6=Sun
-    // UI convention: 0=Sun, 1=Mon, ..., 6=Sat
-    let chrono_weekday = now.weekday().num_days_from_monday(); // 0=Mon ... 6=Sun
-    let ui_weekday = if chrono_weekday == 6 { 0 } else { chrono_weekday + 1 };
-    weekdays.contains(&ui_weekday)
 }
 
 /// Helper to set tray title (macOS only - on other platforms this is a no-op)
@@ -352,6 +369,9 @@ async fn start_tray_timer(
     // Store the start time
     *timer_state.start_time_ms.lock().unwrap() = Some(start_time_ms);
 
+    // Offer to add any untracked activity right before this timer
+    untracked::offer_backfill(&app, start_time_ms / 1000);
+
     // Create channel for stopping
     let (stop_tx, mut stop_rx) = watch::channel(false);
     *timer_state.stop_tx.lock().unwrap() = Some(stop_tx);
@@ -399,14 +419,18 @@ async fn start_tray_timer(
             let actual_elapsed = now_check.duration_since(last_check_time).unwrap_or_default();
             if actual_elapsed.as_secs() > 30 {
                 // System was sleeping - emit event and stop timer
-                use tauri_plugin_notification::NotificationExt;
                 let sleep_mins = actual_elapsed.as_secs() / 60;
-                let _ = app_handle
-                    .notification()
-                    .builder()
-                    .title("Timer Stopped")
-                    .body(format!("Timer was stopped after {} minutes of system sleep.", sleep_mins))
-                    .show();
+                let slept_at = last_check_time
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0);
+                notify::show_idle_stop(
+                    &app_handle,
+                    "Timer Stopped",
+                    &format!("Timer was stopped after {} minutes of system sleep.", sleep_mins),
+                    db::open().and_then(|conn| db::running_entry_id(&conn)),
+                    slept_at,
+                );
                 let _ = app_handle.emit("system-sleep", actual_elapsed.as_secs());
                 return;
             }
@@ -425,16 +449,19 @@ async fn start_tray_timer(
                 if let Some(idle_secs) = get_idle_time_seconds() {
                     if idle_secs >= idle_timeout_secs {
                         // Show notification about idle timeout
-                        {
-                            use tauri_plugin_notification::NotificationExt;
-                            let idle_mins = idle_secs / 60;
-                            let _ = app_handle
-                                .notification()
-                                .builder()
-                                .title("Timer Stopped")
-                                .body(format!("Timer was stopped due to {} minutes of inactivity.", idle_mins))
-                                .show();
-                        }
+                        let idle_mins = idle_secs / 60;
+                        let idle_since = SystemTime::now()
+                            .duration_since(UNIX_EPOCH)
+                            .map(|d| d.as_secs())
+                            .unwrap_or(0)
+                            .saturating_sub(idle_secs);
+                        notify::show_idle_stop(
+                            &app_handle,
+                            "Timer Stopped",
+                            &format!("Timer was stopped due to {} minutes of inactivity.", idle_mins),
+                            db::open().and_then(|conn| db::running_entry_id(&conn)),
+                            idle_since,
+                        );
                         // Emit idle timeout event to frontend
                         let _ = app_handle.emit("idle-timeout", idle_secs);
                         return;
@@ -490,6 +517,40 @@ fn stop_tray_timer(app: tauri::AppHandle) {
     }
 }
 
+/// Start tracking a project entirely from the backend, without going through
+/// the webview (falls back to the last used project when `project_id` is None)
+async fn start_project_timer_native(app: &tauri::AppHandle, project_id: Option<i64>, source: ChangeSource) -> Result<(), String> {
+    let (project, show_in_tray, idle_enabled, idle_timeout_minutes) = {
+        let conn = db::open().ok_or("Database not found")?;
+        let project = match project_id {
+            Some(id) => db::get_project(&conn, id),
+            None => db::last_used_project(&conn),
+        }
+        .ok_or("No project to start")?;
+        db::start_time_entry(&conn, project.id, source).map_err(|e| e.to_string())?;
+        let settings = settings::Settings::load(&conn);
+        (
+            project,
+            settings.show_timer_in_tray,
+            settings.stop_timer_when_idle,
+            settings.idle_timeout_minutes as u64,
+        )
+    };
+
+    set_tray_icon_color(app.state::<TrayState>(), project.color.clone(), project.name.clone());
+    if show_in_tray {
+        let start_time_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        start_tray_timer(app.clone(), start_time_ms, Some(idle_enabled), Some(idle_timeout_minutes)).await?;
+    }
+
+    // Let any open window pick up the new entry
+    let _ = app.emit("timer-changed", project.id);
+    Ok(())
+}
+
 #[tauri::command]
 fn set_tray_title(state: tauri::State<TrayState>, title: String) {
     if let Some(tray) = state.tray.lock().unwrap().as_ref() {
@@ -508,7 +569,8 @@ fn clear_tray_title(state: tauri::State<TrayState>) {
 #[tauri::command]
 fn set_tray_icon_color(state: tauri::State<TrayState>, color: String, name: String) {
     if let Some(tray) = state.tray.lock().unwrap().as_ref() {
-        let first_char = name.chars().next();
+        // No letter of a project name while the database is locked
+        let first_char = name.chars().next().filter(|_| !encryption::database_locked());
         let icon_data = generate_colored_icon(&color, first_char);
         let icon = Image::new_owned(icon_data, 22, 22);
         let _ = tray.set_icon(Some(icon));
@@ -560,14 +622,16 @@ fn generate_menu_icon(hex_color: &str) -> Vec<u8> {
     rgba
 }
 
-/// Start or update the reminder system with new configuration
+/// Start or restart the reminders with the saved settings
 #[tauri::command]
-async fn start_reminder(
-    app: tauri::AppHandle,
-    config: ReminderConfig,
-) -> Result<(), String> {
-    use tauri_plugin_notification::NotificationExt;
+fn start_reminder(app: tauri::AppHandle) -> Result<(), String> {
+    let conn = db::open().ok_or("Database not found")?;
+    restart_reminders(&app, &settings::Settings::load(&conn));
+    Ok(())
+}
 
+/// Start the tracking and break reminders for `settings`, replacing any running ones
+fn restart_reminders(app: &tauri::AppHandle, settings: &settings::Settings) {
     let reminder_state = app.state::<ReminderState>();
 
     // Stop any existing reminder task
@@ -578,9 +642,12 @@ async fn start_reminder(
         }
     }
 
+    // Break reminders run independently of tracking reminders
+    breaks::start(app, settings);
+
     // If reminders are disabled, just return
-    if !config.enabled {
-        return Ok(());
+    if !settings.reminder_enabled {
+        return;
     }
 
     // Create channel for stopping
@@ -588,7 +655,10 @@ async fn start_reminder(
     *reminder_state.stop_tx.lock().unwrap() = Some(stop_tx);
 
     let app_handle = app.clone();
-    let interval_secs = (config.interval_minutes as u64) * 60;
+    let interval_secs = (settings.reminder_interval_minutes as u64) * 60;
+    let schedules = settings.reminder_windows();
+    let only_when_active = settings.reminder_only_when_active;
+    let active_threshold_secs = (settings.reminder_active_threshold_minutes as u64) * 60;
 
     // Spawn background task for reminder checking
     tauri::async_runtime::spawn(async move {
@@ -608,30 +678,49 @@ async fn start_reminder(
                 return;
             }
 
-            // Check if timer is running - skip reminder if so
+            let now_secs = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+
+            // Keep track of activity without a running timer
             let timer_state = app_handle.state::<NativeTimerState>();
-            if timer_state.start_time_ms.lock().unwrap().is_some() {
-                // Timer is running, skip this check
+            let timer_running = timer_state.start_time_ms.lock().unwrap().is_some();
+            let active = untracked::note_activity(
+                &app_handle,
+                now_secs,
+                timer_running,
+                get_idle_time_seconds(),
+                active_threshold_secs,
+            );
+
+            // Check if timer is running - skip reminder if so
+            if timer_running {
                 continue;
             }
 
-            // Check if current day is allowed
-            if !is_allowed_weekday(&config.weekdays) {
+            // Smart reminders: skip while the user is away
+            if only_when_active && !active {
                 continue;
             }
 
-            // Check if current time is within window
-            if !is_within_time_window(&config.start_time, &config.end_time) {
+            // Check if any schedule covers the current time
+            if !schedule::is_active(&schedules, Utc::now()) {
                 continue;
             }
 
-            // Check if enough time has passed since last notification
-            let now_secs = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .map(|d| d.as_secs())
-                .unwrap_or(0);
+            // Check the vacation calendar
+            if is_day_off_today() {
+                continue;
+            }
 
+            // Check if enough time has passed since last notification
             let reminder_state = app_handle.state::<ReminderState>();
+            if let Some(until) = *reminder_state.snoozed_until.lock().unwrap() {
+                if now_secs < until {
+                    continue;
+                }
+            }
             let mut last_time = reminder_state.last_notification_time.lock().unwrap();
 
             if let Some(last) = *last_time {
@@ -642,22 +731,18 @@ async fn start_reminder(
             }
 
             // All conditions met - send notification
-            if let Err(e) = app_handle
-                .notification()
-                .builder()
-                .title("Horalis Reminder")
-                .body("Don't forget to track your time!")
-                .show()
-            {
-                eprintln!("Failed to show notification: {}", e);
-            }
+            notify::show_reminder(&app_handle);
 
             // Update last notification time
             *last_time = Some(now_secs);
         }
     });
+}
 
-    Ok(())
+/// Snooze tracking reminders for the given number of minutes
+#[tauri::command]
+fn snooze_reminder(app: tauri::AppHandle, minutes: u32) {
+    snooze_reminders(&app, minutes);
 }
 
 /// Stop the reminder system
@@ -668,12 +753,72 @@ fn stop_reminder(app: tauri::AppHandle) {
     if let Some(tx) = stop_tx_guard.take() {
         let _ = tx.send(true);
     }
+    breaks::stop(&app);
+}
+
+/// Set up from the database what would otherwise wait for a window: the
+/// settings file, the tray menu, the native timer of an entry left running and
+/// the reminders. Runs on launch and again on unlock.
+fn restore_session(app: &tauri::AppHandle) {
+    settings::apply_file();
+    let Some(conn) = db::open() else {
+        return;
+    };
+    let settings = settings::Settings::load(&conn);
+    sync_tray(app, &conn, &settings);
+    drop(conn);
+    restart_reminders(app, &settings);
+}
+
+/// Bring the tray menu, icon and native timer in line with the database,
+/// e.g. after an undo started or stopped the running entry
+fn sync_tray(app: &tauri::AppHandle, conn: &rusqlite::Connection, settings: &settings::Settings) {
+    let running = db::running_entry(conn);
+    let projects = db::list_projects(conn).unwrap_or_else(|e| {
+        eprintln!("Failed to load projects: {}", e);
+        Vec::new()
+    });
+
+    let projects = projects.into_iter().map(|p| ProjectInfo { id: p.id, name: p.name, color: p.color }).collect();
+    if let Err(e) = update_tray_menu(app.clone(), projects, running.is_some()) {
+        eprintln!("Failed to update tray menu: {}", e);
+    }
+    let Some((project, start_secs)) = running else {
+        stop_tray_timer(app.clone());
+        reset_tray_icon(app.state::<TrayState>());
+        return;
+    };
+    set_tray_icon_color(app.state::<TrayState>(), project.color, project.name);
+    let start_time_ms = start_secs.max(0) as u64 * 1000;
+    // The webview starts the timer with millisecond precision; the database keeps seconds
+    let current_start = *app.state::<NativeTimerState>().start_time_ms.lock().unwrap();
+    if !settings.show_timer_in_tray {
+        stop_tray_timer(app.clone());
+    } else if current_start.map(|ms| ms / 1000) != Some(start_time_ms / 1000) {
+        let app = app.clone();
+        let idle_timeout_minutes = settings.idle_timeout_minutes as u64;
+        let idle_enabled = settings.stop_timer_when_idle;
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = start_tray_timer(app, start_time_ms, Some(idle_enabled), Some(idle_timeout_minutes)).await {
+                eprintln!("Failed to restore the tray timer: {}", e);
+            }
+        });
+    }
+}
+
+/// Take project names out of the tray, e.g. when the database gets locked
+fn hide_tray_details(app: &tauri::AppHandle) {
+    reset_tray_icon(app.state::<TrayState>());
+    if let Err(e) = update_tray_menu(app.clone(), Vec::new(), false) {
+        eprintln!("Failed to update tray menu: {}", e);
+    }
 }
 
 #[tauri::command]
 fn update_tray_menu(app: tauri::AppHandle, projects: Vec<ProjectInfo>, is_running: bool) -> Result<(), String> {
     use tauri::menu::IconMenuItem;
 
+    let projects = if encryption::database_locked() { Vec::new() } else { projects };
     let state = app.state::<TrayState>();
     if let Some(tray) = state.tray.lock().unwrap().as_ref() {
         // Build menu
@@ -698,6 +843,20 @@ fn update_tray_menu(app: tauri::AppHandle, projects: Vec<ProjectInfo>, is_runnin
             .map_err(|e| e.to_string())?;
         menu.append(&stop).map_err(|e| e.to_string())?;
 
+        // Snooze Break Reminder (only enabled when break reminders are on)
+        let break_active = app.state::<breaks::BreakState>().stop_tx.lock().unwrap().is_some();
+        let snooze_break = MenuItem::with_id(&app, "snooze_break", "Snooze Break Reminder", break_active, None::<&str>)
+            .map_err(|e| e.to_string())?;
+        menu.append(&snooze_break).map_err(|e| e.to_string())?;
+
+        // Reminder snoozing
+        let snooze_reminders_item = MenuItem::with_id(&app, "snooze_reminders", "Snooze Reminders for 1 Hour", true, None::<&str>)
+            .map_err(|e| e.to_string())?;
+        menu.append(&snooze_reminders_item).map_err(|e| e.to_string())?;
+        let mute_today = MenuItem::with_id(&app, "mute_today", "Not Working Today", true, None::<&str>)
+            .map_err(|e| e.to_string())?;
+        menu.append(&mute_today).map_err(|e| e.to_string())?;
+
         // Separator before projects
         let separator2 = PredefinedMenuItem::separator(&app).map_err(|e| e.to_string())?;
         menu.append(&separator2).map_err(|e| e.to_string())?;
@@ -734,6 +893,9 @@ fn update_tray_menu(app: tauri::AppHandle, projects: Vec<ProjectInfo>, is_runnin
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // Before any connection is opened, by us or tauri-plugin-sql
+    encryption::init();
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_sql::Builder::default().build())
@@ -750,8 +912,12 @@ pub fn run() {
         .manage(ReminderState {
             stop_tx: Mutex::new(None),
             last_notification_time: Mutex::new(None),
+            snoozed_until: Mutex::new(None),
+            untracked_active_since: Mutex::new(None),
+            pending_backfill: Mutex::new(None),
         })
-        .invoke_handler(tauri::generate_handler![set_tray_title, clear_tray_title, set_tray_icon_color, reset_tray_icon, update_tray_menu, start_tray_timer, stop_tray_timer, start_reminder, stop_reminder])
+        .manage(breaks::BreakState::new())
+        .invoke_handler(tauri::generate_handler![set_tray_title, clear_tray_title, set_tray_icon_color, reset_tray_icon, update_tray_menu, start_tray_timer, stop_tray_timer, start_reminder, stop_reminder, snooze_reminder, breaks::snooze_break_reminder, breaks::get_break_stats, calendar::list_days_off, calendar::add_day_off, calendar::remove_day_off, calendar::mute_reminders_today, calendar::import_days_off_ics, untracked::backfill_untracked_time, untracked::dismiss_untracked_time, activity::get_activity_timeline, activity::clear_activity_samples, rules::list_activity_rules, rules::save_activity_rule, rules::delete_activity_rule, rules::preview_activity_rules, rules::get_entry_tags, git::list_project_repos, git::add_project_repo, git::remove_project_repo, git::sync_git_commits, git::get_entry_commits, gaps::find_gaps, gaps::apply_gap_fills, entries::update_time_entry, entries::list_entry_conflicts, entries::resolve_entry_conflict, entries::split_time_entry, entries::merge_time_entries, entries::duplicate_time_entry, entries::start_time_entry, entries::stop_time_entry, entries::delete_time_entry, projects::create_project, projects::update_project, projects::delete_project, journal::undo, journal::redo, journal::get_journal_state, audit::get_entry_history, audit::get_edited_entries, report::report, report::get_period_total, report::get_day_entries, report_jobs::list_report_jobs, report_jobs::save_report_job, report_jobs::delete_report_job, report_jobs::run_report_job, heatmap::get_heatmap, timesheets::submit_week, timesheets::unlock_week, timesheets::list_timesheets, backups::list_backups, backups::backup_now, backups::restore_backup, sync::sync_now, sync::get_sync_status, sync::resolve_sync_conflict, encryption::get_database_lock, encryption::unlock_database, encryption::lock_database, encryption::forget_database_passphrase, encryption::encrypt_database, notify::answer_notification_prompt, settings::get_settings, settings::update_setting, settings::export_settings, settings::import_settings, periods::get_period, zones::list_timezones])
         .setup(|app| {
             // Build tray menu
             let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
@@ -807,6 +973,17 @@ pub fn run() {
                             // Emit event to frontend to stop the timer
                             let _ = app.emit("stop-timer", ());
                         }
+                        "snooze_break" => {
+                            breaks::snooze_break_reminder(app.clone(), None);
+                        }
+                        "snooze_reminders" => {
+                            snooze_reminders(app, 60);
+                        }
+                        "mute_today" => {
+                            if let Err(e) = calendar::mute_reminders_today() {
+                                eprintln!("Failed to mute reminders: {}", e);
+                            }
+                        }
                         _ => {
                             // Check for project clicks (format: "project_{id}")
                             if event_id.starts_with("project_") {
@@ -828,6 +1005,20 @@ pub fn run() {
             // Store tray reference for later updates
             *app.state::<TrayState>().tray.lock().unwrap() = Some(_tray);
 
+            // Running timer, tray and reminders, without waiting for the webview
+            restore_session(app.handle());
+
+            // Opt-in foreground window sampler (checks its setting on every tick)
+            activity::start_sampler(app.handle().clone());
+            // Opt-in repository watcher for project switching
+            git::start_watcher(app.handle().clone());
+            // Scheduled report files
+            report_jobs::start_scheduler(app.handle().clone());
+            // Opt-in daily backups
+            backups::start_scheduler(app.handle().clone());
+            // Opt-in sync through a shared folder
+            sync::start_sync(app.handle().clone());
+
             // Hide dock icon on macOS
             #[cfg(target_os = "macos")]
             {
@@ -0,0 +1,246 @@
+// Notifications with action buttons, handled entirely in Rust so they keep
+// working while both windows are hidden.
+//
+// Desktop notification actions are only available through the freedesktop
+// notification spec. On macOS and Windows (or when no notification server
+// answers) a plain notification is shown instead, and the floating timer
+// opens with the same actions as buttons.
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::audit::ChangeSource;
+
+const REMINDER_SNOOZE_MINUTES: u32 = 15;
+// Unanswered prompts kept before the oldest are dropped
+const MAX_PROMPTS: usize = 16;
+
+type ActionHandler = Box<dyn FnOnce(&str) + Send>;
+
+static NEXT_PROMPT: AtomicU64 = AtomicU64::new(1);
+static PROMPTS: Mutex<BTreeMap<u64, ActionHandler>> = Mutex::new(BTreeMap::new());
+
+#[derive(Clone, serde::Serialize)]
+struct PromptAction {
+    id: String,
+    label: String,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct Prompt {
+    id: u64,
+    title: String,
+    body: String,
+    actions: Vec<PromptAction>,
+}
+
+/// Show a plain notification through tauri-plugin-notification
+pub fn show_plain(app: &AppHandle, title: &str, body: &str) {
+    use tauri_plugin_notification::NotificationExt;
+
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        eprintln!("Failed to show notification: {}", e);
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn show_with_actions<F>(app: &AppHandle, title: &str, body: &str, actions: &[(&str, &str)], on_action: F)
+where
+    F: FnOnce(&str) + Send + 'static,
+{
+    let mut notification = notify_rust::Notification::new();
+    notification.appname("Horalis").summary(title).body(body);
+    for (id, label) in actions {
+        notification.action(id, label);
+    }
+    match notification.show() {
+        Ok(handle) => {
+            // Blocks until the notification is clicked or dismissed
+            std::thread::spawn(move || handle.wait_for_action(on_action));
+        }
+        Err(e) => {
+            eprintln!("Failed to show actionable notification: {}", e);
+            show_with_prompt(app, title, body, actions, on_action);
+        }
+    }
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn show_with_actions<F>(app: &AppHandle, title: &str, body: &str, actions: &[(&str, &str)], on_action: F)
+where
+    F: FnOnce(&str) + Send + 'static,
+{
+    show_with_prompt(app, title, body, actions, on_action);
+}
+
+/// Plain notification plus the actions as buttons in the floating timer
+fn show_with_prompt<F>(app: &AppHandle, title: &str, body: &str, actions: &[(&str, &str)], on_action: F)
+where
+    F: FnOnce(&str) + Send + 'static,
+{
+    show_plain(app, title, body);
+
+    let id = NEXT_PROMPT.fetch_add(1, Ordering::Relaxed);
+    {
+        let mut prompts = PROMPTS.lock().unwrap();
+        prompts.insert(id, Box::new(on_action));
+        while prompts.len() > MAX_PROMPTS {
+            prompts.pop_first();
+        }
+    }
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+    }
+    let prompt = Prompt {
+        id,
+        title: title.to_string(),
+        body: body.to_string(),
+        actions: actions.iter().map(|(id, label)| PromptAction { id: id.to_string(), label: label.to_string() }).collect(),
+    };
+    let _ = app.emit("notification-prompt", prompt);
+}
+
+/// Run the action picked in the floating timer for prompt `id`. An empty
+/// action dismisses the prompt.
+#[tauri::command]
+pub fn answer_notification_prompt(id: u64, action: String) {
+    let handler = PROMPTS.lock().unwrap().remove(&id);
+    if let Some(handler) = handler {
+        if !action.is_empty() {
+            handler(&action);
+        }
+    }
+}
+
+/// "Don't forget to track your time!" with start/snooze/pick actions
+pub fn show_reminder(app: &AppHandle) {
+    let app_handle = app.clone();
+    show_with_actions(
+        app,
+        "Horalis Reminder",
+        "Don't forget to track your time!",
+        &[
+            ("start_last", "Start last project"),
+            ("snooze", "Snooze 15 min"),
+            ("pick_project", "Pick project"),
+            ("mute_today", "Not working today"),
+        ],
+        move |action| handle_reminder_action(&app_handle, action),
+    );
+}
+
+fn handle_reminder_action(app: &AppHandle, action: &str) {
+    match action {
+        "start_last" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = crate::start_project_timer_native(&app, None, ChangeSource::Tray).await {
+                    eprintln!("Failed to start last project: {}", e);
+                }
+            });
+        }
+        "snooze" => crate::snooze_reminders(app, REMINDER_SNOOZE_MINUTES),
+        "mute_today" => {
+            if let Err(e) = crate::calendar::mute_reminders_today() {
+                eprintln!("Failed to mute reminders: {}", e);
+            }
+        }
+        "pick_project" => {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+            let _ = app.emit("pick-project", ());
+        }
+        _ => {}
+    }
+}
+
+/// "Time for a break" after `worked_mins` of continuous work, with a snooze action
+pub fn show_break_reminder(app: &AppHandle, worked_mins: u64, snooze_mins: u32) {
+    let app_handle = app.clone();
+    let snooze = format!("Snooze {} min", snooze_mins);
+    show_with_actions(
+        app,
+        "Time for a break",
+        &format!("You've been working for {} minutes. Step away for a few minutes.", worked_mins),
+        &[("snooze", snooze.as_str())],
+        move |action| {
+            if action == "snooze" {
+                crate::breaks::snooze_break_reminder(app_handle, None);
+            }
+        },
+    );
+}
+
+/// Notify that the timer was stopped after idle time or sleep, offering to
+/// keep the away time or discard it by ending the entry at `away_since_secs`
+pub fn show_idle_stop(app: &AppHandle, title: &str, body: &str, entry_id: Option<i64>, away_since_secs: u64) {
+    let app_handle = app.clone();
+    show_with_actions(
+        app,
+        title,
+        body,
+        &[("keep", "Keep"), ("discard", "Discard")],
+        move |action| {
+            if action != "discard" {
+                return;
+            }
+            let Some(entry_id) = entry_id else {
+                return;
+            };
+            let Some(conn) = crate::db::open() else {
+                return;
+            };
+            match crate::db::trim_time_entry_end(&conn, entry_id, away_since_secs, ChangeSource::Tray) {
+                Ok(_) => {
+                    let _ = app_handle.emit("entries-changed", entry_id);
+                }
+                Err(e) => eprintln!("Failed to discard idle time: {}", e),
+            }
+        },
+    );
+}
+
+/// Offer to add the untracked minutes before the current timer as an entry
+pub fn show_backfill_offer(app: &AppHandle, minutes: u64) {
+    let app_handle = app.clone();
+    show_with_actions(
+        app,
+        "Add untracked time?",
+        &format!("You were active for {} minutes before starting the timer.", minutes),
+        &[("add", "Add to project"), ("dismiss", "Dismiss")],
+        move |action| match action {
+            "add" => {
+                if let Err(e) = crate::untracked::backfill_untracked_time(app_handle, Some(ChangeSource::Tray)) {
+                    eprintln!("Failed to add untracked time: {}", e);
+                }
+            }
+            "dismiss" => crate::untracked::dismiss_untracked_time(app_handle),
+            _ => {}
+        },
+    );
+}
+
+/// Suggest switching the running timer to a project matched by an activity rule
+pub fn show_project_suggestion(app: &AppHandle, project: &crate::db::ProjectRow, app_name: &str) {
+    let app_handle = app.clone();
+    let project_id = project.id;
+    show_with_actions(
+        app,
+        "Switch project?",
+        &format!("You're working in {}. Track this as {}?", app_name, project.name),
+        &[("switch", "Switch"), ("ignore", "Ignore")],
+        move |action| {
+            if action != "switch" {
+                return;
+            }
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = crate::start_project_timer_native(&app_handle, Some(project_id), ChangeSource::Tray).await {
+                    eprintln!("Failed to switch project: {}", e);
+                }
+            });
+        },
+    );
+}
@@ -0,0 +1,411 @@
+// Calendar periods for totals and reports: weeks starting on a configurable
+// day, ISO weeks, calendar months, quarters and years, 4-4-5 style fiscal
+// calendars and pay periods. Settings:
+//   week_start         first day of the week, 0 = Sunday ... 6 = Saturday (default Monday)
+//   fiscal_pattern     weeks per fiscal period within a quarter: "4-4-5", "4-5-4" or "5-4-4"
+//   fiscal_year_start  "MM-DD"; a fiscal year starts on the first day of the week nearest to it
+//   pay_period         "weekly", "biweekly", "semimonthly" or "monthly"
+//   pay_period_anchor  "YYYY-MM-DD", the first day of any weekly or biweekly pay period
+use chrono::{Datelike, Duration as ChronoDuration, NaiveDate};
+use rusqlite::Connection;
+
+use crate::db;
+use crate::settings::Settings;
+use crate::zones;
+
+#[derive(Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeriodKind {
+    Day,
+    Week,
+    IsoWeek,
+    Month,
+    Quarter,
+    Year,
+    FiscalPeriod,
+    FiscalQuarter,
+    FiscalYear,
+    PayPeriod,
+}
+
+#[derive(Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PayFrequency {
+    Weekly,
+    Biweekly,
+    Semimonthly,
+    Monthly,
+}
+
+#[derive(serde::Serialize)]
+pub struct Period {
+    kind: PeriodKind,
+    start: String, // "YYYY-MM-DD"
+    end: String,   // Inclusive
+    label: String,
+}
+
+pub struct Calendar {
+    week_start: u32, // Days from Sunday, like the reminder weekdays
+    fiscal_pattern: [i64; 3],
+    fiscal_year_start: (u32, u32), // Month and day
+    pay_frequency: PayFrequency,
+    pay_anchor: NaiveDate,
+}
+
+fn last_day_of_month(year: i32, month: u32) -> NaiveDate {
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1).unwrap() - ChronoDuration::days(1)
+}
+
+fn short_date(date: NaiveDate) -> String {
+    date.format("%b %-d").to_string()
+}
+
+fn date_range_label(start: NaiveDate, end: NaiveDate) -> String {
+    format!("{} - {}, {}", short_date(start), short_date(end), end.year())
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Calendar {
+            week_start: 1,
+            fiscal_pattern: [4, 4, 5],
+            fiscal_year_start: (1, 1),
+            pay_frequency: PayFrequency::Biweekly,
+            pay_anchor: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(), // A Monday
+        }
+    }
+}
+
+impl Calendar {
+    /// Read the period settings, falling back to defaults for anything malformed
+    pub fn from_settings(conn: &Connection) -> Self {
+        let settings = Settings::load(conn);
+        let defaults = Calendar::default();
+        let fiscal_pattern = match settings.fiscal_pattern.as_str() {
+            "4-5-4" => [4, 5, 4],
+            "5-4-4" => [5, 4, 4],
+            _ => defaults.fiscal_pattern,
+        };
+        Calendar {
+            week_start: settings.week_start % 7,
+            fiscal_pattern,
+            fiscal_year_start: settings.fiscal_year_start().unwrap_or(defaults.fiscal_year_start),
+            pay_frequency: settings.pay_period,
+            pay_anchor: zones::parse_date(&settings.pay_period_anchor).unwrap_or(defaults.pay_anchor),
+        }
+    }
+
+    fn week_of(&self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let offset = (date.weekday().num_days_from_sunday() + 7 - self.week_start) % 7;
+        let start = date - ChronoDuration::days(offset as i64);
+        (start, start + ChronoDuration::days(6))
+    }
+
+    /// First day of the fiscal year named after the calendar year it starts
+    /// in: the week start nearest to the configured date
+    fn fiscal_year_start_in(&self, year: i32) -> NaiveDate {
+        let (month, day) = self.fiscal_year_start;
+        let target = NaiveDate::from_ymd_opt(year, month, day).unwrap_or_else(|| last_day_of_month(year, month));
+        let (week_start, _) = self.week_of(target);
+        if (target - week_start).num_days() > 3 {
+            week_start + ChronoDuration::days(7)
+        } else {
+            week_start
+        }
+    }
+
+    /// Bounds of the fiscal year containing `date`
+    fn fiscal_year_of(&self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let mut year = date.year();
+        if date < self.fiscal_year_start_in(year) {
+            year -= 1;
+        } else if date >= self.fiscal_year_start_in(year + 1) {
+            year += 1;
+        }
+        (self.fiscal_year_start_in(year), self.fiscal_year_start_in(year + 1) - ChronoDuration::days(1))
+    }
+
+    /// The twelve fiscal periods of the fiscal year starting at `year_start`.
+    /// A 53-week year adds the extra week to the last period.
+    fn fiscal_periods(&self, year_start: NaiveDate, year_end: NaiveDate) -> Vec<(NaiveDate, NaiveDate)> {
+        let mut periods = Vec::with_capacity(12);
+        let mut start = year_start;
+        for index in 0..12 {
+            let end = if index == 11 {
+                year_end
+            } else {
+                start + ChronoDuration::weeks(self.fiscal_pattern[index % 3]) - ChronoDuration::days(1)
+            };
+            periods.push((start, end));
+            start = end + ChronoDuration::days(1);
+        }
+        periods
+    }
+
+    /// Fiscal years are named after the calendar year they mostly fall in
+    fn fiscal_year_name(year_start: NaiveDate) -> i32 {
+        (year_start + ChronoDuration::days(182)).year()
+    }
+
+    fn pay_period_of(&self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let length = match self.pay_frequency {
+            PayFrequency::Weekly => 7,
+            PayFrequency::Biweekly => 14,
+            PayFrequency::Semimonthly => {
+                let last = last_day_of_month(date.year(), date.month());
+                return if date.day() <= 15 {
+                    (date.with_day(1).unwrap(), date.with_day(15).unwrap())
+                } else {
+                    (date.with_day(16).unwrap(), last)
+                };
+            }
+            PayFrequency::Monthly => return (date.with_day(1).unwrap(), last_day_of_month(date.year(), date.month())),
+        };
+        let index = (date - self.pay_anchor).num_days().div_euclid(length);
+        let start = self.pay_anchor + ChronoDuration::days(index * length);
+        (start, start + ChronoDuration::days(length - 1))
+    }
+
+    /// First and last day (inclusive) of the period of `kind` containing `date`
+    pub fn bounds(&self, kind: PeriodKind, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match kind {
+            PeriodKind::Day => (date, date),
+            PeriodKind::Week => self.week_of(date),
+            PeriodKind::IsoWeek => {
+                let start = date - ChronoDuration::days(date.weekday().num_days_from_monday() as i64);
+                (start, start + ChronoDuration::days(6))
+            }
+            PeriodKind::Month => (date.with_day(1).unwrap(), last_day_of_month(date.year(), date.month())),
+            PeriodKind::Quarter => {
+                let first_month = (date.month0() / 3) * 3 + 1;
+                (
+                    NaiveDate::from_ymd_opt(date.year(), first_month, 1).unwrap(),
+                    last_day_of_month(date.year(), first_month + 2),
+                )
+            }
+            PeriodKind::Year => (
+                NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(date.year(), 12, 31).unwrap(),
+            ),
+            PeriodKind::FiscalYear => self.fiscal_year_of(date),
+            PeriodKind::FiscalPeriod | PeriodKind::FiscalQuarter => {
+                let (year_start, year_end) = self.fiscal_year_of(date);
+                let periods = self.fiscal_periods(year_start, year_end);
+                let index = periods.iter().position(|(_, end)| date <= *end).unwrap_or(11);
+                if kind == PeriodKind::FiscalPeriod {
+                    periods[index]
+                } else {
+                    let quarter = index / 3;
+                    (periods[quarter * 3].0, periods[quarter * 3 + 2].1)
+                }
+            }
+            PeriodKind::PayPeriod => self.pay_period_of(date),
+        }
+    }
+
+    /// First day of the week, in days from Sunday
+    pub fn week_start(&self) -> u32 {
+        self.week_start
+    }
+
+    pub fn label(&self, kind: PeriodKind, start: NaiveDate, end: NaiveDate) -> String {
+        match kind {
+            PeriodKind::Day => start.format("%a, %b %-d, %Y").to_string(),
+            PeriodKind::Week | PeriodKind::PayPeriod => date_range_label(start, end),
+            PeriodKind::IsoWeek => {
+                let week = start.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            PeriodKind::Month => start.format("%B %Y").to_string(),
+            PeriodKind::Quarter => format!("Q{} {}", start.month0() / 3 + 1, start.year()),
+            PeriodKind::Year => start.year().to_string(),
+            PeriodKind::FiscalYear => format!("FY{}", Self::fiscal_year_name(start)),
+            PeriodKind::FiscalPeriod | PeriodKind::FiscalQuarter => {
+                let (year_start, year_end) = self.fiscal_year_of(start);
+                let index = self
+                    .fiscal_periods(year_start, year_end)
+                    .iter()
+                    .position(|(period_start, _)| *period_start == start)
+                    .unwrap_or(0);
+                let name = if kind == PeriodKind::FiscalPeriod {
+                    format!("P{}", index + 1)
+                } else {
+                    format!("Q{}", index / 3 + 1)
+                };
+                format!("FY{} {} ({})", Self::fiscal_year_name(year_start), name, date_range_label(start, end))
+            }
+        }
+    }
+
+    /// The period of `kind` containing `date`, moved `offset` periods forward or back
+    pub fn period(&self, kind: PeriodKind, date: NaiveDate, offset: i32) -> Period {
+        let (mut start, mut end) = self.bounds(kind, date);
+        for _ in 0..offset.unsigned_abs() {
+            (start, end) = if offset > 0 {
+                self.bounds(kind, end + ChronoDuration::days(1))
+            } else {
+                self.bounds(kind, start - ChronoDuration::days(1))
+            };
+        }
+        Period {
+            kind,
+            start: start.format("%Y-%m-%d").to_string(),
+            end: end.format("%Y-%m-%d").to_string(),
+            label: self.label(kind, start, end),
+        }
+    }
+}
+
+/// Today in the report time zone
+pub fn today(conn: &Connection) -> NaiveDate {
+    zones::local_date(chrono::Utc::now().timestamp(), zones::report_zone(conn).tz())
+}
+
+/// The period of `kind` containing `date` ("YYYY-MM-DD", default today),
+/// shifted by `offset` periods for navigation
+#[tauri::command]
+pub fn get_period(kind: PeriodKind, date: Option<String>, offset: Option<i32>) -> Result<Period, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    let date = match date {
+        Some(date) => zones::parse_date(&date)?,
+        None => today(&conn),
+    };
+    Ok(Calendar::from_settings(&conn).period(kind, date, offset.unwrap_or(0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        zones::parse_date(text).unwrap()
+    }
+
+    fn fiscal(pattern: [i64; 3]) -> Calendar {
+        Calendar { fiscal_pattern: pattern, ..Calendar::default() }
+    }
+
+    fn pay(frequency: PayFrequency, anchor: &str) -> Calendar {
+        Calendar { pay_frequency: frequency, pay_anchor: date(anchor), ..Calendar::default() }
+    }
+
+    fn bounds(calendar: &Calendar, kind: PeriodKind, day: &str) -> (String, String) {
+        let (start, end) = calendar.bounds(kind, date(day));
+        (start.to_string(), end.to_string())
+    }
+
+    /// Length in weeks of each fiscal period of the fiscal year containing `day`
+    fn period_weeks(calendar: &Calendar, day: &str) -> Vec<i64> {
+        let (start, end) = calendar.fiscal_year_of(date(day));
+        calendar.fiscal_periods(start, end).iter().map(|(start, end)| ((*end - *start).num_days() + 1) / 7).collect()
+    }
+
+    #[test]
+    fn fiscal_year_starts_on_the_nearest_week_start() {
+        let monday = Calendar::default();
+        let sunday = Calendar { week_start: 0, ..Calendar::default() };
+        let july = Calendar { fiscal_year_start: (7, 1), ..Calendar::default() };
+        let cases = [
+            (&monday, 2024, "2024-01-01"), // A Monday itself
+            (&monday, 2025, "2024-12-30"), // Wednesday, two days back
+            (&monday, 2026, "2025-12-29"), // Thursday, three days back
+            (&monday, 2027, "2027-01-04"), // Friday, three days ahead
+            (&sunday, 2026, "2026-01-04"), // Thursday, three days ahead of Sunday
+            (&july, 2026, "2026-06-29"),
+        ];
+        for (calendar, year, expected) in cases {
+            assert_eq!(calendar.fiscal_year_start_in(year), date(expected), "{}", year);
+        }
+    }
+
+    #[test]
+    fn fiscal_periods_follow_the_pattern() {
+        for pattern in [[4, 4, 5], [4, 5, 4], [5, 4, 4]] {
+            let calendar = fiscal(pattern);
+            // FY2025 runs 2024-12-30 to 2025-12-28, 52 weeks
+            assert_eq!(calendar.fiscal_year_of(date("2025-06-01")), (date("2024-12-30"), date("2025-12-28")));
+            assert_eq!(period_weeks(&calendar, "2025-06-01"), pattern.repeat(4));
+            // FY2026 runs 2025-12-29 to 2027-01-03, 53 weeks; period 12 takes the extra one
+            assert_eq!(calendar.fiscal_year_of(date("2027-01-02")), (date("2025-12-29"), date("2027-01-03")));
+            let mut weeks = pattern.repeat(4);
+            weeks[11] += 1;
+            assert_eq!(period_weeks(&calendar, "2026-06-01"), weeks);
+        }
+    }
+
+    #[test]
+    fn fiscal_bounds_and_labels() {
+        let calendar = fiscal([4, 4, 5]);
+        let cases = [
+            (PeriodKind::FiscalPeriod, "2026-10-18", "2026-09-28", "2026-10-25", "FY2026 P10 (Sep 28 - Oct 25, 2026)"),
+            (PeriodKind::FiscalPeriod, "2027-01-03", "2026-11-23", "2027-01-03", "FY2026 P12 (Nov 23 - Jan 3, 2027)"),
+            (PeriodKind::FiscalPeriod, "2025-12-29", "2025-12-29", "2026-01-25", "FY2026 P1 (Dec 29 - Jan 25, 2026)"),
+            (PeriodKind::FiscalQuarter, "2026-10-18", "2026-09-28", "2027-01-03", "FY2026 Q4 (Sep 28 - Jan 3, 2027)"),
+            (PeriodKind::FiscalQuarter, "2026-03-29", "2025-12-29", "2026-03-29", "FY2026 Q1 (Dec 29 - Mar 29, 2026)"),
+            (PeriodKind::FiscalYear, "2027-01-04", "2027-01-04", "2028-01-02", "FY2027"),
+        ];
+        for (kind, day, start, end, label) in cases {
+            assert_eq!(bounds(&calendar, kind, day), (start.to_string(), end.to_string()), "{}", day);
+            assert_eq!(calendar.label(kind, date(start), date(end)), label);
+        }
+    }
+
+    #[test]
+    fn pay_periods() {
+        let semimonthly = pay(PayFrequency::Semimonthly, "2024-01-01");
+        let biweekly = pay(PayFrequency::Biweekly, "2024-01-01");
+        let weekly = pay(PayFrequency::Weekly, "2026-10-14");
+        let monthly = pay(PayFrequency::Monthly, "2024-01-01");
+        let cases = [
+            (&semimonthly, "2026-10-15", "2026-10-01", "2026-10-15"),
+            (&semimonthly, "2026-10-18", "2026-10-16", "2026-10-31"),
+            (&semimonthly, "2026-02-20", "2026-02-16", "2026-02-28"),
+            (&semimonthly, "2028-02-20", "2028-02-16", "2028-02-29"),
+            (&biweekly, "2026-10-18", "2026-10-05", "2026-10-18"),
+            (&biweekly, "2026-10-19", "2026-10-19", "2026-11-01"),
+            (&biweekly, "2024-01-01", "2024-01-01", "2024-01-14"),
+            // Before the anchor
+            (&biweekly, "2023-12-25", "2023-12-18", "2023-12-31"),
+            // Anchored on a Wednesday
+            (&weekly, "2026-10-18", "2026-10-14", "2026-10-20"),
+            (&weekly, "2026-10-13", "2026-10-07", "2026-10-13"),
+            (&monthly, "2026-02-10", "2026-02-01", "2026-02-28"),
+        ];
+        for (calendar, day, start, end) in cases {
+            assert_eq!(bounds(calendar, PeriodKind::PayPeriod, day), (start.to_string(), end.to_string()), "{}", day);
+        }
+        assert_eq!(biweekly.label(PeriodKind::PayPeriod, date("2026-10-05"), date("2026-10-18")), "Oct 5 - Oct 18, 2026");
+    }
+
+    #[test]
+    fn calendar_periods_and_labels() {
+        let monday = Calendar::default();
+        let saturday = Calendar { week_start: 6, ..Calendar::default() };
+        let cases = [
+            (&monday, PeriodKind::Day, "2026-10-18", "2026-10-18", "2026-10-18", "Sun, Oct 18, 2026"),
+            (&monday, PeriodKind::Week, "2026-10-18", "2026-10-12", "2026-10-18", "Oct 12 - Oct 18, 2026"),
+            (&saturday, PeriodKind::Week, "2026-10-18", "2026-10-17", "2026-10-23", "Oct 17 - Oct 23, 2026"),
+            (&saturday, PeriodKind::IsoWeek, "2026-10-18", "2026-10-12", "2026-10-18", "2026-W42"),
+            (&monday, PeriodKind::IsoWeek, "2027-01-01", "2026-12-28", "2027-01-03", "2026-W53"),
+            (&monday, PeriodKind::Month, "2028-02-10", "2028-02-01", "2028-02-29", "February 2028"),
+            (&monday, PeriodKind::Quarter, "2026-10-18", "2026-10-01", "2026-12-31", "Q4 2026"),
+            (&monday, PeriodKind::Year, "2026-10-18", "2026-01-01", "2026-12-31", "2026"),
+        ];
+        for (calendar, kind, day, start, end, label) in cases {
+            assert_eq!(bounds(calendar, kind, day), (start.to_string(), end.to_string()), "{}", label);
+            assert_eq!(calendar.label(kind, date(start), date(end)), label);
+        }
+    }
+
+    #[test]
+    fn periods_move_by_offset() {
+        let calendar = Calendar::default();
+        let period = calendar.period(PeriodKind::Month, date("2026-01-31"), 1);
+        assert_eq!((period.start.as_str(), period.end.as_str(), period.label.as_str()), ("2026-02-01", "2026-02-28", "February 2026"));
+        let period = calendar.period(PeriodKind::FiscalPeriod, date("2026-01-10"), -1);
+        assert_eq!((period.start.as_str(), period.end.as_str()), ("2025-11-24", "2025-12-28"));
+        assert_eq!(period.label, "FY2025 P12 (Nov 24 - Dec 28, 2025)");
+    }
+}
@@ -0,0 +1,61 @@
+// Project changes go through the backend so they can be undone
+use tauri::Emitter;
+
+use crate::db;
+use crate::journal::Recorder;
+
+#[tauri::command]
+pub fn create_project(name: String, color: String) -> Result<i64, String> {
+    let mut conn = db::open().ok_or("Database not found")?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute("INSERT INTO projects (name, color) VALUES (?1, ?2)", [&name, &color])
+        .map_err(|e| e.to_string())?;
+    let id = tx.last_insert_rowid();
+    let mut journal = Recorder::new("Create project");
+    journal.track_new("projects", id);
+    journal.finish(&tx).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+/// Rename or recolor a project. `client` is left alone when None and
+/// cleared when empty.
+#[tauri::command]
+pub fn update_project(id: i64, name: String, color: String, client: Option<String>) -> Result<(), String> {
+    let mut conn = db::open().ok_or("Database not found")?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut journal = Recorder::new("Edit project");
+    journal.track(&tx, "projects", id).map_err(|e| e.to_string())?;
+    tx.execute(
+        "UPDATE projects
+         SET name = ?1, color = ?2, client = CASE WHEN ?3 IS NULL THEN client ELSE NULLIF(TRIM(?3), '') END
+         WHERE id = ?4",
+        rusqlite::params![name, color, client, id],
+    )
+    .map_err(|e| e.to_string())?;
+    journal.finish(&tx).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Delete a project together with its time entries and repository mappings
+#[tauri::command]
+pub fn delete_project(app: tauri::AppHandle, id: i64) -> Result<(), String> {
+    let mut conn = db::open().ok_or("Database not found")?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut journal = Recorder::new("Delete project");
+    journal.track(&tx, "projects", id).map_err(|e| e.to_string())?;
+    journal.track_where(&tx, "time_entries", "project_id = ?1", [id]).map_err(|e| e.to_string())?;
+    journal.track_where(&tx, "project_repos", "project_id = ?1", [id]).map_err(|e| e.to_string())?;
+
+    tx.execute("DELETE FROM time_entries WHERE project_id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM project_repos WHERE project_id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM projects WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    journal.finish(&tx).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    let _ = app.emit("entries-changed", ());
+    Ok(())
+}
@@ -0,0 +1,273 @@
+// Render reports to files: Markdown, HTML, CSV and a plain single-font PDF.
+// Every format shows a title, a few lines of notes and one table. For reports
+// the table has one column per grouped dimension, then the number of entries
+// and the duration (and the rounded duration when the report rounds).
+use crate::report::{Report, ReportSpec};
+
+#[derive(Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Markdown,
+    Html,
+    Csv,
+    Pdf,
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Markdown => "md",
+            Format::Html => "html",
+            Format::Csv => "csv",
+            Format::Pdf => "pdf",
+        }
+    }
+}
+
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    pub total: Vec<String>,
+    /// Leading columns that hold labels; the rest are numbers
+    pub label_columns: usize,
+}
+
+/// "H:MM"; hours go past 24 for long periods
+fn clock(seconds: i64) -> String {
+    format!("{}:{:02}", seconds / 3600, seconds % 3600 / 60)
+}
+
+/// Decimal hours, which spreadsheets can add up
+fn hours(seconds: i64) -> String {
+    format!("{:.2}", seconds as f64 / 3600.0)
+}
+
+/// How durations are written in `format`: decimal hours for CSV, "H:MM" otherwise
+pub fn duration_cell(format: Format) -> fn(i64) -> String {
+    if format == Format::Csv {
+        hours
+    } else {
+        clock
+    }
+}
+
+fn table(report: &Report, spec: &ReportSpec, duration: fn(i64) -> String) -> Table {
+    let rounded = spec.rounding.is_some();
+    let label_columns = spec.group_by.len().max(1);
+    let mut headers: Vec<String> = spec.group_by.iter().map(|d| d.title().to_string()).collect();
+    if headers.is_empty() {
+        headers.push("Period".to_string());
+    }
+    headers.push("Entries".to_string());
+    headers.push("Duration".to_string());
+    if rounded {
+        headers.push("Rounded".to_string());
+    }
+
+    let numbers = |entries: i64, exact: i64, rounded_duration: i64| {
+        let mut cells = vec![entries.to_string(), duration(exact)];
+        if rounded {
+            cells.push(duration(rounded_duration));
+        }
+        cells
+    };
+    let rows = report
+        .rows
+        .iter()
+        .map(|row| {
+            let mut cells = row.labels.clone();
+            cells.resize(label_columns, String::new());
+            cells.extend(numbers(row.entries, row.duration, row.rounded_duration));
+            cells
+        })
+        .collect();
+    let mut total = vec![String::new(); label_columns];
+    total[0] = "Total".to_string();
+    total.extend(numbers(report.entries, report.duration, report.rounded_duration));
+
+    Table { headers, rows, total, label_columns }
+}
+
+fn markdown(title: &str, notes: &[String], table: &Table) -> String {
+    let line = |cells: &[String]| format!("| {} |\n", cells.iter().map(|c| c.replace('|', "\\|")).collect::<Vec<_>>().join(" | "));
+    let mut out = format!("# {}\n\n", title);
+    for note in notes {
+        out += &format!("{}  \n", note);
+    }
+    out += "\n";
+    out += &line(&table.headers);
+    let alignment: Vec<String> = (0..table.headers.len())
+        .map(|i| if i < table.label_columns { "---".to_string() } else { "---:".to_string() })
+        .collect();
+    out += &format!("|{}|\n", alignment.join("|"));
+    for row in &table.rows {
+        out += &line(row);
+    }
+    let total: Vec<String> = table.total.iter().map(|c| if c.is_empty() { String::new() } else { format!("**{}**", c) }).collect();
+    out += &line(&total);
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn html(title: &str, notes: &[String], table: &Table) -> String {
+    let cells = |tag: &str, cells: &[String]| {
+        cells
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                let class = if i < table.label_columns { "" } else { " class=\"num\"" };
+                format!("<{tag}{class}>{}</{tag}>", escape_html(c))
+            })
+            .collect::<String>()
+    };
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n<style>\n\
+         body {{ font-family: system-ui, sans-serif; margin: 2rem; color: #222; }}\n\
+         table {{ border-collapse: collapse; }}\n\
+         th, td {{ padding: 0.3rem 0.8rem; border-bottom: 1px solid #ddd; text-align: left; }}\n\
+         .num {{ text-align: right; font-variant-numeric: tabular-nums; }}\n\
+         tfoot td {{ font-weight: bold; border-bottom: none; }}\n\
+         </style>\n</head>\n<body>\n<h1>{0}</h1>\n<p>{1}</p>\n<table>\n",
+        escape_html(title),
+        notes.iter().map(|note| escape_html(note)).collect::<Vec<_>>().join("<br>\n")
+    );
+    out += &format!("<thead><tr>{}</tr></thead>\n<tbody>\n", cells("th", &table.headers));
+    for row in &table.rows {
+        out += &format!("<tr>{}</tr>\n", cells("td", row));
+    }
+    out += &format!("</tbody>\n<tfoot><tr>{}</tr></tfoot>\n</table>\n</body>\n</html>\n", cells("td", &table.total));
+    out
+}
+
+fn csv(table: &Table) -> String {
+    let field = |value: &String| {
+        if value.contains([',', '"', '\n']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.clone()
+        }
+    };
+    let mut out = String::new();
+    for row in std::iter::once(&table.headers).chain(&table.rows).chain(std::iter::once(&table.total)) {
+        out += &row.iter().map(field).collect::<Vec<_>>().join(",");
+        out += "\r\n";
+    }
+    out
+}
+
+/// Lay the table out as monospaced text lines
+fn text_lines(title: &str, notes: &[String], table: &Table) -> Vec<String> {
+    let mut widths: Vec<usize> = table.headers.iter().map(|h| h.chars().count()).collect();
+    for row in table.rows.iter().chain(std::iter::once(&table.total)) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: &[String]| {
+        cells
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                if i < table.label_columns {
+                    format!("{:<width$}", c, width = widths[i])
+                } else {
+                    format!("{:>width$}", c, width = widths[i])
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    let rule = "-".repeat(widths.iter().sum::<usize>() + 2 * (widths.len() - 1));
+    let mut lines = vec![title.to_string()];
+    lines.extend(notes.iter().cloned());
+    lines.extend([String::new(), line(&table.headers), rule.clone()]);
+    lines.extend(table.rows.iter().map(|row| line(row)));
+    lines.push(rule);
+    lines.push(line(&table.total));
+    lines
+}
+
+/// PDF string literal in WinAnsi; characters outside Latin-1 become '?'
+fn pdf_string(text: &str) -> Vec<u8> {
+    let mut out = vec![b'('];
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push(b'\\');
+                out.push(c as u8);
+            }
+            ' '..='~' | '\u{a0}'..='\u{ff}' => out.push(c as u32 as u8),
+            _ => out.push(b'?'),
+        }
+    }
+    out.push(b')');
+    out
+}
+
+/// A4 pages of Courier text
+fn pdf(lines: &[String]) -> Vec<u8> {
+    const LINES_PER_PAGE: usize = 60;
+    let pages: Vec<&[String]> = if lines.is_empty() { vec![&[]] } else { lines.chunks(LINES_PER_PAGE).collect() };
+
+    // Objects 1-3 are the catalog, page tree and font; each page adds a page and a content stream
+    let mut objects: Vec<Vec<u8>> = Vec::new();
+    let kids: Vec<String> = (0..pages.len()).map(|i| format!("{} 0 R", 4 + i * 2)).collect();
+    objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+    objects.push(format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()).into_bytes());
+    objects.push(b"<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>".to_vec());
+    for (i, page) in pages.iter().enumerate() {
+        let mut content = b"BT /F1 9 Tf 12 TL 40 800 Td\n".to_vec();
+        for line in page.iter() {
+            content.extend(pdf_string(line));
+            content.extend(b" '\n");
+        }
+        content.extend(b"ET");
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                5 + i * 2
+            )
+            .into_bytes(),
+        );
+        let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+        stream.extend(content);
+        stream.extend(b"\nendstream");
+        objects.push(stream);
+    }
+
+    let mut out = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        out.extend(format!("{} 0 obj\n", i + 1).into_bytes());
+        out.extend(object);
+        out.extend(b"\nendobj\n");
+    }
+    let xref = out.len();
+    out.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).into_bytes());
+    for offset in offsets {
+        out.extend(format!("{:010} 00000 n \n", offset).into_bytes());
+    }
+    out.extend(format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref).into_bytes());
+    out
+}
+
+/// Render a table under a title and notes; CSV only has the table
+pub fn render_table(title: &str, notes: &[String], table: &Table, format: Format) -> Vec<u8> {
+    match format {
+        Format::Markdown => markdown(title, notes, table).into_bytes(),
+        Format::Html => html(title, notes, table).into_bytes(),
+        Format::Csv => csv(table).into_bytes(),
+        Format::Pdf => pdf(&text_lines(title, notes, table)),
+    }
+}
+
+/// Render `report`, produced from `spec`, under a title and a period label
+pub fn render(report: &Report, spec: &ReportSpec, title: &str, period: &str, format: Format) -> Vec<u8> {
+    render_table(title, &[period.to_string()], &table(report, spec, duration_cell(format)), format)
+}
@@ -0,0 +1,610 @@
+// Report engine. `report` takes a structured query (date range, group-by
+// dimensions, filters, rounding) and returns typed rows; the Reports page,
+// exports and totals all go through it. Entries are bucketed into calendar
+// days here rather than in SQL, so days follow the report zone (see zones.rs)
+// instead of whatever zone the machine happens to be in, and weeks and other
+// periods follow the configured calendar (see periods.rs).
+use chrono::{Datelike, NaiveDate, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use rusqlite::Connection;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::db;
+use crate::periods::{self, Calendar, PeriodKind};
+use crate::zones::{self, ReportZone};
+
+#[derive(Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dimension {
+    Project,
+    Client,
+    Tag,
+    Day,
+    Week,
+    Month,
+    FiscalPeriod,
+    PayPeriod,
+    /// 0 = Sunday, like the reminder weekdays
+    Weekday,
+    /// Grouping by hour splits entries at hour boundaries and counts only the
+    /// part of an entry that falls inside the range
+    Hour,
+}
+
+#[derive(Clone, Copy, Default, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingMode {
+    #[default]
+    Nearest,
+    Up,
+    Down,
+}
+
+#[derive(Clone, Copy, Default, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingScope {
+    /// Round every entry, then add them up
+    #[default]
+    Entry,
+    /// Add up the entries of a row, then round the sum
+    Row,
+}
+
+#[derive(Clone, Copy, serde::Deserialize, serde::Serialize)]
+pub struct Rounding {
+    minutes: u32,
+    #[serde(default)]
+    mode: RoundingMode,
+    #[serde(default)]
+    scope: RoundingScope,
+}
+
+/// Rows only include entries matching every non-empty filter
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ReportFilters {
+    project_ids: Vec<i64>,
+    clients: Vec<String>,
+    /// Entries carrying at least one of these tags
+    tags: Vec<String>,
+    weekdays: Vec<u32>,
+}
+
+/// What a report shows, independent of its dates; scheduled reports save this
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ReportSpec {
+    pub group_by: Vec<Dimension>,
+    pub filters: ReportFilters,
+    pub rounding: Option<Rounding>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct ReportQuery {
+    pub start_date: String, // "YYYY-MM-DD", inclusive
+    pub end_date: String,
+    #[serde(flatten)]
+    pub spec: ReportSpec,
+}
+
+/// One group of the report. Only the fields of the grouped dimensions are set.
+#[derive(Default, serde::Serialize)]
+pub struct ReportRow {
+    pub project_id: Option<i64>,
+    pub project_name: Option<String>,
+    pub project_color: Option<String>,
+    pub client: Option<String>,
+    pub tag: Option<String>,
+    pub day: Option<String>,           // "YYYY-MM-DD"
+    pub week: Option<String>,          // First day of the week
+    pub month: Option<String>,         // "YYYY-MM"
+    pub fiscal_period: Option<String>, // First day of the period
+    pub pay_period: Option<String>,    // First day of the period
+    pub weekday: Option<u32>,
+    pub hour: Option<u32>,
+    /// Human-readable value of each grouped dimension, in `group_by` order
+    pub labels: Vec<String>,
+    pub duration: i64, // Seconds
+    pub rounded_duration: i64,
+    /// Entries with time in this row
+    pub entries: i64,
+    #[serde(skip)]
+    last_entry: Option<i64>,
+}
+
+/// Rows can overlap when grouping by tag, since an entry counts towards each
+/// of its tags; the totals count every entry once.
+#[derive(serde::Serialize)]
+pub struct Report {
+    pub rows: Vec<ReportRow>,
+    pub duration: i64,
+    pub rounded_duration: i64,
+    pub entries: i64,
+}
+
+struct Entry {
+    id: i64,
+    project_id: i64,
+    project_name: String,
+    project_color: String,
+    client: Option<String>,
+    start: i64,
+    end: i64,
+    timezone: Option<String>,
+}
+
+/// Ordered key of one dimension
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Key {
+    None,
+    Number(i64),
+    Text(String),
+}
+
+fn round(seconds: i64, rounding: Option<Rounding>) -> i64 {
+    let Some(rounding) = rounding.filter(|r| r.minutes > 0) else {
+        return seconds;
+    };
+    let step = rounding.minutes as i64 * 60;
+    match rounding.mode {
+        RoundingMode::Nearest => (seconds + step / 2) / step * step,
+        RoundingMode::Up => (seconds + step - 1) / step * step,
+        RoundingMode::Down => seconds / step * step,
+    }
+}
+
+fn load_entries(conn: &Connection, from: i64, to: i64) -> rusqlite::Result<Vec<Entry>> {
+    let mut stmt = conn.prepare(
+        "SELECT te.id, te.project_id, p.name, p.color, p.client,
+                CAST(strftime('%s', te.start_time) AS INTEGER),
+                CAST(strftime('%s', te.end_time) AS INTEGER), te.timezone
+         FROM time_entries te
+         JOIN projects p ON p.id = te.project_id
+         WHERE te.end_time IS NOT NULL
+           AND te.end_time > datetime(?1, 'unixepoch') AND te.start_time < datetime(?2, 'unixepoch')
+         ORDER BY te.start_time",
+    )?;
+    let rows = stmt.query_map([from, to], |row| {
+        Ok(Entry {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            project_name: row.get(2)?,
+            project_color: row.get(3)?,
+            client: row.get(4)?,
+            start: row.get(5)?,
+            end: row.get(6)?,
+            timezone: row.get(7)?,
+        })
+    })?;
+    rows.collect()
+}
+
+fn load_tags(conn: &Connection, from: i64, to: i64) -> rusqlite::Result<HashMap<i64, Vec<String>>> {
+    let mut stmt = conn.prepare(
+        "SELECT et.entry_id, et.tag
+         FROM entry_tags et
+         JOIN time_entries te ON te.id = et.entry_id
+         WHERE te.end_time > datetime(?1, 'unixepoch') AND te.start_time < datetime(?2, 'unixepoch')
+         ORDER BY et.tag",
+    )?;
+    let mut tags: HashMap<i64, Vec<String>> = HashMap::new();
+    for row in stmt.query_map([from, to], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))? {
+        let (entry_id, tag) = row?;
+        tags.entry(entry_id).or_default().push(tag);
+    }
+    Ok(tags)
+}
+
+/// Cut [start, end) at local hour boundaries in `tz`
+fn hour_segments(start: i64, end: i64, tz: Tz) -> Vec<(i64, i64)> {
+    let mut segments = Vec::new();
+    let mut cursor = start;
+    while cursor < end {
+        let offset = Utc
+            .timestamp_opt(cursor, 0)
+            .single()
+            .map(|t| t.with_timezone(&tz).naive_local().and_utc().timestamp() - cursor)
+            .unwrap_or(0);
+        let next = (cursor + offset).div_euclid(3600) * 3600 + 3600 - offset;
+        segments.push((cursor, next.min(end)));
+        cursor = next;
+    }
+    segments
+}
+
+const WEEKDAY_NAMES: [&str; 7] = ["Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday"];
+
+impl Dimension {
+    /// Column heading for exported reports
+    pub fn title(self) -> &'static str {
+        match self {
+            Dimension::Project => "Project",
+            Dimension::Client => "Client",
+            Dimension::Tag => "Tag",
+            Dimension::Day => "Day",
+            Dimension::Week => "Week",
+            Dimension::Month => "Month",
+            Dimension::FiscalPeriod => "Fiscal period",
+            Dimension::PayPeriod => "Pay period",
+            Dimension::Weekday => "Weekday",
+            Dimension::Hour => "Hour",
+        }
+    }
+}
+
+/// Run a report query
+pub fn run(conn: &Connection, query: &ReportQuery) -> Result<Report, String> {
+    let first_day = zones::parse_date(&query.start_date)?;
+    let last_day = zones::parse_date(&query.end_date)?;
+    if last_day < first_day {
+        return Err("The report ends before it starts".into());
+    }
+    let (from, to) = zones::query_bounds(first_day, last_day);
+    let zone = zones::report_zone(conn);
+    let calendar = Calendar::from_settings(conn);
+    let entries = load_entries(conn, from, to).map_err(|e| e.to_string())?;
+    let tags = load_tags(conn, from, to).map_err(|e| e.to_string())?;
+    let spec = &query.spec;
+    let filters = &spec.filters;
+    let by_hour = spec.group_by.contains(&Dimension::Hour);
+    let per_entry_rounding = spec.rounding.is_some_and(|r| matches!(r.scope, RoundingScope::Entry));
+
+    let mut rows: BTreeMap<Vec<Key>, ReportRow> = BTreeMap::new();
+    let (mut total, mut total_rounded, mut total_entries) = (0, 0, 0);
+    for entry in &entries {
+        let tz = match zone {
+            ReportZone::Fixed(tz) => tz,
+            ReportZone::PerEntry => zones::entry_zone(entry.timezone.as_deref()),
+        };
+        // Entries count on the day they start, unless split by hour
+        let start_day = zones::local_date(entry.start, tz);
+        if !by_hour && (start_day < first_day || start_day > last_day) {
+            continue;
+        }
+        let entry_tags = tags.get(&entry.id).map(Vec::as_slice).unwrap_or_default();
+        if (!filters.project_ids.is_empty() && !filters.project_ids.contains(&entry.project_id))
+            || (!filters.clients.is_empty() && !entry.client.as_ref().is_some_and(|c| filters.clients.contains(c)))
+            || (!filters.tags.is_empty() && !entry_tags.iter().any(|t| filters.tags.contains(t)))
+        {
+            continue;
+        }
+
+        let segments = if by_hour { hour_segments(entry.start, entry.end, tz) } else { vec![(entry.start, entry.end)] };
+        // Per-entry rounding adds the difference to the entry's last segment
+        let rounding_delta = if per_entry_rounding {
+            round(entry.end - entry.start, spec.rounding) - (entry.end - entry.start)
+        } else {
+            0
+        };
+        // An entry counts once towards each tag it carries
+        let tag_values: Vec<Option<&String>> = if spec.group_by.contains(&Dimension::Tag) && !entry_tags.is_empty() {
+            entry_tags.iter().map(Some).collect()
+        } else {
+            vec![None]
+        };
+
+        let mut counted = false;
+        for (index, &(seg_start, seg_end)) in segments.iter().enumerate() {
+            let local = Utc.timestamp_opt(seg_start, 0).single().unwrap_or_default().with_timezone(&tz);
+            let date = local.date_naive();
+            if date < first_day || date > last_day {
+                continue;
+            }
+            let weekday = date.weekday().num_days_from_sunday();
+            if !filters.weekdays.is_empty() && !filters.weekdays.contains(&weekday) {
+                continue;
+            }
+            let duration = seg_end - seg_start;
+            let delta = if index == segments.len() - 1 { rounding_delta } else { 0 };
+            total += duration;
+            total_rounded += duration + delta;
+            if !counted {
+                counted = true;
+                total_entries += 1;
+            }
+
+            for tag in &tag_values {
+                let mut key = Vec::with_capacity(spec.group_by.len());
+                let mut row = ReportRow::default();
+                for dimension in &spec.group_by {
+                    let (part, label) = match dimension {
+                        Dimension::Project => {
+                            row.project_id = Some(entry.project_id);
+                            row.project_name = Some(entry.project_name.clone());
+                            row.project_color = Some(entry.project_color.clone());
+                            (Key::Number(entry.project_id), entry.project_name.clone())
+                        }
+                        Dimension::Client => {
+                            row.client = entry.client.clone();
+                            match &entry.client {
+                                Some(client) => (Key::Text(client.clone()), client.clone()),
+                                None => (Key::None, "No client".to_string()),
+                            }
+                        }
+                        Dimension::Tag => {
+                            row.tag = tag.cloned();
+                            match tag {
+                                Some(tag) => (Key::Text(tag.to_string()), tag.to_string()),
+                                None => (Key::None, "Untagged".to_string()),
+                            }
+                        }
+                        Dimension::Day => {
+                            let day = date.format("%Y-%m-%d").to_string();
+                            row.day = Some(day.clone());
+                            (Key::Text(day), date.format("%a, %b %-d, %Y").to_string())
+                        }
+                        Dimension::Week | Dimension::FiscalPeriod | Dimension::PayPeriod => {
+                            let kind = match dimension {
+                                Dimension::Week => PeriodKind::Week,
+                                Dimension::FiscalPeriod => PeriodKind::FiscalPeriod,
+                                _ => PeriodKind::PayPeriod,
+                            };
+                            let (first, last) = calendar.bounds(kind, date);
+                            let start = first.format("%Y-%m-%d").to_string();
+                            match dimension {
+                                Dimension::Week => row.week = Some(start.clone()),
+                                Dimension::FiscalPeriod => row.fiscal_period = Some(start.clone()),
+                                _ => row.pay_period = Some(start.clone()),
+                            }
+                            (Key::Text(start), calendar.label(kind, first, last))
+                        }
+                        Dimension::Month => {
+                            let month = date.format("%Y-%m").to_string();
+                            row.month = Some(month.clone());
+                            (Key::Text(month), date.format("%B %Y").to_string())
+                        }
+                        Dimension::Weekday => {
+                            row.weekday = Some(weekday);
+                            // Sort from the configured first day of the week
+                            let position = (weekday + 7 - calendar.week_start()) % 7;
+                            (Key::Number(position as i64), WEEKDAY_NAMES[weekday as usize].to_string())
+                        }
+                        Dimension::Hour => {
+                            let hour = local.hour();
+                            row.hour = Some(hour);
+                            (Key::Number(hour as i64), format!("{:02}:00", hour))
+                        }
+                    };
+                    key.push(part);
+                    row.labels.push(label);
+                }
+
+                let row = rows.entry(key).or_insert(row);
+                row.duration += duration;
+                row.rounded_duration += duration + delta;
+                if row.last_entry != Some(entry.id) {
+                    row.last_entry = Some(entry.id);
+                    row.entries += 1;
+                }
+            }
+        }
+    }
+
+    let mut rows: Vec<ReportRow> = rows.into_values().collect();
+    if !per_entry_rounding {
+        for row in &mut rows {
+            row.rounded_duration = round(row.duration, spec.rounding);
+        }
+        // Rounded rows add up to the total unless an entry sits in several of them
+        total_rounded = if spec.group_by.contains(&Dimension::Tag) {
+            round(total, spec.rounding)
+        } else {
+            rows.iter().map(|r| r.rounded_duration).sum()
+        };
+    }
+    Ok(Report { rows, duration: total, rounded_duration: total_rounded, entries: total_entries })
+}
+
+/// Aggregate tracked time as described by `query`
+#[tauri::command]
+pub fn report(query: ReportQuery) -> Result<Report, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    run(&conn, &query)
+}
+
+#[derive(serde::Serialize)]
+pub struct DayEntry {
+    id: i64,
+    project_id: i64,
+    project_name: String,
+    project_color: String,
+    start_time: String, // UTC "YYYY-MM-DD HH:MM:SS"
+    end_time: Option<String>,
+}
+
+/// Entries, the running one included, that count on a date ("YYYY-MM-DD"):
+/// the day they start on in the report zone, as in `report`
+#[tauri::command]
+pub fn get_day_entries(date: String) -> Result<Vec<DayEntry>, String> {
+    let day = zones::parse_date(&date)?;
+    let conn = db::open().ok_or("Database not found")?;
+    let (from, to) = zones::query_bounds(day, day);
+    let zone = zones::report_zone(&conn);
+    let mut stmt = conn
+        .prepare(
+            "SELECT te.id, te.project_id, p.name, p.color, te.start_time, te.end_time,
+                    CAST(strftime('%s', te.start_time) AS INTEGER), te.timezone
+             FROM time_entries te
+             JOIN projects p ON p.id = te.project_id
+             WHERE te.start_time >= datetime(?1, 'unixepoch') AND te.start_time < datetime(?2, 'unixepoch')
+             ORDER BY te.start_time",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([from, to], |row| {
+            let entry = DayEntry {
+                id: row.get(0)?,
+                project_id: row.get(1)?,
+                project_name: row.get(2)?,
+                project_color: row.get(3)?,
+                start_time: row.get(4)?,
+                end_time: row.get(5)?,
+            };
+            Ok((entry, row.get::<_, i64>(6)?, row.get::<_, Option<String>>(7)?))
+        })
+        .map_err(|e| e.to_string())?;
+    let mut entries = Vec::new();
+    for row in rows {
+        let (entry, start, timezone) = row.map_err(|e| e.to_string())?;
+        if zone.date_of(start, timezone.as_deref()) == day {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+/// Tracked seconds in the period of `kind` containing `date` (default today)
+#[tauri::command]
+pub fn get_period_total(kind: PeriodKind, date: Option<String>) -> Result<i64, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    let date: NaiveDate = match date {
+        Some(date) => zones::parse_date(&date)?,
+        None => periods::today(&conn),
+    };
+    let (first_day, last_day) = Calendar::from_settings(&conn).bounds(kind, date);
+    let query = ReportQuery {
+        start_date: first_day.format("%Y-%m-%d").to_string(),
+        end_date: last_day.format("%Y-%m-%d").to_string(),
+        spec: ReportSpec::default(),
+    };
+    Ok(run(&conn, &query)?.duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Connection {
+        let conn = db::testing::memory();
+        conn.execute_batch(
+            "INSERT INTO settings (key, value) VALUES ('report_timezone', 'UTC');
+             INSERT INTO projects (id, name, color) VALUES (1, 'Horalis', '#111'), (2, 'Billing', '#222');
+             INSERT INTO time_entries (id, project_id, start_time, end_time, duration) VALUES
+                 (1, 1, '2026-10-19 09:00:00', '2026-10-19 10:00:00', 3600),
+                 (2, 1, '2026-10-19 10:00:00', '2026-10-19 10:20:00', 1200),
+                 (3, 2, '2026-10-20 14:00:00', '2026-10-20 14:50:00', 3000);
+             INSERT INTO entry_tags (entry_id, tag) VALUES (1, 'meeting'), (1, 'client'), (1, 'review'), (2, 'meeting');",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn query(group_by: Vec<Dimension>, rounding: Option<Rounding>) -> ReportQuery {
+        ReportQuery {
+            start_date: "2026-10-19".into(),
+            end_date: "2026-10-25".into(),
+            spec: ReportSpec { group_by, filters: ReportFilters::default(), rounding },
+        }
+    }
+
+    #[test]
+    fn entry_with_several_tags_counts_once_in_totals() {
+        let conn = setup();
+        let report = run(&conn, &query(vec![Dimension::Tag], None)).unwrap();
+
+        let row = |tag: Option<&str>| report.rows.iter().find(|r| r.tag.as_deref() == tag).unwrap();
+        assert_eq!(row(Some("meeting")).duration, 3600 + 1200);
+        assert_eq!(row(Some("client")).duration, 3600);
+        assert_eq!(row(Some("review")).duration, 3600);
+        assert_eq!(row(None).duration, 3000);
+        assert_eq!(row(Some("meeting")).entries, 2);
+
+        assert_eq!(report.duration, 3600 + 1200 + 3000);
+        assert_eq!(report.rounded_duration, report.duration);
+        assert_eq!(report.entries, 3);
+    }
+
+    #[test]
+    fn rounded_totals_count_tagged_entries_once() {
+        let conn = setup();
+        let quarter_up = |scope| Some(Rounding { minutes: 15, mode: RoundingMode::Up, scope });
+
+        let per_entry = run(&conn, &query(vec![Dimension::Tag], quarter_up(RoundingScope::Entry))).unwrap();
+        // 60 + 30 + 60 minutes once each, not once per tag
+        assert_eq!(per_entry.rounded_duration, (60 + 30 + 60) * 60);
+
+        let per_row = run(&conn, &query(vec![Dimension::Tag], quarter_up(RoundingScope::Row))).unwrap();
+        // 130 minutes in total, rounded up to the quarter
+        assert_eq!(per_row.rounded_duration, 135 * 60);
+    }
+
+    fn secs(text: &str) -> i64 {
+        chrono::NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S").unwrap().and_utc().timestamp()
+    }
+
+    /// Segments as local "HH:MM" pairs, checking they tile [start, end)
+    fn local_segments(start: &str, end: &str, tz: Tz) -> Vec<(String, String)> {
+        let (start, end) = (secs(start), secs(end));
+        let segments = hour_segments(start, end, tz);
+        assert_eq!(segments.first().map(|s| s.0), Some(start));
+        assert_eq!(segments.last().map(|s| s.1), Some(end));
+        assert!(segments.windows(2).all(|pair| pair[0].1 == pair[1].0));
+        let local = |t: i64| Utc.timestamp_opt(t, 0).unwrap().with_timezone(&tz).format("%H:%M").to_string();
+        segments.into_iter().map(|(a, b)| (local(a), local(b))).collect()
+    }
+
+    fn pairs(items: &[(&str, &str)]) -> Vec<(String, String)> {
+        items.iter().map(|(a, b)| (a.to_string(), b.to_string())).collect()
+    }
+
+    #[test]
+    fn hour_segments_split_at_midnight() {
+        assert_eq!(
+            local_segments("2026-10-19 23:30:00", "2026-10-20 00:45:00", Tz::UTC),
+            pairs(&[("23:30", "00:00"), ("00:00", "00:45")])
+        );
+    }
+
+    #[test]
+    fn hour_segments_follow_dst_changes() {
+        let berlin = chrono_tz::Europe::Berlin;
+        // Spring forward: 02:00 CET becomes 03:00 CEST, so 01:30-03:30 is one hour
+        assert_eq!(
+            local_segments("2026-03-29 00:30:00", "2026-03-29 01:30:00", berlin),
+            pairs(&[("01:30", "03:00"), ("03:00", "03:30")])
+        );
+        // Fall back: 03:00 CEST becomes 02:00 CET and the hour repeats
+        assert_eq!(
+            local_segments("2026-10-25 00:30:00", "2026-10-25 01:30:00", berlin),
+            pairs(&[("02:30", "02:00"), ("02:00", "02:30")])
+        );
+    }
+
+    #[test]
+    fn hour_segments_in_half_hour_zone() {
+        // India is UTC+05:30, so local hours start at half past in UTC
+        assert_eq!(
+            local_segments("2026-10-19 03:00:00", "2026-10-19 05:00:00", chrono_tz::Asia::Kolkata),
+            pairs(&[("08:30", "09:00"), ("09:00", "10:00"), ("10:00", "10:30")])
+        );
+    }
+
+    #[test]
+    fn hour_matrix_adds_up_to_entry_durations() {
+        let conn = setup();
+        conn.execute_batch(
+            "INSERT INTO time_entries (id, project_id, start_time, end_time, duration, timezone) VALUES
+                 (4, 2, '2026-10-21 23:10:00', '2026-10-22 01:05:00', 6900, 'UTC'),
+                 (5, 1, '2026-10-23 03:00:00', '2026-10-23 05:17:00', 8220, 'Asia/Kolkata');",
+        )
+        .unwrap();
+        for timezone in ["UTC", "Asia/Kolkata", "Europe/Berlin", "entry"] {
+            conn.execute("UPDATE settings SET value = ?1 WHERE key = 'report_timezone'", [timezone]).unwrap();
+            let matrix = run(&conn, &query(vec![Dimension::Project, Dimension::Weekday, Dimension::Hour], None)).unwrap();
+            let plain = run(&conn, &query(vec![], None)).unwrap();
+            assert_eq!(matrix.rows.iter().map(|r| r.duration).sum::<i64>(), 3600 + 1200 + 3000 + 6900 + 8220, "{}", timezone);
+            assert_eq!(matrix.duration, plain.duration, "{}", timezone);
+            assert_eq!(matrix.entries, 5, "{}", timezone);
+        }
+    }
+
+    #[test]
+    fn totals_match_without_tags() {
+        let conn = setup();
+        let by_tag = run(&conn, &query(vec![Dimension::Project, Dimension::Tag], None)).unwrap();
+        let by_project = run(&conn, &query(vec![Dimension::Project], None)).unwrap();
+        assert_eq!(by_tag.duration, by_project.duration);
+        assert_eq!(by_tag.entries, by_project.entries);
+        assert_eq!(by_project.rows.iter().map(|r| r.duration).sum::<i64>(), by_project.duration);
+    }
+}
@@ -0,0 +1,405 @@
+// Scheduled reports: a saved report spec rendered to a file in a folder on a
+// schedule, e.g. the weekly summary every Friday at 17:00. Schedules follow
+// the local wall clock. The scheduler checks once a minute and, after the app
+// was closed, catches up on the latest missed run only.
+use chrono::{Datelike, Duration as ChronoDuration, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::db;
+use crate::periods::{Calendar, PeriodKind};
+use crate::render::{self, Format};
+use crate::report::{self, ReportQuery, ReportSpec};
+use crate::rules::{enum_name, parse_enum};
+use crate::zones;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// When a job runs and which period it reports on
+#[derive(Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobSchedule {
+    /// Every day, reporting that day
+    Daily,
+    /// On `weekday`, reporting the week (as configured) containing it
+    Weekly,
+    /// On the last day of the month, reporting that month
+    Monthly,
+}
+
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+pub struct ReportJob {
+    #[serde(default)]
+    pub id: Option<i64>,
+    pub name: String,
+    pub spec: ReportSpec,
+    pub format: Format,
+    pub schedule: JobSchedule,
+    #[serde(default)]
+    pub weekday: u32, // 0 = Sunday, weekly jobs only
+    pub time: String, // "HH:MM", local
+    pub folder: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub last_run_at: Option<String>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn read_job(row: &rusqlite::Row) -> rusqlite::Result<(ReportJob, i64)> {
+    let spec: String = row.get(2)?;
+    let job = ReportJob {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        spec: serde_json::from_str(&spec)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e)))?,
+        format: parse_enum(row, 3)?,
+        schedule: parse_enum(row, 4)?,
+        weekday: row.get(5)?,
+        time: row.get(6)?,
+        folder: row.get(7)?,
+        enabled: row.get(8)?,
+        last_run_at: row.get(9)?,
+    };
+    Ok((job, row.get(10)?))
+}
+
+/// Every readable job with the time of its last run (or creation). A job that
+/// can't be read, e.g. saved by a newer version, is skipped so the others still run.
+fn load_jobs(conn: &Connection) -> rusqlite::Result<Vec<(ReportJob, i64)>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, spec, format, schedule, weekday, time, folder, enabled, last_run_at,
+                CAST(strftime('%s', COALESCE(last_run_at, created_at)) AS INTEGER)
+         FROM report_jobs
+         ORDER BY name, id",
+    )?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, read_job(row))))?;
+    let mut jobs = Vec::new();
+    for row in rows {
+        match row? {
+            (_, Ok(job)) => jobs.push(job),
+            (id, Err(e)) => eprintln!("Skipping scheduled report {}: {}", id, e),
+        }
+    }
+    Ok(jobs)
+}
+
+fn parse_time(time: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| format!("Invalid time: {}", time))
+}
+
+impl ReportJob {
+    fn runs_on(&self, date: NaiveDate) -> bool {
+        match self.schedule {
+            JobSchedule::Daily => true,
+            JobSchedule::Weekly => date.weekday().num_days_from_sunday() == self.weekday,
+            JobSchedule::Monthly => (date + ChronoDuration::days(1)).month() != date.month(),
+        }
+    }
+
+    /// The latest scheduled run at or before `now`: its instant and local date
+    fn last_due(&self, now: i64, tz: Tz) -> Option<(i64, NaiveDate)> {
+        let time = parse_time(&self.time).ok()?;
+        let today = zones::local_date(now, tz);
+        (0..=31).map(|days| today - ChronoDuration::days(days)).filter(|date| self.runs_on(*date)).find_map(|date| {
+            // A run in a DST gap happens once the clocks have moved on
+            let at = tz
+                .from_local_datetime(&date.and_time(time))
+                .earliest()
+                .or_else(|| tz.from_local_datetime(&(date.and_time(time) + ChronoDuration::hours(1))).earliest())?
+                .timestamp();
+            (at <= now).then_some((at, date))
+        })
+    }
+
+    fn period_kind(&self) -> PeriodKind {
+        match self.schedule {
+            JobSchedule::Daily => PeriodKind::Day,
+            JobSchedule::Weekly => PeriodKind::Week,
+            JobSchedule::Monthly => PeriodKind::Month,
+        }
+    }
+}
+
+/// File name for a report: the job name made safe for any file system, then the period start
+fn file_name(job: &ReportJob, start: NaiveDate) -> String {
+    let mut slug = String::new();
+    for c in job.name.trim().chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    let slug = if slug.is_empty() { "report" } else { slug };
+    format!("{}-{}.{}", slug, start.format("%Y-%m-%d"), job.format.extension())
+}
+
+/// Render the job's report for the period containing `date` and write it to
+/// the job's folder, replacing an earlier file for the same period
+pub fn run_job(conn: &Connection, job: &ReportJob, date: NaiveDate) -> Result<PathBuf, String> {
+    let calendar = Calendar::from_settings(conn);
+    let kind = job.period_kind();
+    let (start, end) = calendar.bounds(kind, date);
+    let query = ReportQuery {
+        start_date: start.format("%Y-%m-%d").to_string(),
+        end_date: end.format("%Y-%m-%d").to_string(),
+        spec: job.spec.clone(),
+    };
+    let report = report::run(conn, &query)?;
+    let contents = render::render(&report, &job.spec, &job.name, &calendar.label(kind, start, end), job.format);
+
+    let folder = Path::new(&job.folder);
+    std::fs::create_dir_all(folder).map_err(|e| format!("Failed to create {}: {}", folder.display(), e))?;
+    let path = folder.join(file_name(job, start));
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+fn mark_run(conn: &Connection, id: i64) {
+    if let Err(e) = conn.execute("UPDATE report_jobs SET last_run_at = datetime('now') WHERE id = ?1", [id]) {
+        eprintln!("Failed to record report run: {}", e);
+    }
+}
+
+/// Spawn the scheduler. Jobs are read on every tick, so edits apply without a restart.
+pub fn start_scheduler(app: tauri::AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(CHECK_INTERVAL);
+        let Some(conn) = db::open() else {
+            continue;
+        };
+        let jobs = match load_jobs(&conn) {
+            Ok(jobs) => jobs,
+            Err(e) => {
+                eprintln!("Failed to load scheduled reports: {}", e);
+                continue;
+            }
+        };
+        let now = Utc::now().timestamp();
+        let tz = zones::system_zone();
+        for (job, last_run) in jobs {
+            let (Some(id), true) = (job.id, job.enabled) else {
+                continue;
+            };
+            let Some((due_at, date)) = job.last_due(now, tz) else {
+                continue;
+            };
+            if last_run >= due_at {
+                continue;
+            }
+            // A failed run is not retried every minute; it waits for the next slot
+            mark_run(&conn, id);
+            match run_job(&conn, &job, date) {
+                Ok(path) => crate::notify::show_plain(&app, "Report ready", &format!("{} was saved to {}", job.name, path.display())),
+                Err(e) => {
+                    eprintln!("Failed to run scheduled report {}: {}", job.name, e);
+                    crate::notify::show_plain(&app, "Report failed", &format!("{}: {}", job.name, e));
+                }
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn list_report_jobs() -> Result<Vec<ReportJob>, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    let jobs = load_jobs(&conn).map_err(|e| e.to_string())?;
+    Ok(jobs.into_iter().map(|(job, _)| job).collect())
+}
+
+/// Insert a new job, or update it when `id` is set. Returns the job id.
+/// A new job's first run is its next scheduled time, not one already past.
+#[tauri::command]
+pub fn save_report_job(job: ReportJob) -> Result<i64, String> {
+    if job.name.trim().is_empty() {
+        return Err("A scheduled report needs a name".into());
+    }
+    if !Path::new(&job.folder).is_absolute() {
+        return Err("Choose a full folder path".into());
+    }
+    if job.weekday > 6 {
+        return Err("Invalid weekday".into());
+    }
+    parse_time(&job.time)?;
+    let spec = serde_json::to_string(&job.spec).map_err(|e| e.to_string())?;
+
+    let mut conn = db::open().ok_or("Database not found")?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut journal = crate::journal::Recorder::new("Save scheduled report");
+    let id = match job.id {
+        Some(id) => {
+            journal.track(&tx, "report_jobs", id).map_err(|e| e.to_string())?;
+            tx.execute(
+                "UPDATE report_jobs
+                 SET name = ?1, spec = ?2, format = ?3, schedule = ?4, weekday = ?5, time = ?6, folder = ?7, enabled = ?8
+                 WHERE id = ?9",
+                rusqlite::params![
+                    job.name.trim(),
+                    spec,
+                    enum_name(&job.format),
+                    enum_name(&job.schedule),
+                    job.weekday,
+                    job.time,
+                    job.folder,
+                    job.enabled,
+                    id,
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            id
+        }
+        None => {
+            tx.execute(
+                "INSERT INTO report_jobs (name, spec, format, schedule, weekday, time, folder, enabled, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, datetime('now'))",
+                rusqlite::params![
+                    job.name.trim(),
+                    spec,
+                    enum_name(&job.format),
+                    enum_name(&job.schedule),
+                    job.weekday,
+                    job.time,
+                    job.folder,
+                    job.enabled,
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            let id = tx.last_insert_rowid();
+            journal.track_new("report_jobs", id);
+            id
+        }
+    };
+    journal.finish(&tx).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+#[tauri::command]
+pub fn delete_report_job(id: i64) -> Result<(), String> {
+    let mut conn = db::open().ok_or("Database not found")?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut journal = crate::journal::Recorder::new("Delete scheduled report");
+    journal.track(&tx, "report_jobs", id).map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM report_jobs WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    journal.finish(&tx).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Write a job's report for the current period now. Returns the file path.
+#[tauri::command]
+pub fn run_report_job(id: i64) -> Result<String, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    let job = load_jobs(&conn)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(job, _)| job)
+        .find(|job| job.id == Some(id))
+        .ok_or("Scheduled report not found")?;
+    let path = run_job(&conn, &job, crate::periods::today(&conn))?;
+    Ok(path.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(name: &str, schedule: JobSchedule, weekday: u32, time: &str) -> ReportJob {
+        ReportJob {
+            id: Some(1),
+            name: name.to_string(),
+            spec: ReportSpec::default(),
+            format: Format::Markdown,
+            schedule,
+            weekday,
+            time: time.to_string(),
+            folder: "/tmp".to_string(),
+            enabled: true,
+            last_run_at: None,
+        }
+    }
+
+    fn at(text: &str, tz: Tz) -> i64 {
+        let naive = chrono::NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap();
+        tz.from_local_datetime(&naive).unwrap().timestamp()
+    }
+
+    fn date(text: &str) -> NaiveDate {
+        zones::parse_date(text).unwrap()
+    }
+
+    #[test]
+    fn weekly_job_is_due_on_its_weekday() {
+        let tz = chrono_tz::Europe::Berlin;
+        // Fridays at 17:00
+        let weekly = job("Weekly summary", JobSchedule::Weekly, 5, "17:00");
+        // Friday 2026-10-23 before and after the run
+        assert_eq!(weekly.last_due(at("2026-10-23 16:59", tz), tz).map(|(_, d)| d), Some(date("2026-10-16")));
+        assert_eq!(
+            weekly.last_due(at("2026-10-23 17:00", tz), tz),
+            Some((at("2026-10-23 17:00", tz), date("2026-10-23")))
+        );
+        // The following Tuesday still points at that Friday
+        assert_eq!(weekly.last_due(at("2026-10-27 09:00", tz), tz).map(|(_, d)| d), Some(date("2026-10-23")));
+    }
+
+    #[test]
+    fn weekly_job_across_dst_keeps_wall_clock_time() {
+        let tz = chrono_tz::Europe::Berlin;
+        // Sunday 02:30 does not exist on 2026-03-29; the run moves to 03:30
+        let weekly = job("Night", JobSchedule::Weekly, 0, "02:30");
+        let (due, day) = weekly.last_due(at("2026-03-29 12:00", tz), tz).unwrap();
+        assert_eq!(day, date("2026-03-29"));
+        assert_eq!(due, at("2026-03-29 03:30", tz));
+    }
+
+    #[test]
+    fn monthly_job_is_due_on_the_last_day() {
+        let tz = chrono_tz::Europe::Berlin;
+        let monthly = job("Invoice", JobSchedule::Monthly, 0, "18:00");
+        assert_eq!(monthly.last_due(at("2026-10-31 18:30", tz), tz).map(|(_, d)| d), Some(date("2026-10-31")));
+        assert_eq!(monthly.last_due(at("2026-10-31 17:00", tz), tz).map(|(_, d)| d), Some(date("2026-09-30")));
+        assert_eq!(monthly.last_due(at("2026-11-15 12:00", tz), tz).map(|(_, d)| d), Some(date("2026-10-31")));
+        // February of a leap year
+        assert_eq!(monthly.last_due(at("2028-03-01 12:00", tz), tz).map(|(_, d)| d), Some(date("2028-02-29")));
+    }
+
+    #[test]
+    fn invalid_time_is_never_due() {
+        let weekly = job("Broken", JobSchedule::Weekly, 5, "25:00");
+        assert_eq!(weekly.last_due(at("2026-10-23 18:00", Tz::UTC), Tz::UTC), None);
+    }
+
+    #[test]
+    fn file_names_are_safe_slugs() {
+        let start = date("2026-10-19");
+        assert_eq!(file_name(&job("Weekly summary", JobSchedule::Weekly, 5, "17:00"), start), "weekly-summary-2026-10-19.md");
+        assert_eq!(file_name(&job("  Acme / Q4: hours!  ", JobSchedule::Weekly, 5, "17:00"), start), "acme-q4-hours-2026-10-19.md");
+        assert_eq!(file_name(&job("Überstunden", JobSchedule::Monthly, 0, "17:00"), start), "überstunden-2026-10-19.md");
+        assert_eq!(file_name(&job("../..", JobSchedule::Daily, 0, "17:00"), start), "report-2026-10-19.md");
+        let mut pdf = job("Team", JobSchedule::Daily, 0, "17:00");
+        pdf.format = Format::Pdf;
+        assert_eq!(file_name(&pdf, start), "team-2026-10-19.pdf");
+    }
+
+    #[test]
+    fn unreadable_jobs_are_skipped() {
+        let conn = db::testing::memory();
+        conn.execute_batch(
+            "INSERT INTO report_jobs (name, spec, format, schedule, weekday, time, folder, enabled, created_at)
+             VALUES ('Good', '{}', 'markdown', 'weekly', 5, '17:00', '/tmp', 1, '2026-10-01 00:00:00'),
+                    ('Bad spec', 'not json', 'markdown', 'weekly', 5, '17:00', '/tmp', 1, '2026-10-01 00:00:00'),
+                    ('Bad schedule', '{}', 'markdown', 'hourly', 5, '17:00', '/tmp', 1, '2026-10-01 00:00:00');",
+        )
+        .unwrap();
+        let jobs = load_jobs(&conn).unwrap();
+        assert_eq!(jobs.iter().map(|(job, _)| job.name.as_str()).collect::<Vec<_>>(), vec!["Good"]);
+    }
+}
@@ -0,0 +1,456 @@
+// Activity rules: map foreground windows to projects or tags, e.g.
+// "title contains horalis -> project Horalis" or "app is zoom -> tag meeting"
+use rusqlite::Connection;
+
+use crate::activity::ActiveWindow;
+use crate::db;
+
+#[derive(Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleField {
+    App,
+    Title,
+}
+
+#[derive(Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleOperator {
+    Contains,
+    Equals,
+    StartsWith,
+}
+
+/// What happens on a match. Project rules either suggest a switch through a
+/// notification or switch the running entry directly; tag rules always tag
+/// the running entry.
+#[derive(Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleMode {
+    Suggest,
+    Auto,
+}
+
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+pub struct ActivityRule {
+    #[serde(default)]
+    pub id: Option<i64>,
+    pub field: RuleField,
+    pub operator: RuleOperator,
+    pub pattern: String,
+    pub project_id: Option<i64>,
+    pub tag: Option<String>,
+    pub mode: RuleMode,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub priority: i64, // Lower runs first
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(serde::Serialize)]
+pub struct RuleMatch {
+    sample_id: i64,
+    rule_id: Option<i64>,
+    project_id: Option<i64>,
+    tag: Option<String>,
+}
+
+impl ActivityRule {
+    /// Case-insensitive match against the window
+    pub fn matches(&self, window: &ActiveWindow) -> bool {
+        if !self.enabled || self.pattern.is_empty() {
+            return false;
+        }
+        let haystack = match self.field {
+            RuleField::App => window.app.to_lowercase(),
+            RuleField::Title => window.title.to_lowercase(),
+        };
+        let pattern = self.pattern.to_lowercase();
+        match self.operator {
+            RuleOperator::Contains => haystack.contains(&pattern),
+            RuleOperator::Equals => haystack == pattern,
+            RuleOperator::StartsWith => haystack.starts_with(&pattern),
+        }
+    }
+}
+
+/// First matching project rule and all matching tag rules, in priority order
+pub fn evaluate<'a>(rules: &'a [ActivityRule], window: &ActiveWindow) -> (Option<&'a ActivityRule>, Vec<&'a ActivityRule>) {
+    let matching: Vec<&ActivityRule> = rules.iter().filter(|rule| rule.matches(window)).collect();
+    let project_rule = matching.iter().copied().find(|rule| rule.project_id.is_some());
+    let tag_rules = matching.into_iter().filter(|rule| rule.tag.is_some()).collect();
+    (project_rule, tag_rules)
+}
+
+pub fn load_rules(conn: &Connection) -> rusqlite::Result<Vec<ActivityRule>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, field, operator, pattern, project_id, tag, mode, enabled, priority
+         FROM activity_rules
+         ORDER BY priority, id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(ActivityRule {
+            id: row.get(0)?,
+            field: parse_enum(row, 1)?,
+            operator: parse_enum(row, 2)?,
+            pattern: row.get(3)?,
+            project_id: row.get(4)?,
+            tag: row.get(5)?,
+            mode: parse_enum(row, 6)?,
+            enabled: row.get(7)?,
+            priority: row.get(8)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Deserialize a unit enum variant from its stored serde name
+pub fn parse_enum<T: serde::de::DeserializeOwned>(row: &rusqlite::Row, idx: usize) -> rusqlite::Result<T> {
+    let value: String = row.get(idx)?;
+    serde_json::from_value(serde_json::Value::String(value))
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e)))
+}
+
+/// Serialize a unit enum variant to its serde name for storage
+pub fn enum_name<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Consecutive matching samples needed before an auto rule switches the
+/// running entry, so briefly alt-tabbing into a window does not switch
+pub const AUTO_SWITCH_SAMPLES: u32 = 3;
+
+/// What a project rule does for one sample
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RuleAction {
+    Nothing,
+    Suggest(i64),
+    Switch(i64),
+}
+
+/// Per-sampler memory of the project rules across samples
+#[derive(Default)]
+pub struct RuleState {
+    /// Project already suggested, so the user is not asked again every sample
+    last_suggested: Option<i64>,
+    /// Project matched by the latest samples and how many samples in a row
+    streak: Option<(i64, u32)>,
+}
+
+impl RuleState {
+    /// A gap in the samples (user away, window excluded) breaks the streak
+    pub fn interrupt(&mut self) {
+        self.streak = None;
+    }
+
+    /// Decide on the project rule matching a sample. `running_project` is the
+    /// project of the running entry, if any.
+    pub fn step(&mut self, project_rule: Option<&ActivityRule>, running_project: Option<i64>) -> RuleAction {
+        let Some((rule, project_id)) = project_rule.and_then(|rule| Some((rule, rule.project_id?))) else {
+            self.last_suggested = None;
+            self.streak = None;
+            return RuleAction::Nothing;
+        };
+        if running_project == Some(project_id) {
+            self.last_suggested = None;
+            self.streak = None;
+            return RuleAction::Nothing;
+        }
+        let count = match self.streak {
+            Some((streak_project, count)) if streak_project == project_id => count + 1,
+            _ => 1,
+        };
+        self.streak = Some((project_id, count));
+
+        // Only a running entry is switched automatically; otherwise ask first
+        match rule.mode {
+            RuleMode::Auto if running_project.is_some() => {
+                if count < AUTO_SWITCH_SAMPLES {
+                    return RuleAction::Nothing;
+                }
+                self.streak = None;
+                RuleAction::Switch(project_id)
+            }
+            _ => {
+                if self.last_suggested == Some(project_id) {
+                    return RuleAction::Nothing;
+                }
+                self.last_suggested = Some(project_id);
+                RuleAction::Suggest(project_id)
+            }
+        }
+    }
+}
+
+/// Apply the rules to a fresh activity sample
+pub fn apply(app: &tauri::AppHandle, conn: &Connection, window: &ActiveWindow, state: &mut RuleState) {
+    let rules = match load_rules(conn) {
+        Ok(rules) => rules,
+        Err(e) => {
+            eprintln!("Failed to load activity rules: {}", e);
+            return;
+        }
+    };
+    let (project_rule, tag_rules) = evaluate(&rules, window);
+
+    let running: Option<(i64, i64)> = conn
+        .query_row(
+            "SELECT id, project_id FROM time_entries WHERE end_time IS NULL LIMIT 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .ok();
+
+    if let Some((entry_id, _)) = running {
+        for rule in tag_rules {
+            if let Some(tag) = &rule.tag {
+                let _ = conn.execute(
+                    "INSERT OR IGNORE INTO entry_tags (entry_id, tag) VALUES (?1, ?2)",
+                    rusqlite::params![entry_id, tag],
+                );
+            }
+        }
+    }
+
+    match state.step(project_rule, running.map(|(_, project_id)| project_id)) {
+        RuleAction::Nothing => {}
+        RuleAction::Switch(project_id) => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = crate::start_project_timer_native(&app, Some(project_id), crate::audit::ChangeSource::Automatic).await {
+                    eprintln!("Failed to switch project from rule: {}", e);
+                }
+            });
+        }
+        RuleAction::Suggest(project_id) => {
+            if let Some(project) = db::get_project(conn, project_id) {
+                crate::notify::show_project_suggestion(app, &project, &window.app);
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub fn list_activity_rules() -> Result<Vec<ActivityRule>, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    load_rules(&conn).map_err(|e| e.to_string())
+}
+
+/// Insert a new rule, or update it when `id` is set. Returns the rule id.
+#[tauri::command]
+pub fn save_activity_rule(rule: ActivityRule) -> Result<i64, String> {
+    if rule.project_id.is_none() && rule.tag.as_deref().is_none_or(str::is_empty) {
+        return Err("A rule needs a project or a tag".into());
+    }
+    let mut conn = db::open().ok_or("Database not found")?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut journal = crate::journal::Recorder::new("Save rule");
+    let id = match rule.id {
+        Some(id) => {
+            journal.track(&tx, "activity_rules", id).map_err(|e| e.to_string())?;
+            tx.execute(
+                "UPDATE activity_rules
+                 SET field = ?1, operator = ?2, pattern = ?3, project_id = ?4, tag = ?5, mode = ?6, enabled = ?7, priority = ?8
+                 WHERE id = ?9",
+                rusqlite::params![
+                    enum_name(&rule.field),
+                    enum_name(&rule.operator),
+                    rule.pattern,
+                    rule.project_id,
+                    rule.tag,
+                    enum_name(&rule.mode),
+                    rule.enabled,
+                    rule.priority,
+                    id,
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            id
+        }
+        None => {
+            tx.execute(
+                "INSERT INTO activity_rules (field, operator, pattern, project_id, tag, mode, enabled, priority)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                rusqlite::params![
+                    enum_name(&rule.field),
+                    enum_name(&rule.operator),
+                    rule.pattern,
+                    rule.project_id,
+                    rule.tag,
+                    enum_name(&rule.mode),
+                    rule.enabled,
+                    rule.priority,
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            let id = tx.last_insert_rowid();
+            journal.track_new("activity_rules", id);
+            id
+        }
+    };
+    journal.finish(&tx).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+#[tauri::command]
+pub fn delete_activity_rule(id: i64) -> Result<(), String> {
+    let mut conn = db::open().ok_or("Database not found")?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut journal = crate::journal::Recorder::new("Delete rule");
+    journal.track(&tx, "activity_rules", id).map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM activity_rules WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    journal.finish(&tx).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Dry-run the rules against the recorded samples of a local date ("YYYY-MM-DD"),
+/// so rules can be checked against real activity before they act on it
+#[tauri::command]
+pub fn preview_activity_rules(date: String) -> Result<Vec<RuleMatch>, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    let rules = load_rules(&conn).map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare("SELECT id, app, title FROM activity_samples WHERE date(sampled_at, 'localtime') = ?1 ORDER BY sampled_at")
+        .map_err(|e| e.to_string())?;
+    let samples = stmt
+        .query_map([date], |row| {
+            Ok((row.get::<_, i64>(0)?, ActiveWindow { app: row.get(1)?, title: row.get(2)? }))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut matches = Vec::new();
+    for (sample_id, window) in &samples {
+        let (project_rule, tag_rules) = evaluate(&rules, window);
+        if let Some(rule) = project_rule {
+            matches.push(RuleMatch { sample_id: *sample_id, rule_id: rule.id, project_id: rule.project_id, tag: None });
+        }
+        for rule in tag_rules {
+            matches.push(RuleMatch { sample_id: *sample_id, rule_id: rule.id, project_id: None, tag: rule.tag.clone() });
+        }
+    }
+    Ok(matches)
+}
+
+/// Tags attached to a time entry by tag rules
+#[tauri::command]
+pub fn get_entry_tags(entry_id: i64) -> Result<Vec<String>, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    let mut stmt = conn
+        .prepare("SELECT tag FROM entry_tags WHERE entry_id = ?1 ORDER BY tag")
+        .map_err(|e| e.to_string())?;
+    let tags = stmt
+        .query_map([entry_id], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<String>, _>>();
+    tags.map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Recorded foreground windows, one sample per line as "app<TAB>title"
+    const EDITOR_SESSION: &str = include_str!("../tests/fixtures/samples_editor_session.tsv");
+    const ALT_TAB: &str = include_str!("../tests/fixtures/samples_alt_tab.tsv");
+
+    fn samples(fixture: &str) -> Vec<ActiveWindow> {
+        fixture
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let (app, title) = line.split_once('\t').unwrap_or((line, ""));
+                ActiveWindow { app: app.to_string(), title: title.to_string() }
+            })
+            .collect()
+    }
+
+    fn rule(id: i64, pattern: &str, project_id: i64, mode: RuleMode) -> ActivityRule {
+        ActivityRule {
+            id: Some(id),
+            field: RuleField::Title,
+            operator: RuleOperator::Contains,
+            pattern: pattern.to_string(),
+            project_id: Some(project_id),
+            tag: None,
+            mode,
+            enabled: true,
+            priority: id,
+        }
+    }
+
+    /// Replay samples against the rules with a timer running on `project`,
+    /// returning the index and action of every sample that did something
+    fn replay(rules: &[ActivityRule], windows: &[ActiveWindow], mut project: Option<i64>) -> Vec<(usize, RuleAction)> {
+        let mut state = RuleState::default();
+        let mut actions = Vec::new();
+        for (i, window) in windows.iter().enumerate() {
+            let (project_rule, _) = evaluate(rules, window);
+            let action = state.step(project_rule, project);
+            if let RuleAction::Switch(id) = action {
+                project = Some(id);
+            }
+            if action != RuleAction::Nothing {
+                actions.push((i, action));
+            }
+        }
+        actions
+    }
+
+    #[test]
+    fn auto_rule_switches_after_consecutive_samples() {
+        let rules = [rule(1, "horalis", 10, RuleMode::Auto), rule(2, "invoice", 20, RuleMode::Auto)];
+        let windows = samples(EDITOR_SESSION);
+        let actions = replay(&rules, &windows, Some(20));
+        // The editor shows up at sample 2, so the third matching sample switches
+        assert_eq!(actions, vec![(4, RuleAction::Switch(10))]);
+    }
+
+    #[test]
+    fn auto_rule_ignores_brief_visits() {
+        let rules = [rule(1, "horalis", 10, RuleMode::Auto)];
+        let windows = samples(ALT_TAB);
+        assert_eq!(replay(&rules, &windows, Some(20)), vec![]);
+    }
+
+    #[test]
+    fn interruption_restarts_the_streak() {
+        let rules = [rule(1, "horalis", 10, RuleMode::Auto)];
+        let window = ActiveWindow { app: "code".into(), title: "main.rs - horalis".into() };
+        let mut state = RuleState::default();
+        for _ in 1..AUTO_SWITCH_SAMPLES {
+            assert_eq!(state.step(evaluate(&rules, &window).0, Some(20)), RuleAction::Nothing);
+        }
+        state.interrupt();
+        assert_eq!(state.step(evaluate(&rules, &window).0, Some(20)), RuleAction::Nothing);
+    }
+
+    #[test]
+    fn suggest_rule_asks_once_per_visit() {
+        let rules = [rule(1, "horalis", 10, RuleMode::Suggest)];
+        let windows = samples(ALT_TAB);
+        // Each separate visit to a matching window suggests again
+        let suggested: Vec<usize> = replay(&rules, &windows, Some(20)).into_iter().map(|(i, _)| i).collect();
+        let visits: Vec<usize> = (0..windows.len())
+            .filter(|&i| evaluate(&rules, &windows[i]).0.is_some() && (i == 0 || evaluate(&rules, &windows[i - 1]).0.is_none()))
+            .collect();
+        assert_eq!(suggested, visits);
+    }
+
+    #[test]
+    fn auto_rule_without_running_timer_suggests() {
+        let rules = [rule(1, "horalis", 10, RuleMode::Auto)];
+        let windows = samples(EDITOR_SESSION);
+        // Asked when the editor first shows up, and again after the Slack visit
+        assert_eq!(replay(&rules, &windows, None), vec![(2, RuleAction::Suggest(10)), (8, RuleAction::Suggest(10))]);
+    }
+}
@@ -0,0 +1,242 @@
+// Reminder schedules: per-weekday time windows, including windows that run
+// past midnight, evaluated in a chosen time zone
+use chrono::{DateTime, Datelike, Local, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+pub struct ReminderSchedule {
+    /// Days the window starts on (0=Sun, 1=Mon, ..., 6=Sat)
+    pub weekdays: Vec<u32>,
+    pub start_time: String, // "HH:MM" format
+    /// "HH:MM"; an end at or before the start means the window ends the next day
+    pub end_time: String,
+    /// IANA time zone name, e.g. "Europe/Oslo" (system time zone when unset)
+    #[serde(default)]
+    pub timezone: Option<String>,
+}
+
+/// Parse "HH:MM" time string into (hour, minute)
+pub fn parse_time_string(time_str: &str) -> Option<(u32, u32)> {
+    let parts: Vec<&str> = time_str.split(':').collect();
+    if parts.len() != 2 {
+        return None;
+    }
+    let hour = parts[0].parse::<u32>().ok()?;
+    let minute = parts[1].parse::<u32>().ok()?;
+    if hour > 23 || minute > 59 {
+        return None;
+    }
+    Some((hour, minute))
+}
+
+/// Convert chrono's weekday into the UI convention (0=Sun, 1=Mon, ..., 6=Sat)
+pub fn ui_weekday(weekday: chrono::Weekday) -> u32 {
+    weekday.num_days_from_sunday()
+}
+
+/// Check whether `minute_of_day` on `weekday` (UI convention) falls inside the window
+pub fn window_contains(schedule: &ReminderSchedule, weekday: u32, minute_of_day: u32) -> bool {
+    let Some((start_h, start_m)) = parse_time_string(&schedule.start_time) else {
+        return false;
+    };
+    let Some((end_h, end_m)) = parse_time_string(&schedule.end_time) else {
+        return false;
+    };
+    let start = start_h * 60 + start_m;
+    let end = end_h * 60 + end_m;
+    let previous_day = (weekday + 6) % 7;
+
+    if start < end {
+        // Same-day window, end inclusive
+        schedule.weekdays.contains(&weekday) && minute_of_day >= start && minute_of_day <= end
+    } else {
+        // Overnight window: the tail after midnight belongs to the previous day's window
+        let end = end + MINUTES_PER_DAY;
+        let in_head = schedule.weekdays.contains(&weekday) && minute_of_day >= start;
+        let in_tail = schedule.weekdays.contains(&previous_day) && minute_of_day + MINUTES_PER_DAY <= end;
+        in_head || in_tail
+    }
+}
+
+fn local_parts<T: TimeZone>(now: DateTime<Utc>, tz: &T) -> (u32, u32) {
+    let local = now.with_timezone(tz);
+    (ui_weekday(local.weekday()), local.hour() * 60 + local.minute())
+}
+
+/// Check whether a single schedule covers the instant `now`
+pub fn schedule_contains(schedule: &ReminderSchedule, now: DateTime<Utc>) -> bool {
+    let (weekday, minutes) = match schedule.timezone.as_deref().filter(|tz| !tz.is_empty()) {
+        Some(name) => match name.parse::<Tz>() {
+            Ok(tz) => local_parts(now, &tz),
+            Err(_) => return false,
+        },
+        None => local_parts(now, &Local),
+    };
+    window_contains(schedule, weekday, minutes)
+}
+
+/// Check whether any schedule covers the instant `now`
+pub fn is_active(schedules: &[ReminderSchedule], now: DateTime<Utc>) -> bool {
+    schedules.iter().any(|schedule| schedule_contains(schedule, now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn hhmm(minute: u32) -> String {
+        format!("{:02}:{:02}", minute / 60, minute % 60)
+    }
+
+    fn window(weekdays: Vec<u32>, start: u32, end: u32, timezone: Option<&str>) -> ReminderSchedule {
+        ReminderSchedule {
+            weekdays,
+            start_time: hhmm(start),
+            end_time: hhmm(end),
+            timezone: timezone.map(str::to_string),
+        }
+    }
+
+    fn weekdays() -> impl Strategy<Value = Vec<u32>> {
+        proptest::collection::btree_set(0u32..7, 0..=7).prop_map(|days| days.into_iter().collect())
+    }
+
+    // Zones without daylight saving time and their UTC offset in minutes
+    const FIXED_ZONES: [(&str, i64); 4] =
+        [("UTC", 0), ("Asia/Kolkata", 330), ("Asia/Tokyo", 540), ("America/Phoenix", -420)];
+
+    proptest! {
+        #[test]
+        fn same_day_window_covers_its_span(
+            days in weekdays(),
+            start in 0u32..MINUTES_PER_DAY,
+            len in 1u32..MINUTES_PER_DAY,
+            weekday in 0u32..7,
+            minute in 0u32..MINUTES_PER_DAY,
+        ) {
+            let end = start + len;
+            prop_assume!(end < MINUTES_PER_DAY);
+            let expected = days.contains(&weekday) && start <= minute && minute <= end;
+            prop_assert_eq!(window_contains(&window(days, start, end, None), weekday, minute), expected);
+        }
+
+        #[test]
+        fn overnight_window_rolls_into_the_next_weekday(
+            days in weekdays(),
+            start in 0u32..MINUTES_PER_DAY,
+            end in 0u32..MINUTES_PER_DAY,
+            weekday in 0u32..7,
+            minute in 0u32..MINUTES_PER_DAY,
+        ) {
+            prop_assume!(end <= start);
+            let previous = (weekday + 6) % 7;
+            let expected = (days.contains(&weekday) && minute >= start)
+                || (days.contains(&previous) && minute <= end);
+            prop_assert_eq!(window_contains(&window(days, start, end, None), weekday, minute), expected);
+        }
+
+        #[test]
+        fn fixed_zone_matches_shifted_utc(
+            days in weekdays(),
+            start in 0u32..MINUTES_PER_DAY,
+            end in 0u32..MINUTES_PER_DAY,
+            zone in 0usize..FIXED_ZONES.len(),
+            secs in 1_700_000_000i64..1_900_000_000,
+        ) {
+            let (name, offset) = FIXED_ZONES[zone];
+            let now = Utc.timestamp_opt(secs, 0).unwrap();
+            let shifted = now + chrono::Duration::minutes(offset);
+            prop_assert_eq!(
+                schedule_contains(&window(days.clone(), start, end, Some(name)), now),
+                schedule_contains(&window(days, start, end, Some("UTC")), shifted)
+            );
+        }
+    }
+
+    /// Minutes (UTC, "YYYY-MM-DD HH:MM") from `from` up to `to` during which
+    /// `is_active` holds, as (first, last) runs
+    fn active_runs(schedules: &[ReminderSchedule], from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<(String, String)> {
+        let format = |t: DateTime<Utc>| t.format("%Y-%m-%d %H:%M").to_string();
+        let mut runs: Vec<(String, String)> = Vec::new();
+        let mut previous = false;
+        let mut minute = from;
+        while minute < to {
+            let active = is_active(schedules, minute);
+            match (previous, active) {
+                (false, true) => runs.push((format(minute), format(minute))),
+                (true, true) => runs.last_mut().unwrap().1 = format(minute),
+                _ => {}
+            }
+            previous = active;
+            minute += chrono::Duration::minutes(1);
+        }
+        runs
+    }
+
+    fn runs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+        expected.iter().map(|(first, last)| (first.to_string(), last.to_string())).collect()
+    }
+
+    #[test]
+    fn overnight_window_over_the_autumn_change() {
+        // Saturday 22:00 to 02:00 in Berlin. On 2026-10-25 02:00-02:59 happens twice,
+        // first in CEST and then in CET, so 02:00 matches once more an hour later.
+        let late = window(vec![6], 22 * 60, 2 * 60, Some("Europe/Berlin"));
+        let day = (Utc.with_ymd_and_hms(2026, 10, 24, 12, 0, 0).unwrap(), Utc.with_ymd_and_hms(2026, 10, 25, 12, 0, 0).unwrap());
+        assert_eq!(
+            active_runs(std::slice::from_ref(&late), day.0, day.1),
+            runs(&[("2026-10-24 20:00", "2026-10-25 00:00"), ("2026-10-25 01:00", "2026-10-25 01:00")])
+        );
+        // Any schedule counts: Sunday 09:00-10:00 in Tokyo fills the hour between
+        let tokyo = window(vec![0], 9 * 60, 10 * 60, Some("Asia/Tokyo"));
+        assert_eq!(active_runs(&[late, tokyo], day.0, day.1), runs(&[("2026-10-24 20:00", "2026-10-25 01:00")]));
+    }
+
+    #[test]
+    fn windows_over_the_spring_change() {
+        // Saturday 22:00 to 02:30 in Berlin; on 2026-03-29 the clocks skip from 02:00 to 03:00
+        let late = window(vec![6], 22 * 60, 2 * 60 + 30, Some("Europe/Berlin"));
+        let from = Utc.with_ymd_and_hms(2026, 3, 28, 12, 0, 0).unwrap();
+        assert_eq!(
+            active_runs(&[late], from, from + chrono::Duration::days(1)),
+            runs(&[("2026-03-28 21:00", "2026-03-29 00:59")])
+        );
+        // Sunday 01:30 to 03:30 in New York lasts an hour on 2026-03-08
+        let early = window(vec![0], 90, 3 * 60 + 30, Some("America/New_York"));
+        let from = Utc.with_ymd_and_hms(2026, 3, 8, 0, 0, 0).unwrap();
+        assert_eq!(
+            active_runs(&[early], from, from + chrono::Duration::days(1)),
+            runs(&[("2026-03-08 06:30", "2026-03-08 07:30")])
+        );
+    }
+
+    #[test]
+    fn saturday_night_window_covers_sunday_morning() {
+        let late = window(vec![6], 22 * 60, 2 * 60, Some("Europe/Berlin"));
+        // Sunday 2026-10-18 01:30 in Berlin (CEST)
+        assert!(schedule_contains(&late, Utc.with_ymd_and_hms(2026, 10, 17, 23, 30, 0).unwrap()));
+        // Monday 01:30 belongs to a Sunday window, which is not configured
+        assert!(!schedule_contains(&late, Utc.with_ymd_and_hms(2026, 10, 18, 23, 30, 0).unwrap()));
+    }
+
+    #[test]
+    fn same_instant_differs_by_zone() {
+        let office = window(vec![1, 2, 3, 4, 5], 9 * 60, 17 * 60, Some("America/New_York"));
+        let tokyo = ReminderSchedule { timezone: Some("Asia/Tokyo".into()), ..office.clone() };
+        // Monday 2026-10-19 14:00 UTC: 10:00 in New York, 23:00 in Tokyo
+        let now = Utc.with_ymd_and_hms(2026, 10, 19, 14, 0, 0).unwrap();
+        assert!(is_active(std::slice::from_ref(&office), now));
+        assert!(!is_active(std::slice::from_ref(&tokyo), now));
+        assert!(is_active(&[tokyo, office], now));
+        assert!(!is_active(&[], now));
+    }
+
+    #[test]
+    fn unknown_zone_never_matches() {
+        let bad = window((0..7).collect(), 0, 23 * 60 + 59, Some("Mars/Olympus"));
+        assert!(!schedule_contains(&bad, Utc::now()));
+    }
+}
@@ -0,0 +1,550 @@
+// Typed application settings. They are stored one per row in the key/value
+// `settings` table (so sync and older databases keep working), but everything
+// reads and writes them through `Settings`, which owns the defaults and the
+// validation. Background tasks call `Settings::load` on every tick instead of
+// waiting for the webview to push a configuration.
+//
+// The same settings can live in settings.toml in the config folder. When that
+// file exists it is applied on launch and rewritten whenever a setting
+// changes, so it can be kept in a dotfiles repository. Secrets stay out of it.
+use chrono::NaiveTime;
+use rusqlite::{params, Connection};
+use serde_json::{json, Value as Json};
+use std::path::PathBuf;
+
+use crate::breaks::BreakMode;
+use crate::periods::PayFrequency;
+use crate::render::Format;
+use crate::schedule::ReminderSchedule;
+use crate::{db, zones};
+
+/// Bumped when a setting is renamed or changes meaning
+pub const VERSION: u32 = 1;
+
+const VERSION_KEY: &str = "settings_version";
+const FILE_NAME: &str = "settings.toml";
+// Never written to the settings file
+const SECRETS: [&str; 2] = ["backup_passphrase", "sync_server_token"];
+
+/// Where working hours come from when looking for untracked gaps
+#[derive(Clone, Copy, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WorkdaySource {
+    /// The reminder windows
+    #[default]
+    Reminders,
+    /// The separate workday_* settings
+    Custom,
+}
+
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Settings {
+    pub version: u32,
+    pub show_window_on_startup: bool,
+    pub show_timer_in_tray: bool,
+    pub stop_timer_when_idle: bool,
+    pub idle_timeout_minutes: u32,
+    pub reminder_enabled: bool,
+    pub reminder_interval_minutes: u32,
+    pub reminder_start_time: String, // HH:MM
+    pub reminder_end_time: String,   // HH:MM
+    pub reminder_weekdays: Vec<u32>, // 0=Sun, 1=Mon, ..., 6=Sat
+    // Extra windows on top of the primary one above
+    pub reminder_schedules: Vec<ReminderSchedule>,
+    // Only remind while the user is at the computer
+    pub reminder_only_when_active: bool,
+    pub reminder_active_threshold_minutes: u32, // Idle time after which the user counts as away
+    pub reminder_break_enabled: bool,
+    pub reminder_break_mode: BreakMode,
+    pub reminder_break_after_minutes: u32,
+    pub reminder_break_snooze_minutes: u32,
+    pub reminder_break_min_minutes: u32, // Shortest pause that counts as a break
+    pub activity_tracking_enabled: bool,
+    pub activity_sample_seconds: u64,
+    pub activity_retention_days: u64,
+    pub activity_exclusions: String, // One pattern per line
+    pub git_watch_enabled: bool,
+    pub workday_source: WorkdaySource,
+    pub workday_start_time: String,
+    pub workday_end_time: String,
+    pub workday_weekdays: Vec<u32>,
+    pub report_timezone: String, // "" = system zone, "entry" = zone each entry was recorded in
+    pub week_start: u32,         // 0=Sun, 1=Mon, ..., 6=Sat
+    pub fiscal_pattern: String,  // "4-4-5", "4-5-4" or "5-4-4"
+    pub fiscal_year_start: String, // MM-DD, moved to the nearest week start
+    pub pay_period: PayFrequency,
+    pub pay_period_anchor: String, // First day of any weekly or biweekly pay period
+    pub timesheet_folder: String,
+    pub timesheet_format: Format,
+    pub backup_enabled: bool,
+    pub backup_folder: String,
+    pub backup_passphrase: String, // Empty for unencrypted backups
+    // How many days, weeks and months keep a backup; 0 keeps none of that kind,
+    // but the newest backup is always kept
+    pub backup_keep_daily: u32,
+    pub backup_keep_weekly: u32,
+    pub backup_keep_monthly: u32,
+    pub sync_enabled: bool,
+    pub sync_folder: String,
+    pub sync_server_url: String,
+    pub sync_server_token: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let weekdays = vec![1, 2, 3, 4, 5];
+        Settings {
+            version: VERSION,
+            show_window_on_startup: true,
+            show_timer_in_tray: true,
+            stop_timer_when_idle: false,
+            idle_timeout_minutes: 5,
+            reminder_enabled: false,
+            reminder_interval_minutes: 30,
+            reminder_start_time: "09:00".into(),
+            reminder_end_time: "18:00".into(),
+            reminder_weekdays: weekdays.clone(),
+            reminder_schedules: Vec::new(),
+            reminder_only_when_active: false,
+            reminder_active_threshold_minutes: 2,
+            reminder_break_enabled: false,
+            reminder_break_mode: BreakMode::Tracked,
+            reminder_break_after_minutes: 50,
+            reminder_break_snooze_minutes: 10,
+            reminder_break_min_minutes: 5,
+            activity_tracking_enabled: false,
+            activity_sample_seconds: 30,
+            activity_retention_days: 30,
+            activity_exclusions: String::new(),
+            git_watch_enabled: false,
+            workday_source: WorkdaySource::Reminders,
+            workday_start_time: "09:00".into(),
+            workday_end_time: "17:00".into(),
+            workday_weekdays: weekdays,
+            report_timezone: String::new(),
+            week_start: 1,
+            fiscal_pattern: "4-4-5".into(),
+            fiscal_year_start: "01-01".into(),
+            pay_period: PayFrequency::Biweekly,
+            pay_period_anchor: "2024-01-01".into(),
+            timesheet_folder: String::new(),
+            timesheet_format: Format::Pdf,
+            backup_enabled: false,
+            backup_folder: String::new(),
+            backup_passphrase: String::new(),
+            backup_keep_daily: 7,
+            backup_keep_weekly: 4,
+            backup_keep_monthly: 12,
+            sync_enabled: false,
+            sync_folder: String::new(),
+            sync_server_url: String::new(),
+            sync_server_token: String::new(),
+        }
+    }
+}
+
+fn check_time(name: &str, value: &str) -> Result<(), String> {
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .map(|_| ())
+        .map_err(|_| format!("{}: expected a time like 09:00, got \"{}\"", name, value))
+}
+
+fn check_weekdays(name: &str, days: &[u32]) -> Result<(), String> {
+    match days.iter().find(|&&d| d > 6) {
+        Some(day) => Err(format!("{}: {} is not a weekday (0=Sun ... 6=Sat)", name, day)),
+        None => Ok(()),
+    }
+}
+
+fn check_positive(name: &str, value: u64) -> Result<(), String> {
+    if value == 0 {
+        return Err(format!("{}: must be at least 1", name));
+    }
+    Ok(())
+}
+
+impl Settings {
+    /// Reject values the rest of the app would silently replace or misread
+    pub fn validate(&self) -> Result<(), String> {
+        if self.version > VERSION {
+            return Err(format!("These settings are from a newer Horalis (version {})", self.version));
+        }
+        check_time("reminder_start_time", &self.reminder_start_time)?;
+        check_time("reminder_end_time", &self.reminder_end_time)?;
+        check_time("workday_start_time", &self.workday_start_time)?;
+        check_time("workday_end_time", &self.workday_end_time)?;
+        check_weekdays("reminder_weekdays", &self.reminder_weekdays)?;
+        check_weekdays("workday_weekdays", &self.workday_weekdays)?;
+        for schedule in &self.reminder_schedules {
+            check_time("reminder_schedules", &schedule.start_time)?;
+            check_time("reminder_schedules", &schedule.end_time)?;
+            check_weekdays("reminder_schedules", &schedule.weekdays)?;
+            if let Some(zone) = schedule.timezone.as_deref() {
+                zone.parse::<chrono_tz::Tz>().map_err(|_| format!("reminder_schedules: unknown time zone \"{}\"", zone))?;
+            }
+        }
+        for (name, value) in [
+            ("idle_timeout_minutes", self.idle_timeout_minutes),
+            ("reminder_interval_minutes", self.reminder_interval_minutes),
+            ("reminder_active_threshold_minutes", self.reminder_active_threshold_minutes),
+            ("reminder_break_after_minutes", self.reminder_break_after_minutes),
+            ("reminder_break_snooze_minutes", self.reminder_break_snooze_minutes),
+            ("reminder_break_min_minutes", self.reminder_break_min_minutes),
+        ] {
+            check_positive(name, value as u64)?;
+        }
+        check_positive("activity_retention_days", self.activity_retention_days)?;
+        if self.activity_sample_seconds < 5 {
+            return Err("activity_sample_seconds: must be at least 5".into());
+        }
+        if !matches!(self.report_timezone.as_str(), "" | "entry") && self.report_timezone.parse::<chrono_tz::Tz>().is_err() {
+            return Err(format!("report_timezone: unknown time zone \"{}\"", self.report_timezone));
+        }
+        if self.week_start > 6 {
+            return Err("week_start: expected 0 (Sunday) to 6 (Saturday)".into());
+        }
+        if !matches!(self.fiscal_pattern.as_str(), "4-4-5" | "4-5-4" | "5-4-4") {
+            return Err("fiscal_pattern: expected 4-4-5, 4-5-4 or 5-4-4".into());
+        }
+        if self.fiscal_year_start().is_none() {
+            return Err(format!("fiscal_year_start: expected MM-DD, got \"{}\"", self.fiscal_year_start));
+        }
+        zones::parse_date(&self.pay_period_anchor).map_err(|e| format!("pay_period_anchor: {}", e))?;
+        let url = self.sync_server_url.trim();
+        if !url.is_empty() && !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err("sync_server_url: must start with http:// or https://".into());
+        }
+        Ok(())
+    }
+
+    /// The fiscal year start as (month, day), checked against a leap year so
+    /// 02-29 is accepted
+    pub fn fiscal_year_start(&self) -> Option<(u32, u32)> {
+        let (month, day) = self.fiscal_year_start.split_once('-')?;
+        let (month, day) = (month.parse().ok()?, day.parse().ok()?);
+        chrono::NaiveDate::from_ymd_opt(2024, month, day).map(|_| (month, day))
+    }
+
+    /// The reminder windows: the primary start/end/weekdays plus any extra windows
+    pub fn reminder_windows(&self) -> Vec<ReminderSchedule> {
+        let mut windows = vec![ReminderSchedule {
+            weekdays: self.reminder_weekdays.clone(),
+            start_time: self.reminder_start_time.clone(),
+            end_time: self.reminder_end_time.clone(),
+            timezone: None,
+        }];
+        windows.extend(self.reminder_schedules.iter().cloned());
+        windows
+    }
+
+    /// Working hours: the reminder windows, or the workday_* settings when
+    /// `workday_source` is custom
+    pub fn workday_windows(&self) -> Vec<ReminderSchedule> {
+        match self.workday_source {
+            WorkdaySource::Reminders => self.reminder_windows(),
+            WorkdaySource::Custom => vec![ReminderSchedule {
+                weekdays: self.workday_weekdays.clone(),
+                start_time: self.workday_start_time.clone(),
+                end_time: self.workday_end_time.clone(),
+                timezone: None,
+            }],
+        }
+    }
+
+    /// Read the settings, using the default for anything missing or unreadable
+    pub fn load(conn: &Connection) -> Settings {
+        let mut map = defaults();
+        let rows = conn.prepare("SELECT key, value FROM settings").and_then(|mut stmt| {
+            stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+                .collect::<Result<Vec<_>, _>>()
+        });
+        match rows {
+            Ok(rows) => {
+                for (key, text) in rows {
+                    let key = if key == VERSION_KEY { "version".to_string() } else { key };
+                    let Some(default) = map.get(&key) else {
+                        continue;
+                    };
+                    let value = from_stored(&text, default);
+                    // A single bad row falls back to its default instead of losing everything
+                    if serde_json::from_value::<Settings>(json!({ &key: value.clone() })).is_ok() {
+                        map.insert(key, value);
+                    }
+                }
+            }
+            Err(e) => eprintln!("Failed to read settings: {}", e),
+        }
+        serde_json::from_value(Json::Object(map)).unwrap_or_default()
+    }
+
+    /// Write every setting, tagged with the current version
+    pub fn save(&self, conn: &Connection) -> rusqlite::Result<()> {
+        let Json::Object(map) = serde_json::to_value(self).unwrap_or_default() else {
+            return Ok(());
+        };
+        let mut stmt = conn.prepare("INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)")?;
+        for (key, value) in &map {
+            if key != "version" {
+                stmt.execute(params![key, to_stored(value)])?;
+            }
+        }
+        stmt.execute(params![VERSION_KEY, VERSION.to_string()])?;
+        Ok(())
+    }
+
+    /// A copy with the fields in `changes` replaced, validated
+    pub fn merged(&self, changes: serde_json::Map<String, Json>) -> Result<Settings, String> {
+        let Json::Object(mut map) = serde_json::to_value(self).map_err(|e| e.to_string())? else {
+            return Err("Settings are not an object".into());
+        };
+        for (key, value) in changes {
+            if !map.contains_key(&key) {
+                return Err(format!("Unknown setting \"{}\"", key));
+            }
+            map.insert(key, value);
+        }
+        let mut settings: Settings = serde_json::from_value(Json::Object(map)).map_err(|e| format!("Invalid setting: {}", e))?;
+        settings.validate()?;
+        settings.version = VERSION;
+        Ok(settings)
+    }
+
+    fn to_toml(&self) -> Result<String, String> {
+        let mut table = toml::Table::try_from(self).map_err(|e| e.to_string())?;
+        for key in SECRETS {
+            table.remove(key);
+        }
+        let body = toml::to_string(&table).map_err(|e| e.to_string())?;
+        Ok(format!("# Horalis settings. Applied on launch and rewritten when a setting changes.\n{}", body))
+    }
+}
+
+fn defaults() -> serde_json::Map<String, Json> {
+    match serde_json::to_value(Settings::default()) {
+        Ok(Json::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    }
+}
+
+/// Parse a stored value into the JSON type of its default
+fn from_stored(text: &str, default: &Json) -> Json {
+    match default {
+        Json::Bool(_) => Json::Bool(text == "true"),
+        Json::Number(_) => serde_json::from_str(text.trim()).unwrap_or(Json::Null),
+        // Weekday lists are comma-separated, anything more complex is JSON
+        Json::Array(_) if !text.trim_start().starts_with('[') => Json::Array(
+            text.split(',')
+                .filter(|day| !day.trim().is_empty())
+                .map(|day| day.trim().parse::<u32>().map_or(Json::Null, Json::from))
+                .collect(),
+        ),
+        Json::Array(_) | Json::Object(_) => serde_json::from_str(text).unwrap_or(Json::Null),
+        _ => Json::String(text.to_string()),
+    }
+}
+
+fn to_stored(value: &Json) -> String {
+    match value {
+        Json::String(text) => text.clone(),
+        Json::Array(items) if items.iter().all(Json::is_number) => {
+            items.iter().map(Json::to_string).collect::<Vec<_>>().join(",")
+        }
+        other => other.to_string(),
+    }
+}
+
+/// settings.toml in the app's config folder
+pub fn file_path() -> Option<PathBuf> {
+    Some(crate::dirs::config_dir()?.join("com.horalis.app").join(FILE_NAME))
+}
+
+fn read_file(path: &PathBuf, current: &Settings) -> Result<Settings, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let table: toml::Table = text.parse().map_err(|e| format!("{} is not valid TOML: {}", path.display(), e))?;
+    let Json::Object(mut changes) = serde_json::to_value(table).map_err(|e| e.to_string())? else {
+        return Err(format!("{} is not a table", path.display()));
+    };
+    // Settings from newer files are refused rather than half understood
+    if let Some(version) = changes.remove("version") {
+        if version.as_u64().is_none_or(|v| v > VERSION as u64) {
+            return Err(format!("{} is from a newer Horalis (settings version {})", path.display(), version));
+        }
+    }
+    for key in SECRETS {
+        changes.remove(key);
+    }
+    current.merged(changes).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn write_file(path: &PathBuf, settings: &Settings) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(path, settings.to_toml()?).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Apply the settings file, if there is one. Called on launch and on unlock.
+pub fn apply_file() {
+    let Some(path) = file_path().filter(|p| p.exists()) else {
+        return;
+    };
+    let Some(conn) = db::open() else {
+        return;
+    };
+    let result = read_file(&path, &Settings::load(&conn)).and_then(|settings| settings.save(&conn).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        eprintln!("Ignoring the settings file: {}", e);
+    }
+}
+
+#[tauri::command]
+pub fn get_settings() -> Result<Settings, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    Ok(Settings::load(&conn))
+}
+
+/// Change a single setting; `value` is in the type of the field
+#[tauri::command]
+pub fn update_setting(app: tauri::AppHandle, key: String, value: Json) -> Result<Settings, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    let mut changes = serde_json::Map::new();
+    changes.insert(key.clone(), value);
+    let settings = Settings::load(&conn).merged(changes)?;
+    settings.save(&conn).map_err(|e| e.to_string())?;
+    if let Some(path) = file_path().filter(|p| p.exists()) {
+        if let Err(e) = write_file(&path, &settings) {
+            eprintln!("{}", e);
+        }
+    }
+    if key.starts_with("reminder_") {
+        crate::restart_reminders(&app, &settings);
+    }
+    Ok(settings)
+}
+
+/// Write the settings file and return its path
+#[tauri::command]
+pub fn export_settings() -> Result<String, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    let path = file_path().ok_or("No configuration folder")?;
+    write_file(&path, &Settings::load(&conn))?;
+    Ok(path.display().to_string())
+}
+
+/// Apply the settings file now instead of on the next launch
+#[tauri::command]
+pub fn import_settings(app: tauri::AppHandle) -> Result<Settings, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    let path = file_path().filter(|p| p.exists()).ok_or("There is no settings file yet; export one first")?;
+    let settings = read_file(&path, &Settings::load(&conn))?;
+    settings.save(&conn).map_err(|e| e.to_string())?;
+    crate::restart_reminders(&app, &settings);
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Settings with a value of every kind changed from the defaults
+    fn sample() -> Settings {
+        Settings {
+            show_timer_in_tray: false,
+            idle_timeout_minutes: 12,
+            reminder_weekdays: Vec::new(),
+            reminder_schedules: vec![ReminderSchedule {
+                weekdays: vec![0, 6],
+                start_time: "22:00".into(),
+                end_time: "02:00".into(),
+                timezone: Some("Asia/Kolkata".into()),
+            }],
+            reminder_break_mode: BreakMode::Activity,
+            activity_sample_seconds: 45,
+            activity_exclusions: "firefox, private\n*.pdf".into(),
+            workday_source: WorkdaySource::Custom,
+            workday_weekdays: vec![6, 0],
+            report_timezone: "entry".into(),
+            fiscal_year_start: "02-29".into(),
+            pay_period: PayFrequency::Semimonthly,
+            timesheet_format: Format::Csv,
+            backup_passphrase: "correct horse".into(),
+            backup_keep_weekly: 0,
+            sync_server_token: "token".into(),
+            ..Settings::default()
+        }
+    }
+
+    fn value(settings: &Settings) -> Json {
+        serde_json::to_value(settings).unwrap()
+    }
+
+    #[test]
+    fn stored_values_round_trip() {
+        let defaults = defaults();
+        let Json::Object(map) = value(&sample()) else {
+            panic!("settings are not an object");
+        };
+        for (key, value) in map {
+            assert_eq!(from_stored(&to_stored(&value), &defaults[&key]), value, "{}", key);
+        }
+    }
+
+    #[test]
+    fn older_weekday_lists_are_read() {
+        let default = json!([1, 2, 3]);
+        assert_eq!(from_stored("1, 3,5", &default), json!([1, 3, 5]));
+        assert_eq!(from_stored("", &default), json!([]));
+        assert_eq!(from_stored("[0,6]", &default), json!([0, 6]));
+    }
+
+    #[test]
+    fn saved_settings_load_unchanged() {
+        let conn = db::testing::memory();
+        let settings = sample();
+        settings.save(&conn).unwrap();
+        assert_eq!(value(&Settings::load(&conn)), value(&settings));
+    }
+
+    #[test]
+    fn bad_rows_fall_back_to_their_defaults() {
+        let conn = db::testing::memory();
+        sample().save(&conn).unwrap();
+        conn.execute("UPDATE settings SET value = 'soon' WHERE key = 'idle_timeout_minutes'", []).unwrap();
+        let loaded = Settings::load(&conn);
+        assert_eq!(loaded.idle_timeout_minutes, Settings::default().idle_timeout_minutes);
+        assert_eq!(loaded.activity_sample_seconds, 45);
+    }
+
+    #[test]
+    fn settings_file_round_trips_without_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        let settings = sample();
+        write_file(&path, &settings).unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(!text.contains("correct horse") && !text.contains("token"));
+        let current = Settings { backup_passphrase: "kept".into(), ..Settings::default() };
+        let imported = read_file(&path, &current).unwrap();
+        let expected = Settings { backup_passphrase: "kept".into(), sync_server_token: String::new(), ..settings };
+        assert_eq!(value(&imported), value(&expected));
+    }
+
+    #[test]
+    fn settings_file_from_newer_version_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        std::fs::write(&path, format!("version = {}\nidle_timeout_minutes = 3\n", VERSION + 1)).unwrap();
+        assert!(read_file(&path, &Settings::default()).err().unwrap().contains("newer Horalis"));
+    }
+
+    #[test]
+    fn validate_rejects_zero_minutes() {
+        let settings = Settings { reminder_break_min_minutes: 0, ..Settings::default() };
+        assert_eq!(settings.validate().unwrap_err(), "reminder_break_min_minutes: must be at least 1");
+        let settings = Settings { backup_keep_daily: 0, backup_keep_weekly: 0, backup_keep_monthly: 0, ..Settings::default() };
+        assert!(settings.validate().is_ok());
+        assert!(sample().validate().is_ok());
+    }
+}
@@ -0,0 +1,1089 @@
+// Sync between devices through change logs instead of copying the database.
+// Every device records its own changes to projects and time entries as
+// numbered operations in sync_outbox; other devices apply them in turn.
+// Transports only move operations around. Folder sync (below) appends them to
+// one file per device in a shared folder, so Syncthing, Dropbox or a NAS never
+// see two writers on the same file; sync_server.rs exchanges them with a
+// server instead. Either or both run, depending on the settings.
+//
+// Rows are matched across devices by a `uid` column, derived when first
+// needed from a project's name and from an entry's project and start time, so
+// the default projects of two installs, or a database copied over before, line
+// up instead of doubling. A row's version is the time and device of its latest
+// change and the newest version wins. Triggers note when each row was edited
+// in sync_edits, so a change made offline is versioned by when it was made
+// rather than by when it was next synced. When a change from elsewhere meets an
+// edit made on this device without having seen it, the losing side is kept in
+// sync_conflicts so it can be restored. Tags, commit links and timesheet
+// locks stay local; a change to an entry of a submitted timesheet, or the
+// deletion of a project with such entries, is not applied and ends up in
+// sync_conflicts as well, as does a change that cannot be applied at all, such
+// as an entry for a project deleted here. When timers end up running on two devices, the one
+// started last keeps running.
+use rusqlite::types::Value;
+use rusqlite::{Connection, OptionalExtension};
+use serde_json::{Map, Value as Json};
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::Emitter;
+
+use crate::audit::{self, ChangeSource};
+use crate::db;
+use crate::journal::{self, Recorder};
+use crate::settings::Settings;
+use crate::sync_server;
+use crate::timesheets;
+
+const SYNC_INTERVAL: Duration = Duration::from_secs(30);
+const LOG_PREFIX: &str = "horalis-sync-";
+const LOG_SUFFIX: &str = ".jsonl";
+
+type Row = Map<String, Json>;
+
+/// Synced columns per table, as named in a change. Entries name their project
+/// by uid under "project".
+const PROJECT_FIELDS: &[&str] = &["name", "color", "client", "created_at"];
+const ENTRY_FIELDS: &[&str] = &["project", "start_time", "end_time", "duration", "timezone", "created_at"];
+
+/// Synced tables, in the order changes are applied: entries refer to projects
+const TABLES: [&str; 2] = ["projects", "time_entries"];
+
+/// When and where a row was last changed; later versions win, ties go to the
+/// higher device id
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, serde::Deserialize, serde::Serialize)]
+pub struct Version {
+    pub at: i64, // Unix milliseconds
+    pub device: String,
+}
+
+/// One operation from a device's log
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+pub struct Change {
+    /// Position in the device's log, from 1
+    pub seq: i64,
+    pub table: String,
+    pub uid: String,
+    pub version: Version,
+    /// The version this change replaced on its device, None for a new row
+    pub base: Option<Version>,
+    /// New contents, None when the row was deleted
+    pub row: Option<Row>,
+}
+
+#[derive(Default, serde::Serialize)]
+pub struct SyncSummary {
+    pub sent: usize,
+    pub received: usize,
+    pub conflicts: usize,
+}
+
+impl SyncSummary {
+    fn add(&mut self, other: SyncSummary) {
+        self.sent += other.sent;
+        self.received += other.received;
+        self.conflicts += other.conflicts;
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct SyncPeer {
+    device: String,
+    seq: i64,
+    synced_at: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct SyncConflict {
+    id: i64,
+    table: String,
+    device: String, // The other device involved
+    kept: Option<Row>,
+    discarded: Option<Row>,
+    detected_at: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct SyncStatus {
+    device: String,
+    server_synced_at: Option<String>,
+    peers: Vec<SyncPeer>,
+    conflicts: Vec<SyncConflict>,
+}
+
+/// 128 random bits as hex. RandomState is seeded from the OS, which is all the
+/// randomness an identifier needs.
+pub fn new_uid() -> String {
+    let state = std::collections::hash_map::RandomState::new();
+    let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_nanos();
+    let part = |salt: u64| {
+        let mut hasher = state.build_hasher();
+        hasher.write_u128(nanos);
+        hasher.write_u64(salt);
+        hasher.finish()
+    };
+    format!("{:016x}{:016x}", part(1), part(2))
+}
+
+/// Uid derived from `key`, the same on every device (FNV-1a, twice)
+fn stable_uid(key: &str) -> String {
+    let fnv = |offset: u64| key.bytes().fold(offset, |hash, b| (hash ^ b as u64).wrapping_mul(0x100000001b3));
+    format!("{:016x}{:016x}", fnv(0xcbf29ce484222325), fnv(0x84222325cbf29ce4))
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+pub fn get_state(conn: &Connection, key: &str) -> rusqlite::Result<Option<String>> {
+    conn.query_row("SELECT value FROM sync_state WHERE key = ?1", [key], |row| row.get(0)).optional()
+}
+
+pub fn set_state(conn: &Connection, key: &str, value: &str) -> rusqlite::Result<()> {
+    conn.execute("INSERT OR REPLACE INTO sync_state (key, value) VALUES (?1, ?2)", [key, value])?;
+    Ok(())
+}
+
+/// This device's id, created on first use
+pub fn device_id(conn: &Connection) -> rusqlite::Result<String> {
+    if let Some(id) = get_state(conn, "device_id")? {
+        return Ok(id);
+    }
+    let id = new_uid();
+    set_state(conn, "device_id", &id)?;
+    Ok(id)
+}
+
+/// Indexes on the uid columns and the triggers filling sync_edits; the tables
+/// belong to the frontend, so this runs before each sync rather than in db.rs
+fn prepare(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_projects_uid ON projects(uid);
+         CREATE UNIQUE INDEX IF NOT EXISTS idx_time_entries_uid ON time_entries(uid);",
+    )?;
+    let columns = [("projects", "name, color, client, created_at"), ("time_entries", "project_id, start_time, end_time, duration, timezone, created_at")];
+    for (table, columns) in columns {
+        // Only synced columns: assigning a uid is not an edit
+        let events = [("insert", "INSERT".to_string(), "NEW"), ("update", format!("UPDATE OF {}", columns), "NEW"), ("delete", "DELETE".to_string(), "OLD")];
+        for (name, event, row) in events {
+            conn.execute_batch(&format!(
+                "CREATE TRIGGER IF NOT EXISTS sync_edit_{table}_{name} AFTER {event} ON {table} BEGIN
+                     DELETE FROM sync_edits WHERE tbl = '{table}' AND row_id = {row}.id;
+                     INSERT INTO sync_edits (tbl, row_id, uid, at)
+                     VALUES ('{table}', {row}.id, {row}.uid, CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER));
+                 END;"
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+/// When the row `uid` was last edited here, if since the last sync
+fn edited_at(conn: &Connection, table: &str, uid: &str) -> rusqlite::Result<Option<i64>> {
+    conn.query_row(
+        &format!("SELECT MAX(at) FROM sync_edits WHERE tbl = ?1 AND (uid = ?2 OR row_id = (SELECT id FROM {} WHERE uid = ?2))", table),
+        [table, uid],
+        |row| row.get(0),
+    )
+}
+
+/// Give new rows a uid: the derived one, or a random one when that is taken
+fn assign_uids(conn: &Connection) -> rusqlite::Result<()> {
+    let queries = [
+        ("projects", "SELECT id, lower(trim(name)) FROM projects WHERE uid IS NULL"),
+        (
+            "time_entries",
+            "SELECT e.id, COALESCE(p.uid, '') || ' ' || e.start_time
+             FROM time_entries e
+             LEFT JOIN projects p ON p.id = e.project_id
+             WHERE e.uid IS NULL",
+        ),
+    ];
+    for (table, sql) in queries {
+        let rows: Vec<(i64, String)> = conn
+            .prepare(sql)?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        for (id, key) in rows {
+            let uid = stable_uid(&key);
+            let taken: bool = conn.query_row(&format!("SELECT EXISTS (SELECT 1 FROM {} WHERE uid = ?1)", table), [&uid], |row| row.get(0))?;
+            let uid = if taken { new_uid() } else { uid };
+            conn.execute(&format!("UPDATE {} SET uid = ?1 WHERE id = ?2", table), rusqlite::params![uid, id])?;
+        }
+    }
+    Ok(())
+}
+
+/// Every row of a synced table as (uid, contents)
+fn load_rows(conn: &Connection, table: &str) -> rusqlite::Result<Vec<(String, Row)>> {
+    let (sql, fields) = match table {
+        "projects" => ("SELECT uid, name, color, client, created_at FROM projects", PROJECT_FIELDS),
+        _ => (
+            "SELECT e.uid, p.uid, e.start_time, e.end_time, e.duration, e.timezone, e.created_at
+             FROM time_entries e
+             LEFT JOIN projects p ON p.id = e.project_id",
+            ENTRY_FIELDS,
+        ),
+    };
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map([], |row| {
+        let mut contents = Row::new();
+        for (i, field) in fields.iter().enumerate() {
+            contents.insert(field.to_string(), journal::to_json(row.get::<_, Value>(i + 1)?));
+        }
+        Ok((row.get(0)?, contents))
+    })?;
+    rows.collect()
+}
+
+fn fingerprint(row: &Row) -> String {
+    // Keys are sorted, so equal rows serialize the same
+    serde_json::to_string(row).unwrap_or_default()
+}
+
+/// Fingerprint (None once deleted) and version of a row as last synced
+fn known_version(conn: &Connection, table: &str, uid: &str) -> rusqlite::Result<Option<(Option<String>, Version)>> {
+    conn.query_row(
+        "SELECT fingerprint, version_at, version_device FROM sync_rows WHERE tbl = ?1 AND uid = ?2",
+        [table, uid],
+        |row| Ok((row.get(0)?, Version { at: row.get(1)?, device: row.get(2)? })),
+    )
+    .optional()
+}
+
+fn set_known_version(conn: &Connection, table: &str, uid: &str, fingerprint: Option<&str>, version: &Version) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO sync_rows (tbl, uid, fingerprint, version_at, version_device) VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![table, uid, fingerprint, version.at, version.device],
+    )?;
+    Ok(())
+}
+
+/// Compare every synced row with its last synced state and add an operation
+/// to the outbox for each that changed here. Returns the number added.
+pub fn record_local_changes(conn: &Connection) -> rusqlite::Result<usize> {
+    prepare(conn)?;
+    assign_uids(conn)?;
+    let device = device_id(conn)?;
+    let now = now_ms();
+    let mut seq: i64 = conn.query_row("SELECT COALESCE(MAX(seq), 0) FROM sync_outbox", [], |row| row.get(0))?;
+    let start = seq;
+    let mut add = |table: &str, uid: &str, base: Option<Version>, row: Option<Row>| -> rusqlite::Result<()> {
+        seq += 1;
+        // An edit follows the version it replaced, even when this clock is behind
+        let at = edited_at(conn, table, uid)?.unwrap_or(now).max(base.as_ref().map_or(0, |b| b.at + 1));
+        let version = Version { at, device: device.clone() };
+        let fingerprint = row.as_ref().map(fingerprint);
+        set_known_version(conn, table, uid, fingerprint.as_deref(), &version)?;
+        let change = Change { seq, table: table.to_string(), uid: uid.to_string(), version, base, row };
+        let change = serde_json::to_string(&change).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        conn.execute("INSERT INTO sync_outbox (seq, change) VALUES (?1, ?2)", rusqlite::params![seq, change])?;
+        Ok(())
+    };
+
+    for table in TABLES {
+        let mut present = HashSet::new();
+        for (uid, row) in load_rows(conn, table)? {
+            let known = known_version(conn, table, &uid)?;
+            present.insert(uid.clone());
+            if known.as_ref().is_some_and(|(f, _)| f.as_deref() == Some(fingerprint(&row).as_str())) {
+                continue;
+            }
+            add(table, &uid, known.map(|(_, v)| v), Some(row))?;
+        }
+        // Rows synced before but gone now were deleted here
+        let deleted: Vec<(String, Version)> = conn
+            .prepare("SELECT uid, version_at, version_device FROM sync_rows WHERE tbl = ?1 AND fingerprint IS NOT NULL")?
+            .query_map([table], |row| Ok((row.get(0)?, Version { at: row.get(1)?, device: row.get(2)? })))?
+            .collect::<rusqlite::Result<Vec<_>>>()?
+            .into_iter()
+            .filter(|(uid, _)| !present.contains(uid))
+            .collect();
+        for (uid, base) in deleted {
+            add(table, &uid, Some(base), None)?;
+        }
+    }
+    conn.execute("DELETE FROM sync_edits", [])?;
+    Ok((seq - start) as usize)
+}
+
+/// Whether the entry's latest synced version came from another device, e.g.
+/// a timer running there
+pub fn changed_elsewhere(conn: &Connection, entry_id: i64) -> rusqlite::Result<bool> {
+    let Some(device) = get_state(conn, "device_id")? else {
+        return Ok(false);
+    };
+    conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM time_entries e
+                        JOIN sync_rows r ON r.tbl = 'time_entries' AND r.uid = e.uid
+                        WHERE e.id = ?1 AND r.version_device != ?2)",
+        rusqlite::params![entry_id, device],
+        |row| row.get(0),
+    )
+}
+
+/// This device's operations after `seq`, oldest first
+pub fn outbox_since(conn: &Connection, seq: i64) -> rusqlite::Result<Vec<Change>> {
+    let mut stmt = conn.prepare("SELECT change FROM sync_outbox WHERE seq > ?1 ORDER BY seq")?;
+    let changes = stmt.query_map([seq], |row| {
+        let change: String = row.get(0)?;
+        serde_json::from_str(&change).map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))
+    })?;
+    changes.collect()
+}
+
+fn local_id(conn: &Connection, table: &str, uid: &str) -> rusqlite::Result<Option<i64>> {
+    conn.query_row(&format!("SELECT id FROM {} WHERE uid = ?1", table), [uid], |row| row.get(0)).optional()
+}
+
+/// Current contents of a row in change form, None if it does not exist
+fn current_row(conn: &Connection, table: &str, uid: &str) -> rusqlite::Result<Option<Row>> {
+    // Tables are small enough to scan; this only runs for conflicts
+    Ok(load_rows(conn, table)?.into_iter().find(|(u, _)| u == uid).map(|(_, row)| row))
+}
+
+/// Make the local row `uid` match `row`, deleting it for None. Returns its id.
+fn write_row(conn: &Connection, table: &str, uid: &str, row: Option<&Row>) -> Result<Option<i64>, String> {
+    let id = local_id(conn, table, uid).map_err(|e| e.to_string())?;
+    let field = |name: &str| row.and_then(|r| r.get(name)).map(journal::to_sql).unwrap_or(Value::Null);
+    let result = match (table, row, id) {
+        (_, None, None) => return Ok(None),
+        ("projects", None, Some(id)) => conn
+            .execute("DELETE FROM time_entries WHERE project_id = ?1", [id])
+            .and_then(|_| conn.execute("DELETE FROM project_repos WHERE project_id = ?1", [id]))
+            .and_then(|_| conn.execute("DELETE FROM projects WHERE id = ?1", [id])),
+        (_, None, Some(id)) => conn.execute("DELETE FROM time_entries WHERE id = ?1", [id]),
+        ("projects", Some(_), _) => conn.execute(
+            "INSERT INTO projects (id, uid, name, color, client, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT (id) DO UPDATE SET name = excluded.name, color = excluded.color, client = excluded.client, created_at = excluded.created_at",
+            rusqlite::params![id, uid, field("name"), field("color"), field("client"), field("created_at")],
+        ),
+        (_, Some(row), _) => {
+            let project_uid = row.get("project").and_then(Json::as_str).unwrap_or_default();
+            let project_id = local_id(conn, "projects", project_uid)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("Unknown project {}", project_uid))?;
+            conn.execute(
+                "INSERT INTO time_entries (id, uid, project_id, start_time, end_time, duration, timezone, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                 ON CONFLICT (id) DO UPDATE SET project_id = excluded.project_id, start_time = excluded.start_time,
+                    end_time = excluded.end_time, duration = excluded.duration, timezone = excluded.timezone,
+                    created_at = excluded.created_at",
+                rusqlite::params![
+                    id,
+                    uid,
+                    project_id,
+                    field("start_time"),
+                    field("end_time"),
+                    field("duration"),
+                    field("timezone"),
+                    field("created_at"),
+                ],
+            )
+        }
+    };
+    result.map_err(|e| e.to_string())?;
+    Ok(if row.is_some() { id.or(Some(conn.last_insert_rowid())) } else { id })
+}
+
+/// Local entries a change touches: the entry itself, or every entry of a
+/// deleted project
+fn affected_entries(conn: &Connection, change: &Change) -> rusqlite::Result<Vec<i64>> {
+    match (change.table.as_str(), &change.row) {
+        ("time_entries", _) => Ok(local_id(conn, "time_entries", &change.uid)?.into_iter().collect()),
+        ("projects", None) => {
+            let Some(project_id) = local_id(conn, "projects", &change.uid)? else {
+                return Ok(Vec::new());
+            };
+            conn.prepare("SELECT id FROM time_entries WHERE project_id = ?1")?
+                .query_map([project_id], |row| row.get(0))?
+                .collect()
+        }
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// Write a change from another device. Entries it touches, including those
+/// of a deleted project, get an audit record, and changes to entries of a
+/// submitted timesheet are refused: Ok(false).
+fn apply_row(conn: &Connection, change: &Change) -> Result<bool, String> {
+    let entries = affected_entries(conn, change).map_err(|e| e.to_string())?;
+    if change.table != "time_entries" && entries.is_empty() {
+        write_row(conn, &change.table, &change.uid, change.row.as_ref())?;
+        return Ok(true);
+    }
+    let sql = |sql: &str| conn.execute_batch(sql).map_err(|e| e.to_string());
+    sql("SAVEPOINT sync_row")?;
+    let result = (|| {
+        let mut before = Vec::new();
+        for id in entries {
+            before.push((id, journal::snapshot(conn, "time_entries", id).map_err(|e| e.to_string())?));
+        }
+        let written = write_row(conn, &change.table, &change.uid, change.row.as_ref())?;
+        if change.table == "time_entries" && before.is_empty() {
+            // A new entry
+            before.extend(written.map(|id| (id, None)));
+        }
+        for (id, before) in before {
+            let after = journal::snapshot(conn, "time_entries", id).map_err(|e| e.to_string())?;
+            if let Err(e) = timesheets::check_unlocked(conn, before.as_ref(), after.as_ref()) {
+                eprintln!("Refused synced change to {} {}: {}", change.table, change.uid, e);
+                return Ok(false);
+            }
+            audit::record(conn, id, before.as_ref(), after.as_ref(), ChangeSource::Import, "Synced from another device")
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(true)
+    })();
+    match result {
+        Ok(true) => sql("RELEASE sync_row")?,
+        _ => {
+            sql("ROLLBACK TO sync_row")?;
+            sql("RELEASE sync_row")?;
+        }
+    }
+    result
+}
+
+fn record_conflict(conn: &Connection, change: &Change, kept: Option<&Row>, discarded: Option<&Row>) -> rusqlite::Result<()> {
+    let json = |row: Option<&Row>| row.map(|r| Json::Object(r.clone()).to_string());
+    conn.execute(
+        "INSERT INTO sync_conflicts (tbl, uid, device, kept, discarded, detected_at) VALUES (?1, ?2, ?3, ?4, ?5, datetime('now'))",
+        rusqlite::params![change.table, change.uid, change.version.device, json(kept), json(discarded)],
+    )?;
+    Ok(())
+}
+
+/// Highest sequence number applied from `device`, through any transport
+fn applied_seq(conn: &Connection, device: &str) -> rusqlite::Result<i64> {
+    Ok(conn
+        .query_row("SELECT seq FROM sync_peers WHERE device = ?1", [device], |row| row.get(0))
+        .optional()?
+        .unwrap_or(0))
+}
+
+/// Apply operations from other devices, oldest first within each table.
+/// Operations already applied, e.g. read from the folder before and now
+/// from the server, are skipped. Returns how many changed something and how
+/// many conflicted.
+pub fn apply_changes(conn: &Connection, mut changes: Vec<Change>) -> Result<(usize, usize), String> {
+    prepare(conn).map_err(|e| e.to_string())?;
+    assign_uids(conn).map_err(|e| e.to_string())?;
+    let device = device_id(conn).map_err(|e| e.to_string())?;
+    let mut seen: HashMap<String, i64> = HashMap::new();
+    for change in &changes {
+        if !seen.contains_key(&change.version.device) {
+            seen.insert(change.version.device.clone(), applied_seq(conn, &change.version.device).map_err(|e| e.to_string())?);
+        }
+    }
+    changes.retain(|c| c.version.device != device && c.seq > seen[&c.version.device]);
+    changes.sort_by(|a, b| {
+        let table = |c: &Change| TABLES.iter().position(|t| *t == c.table);
+        table(a).cmp(&table(b)).then_with(|| a.version.cmp(&b.version))
+    });
+
+    let (mut applied, mut conflicts) = (0, 0);
+    for change in changes.iter().filter(|c| TABLES.contains(&c.table.as_str())) {
+        let known = known_version(conn, &change.table, &change.uid).map_err(|e| e.to_string())?;
+        let incoming = change.row.as_ref().map(fingerprint);
+        let (newer, concurrent) = match &known {
+            None => (true, false),
+            Some((_, current)) if *current == change.version => continue,
+            Some((fingerprint, current)) => (
+                change.version > *current,
+                // An edit made here that the other device had not seen
+                current.device == device && change.base.as_ref() != Some(current) && *fingerprint != incoming,
+            ),
+        };
+        let local = if concurrent || newer {
+            current_row(conn, &change.table, &change.uid).map_err(|e| e.to_string())?
+        } else {
+            None
+        };
+        let mut conflict = |kept: Option<&Row>, discarded: Option<&Row>| -> Result<(), String> {
+            conflicts += 1;
+            record_conflict(conn, change, kept, discarded).map_err(|e| e.to_string())
+        };
+        if !newer {
+            if concurrent {
+                conflict(local.as_ref(), change.row.as_ref())?;
+            }
+            continue;
+        }
+        if local.as_ref().map(fingerprint) == incoming {
+            // Same contents, e.g. both devices started from a copy of one database
+            set_known_version(conn, &change.table, &change.uid, incoming.as_deref(), &change.version).map_err(|e| e.to_string())?;
+            continue;
+        }
+        match apply_row(conn, change) {
+            Ok(true) => {
+                if concurrent {
+                    conflict(change.row.as_ref(), local.as_ref())?;
+                }
+                set_known_version(conn, &change.table, &change.uid, incoming.as_deref(), &change.version).map_err(|e| e.to_string())?;
+                applied += 1;
+            }
+            // A submitted timesheet covers the entry here, or one of the deleted project's: it stays, and the other version is kept for review
+            Ok(false) => {
+                conflict(local.as_ref(), change.row.as_ref())?;
+                let local = local.as_ref().map(fingerprint);
+                set_known_version(conn, &change.table, &change.uid, local.as_deref(), &change.version).map_err(|e| e.to_string())?;
+            }
+            // Kept for review rather than lost once the peer's sequence moves past it
+            Err(e) => {
+                eprintln!("Failed to apply synced change to {} {}: {}", change.table, change.uid, e);
+                conflict(local.as_ref(), change.row.as_ref())?;
+            }
+        }
+    }
+    for change in &changes {
+        seen.insert(change.version.device.clone(), seen[&change.version.device].max(change.seq));
+    }
+    seen.remove(&device);
+    for (peer, seq) in seen {
+        conn.execute(
+            "INSERT INTO sync_peers (device, seq, synced_at) VALUES (?1, ?2, datetime('now'))
+             ON CONFLICT (device) DO UPDATE SET seq = MAX(seq, excluded.seq), synced_at = excluded.synced_at",
+            rusqlite::params![peer, seq],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    if applied > 0 {
+        settle_running(conn)?;
+    }
+    Ok((applied, conflicts))
+}
+
+/// Keep one timer running. A timer started on another device stops the ones
+/// started before it, at its start time, as starting a timer does locally.
+fn settle_running(conn: &Connection) -> Result<(), String> {
+    let newest: Option<(i64, String)> = conn
+        .query_row(
+            "SELECT id, start_time FROM time_entries WHERE end_time IS NULL ORDER BY start_time DESC, id DESC LIMIT 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let Some((id, start)) = newest else {
+        return Ok(());
+    };
+    let sql = |sql: &str| conn.execute_batch(sql).map_err(|e| e.to_string());
+    sql("SAVEPOINT sync_settle")?;
+    let result = (|| -> rusqlite::Result<()> {
+        let mut journal = Recorder::new("Timer continued on another device").source(ChangeSource::Automatic);
+        journal.track_where(conn, "time_entries", "end_time IS NULL AND id != ?1", [id])?;
+        conn.execute(
+            "UPDATE time_entries
+             SET end_time = ?1,
+                 duration = MAX(0, CAST((julianday(?1) - julianday(start_time)) * 86400 AS INTEGER))
+             WHERE end_time IS NULL AND id != ?2",
+            rusqlite::params![start, id],
+        )?;
+        journal.finish(conn)?;
+        Ok(())
+    })();
+    match result {
+        Ok(()) => sql("RELEASE sync_settle"),
+        // E.g. an entry of a submitted timesheet; both keep running
+        Err(e) => {
+            eprintln!("Failed to stop timers running elsewhere: {}", e);
+            sql("ROLLBACK TO sync_settle")?;
+            sql("RELEASE sync_settle")
+        }
+    }
+}
+
+fn log_path(folder: &Path, device: &str) -> PathBuf {
+    folder.join(format!("{}{}{}", LOG_PREFIX, device, LOG_SUFFIX))
+}
+
+/// Append this device's new operations to its log in `folder`
+fn push_to_folder(conn: &Connection, folder: &Path, device: &str) -> Result<usize, String> {
+    let path = log_path(folder, device);
+    let mut pushed: i64 = get_state(conn, "folder_seq").map_err(|e| e.to_string())?.and_then(|s| s.parse().ok()).unwrap_or(0);
+    // A new folder, or a log removed from it, gets the whole history
+    if std::fs::metadata(&path).map(|m| m.len() == 0).unwrap_or(true) {
+        pushed = 0;
+    }
+    let changes = outbox_since(conn, pushed).map_err(|e| e.to_string())?;
+    let Some(last) = changes.last() else {
+        return Ok(0);
+    };
+    let mut lines = String::new();
+    for change in &changes {
+        lines += &serde_json::to_string(change).map_err(|e| e.to_string())?;
+        lines.push('\n');
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    file.write_all(lines.as_bytes()).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    set_state(conn, "folder_seq", &last.seq.to_string()).map_err(|e| e.to_string())?;
+    Ok(changes.len())
+}
+
+/// How far another device's log has been read
+struct LogPosition {
+    device: String,
+    offset: u64,
+    seq: i64,
+}
+
+/// New operations from the other devices' logs in `folder`, with the positions
+/// to store once they are applied
+fn pull_from_folder(conn: &Connection, folder: &Path, device: &str) -> Result<(Vec<Change>, Vec<LogPosition>), String> {
+    let dir = std::fs::read_dir(folder).map_err(|e| format!("Failed to read {}: {}", folder.display(), e))?;
+    let mut changes = Vec::new();
+    let mut positions = Vec::new();
+    for entry in dir.filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Some(peer) = name.strip_prefix(LOG_PREFIX).and_then(|n| n.strip_suffix(LOG_SUFFIX)) else {
+            continue;
+        };
+        if peer == device {
+            continue;
+        }
+        let (offset, mut seq): (u64, i64) = conn
+            .query_row("SELECT read_offset, seq FROM sync_peers WHERE device = ?1", [peer], |row| Ok((row.get(0)?, row.get(1)?)))
+            .optional()
+            .map_err(|e| e.to_string())?
+            .unwrap_or((0, 0));
+
+        let mut file = std::fs::File::open(entry.path()).map_err(|e| e.to_string())?;
+        let len = file.metadata().map_err(|e| e.to_string())?.len();
+        // A log that shrank was replaced; sequence numbers skip what was applied
+        let offset = if len < offset { 0 } else { offset };
+        file.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
+        let mut text = String::new();
+        file.read_to_string(&mut text).map_err(|e| e.to_string())?;
+
+        // Only whole lines; the last one may still be arriving
+        let complete = text.rfind('\n').map_or(0, |i| i + 1);
+        for line in text[..complete].lines().filter(|l| !l.trim().is_empty()) {
+            match serde_json::from_str::<Change>(line) {
+                Ok(change) if change.seq > seq && change.version.device == peer => {
+                    seq = change.seq;
+                    changes.push(change);
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("Skipping unreadable line in {}: {}", name, e),
+            }
+        }
+        positions.push(LogPosition { device: peer.to_string(), offset: offset + complete as u64, seq });
+    }
+    Ok((changes, positions))
+}
+
+/// Turn local edits since the last run into operations, before sending
+pub fn record(conn: &mut Connection) -> Result<(), String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    record_local_changes(&tx).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Exchange changes with the other devices through `folder`
+pub fn sync_folder(conn: &mut Connection, folder: &Path) -> Result<SyncSummary, String> {
+    std::fs::create_dir_all(folder).map_err(|e| format!("Failed to create {}: {}", folder.display(), e))?;
+    record(conn)?;
+
+    let device = device_id(conn).map_err(|e| e.to_string())?;
+    let sent = push_to_folder(conn, folder, &device)?;
+    let (changes, positions) = pull_from_folder(conn, folder, &device)?;
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let (received, conflicts) = apply_changes(&tx, changes)?;
+    for position in positions {
+        tx.execute(
+            "INSERT INTO sync_peers (device, read_offset, seq, synced_at) VALUES (?1, ?2, ?3, datetime('now'))
+             ON CONFLICT (device) DO UPDATE SET read_offset = excluded.read_offset, seq = MAX(seq, excluded.seq),
+                synced_at = excluded.synced_at",
+            rusqlite::params![position.device, position.offset, position.seq],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(SyncSummary { sent, received, conflicts })
+}
+
+/// Sync through the folder and the server, whichever are set
+fn run(conn: &mut Connection) -> Result<SyncSummary, String> {
+    let settings = Settings::load(conn);
+    let setting = |value: &str| Some(value.trim().to_string()).filter(|v| !v.is_empty());
+    let folder = setting(&settings.sync_folder);
+    let server = setting(&settings.sync_server_url);
+    let token = setting(&settings.sync_server_token);
+    if folder.is_none() && server.is_none() {
+        return Err("Choose a sync folder or server".into());
+    }
+    let mut summary = SyncSummary::default();
+    if let Some(folder) = folder {
+        if !Path::new(&folder).is_absolute() {
+            return Err("Choose a full sync folder path".into());
+        }
+        summary.add(sync_folder(conn, Path::new(&folder))?);
+    }
+    if let Some(url) = server {
+        summary.add(sync_server::sync_server(conn, &url, token.as_deref())?);
+    }
+    Ok(summary)
+}
+
+fn notify_received(app: &tauri::AppHandle, summary: &SyncSummary) {
+    if summary.received > 0 {
+        let _ = app.emit("entries-changed", ());
+        let _ = app.emit("timer-changed", ());
+    }
+}
+
+/// Spawn the sync loop. Settings are read on every tick.
+pub fn start_sync(app: tauri::AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(SYNC_INTERVAL);
+        let Some(mut conn) = db::open() else {
+            continue;
+        };
+        if !Settings::load(&conn).sync_enabled {
+            continue;
+        }
+        match run(&mut conn) {
+            Ok(summary) => notify_received(&app, &summary),
+            Err(e) => eprintln!("Failed to sync: {}", e),
+        }
+    });
+}
+
+#[tauri::command]
+pub fn sync_now(app: tauri::AppHandle) -> Result<SyncSummary, String> {
+    let mut conn = db::open().ok_or("Database not found")?;
+    let summary = run(&mut conn)?;
+    notify_received(&app, &summary);
+    Ok(summary)
+}
+
+#[tauri::command]
+pub fn get_sync_status() -> Result<SyncStatus, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    let peers = conn
+        .prepare("SELECT device, seq, synced_at FROM sync_peers ORDER BY device")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| Ok(SyncPeer { device: row.get(0)?, seq: row.get(1)?, synced_at: row.get(2)? }))?
+                .collect::<rusqlite::Result<Vec<_>>>()
+        })
+        .map_err(|e| e.to_string())?;
+    let parse = |text: Option<String>| text.and_then(|t| serde_json::from_str::<Row>(&t).ok());
+    let conflicts = conn
+        .prepare("SELECT id, tbl, device, kept, discarded, detected_at FROM sync_conflicts ORDER BY id DESC")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| {
+                Ok(SyncConflict {
+                    id: row.get(0)?,
+                    table: row.get(1)?,
+                    device: row.get(2)?,
+                    kept: parse(row.get(3)?),
+                    discarded: parse(row.get(4)?),
+                    detected_at: row.get(5)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()
+        })
+        .map_err(|e| e.to_string())?;
+    Ok(SyncStatus {
+        device: device_id(&conn).map_err(|e| e.to_string())?,
+        server_synced_at: get_state(&conn, "server_synced_at").map_err(|e| e.to_string())?,
+        peers,
+        conflicts,
+    })
+}
+
+/// Close a conflict, optionally bringing back the discarded version. A
+/// restored version is journaled like an edit and synced on the next run.
+#[tauri::command]
+pub fn resolve_sync_conflict(app: tauri::AppHandle, id: i64, restore: bool) -> Result<(), String> {
+    let mut conn = db::open().ok_or("Database not found")?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let (table, uid, discarded): (String, String, Option<String>) = tx
+        .query_row("SELECT tbl, uid, discarded FROM sync_conflicts WHERE id = ?1", [id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })
+        .map_err(|e| e.to_string())?;
+    if restore {
+        let row: Option<Row> = discarded.map(|d| serde_json::from_str(&d)).transpose().map_err(|e| e.to_string())?;
+        let mut journal = Recorder::new("Restore synced version");
+        if let Some(rowid) = local_id(&tx, &table, &uid).map_err(|e| e.to_string())? {
+            journal.track(&tx, &table, rowid).map_err(|e| e.to_string())?;
+            if table == "projects" {
+                journal.track_where(&tx, "time_entries", "project_id = ?1", [rowid]).map_err(|e| e.to_string())?;
+            }
+        }
+        if let Some(rowid) = write_row(&tx, &table, &uid, row.as_ref())? {
+            journal.track_new(&table, rowid);
+        }
+        journal.finish(&tx).map_err(|e| e.to_string())?;
+    }
+    tx.execute("DELETE FROM sync_conflicts WHERE id = ?1", [id]).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    if restore {
+        let _ = app.emit("entries-changed", ());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two devices with their own databases and a shared sync folder
+    struct Devices {
+        dir: tempfile::TempDir,
+        a: Connection,
+        b: Connection,
+    }
+
+    impl Devices {
+        fn new() -> Self {
+            let dir = tempfile::tempdir().unwrap();
+            let open = |name: &str| {
+                std::fs::create_dir(dir.path().join(name)).unwrap();
+                let conn = Connection::open(dir.path().join(name).join("horalis.db")).unwrap();
+                db::testing::init(&conn);
+                conn
+            };
+            let (a, b) = (open("a"), open("b"));
+            Devices { dir, a, b }
+        }
+
+        fn sync(conn: &mut Connection, folder: &Path) -> SyncSummary {
+            sync_folder(conn, folder).unwrap()
+        }
+
+        fn sync_a(&mut self) -> SyncSummary {
+            Self::sync(&mut self.a, &self.dir.path().join("shared"))
+        }
+
+        fn sync_b(&mut self) -> SyncSummary {
+            Self::sync(&mut self.b, &self.dir.path().join("shared"))
+        }
+
+        /// Sync both ways until neither side has anything new
+        fn settle(&mut self) {
+            for _ in 0..4 {
+                self.sync_a();
+                self.sync_b();
+            }
+        }
+    }
+
+    fn execute(conn: &Connection, sql: &str) {
+        conn.execute_batch(sql).unwrap();
+    }
+
+    /// (project, start, end, duration) of every entry, by start time
+    fn entries(conn: &Connection) -> Vec<(String, String, Option<String>, Option<i64>)> {
+        conn.prepare(
+            "SELECT p.name, e.start_time, e.end_time, e.duration FROM time_entries e
+             JOIN projects p ON p.id = e.project_id ORDER BY e.start_time",
+        )
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+        .unwrap()
+        .collect::<rusqlite::Result<_>>()
+        .unwrap()
+    }
+
+    fn projects(conn: &Connection) -> Vec<String> {
+        conn.prepare("SELECT name FROM projects ORDER BY name")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap()
+    }
+
+    fn count(conn: &Connection, sql: &str) -> i64 {
+        conn.query_row(sql, [], |row| row.get(0)).unwrap()
+    }
+
+    /// A project with one finished entry, created on A and synced to B
+    fn shared_entry() -> Devices {
+        let mut devices = Devices::new();
+        execute(
+            &devices.a,
+            "INSERT INTO projects (id, name, color) VALUES (1, 'Horalis', '#111');
+             INSERT INTO time_entries (project_id, start_time, end_time, duration)
+             VALUES (1, '2026-10-19 09:00:00', '2026-10-19 10:00:00', 3600);",
+        );
+        devices.settle();
+        assert_eq!(entries(&devices.a), entries(&devices.b));
+        devices
+    }
+
+    #[test]
+    fn edits_on_both_sides_reach_each_other() {
+        let mut devices = shared_entry();
+        execute(&devices.b, "UPDATE time_entries SET end_time = '2026-10-19 10:30:00', duration = 5400");
+        execute(
+            &devices.a,
+            "INSERT INTO projects (name, color) VALUES ('Billing', '#222');
+             INSERT INTO time_entries (project_id, start_time, end_time, duration)
+             SELECT id, '2026-10-19 11:00:00', '2026-10-19 11:20:00', 1200 FROM projects WHERE name = 'Billing';",
+        );
+        devices.settle();
+
+        let expected = vec![
+            ("Horalis".to_string(), "2026-10-19 09:00:00".to_string(), Some("2026-10-19 10:30:00".to_string()), Some(5400)),
+            ("Billing".to_string(), "2026-10-19 11:00:00".to_string(), Some("2026-10-19 11:20:00".to_string()), Some(1200)),
+        ];
+        assert_eq!(entries(&devices.a), expected);
+        assert_eq!(entries(&devices.b), expected);
+        assert_eq!(projects(&devices.b), ["Billing", "Horalis"]);
+        assert_eq!(count(&devices.a, "SELECT count(*) FROM sync_conflicts"), 0);
+        assert_eq!(count(&devices.b, "SELECT count(*) FROM sync_conflicts"), 0);
+    }
+
+    #[test]
+    fn concurrent_edits_converge_and_keep_the_loser() {
+        let mut devices = shared_entry();
+        execute(&devices.a, "UPDATE time_entries SET end_time = '2026-10-19 10:10:00', duration = 4200");
+        devices.sync_a();
+        std::thread::sleep(Duration::from_millis(5));
+        execute(&devices.b, "UPDATE time_entries SET end_time = '2026-10-19 10:50:00', duration = 6600");
+        // B's edit is newer and wins; A's is kept as a conflict on B
+        assert_eq!(devices.sync_b().conflicts, 1);
+        devices.settle();
+
+        assert_eq!(entries(&devices.a), entries(&devices.b));
+        assert_eq!(entries(&devices.a)[0].3, Some(6600));
+        let discarded: String = devices.b.query_row("SELECT discarded FROM sync_conflicts", [], |row| row.get(0)).unwrap();
+        assert!(discarded.contains("2026-10-19 10:10:00"));
+    }
+
+    #[test]
+    fn deleted_project_takes_its_entries_with_an_audit_record() {
+        let mut devices = shared_entry();
+        execute(&devices.a, "DELETE FROM time_entries; DELETE FROM projects;");
+        devices.sync_a();
+        // Added on B before it heard of the deletion
+        execute(
+            &devices.b,
+            "INSERT INTO time_entries (project_id, start_time, end_time, duration)
+             SELECT id, '2026-10-19 11:00:00', '2026-10-19 11:20:00', 1200 FROM projects;",
+        );
+        devices.sync_b();
+
+        assert!(projects(&devices.b).is_empty());
+        assert!(entries(&devices.b).is_empty());
+        let deleted: Vec<String> = devices
+            .b
+            .prepare("SELECT old_start_time FROM entry_history WHERE action = 'delete' ORDER BY old_start_time")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(deleted, ["2026-10-19 09:00:00", "2026-10-19 11:00:00"]);
+
+        devices.settle();
+        assert!(entries(&devices.a).is_empty());
+        assert!(projects(&devices.a).is_empty());
+    }
+
+    #[test]
+    fn deleted_project_with_submitted_entries_is_kept() {
+        let mut devices = shared_entry();
+        execute(&devices.b, "UPDATE time_entries SET locked_by = 1");
+        execute(&devices.a, "DELETE FROM time_entries; DELETE FROM projects;");
+        devices.sync_a();
+        // Both the entry's deletion and the project's are refused
+        assert_eq!(devices.sync_b().conflicts, 2);
+
+        assert_eq!(projects(&devices.b), ["Horalis"]);
+        assert_eq!(entries(&devices.b).len(), 1);
+        assert_eq!(count(&devices.b, "SELECT count(*) FROM entry_history WHERE action = 'delete'"), 0);
+    }
+
+    #[test]
+    fn entry_for_a_project_deleted_here_is_kept_as_a_conflict() {
+        let mut devices = shared_entry();
+        execute(&devices.b, "DELETE FROM time_entries; DELETE FROM projects;");
+        // Added on A before it heard of the deletion
+        execute(
+            &devices.a,
+            "INSERT INTO time_entries (project_id, start_time, end_time, duration)
+             VALUES (1, '2026-10-19 11:00:00', '2026-10-19 11:20:00', 1200);",
+        );
+        devices.sync_a();
+        assert_eq!(devices.sync_b().conflicts, 1);
+
+        assert!(entries(&devices.b).is_empty());
+        let (kept, discarded): (Option<String>, String) = devices
+            .b
+            .query_row("SELECT kept, discarded FROM sync_conflicts", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert_eq!(kept, None);
+        assert!(discarded.contains("2026-10-19 11:00:00"));
+        // Not tried again on the next sync
+        assert_eq!(devices.sync_b().conflicts, 0);
+        devices.settle();
+        assert!(entries(&devices.a).is_empty());
+        assert_eq!(count(&devices.b, "SELECT count(*) FROM sync_conflicts"), 1);
+    }
+
+    #[test]
+    fn later_edit_wins_even_when_synced_first() {
+        let mut devices = shared_entry();
+        execute(&devices.a, "UPDATE time_entries SET end_time = '2026-10-19 10:10:00', duration = 4200");
+        std::thread::sleep(Duration::from_millis(20));
+        execute(&devices.b, "UPDATE time_entries SET end_time = '2026-10-19 10:50:00', duration = 6600");
+        std::thread::sleep(Duration::from_millis(20));
+        // A edited first but syncs last
+        devices.sync_b();
+        devices.sync_a();
+        devices.settle();
+
+        assert_eq!(entries(&devices.a), entries(&devices.b));
+        assert_eq!(entries(&devices.a)[0].3, Some(6600));
+        let discarded: String = devices.a.query_row("SELECT discarded FROM sync_conflicts", [], |row| row.get(0)).unwrap();
+        assert!(discarded.contains("2026-10-19 10:10:00"));
+    }
+
+    #[test]
+    fn timer_started_last_keeps_running() {
+        let mut devices = shared_entry();
+        execute(
+            &devices.a,
+            "INSERT INTO time_entries (project_id, start_time) VALUES (1, '2026-10-19 13:00:00');",
+        );
+        devices.sync_a();
+        execute(
+            &devices.b,
+            "INSERT INTO time_entries (project_id, start_time) VALUES (1, '2026-10-19 13:30:00');",
+        );
+        devices.settle();
+
+        for conn in [&devices.a, &devices.b] {
+            let running = entries(conn).into_iter().filter(|e| e.2.is_none()).map(|e| e.1).collect::<Vec<_>>();
+            assert_eq!(running, ["2026-10-19 13:30:00"]);
+            let stopped = entries(conn).into_iter().find(|e| e.1 == "2026-10-19 13:00:00").unwrap();
+            assert_eq!((stopped.2.as_deref(), stopped.3), (Some("2026-10-19 13:30:00"), Some(1800)));
+        }
+        assert_eq!(entries(&devices.a), entries(&devices.b));
+    }
+
+    #[test]
+    fn timers_from_other_devices_are_told_apart() {
+        let mut devices = shared_entry();
+        execute(&devices.a, "INSERT INTO time_entries (project_id, start_time) VALUES (1, '2026-10-19 13:00:00');");
+        // Not synced yet: local on A
+        assert!(!changed_elsewhere(&devices.a, 2).unwrap());
+        devices.settle();
+
+        let running = |conn: &Connection| -> i64 { conn.query_row("SELECT id FROM time_entries WHERE end_time IS NULL", [], |row| row.get(0)).unwrap() };
+        assert!(!changed_elsewhere(&devices.a, running(&devices.a)).unwrap());
+        assert!(changed_elsewhere(&devices.b, running(&devices.b)).unwrap());
+    }
+}
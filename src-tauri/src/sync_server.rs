@@ -0,0 +1,253 @@
+// Sync through a server speaking the protocol in docs/sync-protocol.md, such
+// as the reference server in bin/horalis-sync-server.rs. This device's outbox
+// is uploaded from `server_seq` on and everything the server holds is read
+// from `server_cursor` on. Both live in sync_state and start over when
+// `sync_server_url` changes, so a new server gets the whole history.
+use rusqlite::Connection;
+use serde_json::{json, Value as Json};
+use std::time::Duration;
+
+use crate::sync::{self, Change, SyncSummary};
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+const PAGE_SIZE: usize = 500;
+
+#[derive(serde::Deserialize)]
+struct Page {
+    changes: Vec<Json>,
+    cursor: i64,
+    more: bool,
+}
+
+struct Server {
+    agent: ureq::Agent,
+    endpoint: String,
+    authorization: Option<String>,
+}
+
+impl Server {
+    fn new(url: &str, token: Option<&str>) -> Result<Self, String> {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err("The sync server URL must start with http:// or https://".into());
+        }
+        Ok(Server {
+            agent: ureq::AgentBuilder::new().timeout(TIMEOUT).build(),
+            endpoint: format!("{}/v1/changes", url.trim_end_matches('/')),
+            authorization: token.map(|t| format!("Bearer {}", t)),
+        })
+    }
+
+    fn send(&self, request: ureq::Request, body: Option<String>) -> Result<String, String> {
+        let request = match &self.authorization {
+            Some(authorization) => request.set("Authorization", authorization),
+            None => request,
+        };
+        let response = match body {
+            Some(body) => request.set("Content-Type", "application/json").send_string(&body),
+            None => request.call(),
+        };
+        match response {
+            Ok(response) => response.into_string().map_err(|e| e.to_string()),
+            Err(ureq::Error::Status(401, _)) => Err("The sync server refused the token".into()),
+            Err(ureq::Error::Status(code, response)) => {
+                let body: Json = response.into_string().ok().and_then(|b| serde_json::from_str(&b).ok()).unwrap_or_default();
+                match body["error"].as_str() {
+                    Some(error) => Err(format!("Sync server error {}: {}", code, error)),
+                    None => Err(format!("Sync server error {}", code)),
+                }
+            }
+            Err(e) => Err(format!("Sync server unreachable: {}", e)),
+        }
+    }
+
+    fn push(&self, changes: &[Change]) -> Result<(), String> {
+        let body = json!({ "changes": changes }).to_string();
+        self.send(self.agent.post(&self.endpoint), Some(body)).map(|_| ())
+    }
+
+    fn pull(&self, since: i64) -> Result<Page, String> {
+        let request = self
+            .agent
+            .get(&self.endpoint)
+            .query("since", &since.to_string())
+            .query("limit", &PAGE_SIZE.to_string());
+        let body = self.send(request, None)?;
+        serde_json::from_str(&body).map_err(|e| format!("Unexpected answer from the sync server: {}", e))
+    }
+}
+
+fn state_number(conn: &Connection, key: &str) -> Result<i64, String> {
+    Ok(sync::get_state(conn, key).map_err(|e| e.to_string())?.and_then(|v| v.parse().ok()).unwrap_or(0))
+}
+
+/// Exchange changes with the other devices through the server at `url`
+pub fn sync_server(conn: &mut Connection, url: &str, token: Option<&str>) -> Result<SyncSummary, String> {
+    let server = Server::new(url, token)?;
+    if sync::get_state(conn, "server_url").map_err(|e| e.to_string())?.as_deref() != Some(url) {
+        for (key, value) in [("server_url", url), ("server_seq", "0"), ("server_cursor", "0")] {
+            sync::set_state(conn, key, value).map_err(|e| e.to_string())?;
+        }
+    }
+    sync::record(conn)?;
+    let device = sync::device_id(conn).map_err(|e| e.to_string())?;
+    let mut summary = SyncSummary::default();
+
+    let changes = sync::outbox_since(conn, state_number(conn, "server_seq")?).map_err(|e| e.to_string())?;
+    for chunk in changes.chunks(PAGE_SIZE) {
+        server.push(chunk)?;
+        // Stored per chunk, so an interrupted upload resumes where it stopped
+        let last = chunk.last().map_or(0, |c| c.seq);
+        sync::set_state(conn, "server_seq", &last.to_string()).map_err(|e| e.to_string())?;
+        summary.sent += chunk.len();
+    }
+
+    let mut cursor = state_number(conn, "server_cursor")?;
+    loop {
+        let page = server.pull(cursor)?;
+        if page.cursor < cursor {
+            // The server lost its data: read it from the start and upload everything again next time
+            eprintln!("Sync server went back from {} to {}; starting over", cursor, page.cursor);
+            sync::set_state(conn, "server_seq", "0").map_err(|e| e.to_string())?;
+            sync::set_state(conn, "server_cursor", "0").map_err(|e| e.to_string())?;
+            cursor = 0;
+            continue;
+        }
+        let changes = page
+            .changes
+            .into_iter()
+            .filter_map(|change| match serde_json::from_value::<Change>(change) {
+                Ok(change) => Some(change),
+                Err(e) => {
+                    eprintln!("Skipping unreadable change from the sync server: {}", e);
+                    None
+                }
+            })
+            .filter(|change| change.version.device != device)
+            .collect();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let (received, conflicts) = sync::apply_changes(&tx, changes)?;
+        sync::set_state(&tx, "server_cursor", &page.cursor.to_string()).map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())?;
+        summary.received += received;
+        summary.conflicts += conflicts;
+        cursor = page.cursor;
+        if !page.more {
+            break;
+        }
+    }
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    sync::set_state(conn, "server_synced_at", &now).map_err(|e| e.to_string())?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use crate::sync_service::{self, Store};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    /// A reference server on a loopback port. The store can be swapped to
+    /// simulate a server that lost its data.
+    struct TestServer {
+        url: String,
+        store: Arc<Mutex<Store>>,
+        dir: tempfile::TempDir,
+    }
+
+    impl TestServer {
+        fn start(token: Option<&str>) -> Self {
+            let dir = tempfile::tempdir().unwrap();
+            let store = Arc::new(Mutex::new(Store::open(&dir.path().join("sync.jsonl")).unwrap()));
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            let (shared, token) = (store.clone(), token.map(str::to_string));
+            std::thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    sync_service::serve(stream, &mut shared.lock().unwrap(), token.as_deref());
+                }
+            });
+            TestServer { url, store, dir }
+        }
+
+        fn len(&self) -> usize {
+            self.store.lock().unwrap().len()
+        }
+
+        fn lose_data(&self) {
+            *self.store.lock().unwrap() = Store::open(&self.dir.path().join("empty.jsonl")).unwrap();
+        }
+    }
+
+    fn count(conn: &Connection, sql: &str) -> i64 {
+        conn.query_row(sql, [], |row| row.get(0)).unwrap()
+    }
+
+    /// A project and `entries` finished entries, one a day from 2026-01-01
+    fn tracked(entries: i64) -> Connection {
+        let conn = db::testing::memory();
+        conn.execute_batch(&format!(
+            "INSERT INTO projects (id, name, color) VALUES (1, 'Horalis', '#111');
+             WITH RECURSIVE n(i) AS (SELECT 0 UNION ALL SELECT i + 1 FROM n WHERE i + 1 < {})
+             INSERT INTO time_entries (project_id, start_time, end_time, duration)
+             SELECT 1, datetime('2026-01-01 09:00:00', '+' || i || ' days'), datetime('2026-01-01 10:00:00', '+' || i || ' days'), 3600 FROM n;",
+            entries
+        ))
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn devices_exchange_changes_through_the_server() {
+        let server = TestServer::start(None);
+        // More changes than fit on one page
+        let mut a = tracked(PAGE_SIZE as i64 + 20);
+        let mut b = db::testing::memory();
+
+        let summary = sync_server(&mut a, &server.url, None).unwrap();
+        assert_eq!((summary.sent, summary.received), (PAGE_SIZE + 21, 0));
+        assert_eq!(server.len(), PAGE_SIZE + 21);
+        let summary = sync_server(&mut b, &server.url, None).unwrap();
+        assert_eq!((summary.sent, summary.received), (0, PAGE_SIZE + 21));
+        assert_eq!(count(&b, "SELECT COUNT(*) FROM time_entries WHERE duration = 3600"), PAGE_SIZE as i64 + 20);
+
+        b.execute("UPDATE time_entries SET duration = 1800, end_time = datetime(start_time, '+30 minutes') WHERE start_time = '2026-01-01 09:00:00'", [])
+            .unwrap();
+        assert_eq!(sync_server(&mut b, &server.url, None).unwrap().sent, 1);
+        let summary = sync_server(&mut a, &server.url, None).unwrap();
+        assert_eq!((summary.sent, summary.received), (0, 1));
+        assert_eq!(count(&a, "SELECT duration FROM time_entries WHERE start_time = '2026-01-01 09:00:00'"), 1800);
+        // Nothing new either way
+        assert_eq!(sync_server(&mut b, &server.url, None).unwrap().received, 0);
+    }
+
+    #[test]
+    fn wrong_token_is_refused() {
+        let server = TestServer::start(Some("secret"));
+        let mut a = tracked(1);
+        assert_eq!(sync_server(&mut a, &server.url, Some("wrong")).err().unwrap(), "The sync server refused the token");
+        assert_eq!(sync_server(&mut a, &server.url, None).err().unwrap(), "The sync server refused the token");
+        assert_eq!(server.len(), 0);
+        assert_eq!(sync_server(&mut a, &server.url, Some("secret")).unwrap().sent, 2);
+    }
+
+    #[test]
+    fn client_starts_over_when_the_server_goes_back() {
+        let server = TestServer::start(None);
+        let mut a = tracked(3);
+        sync_server(&mut a, &server.url, None).unwrap();
+        assert_eq!(server.len(), 4);
+
+        server.lose_data();
+        // Noticed on the next pull; everything is uploaded again on the one after
+        sync_server(&mut a, &server.url, None).unwrap();
+        assert_eq!(state_number(&a, "server_cursor").unwrap(), 0);
+        assert_eq!(sync_server(&mut a, &server.url, None).unwrap().sent, 4);
+        assert_eq!(server.len(), 4);
+
+        let mut b = db::testing::memory();
+        assert_eq!(sync_server(&mut b, &server.url, None).unwrap().received, 4);
+        assert_eq!(count(&b, "SELECT COUNT(*) FROM time_entries"), 3);
+    }
+}
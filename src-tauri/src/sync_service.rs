@@ -0,0 +1,285 @@
+// The reference sync server of docs/sync-protocol.md, run by
+// bin/horalis-sync-server.rs. Stores changes from any number of devices in
+// memory and in an append-only file, and hands them out by cursor. Changes are
+// opaque apart from the device and sequence number used to drop duplicates.
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::time::Duration;
+
+const DEFAULT_LIMIT: usize = 500;
+const MAX_BODY: usize = 32 * 1024 * 1024;
+
+pub struct Store {
+    changes: Vec<Value>,
+    seen: HashSet<(String, i64)>,
+    file: File,
+}
+
+fn key(change: &Value) -> Option<(String, i64)> {
+    Some((change["version"]["device"].as_str()?.to_string(), change["seq"].as_i64()?))
+}
+
+impl Store {
+    pub fn open(path: &Path) -> std::io::Result<Store> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        let mut changes = Vec::new();
+        let mut seen = HashSet::new();
+        for line in text.lines() {
+            // A torn last line from a crash is dropped
+            let Ok(change) = serde_json::from_str::<Value>(line) else {
+                continue;
+            };
+            if key(&change).is_some_and(|k| seen.insert(k)) {
+                changes.push(change);
+            }
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        if !text.is_empty() && !text.ends_with('\n') {
+            file.write_all(b"\n")?;
+        }
+        Ok(Store { changes, seen, file })
+    }
+
+    /// Number of changes held
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Store new changes; returns how many were new
+    fn push(&mut self, changes: Vec<Value>) -> Result<usize, String> {
+        let mut lines = String::new();
+        let mut accepted = Vec::new();
+        for change in changes {
+            let k = key(&change).ok_or("Every change needs seq and version.device")?;
+            if self.seen.contains(&k) || accepted.iter().any(|(a, _)| *a == k) {
+                continue;
+            }
+            lines += &change.to_string();
+            lines.push('\n');
+            accepted.push((k, change));
+        }
+        // Written before anything is acknowledged
+        self.file.write_all(lines.as_bytes()).map_err(|e| e.to_string())?;
+        self.file.sync_data().map_err(|e| e.to_string())?;
+        let count = accepted.len();
+        for (k, change) in accepted {
+            self.seen.insert(k);
+            self.changes.push(change);
+        }
+        Ok(count)
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    authorization: Option<String>,
+    body: Vec<u8>,
+}
+
+fn read_request(stream: &mut TcpStream) -> Result<Request, String> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(|e| e.to_string())?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().ok_or("Empty request")?.to_string();
+    let target = parts.next().ok_or("No path")?.to_string();
+
+    let mut length = 0;
+    let mut authorization = None;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).map_err(|e| e.to_string())?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            match name.trim().to_ascii_lowercase().as_str() {
+                "content-length" => length = value.trim().parse().map_err(|_| "Bad Content-Length")?,
+                "authorization" => authorization = Some(value.trim().to_string()),
+                _ => {}
+            }
+        }
+    }
+    if length > MAX_BODY {
+        return Err("Request too large".into());
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+
+    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+    let query = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    Ok(Request { method, path: path.to_string(), query, authorization, body })
+}
+
+fn respond(stream: &mut TcpStream, status: u16, body: &Value) {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        _ => "Internal Server Error",
+    };
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes());
+}
+
+fn handle(store: &mut Store, token: Option<&str>, request: Request) -> (u16, Value) {
+    if let Some(token) = token {
+        if request.authorization.as_deref() != Some(&format!("Bearer {}", token)) {
+            return (401, json!({ "error": "Missing or wrong token" }));
+        }
+    }
+    let param = |name: &str| request.query.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str());
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/v1/changes") => {
+            let since = param("since").and_then(|v| v.parse::<usize>().ok()).unwrap_or(0).min(store.changes.len());
+            let limit = param("limit").and_then(|v| v.parse::<usize>().ok()).unwrap_or(DEFAULT_LIMIT).max(1);
+            let end = (since + limit).min(store.changes.len());
+            (200, json!({ "changes": &store.changes[since..end], "cursor": end, "more": end < store.changes.len() }))
+        }
+        ("POST", "/v1/changes") => {
+            let changes = match serde_json::from_slice::<Value>(&request.body) {
+                Ok(Value::Object(mut body)) => match body.remove("changes") {
+                    Some(Value::Array(changes)) => changes,
+                    _ => return (400, json!({ "error": "Expected {\"changes\": [...]}" })),
+                },
+                _ => return (400, json!({ "error": "Expected a JSON object" })),
+            };
+            match store.push(changes) {
+                Ok(accepted) => (200, json!({ "accepted": accepted, "cursor": store.changes.len() })),
+                Err(e) => (400, json!({ "error": e })),
+            }
+        }
+        _ => (404, json!({ "error": "Not found" })),
+    }
+}
+
+/// Answer one request on `stream`
+pub fn serve(mut stream: TcpStream, store: &mut Store, token: Option<&str>) {
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(30)));
+    match read_request(&mut stream) {
+        Ok(request) => {
+            let (status, body) = handle(store, token, request);
+            respond(&mut stream, status, &body);
+        }
+        Err(e) => respond(&mut stream, 400, &json!({ "error": e })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(device: &str, seq: i64) -> Value {
+        json!({ "table": "projects", "uid": format!("{}-{}", device, seq), "seq": seq, "version": { "device": device, "at": "2026-10-18 09:00:00" } })
+    }
+
+    fn request(method: &str, path: &str, query: &[(&str, &str)], token: Option<&str>, body: &Value) -> Request {
+        Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            query: query.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            authorization: token.map(|t| format!("Bearer {}", t)),
+            body: body.to_string().into_bytes(),
+        }
+    }
+
+    fn push(store: &mut Store, changes: Vec<Value>) -> (u16, Value) {
+        handle(store, None, request("POST", "/v1/changes", &[], None, &json!({ "changes": changes })))
+    }
+
+    fn pull(store: &mut Store, since: &str, limit: &str) -> (u16, Value) {
+        handle(store, None, request("GET", "/v1/changes", &[("since", since), ("limit", limit)], None, &Value::Null))
+    }
+
+    /// (device, seq) of the changes in a page
+    fn keys(page: &Value) -> Vec<(String, i64)> {
+        page["changes"].as_array().unwrap().iter().filter_map(key).collect()
+    }
+
+    #[test]
+    fn pushes_are_deduplicated_by_device_and_seq() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sync.jsonl");
+        let mut store = Store::open(&path).unwrap();
+        let (status, body) = push(&mut store, vec![change("a", 1), change("a", 2), change("a", 2), change("b", 1)]);
+        assert_eq!((status, body["accepted"].as_i64(), body["cursor"].as_i64()), (200, Some(3), Some(3)));
+        let (_, body) = push(&mut store, vec![change("a", 2), change("a", 3), change("b", 1)]);
+        assert_eq!(body["accepted"].as_i64(), Some(1));
+        assert_eq!(store.len(), 4);
+
+        // Kept across a restart, without a torn last line
+        std::fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(b"{\"seq\": 9, \"vers").unwrap();
+        let mut store = Store::open(&path).unwrap();
+        assert_eq!(store.len(), 4);
+        let (_, body) = push(&mut store, vec![change("a", 3), change("a", 4)]);
+        assert_eq!(body["accepted"].as_i64(), Some(1));
+        assert_eq!(Store::open(&path).unwrap().len(), 5);
+
+        let (status, body) = push(&mut store, vec![json!({ "seq": 5 })]);
+        assert_eq!((status, body["error"].as_str()), (400, Some("Every change needs seq and version.device")));
+    }
+
+    #[test]
+    fn pages_follow_the_cursor() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = Store::open(&dir.path().join("sync.jsonl")).unwrap();
+        push(&mut store, (1..=5).map(|seq| change("a", seq)).collect());
+
+        let (status, page) = pull(&mut store, "0", "2");
+        assert_eq!(status, 200);
+        assert_eq!(keys(&page), [("a".to_string(), 1), ("a".to_string(), 2)]);
+        assert_eq!((page["cursor"].as_i64(), page["more"].as_bool()), (Some(2), Some(true)));
+        let (_, page) = pull(&mut store, "4", "2");
+        assert_eq!(keys(&page), [("a".to_string(), 5)]);
+        assert_eq!((page["cursor"].as_i64(), page["more"].as_bool()), (Some(5), Some(false)));
+        // Past the end, e.g. a client of a server that lost its data
+        let (_, page) = pull(&mut store, "9", "2");
+        assert!(keys(&page).is_empty());
+        assert_eq!((page["cursor"].as_i64(), page["more"].as_bool()), (Some(5), Some(false)));
+        // A zero limit still makes progress
+        let (_, page) = pull(&mut store, "0", "0");
+        assert_eq!(keys(&page).len(), 1);
+    }
+
+    #[test]
+    fn requests_need_the_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = Store::open(&dir.path().join("sync.jsonl")).unwrap();
+        let get = |token| request("GET", "/v1/changes", &[], token, &Value::Null);
+        assert_eq!(handle(&mut store, Some("secret"), get(Some("wrong"))).0, 401);
+        assert_eq!(handle(&mut store, Some("secret"), get(None)).0, 401);
+        assert_eq!(handle(&mut store, Some("secret"), get(Some("secret"))).0, 200);
+        assert_eq!(handle(&mut store, None, get(None)).0, 200);
+
+        let (status, body) = handle(&mut store, None, request("POST", "/v1/changes", &[], None, &json!([])));
+        assert_eq!((status, body["error"].as_str()), (400, Some("Expected a JSON object")));
+        assert_eq!(handle(&mut store, None, request("GET", "/v2/changes", &[], None, &Value::Null)).0, 404);
+    }
+}
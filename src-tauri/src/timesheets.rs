@@ -0,0 +1,414 @@
+// Weekly timesheets. Submitting a week snapshots its totals per project and
+// day, writes a timesheet document and locks the week's entries. The journal
+// then refuses any change to a locked entry and any new entry inside the
+// submitted week (see `check_unlocked`) until the week is explicitly unlocked.
+// Submitting and unlocking are recorded in the audit trail and are not undoable.
+use chrono::{Datelike, Duration as ChronoDuration, NaiveDate};
+use rusqlite::{Connection, OptionalExtension};
+use serde_json::{Map, Value as Json};
+use std::path::Path;
+use tauri::Emitter;
+
+use crate::audit::{self, ChangeSource};
+use crate::db;
+use crate::entries;
+use crate::journal;
+use crate::periods::{Calendar, PeriodKind};
+use crate::render::{self, Format, Table};
+use crate::report::{self, Dimension, ReportQuery, ReportSpec};
+use crate::zones;
+
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct TimesheetRow {
+    project_id: i64,
+    project_name: String,
+    days: [i64; 7], // Seconds per day of the week
+    total: i64,
+}
+
+#[derive(serde::Serialize)]
+pub struct Timesheet {
+    id: i64,
+    start_date: String, // "YYYY-MM-DD", first day of the week
+    end_date: String,
+    submitted_at: String,
+    rows: Vec<TimesheetRow>,
+    total: i64,
+    entries: i64, // Entries locked by this timesheet
+    file_path: Option<String>,
+    unlocked_at: Option<String>,
+    unlock_reason: Option<String>,
+}
+
+const TIMESHEET_SELECT: &str = "
+    SELECT t.id, t.start_date, t.end_date, t.submitted_at, t.totals, t.total,
+           (SELECT COUNT(*) FROM time_entries te WHERE te.locked_by = t.id),
+           t.file_path, t.unlocked_at, t.unlock_reason
+    FROM timesheets t";
+
+fn read_timesheet(row: &rusqlite::Row) -> rusqlite::Result<Timesheet> {
+    let totals: String = row.get(4)?;
+    Ok(Timesheet {
+        id: row.get(0)?,
+        start_date: row.get(1)?,
+        end_date: row.get(2)?,
+        submitted_at: row.get(3)?,
+        rows: serde_json::from_str(&totals)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(4, rusqlite::types::Type::Text, Box::new(e)))?,
+        total: row.get(5)?,
+        entries: row.get(6)?,
+        file_path: row.get(7)?,
+        unlocked_at: row.get(8)?,
+        unlock_reason: row.get(9)?,
+    })
+}
+
+/// Refuse a change to a time entry that a submitted timesheet covers.
+/// `before` and `after` are full rows as snapshotted by the journal.
+pub fn check_unlocked(conn: &Connection, before: Option<&Map<String, Json>>, after: Option<&Map<String, Json>>) -> Result<(), String> {
+    let locked = |row: Option<&Map<String, Json>>| row.and_then(|r| r.get("locked_by")).is_some_and(|v| !v.is_null());
+    if locked(before) || locked(after) {
+        return Err("This entry is part of a submitted timesheet. Unlock the week to change it.".into());
+    }
+    let Some(start) = after.and_then(|r| r.get("start_time")).and_then(Json::as_str) else {
+        return Ok(());
+    };
+    let start = entries::parse_db_time(start)?;
+    let submitted: Option<String> = conn
+        .query_row(
+            "SELECT start_date FROM timesheets
+             WHERE unlocked_at IS NULL AND start_at <= datetime(?1, 'unixepoch') AND end_at > datetime(?1, 'unixepoch')",
+            [start],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    match submitted {
+        Some(week) => Err(format!("The week of {} has been submitted. Unlock it to add time.", week)),
+        None => Ok(()),
+    }
+}
+
+fn timesheet_table(rows: &[TimesheetRow], first_day: NaiveDate, format: Format) -> Table {
+    let duration = render::duration_cell(format);
+    let mut headers = vec!["Project".to_string()];
+    headers.extend((0..7).map(|i| (first_day + ChronoDuration::days(i)).format("%a %-d").to_string()));
+    headers.push("Total".to_string());
+    let mut total = vec!["Total".to_string()];
+    total.extend((0..7).map(|day| duration(rows.iter().map(|r| r.days[day]).sum())));
+    total.push(duration(rows.iter().map(|r| r.total).sum()));
+    Table {
+        headers,
+        rows: rows
+            .iter()
+            .map(|row| {
+                let mut cells = vec![row.project_name.clone()];
+                cells.extend(row.days.iter().map(|&seconds| duration(seconds)));
+                cells.push(duration(row.total));
+                cells
+            })
+            .collect(),
+        total,
+        label_columns: 1,
+    }
+}
+
+/// Submit the week containing `date` ("YYYY-MM-DD"): snapshot its totals,
+/// write the timesheet to `folder` and lock its entries
+#[tauri::command]
+pub fn submit_week(app: tauri::AppHandle, date: String, folder: String, format: Format) -> Result<Timesheet, String> {
+    if !Path::new(&folder).is_absolute() {
+        return Err("Choose a full folder path".into());
+    }
+    let mut conn = db::open().ok_or("Database not found")?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let timesheet = submit(&tx, zones::parse_date(&date)?, Path::new(&folder), format)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    let _ = app.emit("entries-changed", ());
+    Ok(timesheet)
+}
+
+/// File name for a week: the ISO week of the Monday within it, so weeks
+/// starting on another day keep the number most calendars show for them
+fn file_name(first_day: NaiveDate, format: Format) -> String {
+    let monday = first_day + ChronoDuration::days((7 - first_day.weekday().num_days_from_monday() as i64) % 7);
+    format!("timesheet-{}-W{:02}.{}", monday.iso_week().year(), monday.iso_week().week(), format.extension())
+}
+
+fn submit(conn: &Connection, date: NaiveDate, folder: &Path, format: Format) -> Result<Timesheet, String> {
+    let calendar = Calendar::from_settings(conn);
+    let (first_day, last_day) = calendar.bounds(PeriodKind::Week, date);
+    let (start_date, end_date) = (first_day.format("%Y-%m-%d").to_string(), last_day.format("%Y-%m-%d").to_string());
+    let already: Option<i64> = conn
+        .query_row("SELECT id FROM timesheets WHERE start_date = ?1 AND unlocked_at IS NULL", [&start_date], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if already.is_some() {
+        return Err(format!("The week of {} has already been submitted", start_date));
+    }
+
+    // Entries of the week, by their start day in the report zone
+    let zone = zones::report_zone(conn);
+    let (from, to) = zones::query_bounds(first_day, last_day);
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, end_time IS NULL, CAST(strftime('%s', start_time) AS INTEGER), timezone
+             FROM time_entries
+             WHERE start_time >= datetime(?1, 'unixepoch') AND start_time < datetime(?2, 'unixepoch')",
+        )
+        .map_err(|e| e.to_string())?;
+    let week_entries: Vec<(i64, bool)> = stmt
+        .query_map([from, to], |row| {
+            let day = zone.date_of(row.get(2)?, row.get::<_, Option<String>>(3)?.as_deref());
+            Ok((row.get(0)?, row.get(1)?, day))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<(i64, bool, NaiveDate)>, _>>()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|(_, _, day)| *day >= first_day && *day <= last_day)
+        .map(|(id, running, _)| (id, running))
+        .collect();
+    drop(stmt);
+    if week_entries.iter().any(|(_, running)| *running) {
+        return Err("Stop the timer before submitting this week".into());
+    }
+
+    let query = ReportQuery {
+        start_date: start_date.clone(),
+        end_date: end_date.clone(),
+        spec: ReportSpec { group_by: vec![Dimension::Project, Dimension::Day], ..ReportSpec::default() },
+    };
+    let report = report::run(conn, &query)?;
+    let mut rows: Vec<TimesheetRow> = Vec::new();
+    for row in &report.rows {
+        let (Some(project_id), Some(day)) = (row.project_id, row.day.as_deref()) else {
+            continue;
+        };
+        let index = (zones::parse_date(day)? - first_day).num_days() as usize;
+        if rows.last().is_none_or(|r| r.project_id != project_id) {
+            rows.push(TimesheetRow {
+                project_id,
+                project_name: row.project_name.clone().unwrap_or_default(),
+                days: [0; 7],
+                total: 0,
+            });
+        }
+        let sheet_row = rows.last_mut().unwrap();
+        sheet_row.days[index] += row.duration;
+        sheet_row.total += row.duration;
+    }
+    let total: i64 = rows.iter().map(|r| r.total).sum();
+
+    let tz = zone.tz();
+    conn.execute(
+        "INSERT INTO timesheets (start_date, end_date, start_at, end_at, submitted_at, totals, total)
+         VALUES (?1, ?2, datetime(?3, 'unixepoch'), datetime(?4, 'unixepoch'), datetime('now'), ?5, ?6)",
+        rusqlite::params![
+            start_date,
+            end_date,
+            zones::day_start(first_day, tz),
+            zones::day_start(last_day + ChronoDuration::days(1), tz),
+            serde_json::to_string(&rows).map_err(|e| e.to_string())?,
+            total,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    let id = conn.last_insert_rowid();
+    let label = format!("Submitted timesheet for {}", calendar.label(PeriodKind::Week, first_day, last_day));
+    for (entry_id, _) in &week_entries {
+        let before = journal::snapshot(conn, "time_entries", *entry_id).map_err(|e| e.to_string())?;
+        conn.execute("UPDATE time_entries SET locked_by = ?1 WHERE id = ?2", [id, *entry_id])
+            .map_err(|e| e.to_string())?;
+        let after = journal::snapshot(conn, "time_entries", *entry_id).map_err(|e| e.to_string())?;
+        audit::record(conn, *entry_id, before.as_ref(), after.as_ref(), ChangeSource::Ui, &label).map_err(|e| e.to_string())?;
+    }
+
+    let submitted_at: String = conn
+        .query_row("SELECT submitted_at FROM timesheets WHERE id = ?1", [id], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    let notes = vec![
+        format!("Week: {}", calendar.label(PeriodKind::Week, first_day, last_day)),
+        format!("Submitted: {} UTC", submitted_at),
+        String::new(),
+        "Approved by: ______________________    Date: ____________".to_string(),
+    ];
+    let contents = render::render_table("Timesheet", &notes, &timesheet_table(&rows, first_day, format), format);
+    std::fs::create_dir_all(folder).map_err(|e| format!("Failed to create {}: {}", folder.display(), e))?;
+    let path = folder.join(file_name(first_day, format));
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    conn.execute("UPDATE timesheets SET file_path = ?1 WHERE id = ?2", rusqlite::params![path.display().to_string(), id])
+        .map_err(|e| e.to_string())?;
+    conn.query_row(&format!("{} WHERE t.id = ?1", TIMESHEET_SELECT), [id], read_timesheet)
+        .map_err(|e| e.to_string())
+}
+
+/// Unlock a submitted week so its entries can be edited again. The reason is
+/// kept with the timesheet and in every entry's history.
+#[tauri::command]
+pub fn unlock_week(app: tauri::AppHandle, id: i64, reason: String) -> Result<(), String> {
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err("Give a reason for unlocking the week".into());
+    }
+    let mut conn = db::open().ok_or("Database not found")?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    unlock(&tx, id, reason)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    let _ = app.emit("entries-changed", ());
+    Ok(())
+}
+
+fn unlock(conn: &Connection, id: i64, reason: &str) -> Result<(), String> {
+    let unlocked = conn
+        .execute(
+            "UPDATE timesheets SET unlocked_at = datetime('now'), unlock_reason = ?1 WHERE id = ?2 AND unlocked_at IS NULL",
+            rusqlite::params![reason, id],
+        )
+        .map_err(|e| e.to_string())?;
+    if unlocked == 0 {
+        return Err("This timesheet is not locked".into());
+    }
+    let entry_ids: Vec<i64> = conn
+        .prepare("SELECT id FROM time_entries WHERE locked_by = ?1")
+        .and_then(|mut stmt| stmt.query_map([id], |row| row.get(0))?.collect())
+        .map_err(|e| e.to_string())?;
+    let label = format!("Unlocked timesheet: {}", reason);
+    for entry_id in entry_ids {
+        let before = journal::snapshot(conn, "time_entries", entry_id).map_err(|e| e.to_string())?;
+        conn.execute("UPDATE time_entries SET locked_by = NULL WHERE id = ?1", [entry_id])
+            .map_err(|e| e.to_string())?;
+        let after = journal::snapshot(conn, "time_entries", entry_id).map_err(|e| e.to_string())?;
+        audit::record(conn, entry_id, before.as_ref(), after.as_ref(), ChangeSource::Ui, &label).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Every submitted timesheet, newest week first
+#[tauri::command]
+pub fn list_timesheets() -> Result<Vec<Timesheet>, String> {
+    let conn = db::open().ok_or("Database not found")?;
+    let mut stmt = conn
+        .prepare(&format!("{} ORDER BY t.start_date DESC, t.id DESC", TIMESHEET_SELECT))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([], read_timesheet).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::Recorder;
+
+    fn setup(week_start: u32) -> Connection {
+        let conn = db::testing::memory();
+        conn.execute_batch(&format!(
+            "INSERT INTO settings (key, value) VALUES ('report_timezone', 'UTC'), ('week_start', '{}');
+             INSERT INTO projects (id, name, color) VALUES (1, 'Horalis', '#111'), (2, 'Billing', '#222');
+             INSERT INTO time_entries (id, project_id, start_time, end_time, duration) VALUES
+                 (1, 1, '2026-10-12 09:00:00', '2026-10-12 11:00:00', 7200),
+                 (2, 2, '2026-10-14 13:00:00', '2026-10-14 14:00:00', 3600),
+                 (3, 1, '2026-10-18 10:00:00', '2026-10-18 10:30:00', 1800),
+                 (4, 1, '2026-10-19 09:00:00', '2026-10-19 10:00:00', 3600);",
+            week_start
+        ))
+        .unwrap();
+        conn
+    }
+
+    fn date(text: &str) -> NaiveDate {
+        zones::parse_date(text).unwrap()
+    }
+
+    fn locked_by(conn: &Connection, id: i64) -> Option<i64> {
+        conn.query_row("SELECT locked_by FROM time_entries WHERE id = ?1", [id], |row| row.get(0)).unwrap()
+    }
+
+    fn edit(conn: &Connection, id: i64) -> Result<(), String> {
+        let mut journal = Recorder::new("Edit entry");
+        journal.track(conn, "time_entries", id).map_err(|e| e.to_string())?;
+        conn.execute("UPDATE time_entries SET project_id = 2 WHERE id = ?1", [id]).map_err(|e| e.to_string())?;
+        journal.finish(conn).map(|_| ()).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn submit_locks_the_week_and_writes_the_sheet() {
+        let conn = setup(1);
+        let folder = tempfile::tempdir().unwrap();
+        let sheet = submit(&conn, date("2026-10-15"), folder.path(), Format::Markdown).unwrap();
+        assert_eq!((sheet.start_date.as_str(), sheet.end_date.as_str()), ("2026-10-12", "2026-10-18"));
+        assert_eq!((sheet.entries, sheet.total), (3, 7200 + 3600 + 1800));
+        let days: Vec<(i64, [i64; 7])> = sheet.rows.iter().map(|r| (r.project_id, r.days)).collect();
+        assert_eq!(days, vec![(1, [7200, 0, 0, 0, 0, 0, 1800]), (2, [0, 0, 3600, 0, 0, 0, 0])]);
+        assert_eq!([1, 2, 3, 4].map(|id| locked_by(&conn, id)), [Some(sheet.id), Some(sheet.id), Some(sheet.id), None]);
+
+        let path = folder.path().join("timesheet-2026-W42.md");
+        assert_eq!(sheet.file_path.as_deref(), Some(path.display().to_string().as_str()));
+        assert!(std::fs::read_to_string(&path).unwrap().contains("Horalis"));
+        let history: i64 = conn
+            .query_row("SELECT COUNT(*) FROM entry_history WHERE label LIKE 'Submitted timesheet%'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(history, 3);
+
+        let again = submit(&conn, date("2026-10-12"), folder.path(), Format::Markdown);
+        assert_eq!(again.err().unwrap(), "The week of 2026-10-12 has already been submitted");
+    }
+
+    #[test]
+    fn submit_refuses_a_running_timer() {
+        let conn = setup(1);
+        conn.execute("INSERT INTO time_entries (project_id, start_time) VALUES (1, '2026-10-13 09:00:00')", []).unwrap();
+        let folder = tempfile::tempdir().unwrap();
+        let result = submit(&conn, date("2026-10-13"), folder.path(), Format::Markdown);
+        assert_eq!(result.err().unwrap(), "Stop the timer before submitting this week");
+        assert_eq!(locked_by(&conn, 1), None);
+    }
+
+    #[test]
+    fn locked_weeks_refuse_changes_until_unlocked() {
+        let conn = setup(1);
+        let folder = tempfile::tempdir().unwrap();
+        let sheet = submit(&conn, date("2026-10-12"), folder.path(), Format::Markdown).unwrap();
+
+        let refused = edit(&conn, 1).unwrap_err();
+        assert!(refused.contains("part of a submitted timesheet"), "{}", refused);
+        edit(&conn, 4).unwrap();
+        // A new entry inside the submitted week
+        let mut row = journal::snapshot(&conn, "time_entries", 4).unwrap().unwrap();
+        row.insert("start_time".into(), Json::from("2026-10-16 09:00:00"));
+        assert_eq!(check_unlocked(&conn, None, Some(&row)).unwrap_err(), "The week of 2026-10-12 has been submitted. Unlock it to add time.");
+        row.insert("start_time".into(), Json::from("2026-10-19 00:00:00"));
+        check_unlocked(&conn, None, Some(&row)).unwrap();
+
+        unlock(&conn, sheet.id, "Forgot a meeting").unwrap();
+        assert_eq!([1, 2, 3].map(|id| locked_by(&conn, id)), [None, None, None]);
+        let reason: Option<String> = conn
+            .query_row("SELECT unlock_reason FROM timesheets WHERE id = ?1", [sheet.id], |row| row.get(0))
+            .unwrap();
+        assert_eq!(reason.as_deref(), Some("Forgot a meeting"));
+        edit(&conn, 1).unwrap();
+        row.insert("start_time".into(), Json::from("2026-10-16 09:00:00"));
+        check_unlocked(&conn, None, Some(&row)).unwrap();
+        assert_eq!(unlock(&conn, sheet.id, "Again").unwrap_err(), "This timesheet is not locked");
+
+        // The week can be submitted again once unlocked
+        let again = submit(&conn, date("2026-10-12"), folder.path(), Format::Markdown).unwrap();
+        assert_eq!(locked_by(&conn, 1), Some(again.id));
+    }
+
+    #[test]
+    fn file_names_follow_the_monday_of_the_week() {
+        let conn = setup(0);
+        let folder = tempfile::tempdir().unwrap();
+        // Sunday 2026-10-18 to Saturday 2026-10-24, ISO week 43
+        let sheet = submit(&conn, date("2026-10-21"), folder.path(), Format::Csv).unwrap();
+        assert_eq!(sheet.start_date, "2026-10-18");
+        assert!(sheet.file_path.unwrap().ends_with("timesheet-2026-W43.csv"));
+        assert_eq!(sheet.entries, 2);
+
+        assert_eq!(file_name(date("2026-10-17"), Format::Markdown), "timesheet-2026-W43.md");
+        assert_eq!(file_name(date("2026-10-19"), Format::Markdown), "timesheet-2026-W43.md");
+        assert_eq!(file_name(date("2026-12-27"), Format::Html), "timesheet-2026-W53.html");
+        assert_eq!(file_name(date("2027-01-03"), Format::Html), "timesheet-2027-W01.html");
+    }
+}
@@ -0,0 +1,77 @@
+// Untracked activity: notice when the user is working without a timer and
+// offer to turn that period into a time entry once tracking starts
+use tauri::{Emitter, Manager};
+
+use crate::audit::ChangeSource;
+use crate::ReminderState;
+
+// Shorter stretches are not worth offering
+const MIN_BACKFILL_SECS: u64 = 5 * 60;
+
+/// Update the untracked-activity marker from the reminder loop. Returns true
+/// when the user is currently active without a running timer.
+pub fn note_activity(app: &tauri::AppHandle, now_secs: u64, timer_running: bool, idle_secs: Option<u64>, active_threshold_secs: u64) -> bool {
+    let reminder_state = app.state::<ReminderState>();
+    let mut since = reminder_state.untracked_active_since.lock().unwrap();
+
+    if timer_running {
+        *since = None;
+        return false;
+    }
+    // Without idle information assume the user is present
+    let active = idle_secs.map(|idle| idle < active_threshold_secs).unwrap_or(true);
+    if active {
+        since.get_or_insert(now_secs.saturating_sub(idle_secs.unwrap_or(0)));
+    } else {
+        *since = None;
+    }
+    active
+}
+
+/// Called when a timer starts at `started_at_secs`: remember the untracked
+/// stretch before it and offer to add it as a time entry
+pub fn offer_backfill(app: &tauri::AppHandle, started_at_secs: u64) {
+    let reminder_state = app.state::<ReminderState>();
+    let Some(since) = reminder_state.untracked_active_since.lock().unwrap().take() else {
+        return;
+    };
+    if started_at_secs < since + MIN_BACKFILL_SECS {
+        return;
+    }
+    *reminder_state.pending_backfill.lock().unwrap() = Some((since, started_at_secs));
+
+    let minutes = (started_at_secs - since) / 60;
+    let _ = app.emit("untracked-activity", started_at_secs - since);
+    crate::notify::show_backfill_offer(app, minutes);
+}
+
+/// Add the pending untracked period as a time entry for the project just started
+#[tauri::command]
+pub fn backfill_untracked_time(app: tauri::AppHandle, source: Option<ChangeSource>) -> Result<Option<i64>, String> {
+    let reminder_state = app.state::<ReminderState>();
+    let Some((since, until)) = reminder_state.pending_backfill.lock().unwrap().take() else {
+        return Ok(None);
+    };
+
+    let conn = crate::db::open().ok_or("Database not found")?;
+    // The entry that was just started, even if it has been stopped since
+    let project = crate::db::last_used_project(&conn).ok_or("No project to add the time to")?;
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    // Time may have been added by hand since the offer was made
+    crate::entries::validate(&tx, &[], since as i64, Some(until as i64))?;
+    let entry_id = crate::entries::insert_entry(&tx, project.id, since as i64, until as i64, None).map_err(|e| e.to_string())?;
+    let mut journal = crate::journal::Recorder::new("Add untracked time").source(source.unwrap_or_default());
+    journal.track_new("time_entries", entry_id);
+    journal.finish(&tx).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    let _ = app.emit("entries-changed", entry_id);
+    Ok(Some(entry_id))
+}
+
+/// Forget the pending untracked period
+#[tauri::command]
+pub fn dismiss_untracked_time(app: tauri::AppHandle) {
+    let reminder_state = app.state::<ReminderState>();
+    *reminder_state.pending_backfill.lock().unwrap() = None;
+}
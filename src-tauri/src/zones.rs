@@ -0,0 +1,189 @@
+// Time zones. Entries are stored as UTC instants together with the IANA zone
+// they were recorded in; calendar days for reports are worked out here, in
+// the zone chosen under the `report_timezone` setting, so travel and DST
+// changes don't move time to the wrong day. The setting is empty for the
+// system zone, "entry" for the zone each entry was recorded in, or an IANA name.
+use chrono::{Duration as ChronoDuration, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use rusqlite::Connection;
+
+use crate::settings::Settings;
+
+// Longest DST gap we step over when midnight does not exist
+const MAX_GAP_MINUTES: i64 = 180;
+
+#[derive(serde::Serialize)]
+pub struct TimeZones {
+    system: String,
+    zones: Vec<String>,
+}
+
+/// IANA name of the zone the system is in, UTC if it can't be determined
+pub fn system_zone() -> Tz {
+    iana_time_zone::get_timezone()
+        .ok()
+        .and_then(|name| name.parse().ok())
+        .unwrap_or(Tz::UTC)
+}
+
+/// Zone that calendar days are counted in for reports and totals
+#[derive(Clone, Copy)]
+pub enum ReportZone {
+    Fixed(Tz),
+    /// Each entry counts on its date in the zone it was recorded in
+    PerEntry,
+}
+
+impl ReportZone {
+    /// Calendar date of an entry starting at `secs`, recorded in `entry_zone`
+    pub fn date_of(self, secs: i64, entry_zone: Option<&str>) -> NaiveDate {
+        match self {
+            ReportZone::Fixed(tz) => local_date(secs, tz),
+            ReportZone::PerEntry => local_date(secs, self::entry_zone(entry_zone)),
+        }
+    }
+
+    /// Zone for everything that isn't tied to an entry, like the days of a range
+    pub fn tz(self) -> Tz {
+        match self {
+            ReportZone::Fixed(tz) => tz,
+            ReportZone::PerEntry => system_zone(),
+        }
+    }
+}
+
+pub fn report_zone(conn: &Connection) -> ReportZone {
+    match Settings::load(conn).report_timezone.as_str() {
+        "entry" => ReportZone::PerEntry,
+        "" => ReportZone::Fixed(system_zone()),
+        name => ReportZone::Fixed(name.parse().unwrap_or_else(|_| system_zone())),
+    }
+}
+
+/// Zone an entry was recorded in; entries from before zones were stored fall
+/// back to the system zone
+pub fn entry_zone(name: Option<&str>) -> Tz {
+    name.and_then(|name| name.parse().ok()).unwrap_or_else(system_zone)
+}
+
+/// Calendar date of a Unix timestamp in `tz`
+pub fn local_date(secs: i64, tz: Tz) -> NaiveDate {
+    Utc.timestamp_opt(secs, 0).single().unwrap_or_default().with_timezone(&tz).date_naive()
+}
+
+/// First instant of a calendar day in `tz`. Where midnight is skipped by a DST
+/// change the day starts at the first minute that exists.
+pub fn day_start(date: NaiveDate, tz: Tz) -> i64 {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap();
+    (0..=MAX_GAP_MINUTES)
+        .find_map(|minutes| tz.from_local_datetime(&(midnight + ChronoDuration::minutes(minutes))).earliest())
+        .map(|t| t.timestamp())
+        .unwrap_or_else(|| Utc.from_utc_datetime(&midnight).timestamp())
+}
+
+/// Start and end of a calendar day in Unix seconds; 23 or 25 hours long on DST changes
+pub fn day_bounds(date: NaiveDate, tz: Tz) -> (i64, i64) {
+    (day_start(date, tz), day_start(date + ChronoDuration::days(1), tz))
+}
+
+pub fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| format!("Invalid date {}: {}", date, e))
+}
+
+/// Unix seconds from the start of `start_date` to the end of `end_date`, wide
+/// enough to hold every entry that falls on those days in any zone. Callers
+/// narrow it down with `ReportZone::date_of`.
+pub fn query_bounds(start_date: NaiveDate, end_date: NaiveDate) -> (i64, i64) {
+    let (start, _) = day_bounds(start_date, Tz::UTC);
+    let (_, end) = day_bounds(end_date, Tz::UTC);
+    // UTC offsets range from -12:00 to +14:00
+    (start - 14 * 3600, end + 12 * 3600)
+}
+
+/// The system zone and every zone that can be chosen for reports
+#[tauri::command]
+pub fn list_timezones() -> TimeZones {
+    TimeZones {
+        system: system_zone().name().to_string(),
+        zones: chrono_tz::TZ_VARIANTS.iter().map(|tz| tz.name().to_string()).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        parse_date(text).unwrap()
+    }
+
+    fn secs(text: &str) -> i64 {
+        chrono::NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S").unwrap().and_utc().timestamp()
+    }
+
+    #[test]
+    fn berlin_spring_forward_day_has_23_hours() {
+        let (start, end) = day_bounds(date("2026-03-29"), chrono_tz::Europe::Berlin);
+        assert_eq!(start, secs("2026-03-28 23:00:00"));
+        assert_eq!(end, secs("2026-03-29 22:00:00"));
+        assert_eq!(end - start, 23 * 3600);
+    }
+
+    #[test]
+    fn berlin_fall_back_day_has_25_hours() {
+        let (start, end) = day_bounds(date("2026-10-25"), chrono_tz::Europe::Berlin);
+        assert_eq!(start, secs("2026-10-24 22:00:00"));
+        assert_eq!(end, secs("2026-10-25 23:00:00"));
+        assert_eq!(end - start, 25 * 3600);
+        // Both 02:30s of the repeated hour fall on the same day
+        let tz = chrono_tz::Europe::Berlin;
+        assert_eq!(local_date(secs("2026-10-25 00:30:00"), tz), date("2026-10-25"));
+        assert_eq!(local_date(secs("2026-10-25 01:30:00"), tz), date("2026-10-25"));
+    }
+
+    #[test]
+    fn day_starts_at_first_minute_when_midnight_is_skipped() {
+        // Chile moves from 00:00 to 01:00 on the first Sunday of September
+        let tz = chrono_tz::America::Santiago;
+        let start = day_start(date("2026-09-06"), tz);
+        assert_eq!(Utc.timestamp_opt(start, 0).unwrap().with_timezone(&tz).format("%H:%M").to_string(), "01:00");
+    }
+
+    #[test]
+    fn per_entry_zone_counts_on_the_recorded_date() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+             INSERT INTO settings (key, value) VALUES ('report_timezone', 'entry');",
+        )
+        .unwrap();
+        let zone = report_zone(&conn);
+        assert!(matches!(zone, ReportZone::PerEntry));
+
+        // Monday 08:30 in Tokyo is still Sunday in UTC and New York
+        let start = secs("2026-10-18 23:30:00");
+        assert_eq!(zone.date_of(start, Some("Asia/Tokyo")), date("2026-10-19"));
+        assert_eq!(zone.date_of(start, Some("America/New_York")), date("2026-10-18"));
+        assert_eq!(ReportZone::Fixed(Tz::UTC).date_of(start, Some("Asia/Tokyo")), date("2026-10-18"));
+
+        // The query range for that Monday still reaches the entry
+        let (from, to) = query_bounds(date("2026-10-19"), date("2026-10-19"));
+        assert!(from <= start && start < to);
+    }
+
+    #[test]
+    fn query_bounds_cover_every_zone() {
+        let day = date("2026-10-25");
+        let (from, to) = query_bounds(day, day);
+        for tz in [chrono_tz::Pacific::Kiritimati, chrono_tz::Etc::GMTPlus12, chrono_tz::Europe::Berlin] {
+            let (start, end) = day_bounds(day, tz);
+            assert!(from <= start && end <= to, "{}", tz.name());
+        }
+    }
+
+    #[test]
+    fn unknown_entry_zone_falls_back_to_system_zone() {
+        assert_eq!(entry_zone(Some("Nowhere/Special")), system_zone());
+        assert_eq!(entry_zone(None), system_zone());
+    }
+}
@@ -1,6 +1,7 @@
 import { HashRouter, Routes, Route } from "react-router-dom";
 import FloatingTimer from "./components/FloatingTimer";
 import Dashboard from "./components/Dashboard";
+import LockGate from "./components/LockGate";
 import "./App.css";
 
 function App() {
@@ -11,11 +12,20 @@ function App() {
           path="/"
           element={
             <div className="timer-window w-full h-full p-1">
-              <FloatingTimer />
+              <LockGate compact>
+                <FloatingTimer />
+              </LockGate>
             </div>
           }
         />
-        <Route path="/dashboard" element={<Dashboard />} />
+        <Route
+          path="/dashboard"
+          element={
+            <LockGate>
+              <Dashboard />
+            </LockGate>
+          }
+        />
       </Routes>
     </HashRouter>
   );
@@ -1,19 +1,77 @@
-import { useState } from "react";
-import { Clock, BarChart3, Settings, History } from "lucide-react";
+import { useEffect, useState } from "react";
+import { Clock, BarChart3, Settings, History, Activity, Undo2, Redo2 } from "lucide-react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { JournalState } from "../lib/db";
 import HistoryPage from "./pages/HistoryPage";
 import ReportsPage from "./pages/ReportsPage";
+import ActivityPage from "./pages/ActivityPage";
 import SettingsPage from "./pages/SettingsPage";
 
-type Page = "history" | "reports" | "settings";
+type Page = "history" | "activity" | "reports" | "settings";
 
 const navItems: { id: Page; label: string; icon: typeof Clock }[] = [
   { id: "history", label: "History", icon: History },
+  { id: "activity", label: "Activity", icon: Activity },
   { id: "reports", label: "Reports", icon: BarChart3 },
   { id: "settings", label: "Settings", icon: Settings },
 ];
 
 export default function Dashboard() {
   const [activePage, setActivePage] = useState<Page>("history");
+  const [journal, setJournal] = useState<JournalState>({ undo: null, redo: null });
+  const [message, setMessage] = useState<string | null>(null);
+  // Remounts the page after undo/redo so it reloads its data
+  const [pageKey, setPageKey] = useState(0);
+
+  useEffect(() => {
+    loadJournalState();
+    const unlisten = listen("entries-changed", () => loadJournalState());
+    window.addEventListener("focus", loadJournalState);
+
+    function handleKeyDown(e: KeyboardEvent) {
+      const target = e.target as HTMLElement;
+      if (target.tagName === "INPUT" || target.tagName === "TEXTAREA" || target.tagName === "SELECT") return;
+      if (!(e.ctrlKey || e.metaKey)) return;
+      const key = e.key.toLowerCase();
+      if (key === "z" && !e.shiftKey) {
+        e.preventDefault();
+        handleJournal("undo");
+      } else if ((key === "z" && e.shiftKey) || key === "y") {
+        e.preventDefault();
+        handleJournal("redo");
+      }
+    }
+    window.addEventListener("keydown", handleKeyDown);
+
+    return () => {
+      unlisten.then((fn) => fn());
+      window.removeEventListener("focus", loadJournalState);
+      window.removeEventListener("keydown", handleKeyDown);
+    };
+  }, []);
+
+  async function loadJournalState() {
+    try {
+      setJournal(await invoke<JournalState>("get_journal_state"));
+    } catch (error) {
+      console.error("Failed to load undo history:", error);
+    }
+  }
+
+  async function handleJournal(command: "undo" | "redo") {
+    try {
+      const label = await invoke<string | null>(command);
+      if (label) {
+        setMessage(`${command === "undo" ? "Undid" : "Redid"}: ${label}`);
+        setPageKey((key) => key + 1);
+      }
+    } catch (error) {
+      setMessage(String(error));
+    }
+    await loadJournalState();
+    setTimeout(() => setMessage(null), 3000);
+  }
 
   return (
     <div className="flex h-screen bg-[#1a1a1a] text-white">
@@ -49,11 +107,37 @@ export default function Dashboard() {
             })}
           </ul>
         </nav>
+
+        {/* Undo / redo */}
+        <div className="p-3 border-t border-white/10">
+          {message && <div className="text-xs text-gray-400 mb-2 px-1">{message}</div>}
+          <div className="flex gap-2">
+            <button
+              onClick={() => handleJournal("undo")}
+              disabled={!journal.undo}
+              title={journal.undo ? `Undo ${journal.undo}` : "Nothing to undo"}
+              className="flex-1 flex items-center justify-center gap-2 px-3 py-2 rounded-lg text-sm text-gray-400 hover:text-white hover:bg-white/5 disabled:opacity-40 disabled:hover:bg-transparent disabled:hover:text-gray-400"
+            >
+              <Undo2 size={16} />
+              Undo
+            </button>
+            <button
+              onClick={() => handleJournal("redo")}
+              disabled={!journal.redo}
+              title={journal.redo ? `Redo ${journal.redo}` : "Nothing to redo"}
+              className="flex-1 flex items-center justify-center gap-2 px-3 py-2 rounded-lg text-sm text-gray-400 hover:text-white hover:bg-white/5 disabled:opacity-40 disabled:hover:bg-transparent disabled:hover:text-gray-400"
+            >
+              <Redo2 size={16} />
+              Redo
+            </button>
+          </div>
+        </div>
       </aside>
 
       {/* Main content */}
-      <main className="flex-1 overflow-auto">
+      <main key={pageKey} className="flex-1 overflow-auto">
         {activePage === "history" && <HistoryPage />}
+        {activePage === "activity" && <ActivityPage />}
         {activePage === "reports" && <ReportsPage />}
         {activePage === "settings" && <SettingsPage />}
       </main>
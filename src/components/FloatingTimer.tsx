@@ -1,8 +1,17 @@
 import { useEffect, useRef, useState } from "react";
-import { GripVertical, Play, Square, ChevronDown } from "lucide-react";
+import { GripVertical, Play, Square, ChevronDown, Plus, X } from "lucide-react";
 import { useTimerStore } from "../store";
 import { getCurrentWindow, LogicalSize } from "@tauri-apps/api/window";
 import { listen } from "@tauri-apps/api/event";
+import { invoke } from "@tauri-apps/api/core";
+
+// Notification actions shown in the window where notifications have no buttons
+interface NotificationPrompt {
+  id: number;
+  title: string;
+  body: string;
+  actions: { id: string; label: string }[];
+}
 
 function formatTime(seconds: number): string {
   const h = Math.floor(seconds / 3600);
@@ -31,6 +40,9 @@ export default function FloatingTimer() {
   } = useTimerStore();
 
   const [dropdownOpen, setDropdownOpen] = useState(false);
+  // Untracked seconds before the current timer that can be added as an entry
+  const [backfillSeconds, setBackfillSeconds] = useState<number | null>(null);
+  const [prompt, setPrompt] = useState<NotificationPrompt | null>(null);
   const dropdownRef = useRef<HTMLDivElement>(null);
   const startupHandledRef = useRef(false);
 
@@ -42,10 +54,12 @@ export default function FloatingTimer() {
       // Calculate height based on number of projects (each item ~40px + padding)
       const dropdownHeight = Math.min(projects.length * 40 + 16, 200);
       win.setSize(new LogicalSize(240, 44 + dropdownHeight + 8));
+    } else if (prompt) {
+      win.setSize(new LogicalSize(240, 44 + 96));
     } else {
       win.setSize(new LogicalSize(240, 44));
     }
-  }, [dropdownOpen, projects.length]);
+  }, [dropdownOpen, prompt, projects.length]);
 
   useEffect(() => {
     async function init() {
@@ -71,7 +85,7 @@ export default function FloatingTimer() {
   // Listen for tray menu project clicks
   useEffect(() => {
     const unlisten = listen<number>("start-project-timer", (event) => {
-      startTimerForProject(event.payload);
+      startTimerForProject(event.payload, "tray");
     });
     return () => {
       unlisten.then(fn => fn());
@@ -81,18 +95,64 @@ export default function FloatingTimer() {
   // Listen for tray menu stop timer
   useEffect(() => {
     const unlisten = listen("stop-timer", () => {
-      stopTimer();
+      stopTimer("tray");
     });
     return () => {
       unlisten.then(fn => fn());
     };
   }, [stopTimer]);
 
+  // Listen for timers started by the backend (e.g. from a notification action)
+  useEffect(() => {
+    const unlisten = listen<number>("timer-changed", async () => {
+      await loadCurrentEntry();
+      await loadProjects();
+    });
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, [loadCurrentEntry, loadProjects]);
+
+  // Listen for "Pick project" notification action
+  useEffect(() => {
+    const unlisten = listen("pick-project", () => {
+      setDropdownOpen(true);
+    });
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, []);
+
+  // Listen for notification actions to offer here
+  useEffect(() => {
+    const unlisten = listen<NotificationPrompt>("notification-prompt", (event) => {
+      setPrompt(event.payload);
+    });
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, []);
+
+  // Listen for untracked activity detected before the timer started
+  useEffect(() => {
+    let timeout: ReturnType<typeof setTimeout> | undefined;
+    const unlisten = listen<number>("untracked-activity", (event) => {
+      setBackfillSeconds(event.payload);
+      // The offer goes away on its own if ignored
+      clearTimeout(timeout);
+      timeout = setTimeout(() => setBackfillSeconds(null), 60000);
+    });
+    return () => {
+      clearTimeout(timeout);
+      unlisten.then(fn => fn());
+    };
+  }, []);
+
   // Listen for idle timeout from Rust backend
   useEffect(() => {
     const unlisten = listen<number>("idle-timeout", (event) => {
       console.log(`Idle timeout reached: ${event.payload} seconds`);
-      stopTimer();
+      stopTimer("automatic");
     });
     return () => {
       unlisten.then(fn => fn());
@@ -103,7 +163,7 @@ export default function FloatingTimer() {
   useEffect(() => {
     const unlisten = listen<number>("system-sleep", (event) => {
       console.log(`System sleep detected: ${event.payload} seconds`);
-      stopTimer();
+      stopTimer("automatic");
     });
     return () => {
       unlisten.then(fn => fn());
@@ -135,6 +195,25 @@ export default function FloatingTimer() {
     }
   };
 
+  const handleBackfill = async () => {
+    setBackfillSeconds(null);
+    try {
+      await invoke("backfill_untracked_time");
+    } catch (e) {
+      console.error("Failed to add untracked time:", e);
+    }
+  };
+
+  const handlePromptAction = async (action: string) => {
+    if (!prompt) return;
+    setPrompt(null);
+    try {
+      await invoke("answer_notification_prompt", { id: prompt.id, action });
+    } catch (e) {
+      console.error("Failed to answer notification:", e);
+    }
+  };
+
   const handleDragStart = (e: React.MouseEvent) => {
     e.preventDefault();
     getCurrentWindow().startDragging();
@@ -162,84 +241,129 @@ export default function FloatingTimer() {
 
 
   return (
-    <div
-      className="h-9 bg-[#1a1a1a] rounded-lg flex items-center px-1.5 gap-1 select-none cursor-grab active:cursor-grabbing"
-      onMouseDown={handleDragStart}
-      style={{ paddingTop: '2px', paddingBottom: '2px' }}
-    >
-      {/* Drag handle */}
-      <div className="p-0.5 text-gray-500">
-        <GripVertical size={14} />
-      </div>
+    <div>
+      <div
+        className="h-9 bg-[#1a1a1a] rounded-lg flex items-center px-1.5 gap-1 select-none cursor-grab active:cursor-grabbing"
+        onMouseDown={handleDragStart}
+        style={{ paddingTop: '2px', paddingBottom: '2px' }}
+      >
+        {/* Drag handle */}
+        <div className="p-0.5 text-gray-500">
+          <GripVertical size={14} />
+        </div>
+
+        {/* Project selector */}
+        <div className="relative flex-1 min-w-0" ref={dropdownRef}>
+          <button
+            onClick={() => setDropdownOpen(!dropdownOpen)}
+            onMouseDown={(e) => e.stopPropagation()}
+            className="flex items-center gap-1.5 px-1.5 py-1 rounded w-full text-left hover:bg-white/5 cursor-pointer"
+          >
+            <span
+              className="w-2 h-2 rounded-full shrink-0"
+              style={{ backgroundColor: displayProject?.color || "#3B82F6" }}
+            />
+            <span className="text-white text-base font-semibold truncate">
+              {displayProject?.name || "Select"}
+            </span>
+            <ChevronDown size={10} className="text-gray-400 shrink-0 ml-auto" />
+          </button>
 
-      {/* Project selector */}
-      <div className="relative flex-1 min-w-0" ref={dropdownRef}>
+          {dropdownOpen && (
+            <div className="absolute top-full left-0 mt-1.5 w-36 bg-[#252525] rounded-lg shadow-xl border border-white/10 py-2 z-50 max-h-48 overflow-y-auto">
+              {projects.map((project) => (
+                <button
+                  key={project.id}
+                  onClick={() => {
+                    if (isRunning) {
+                      // Switch to new project (stop current and start new)
+                      startTimerForProject(project.id);
+                    } else {
+                      selectProject(project);
+                    }
+                    setDropdownOpen(false);
+                  }}
+                  className={`w-full flex items-center gap-2 px-3 py-2 hover:bg-white/10 text-left ${
+                    (isRunning ? currentEntry?.project_id : selectedProject?.id) === project.id ? "bg-white/5" : ""
+                  }`}
+                >
+                  <span
+                    className="w-3 h-3 rounded-full shrink-0"
+                    style={{ backgroundColor: project.color }}
+                  />
+                  <span className="text-white text-base font-medium truncate">{project.name}</span>
+                </button>
+              ))}
+            </div>
+          )}
+        </div>
+
+        {/* Timer display */}
+        <div className="font-mono text-white text-base font-semibold tracking-wider tabular-nums">
+          {formatTime(elapsedSeconds)}
+        </div>
+
+        {/* Add untracked time offer */}
+        {isRunning && backfillSeconds !== null && (
+          <button
+            onClick={handleBackfill}
+            onMouseDown={(e) => e.stopPropagation()}
+            title="Add untracked time before this timer"
+            className="flex items-center px-1 rounded text-xs text-[#5BA4C4] hover:bg-white/5 cursor-pointer"
+          >
+            <Plus size={10} />
+            {Math.round(backfillSeconds / 60)}m
+          </button>
+        )}
+
+        {/* Play/Stop button */}
         <button
-          onClick={() => setDropdownOpen(!dropdownOpen)}
+          onClick={handleToggleTimer}
           onMouseDown={(e) => e.stopPropagation()}
-          className="flex items-center gap-1.5 px-1.5 py-1 rounded w-full text-left hover:bg-white/5 cursor-pointer"
+          disabled={!selectedProject && !isRunning}
+          className={`w-6 h-6 rounded-full flex items-center justify-center transition-colors cursor-pointer ${
+            isRunning
+              ? "bg-red-500 hover:bg-red-600"
+              : "bg-[#5BA4C4] hover:bg-[#4A93B3]"
+          } ${!selectedProject && !isRunning ? "opacity-50 cursor-not-allowed" : ""}`}
         >
-          <span
-            className="w-2 h-2 rounded-full shrink-0"
-            style={{ backgroundColor: displayProject?.color || "#3B82F6" }}
-          />
-          <span className="text-white text-base font-semibold truncate">
-            {displayProject?.name || "Select"}
-          </span>
-          <ChevronDown size={10} className="text-gray-400 shrink-0 ml-auto" />
+          {isRunning ? (
+            <Square size={10} className="text-white" fill="white" />
+          ) : (
+            <Play size={12} className="text-white ml-0.5" fill="white" />
+          )}
         </button>
+      </div>
 
-        {dropdownOpen && (
-          <div className="absolute top-full left-0 mt-1.5 w-36 bg-[#252525] rounded-lg shadow-xl border border-white/10 py-2 z-50 max-h-48 overflow-y-auto">
-            {projects.map((project) => (
+      {/* Actions of the last notification */}
+      {prompt && !dropdownOpen && (
+        <div className="mt-2 bg-[#1a1a1a] rounded-lg px-2 py-1.5 space-y-1.5">
+          <div className="flex items-start gap-1">
+            <div className="flex-1 min-w-0">
+              <div className="text-white text-xs font-semibold truncate">{prompt.title}</div>
+              <div className="text-gray-400 text-xs truncate" title={prompt.body}>{prompt.body}</div>
+            </div>
+            <button
+              onClick={() => handlePromptAction("")}
+              className="p-0.5 text-gray-500 hover:text-white cursor-pointer"
+              title="Dismiss"
+            >
+              <X size={12} />
+            </button>
+          </div>
+          <div className="flex flex-wrap gap-1">
+            {prompt.actions.map((action) => (
               <button
-                key={project.id}
-                onClick={() => {
-                  if (isRunning) {
-                    // Switch to new project (stop current and start new)
-                    startTimerForProject(project.id);
-                  } else {
-                    selectProject(project);
-                  }
-                  setDropdownOpen(false);
-                }}
-                className={`w-full flex items-center gap-2 px-3 py-2 hover:bg-white/10 text-left ${
-                  (isRunning ? currentEntry?.project_id : selectedProject?.id) === project.id ? "bg-white/5" : ""
-                }`}
+                key={action.id}
+                onClick={() => handlePromptAction(action.id)}
+                className="px-1.5 py-0.5 rounded bg-white/10 hover:bg-white/15 text-white text-xs cursor-pointer"
               >
-                <span
-                  className="w-3 h-3 rounded-full shrink-0"
-                  style={{ backgroundColor: project.color }}
-                />
-                <span className="text-white text-base font-medium truncate">{project.name}</span>
+                {action.label}
               </button>
             ))}
           </div>
-        )}
-      </div>
-
-      {/* Timer display */}
-      <div className="font-mono text-white text-base font-semibold tracking-wider tabular-nums">
-        {formatTime(elapsedSeconds)}
-      </div>
-
-      {/* Play/Stop button */}
-      <button
-        onClick={handleToggleTimer}
-        onMouseDown={(e) => e.stopPropagation()}
-        disabled={!selectedProject && !isRunning}
-        className={`w-6 h-6 rounded-full flex items-center justify-center transition-colors cursor-pointer ${
-          isRunning
-            ? "bg-red-500 hover:bg-red-600"
-            : "bg-[#5BA4C4] hover:bg-[#4A93B3]"
-        } ${!selectedProject && !isRunning ? "opacity-50 cursor-not-allowed" : ""}`}
-      >
-        {isRunning ? (
-          <Square size={10} className="text-white" fill="white" />
-        ) : (
-          <Play size={12} className="text-white ml-0.5" fill="white" />
-        )}
-      </button>
+        </div>
+      )}
     </div>
   );
 }
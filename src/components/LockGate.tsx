@@ -0,0 +1,111 @@
+import { ReactNode, useEffect, useState } from "react";
+import { Lock } from "lucide-react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { closeDb, DatabaseLock } from "../lib/db";
+
+interface LockGateProps {
+  children: ReactNode;
+  // The floating timer only has room for the passphrase field
+  compact?: boolean;
+}
+
+// Shows an unlock form instead of `children` while the database is encrypted
+// and locked, so nothing touches it before the passphrase is known
+export default function LockGate({ children, compact }: LockGateProps) {
+  const [lock, setLock] = useState<DatabaseLock | null>(null);
+  const [passphrase, setPassphrase] = useState("");
+  const [remember, setRemember] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    invoke<DatabaseLock>("get_database_lock")
+      .then(setLock)
+      .catch((e) => {
+        console.error("Failed to read database lock:", e);
+        setLock({ encrypted: false, locked: false, remembered: false });
+      });
+    const unlisten = listen<DatabaseLock>("database-lock-changed", async (event) => {
+      if (event.payload.locked) {
+        await closeDb();
+        setLock(event.payload);
+      } else {
+        // Unlocked or newly encrypted: start over with a fresh connection
+        window.location.reload();
+      }
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  async function handleUnlock() {
+    if (!passphrase) return;
+    try {
+      await invoke("unlock_database", { passphrase, rememberPassphrase: remember });
+    } catch (e) {
+      setError(String(e));
+      setPassphrase("");
+    }
+  }
+
+  if (!lock) return null;
+  if (!lock.locked) return <>{children}</>;
+
+  if (compact) {
+    return (
+      <div className="h-9 bg-[#1a1a1a] rounded-lg flex items-center px-2 gap-1.5" data-tauri-drag-region>
+        <Lock size={14} className="text-gray-500 shrink-0" />
+        <input
+          type="password"
+          autoFocus
+          value={passphrase}
+          onChange={(e) => {
+            setPassphrase(e.target.value);
+            setError(null);
+          }}
+          onKeyDown={(e) => e.key === "Enter" && handleUnlock()}
+          placeholder={error ? "Wrong passphrase" : "Passphrase"}
+          className={`flex-1 min-w-0 bg-transparent text-sm text-white focus:outline-none ${
+            error ? "placeholder-red-400" : "placeholder-gray-500"
+          }`}
+        />
+      </div>
+    );
+  }
+
+  return (
+    <div className="flex h-screen items-center justify-center bg-[#1a1a1a] text-white">
+      <div className="w-80 space-y-4">
+        <div className="flex items-center gap-2 text-lg font-semibold">
+          <Lock size={18} className="text-gray-400" />
+          Horalis is locked
+        </div>
+        <p className="text-sm text-gray-400">Enter the passphrase of your encrypted database.</p>
+        <input
+          type="password"
+          autoFocus
+          value={passphrase}
+          onChange={(e) => {
+            setPassphrase(e.target.value);
+            setError(null);
+          }}
+          onKeyDown={(e) => e.key === "Enter" && handleUnlock()}
+          placeholder="Passphrase"
+          className="w-full bg-[#252525] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500"
+        />
+        <label className="flex items-center gap-2 text-sm text-gray-400">
+          <input type="checkbox" checked={remember} onChange={(e) => setRemember(e.target.checked)} />
+          Remember in the system keychain
+        </label>
+        {error && <div className="text-sm text-red-400">{error}</div>}
+        <button
+          onClick={handleUnlock}
+          className="w-full px-4 py-2 rounded-lg bg-blue-600 hover:bg-blue-700 text-white text-sm transition-colors"
+        >
+          Unlock
+        </button>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,161 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { ChevronLeft, ChevronRight } from "lucide-react";
+import { ActivitySegment, DayEntry, getDayEntries } from "../../lib/db";
+
+const DAY_SECONDS = 24 * 60 * 60;
+
+function toLocalDateString(date: Date): string {
+  const year = date.getFullYear();
+  const month = String(date.getMonth() + 1).padStart(2, "0");
+  const day = String(date.getDate()).padStart(2, "0");
+  return `${year}-${month}-${day}`;
+}
+
+function parseDbDate(dateStr: string): Date {
+  if (dateStr.includes("T")) {
+    return new Date(dateStr);
+  }
+  return new Date(dateStr + "Z");
+}
+
+function formatTime(dateStr: string): string {
+  return parseDbDate(dateStr).toLocaleTimeString([], { hour: "2-digit", minute: "2-digit" });
+}
+
+function formatDuration(seconds: number): string {
+  const h = Math.floor(seconds / 3600);
+  const m = Math.floor((seconds % 3600) / 60);
+  if (h > 0) {
+    return `${h}h ${m}m`;
+  }
+  return `${m}m`;
+}
+
+// Position of a UTC timestamp within the local day, as a percentage
+function dayOffset(dateStr: string, dayStart: Date): number {
+  const seconds = (parseDbDate(dateStr).getTime() - dayStart.getTime()) / 1000;
+  return Math.min(Math.max(seconds / DAY_SECONDS, 0), 1) * 100;
+}
+
+export default function ActivityPage() {
+  const [day, setDay] = useState(() => {
+    const d = new Date();
+    d.setHours(0, 0, 0, 0);
+    return d;
+  });
+  const [segments, setSegments] = useState<ActivitySegment[]>([]);
+  const [entries, setEntries] = useState<DayEntry[]>([]);
+
+  useEffect(() => {
+    loadDay();
+  }, [day]);
+
+  async function loadDay() {
+    const dateKey = toLocalDateString(day);
+    try {
+      const [activity, dayEntries] = await Promise.all([
+        invoke<ActivitySegment[]>("get_activity_timeline", { date: dateKey }),
+        getDayEntries(dateKey),
+      ]);
+      setSegments(activity);
+      setEntries(dayEntries);
+    } catch (error) {
+      console.error("Failed to load activity:", error);
+    }
+  }
+
+  function shiftDay(days: number) {
+    const next = new Date(day);
+    next.setDate(next.getDate() + days);
+    setDay(next);
+  }
+
+  const nowIso = new Date().toISOString();
+
+  return (
+    <div className="p-8">
+      <header className="mb-8 flex items-center justify-between">
+        <div>
+          <h1 className="text-2xl font-semibold">Activity</h1>
+          <p className="text-gray-400 text-sm mt-1">What you were doing, next to what you tracked</p>
+        </div>
+        <div className="flex items-center gap-2">
+          <button onClick={() => shiftDay(-1)} className="p-2 hover:bg-white/10 rounded-lg text-gray-400">
+            <ChevronLeft size={18} />
+          </button>
+          <span className="text-sm font-medium w-28 text-center">{day.toLocaleDateString()}</span>
+          <button onClick={() => shiftDay(1)} className="p-2 hover:bg-white/10 rounded-lg text-gray-400">
+            <ChevronRight size={18} />
+          </button>
+        </div>
+      </header>
+
+      {/* Timeline */}
+      <div className="bg-[#252525] rounded-xl p-5 mb-6">
+        <div className="text-xs text-gray-400 mb-1">Tracked</div>
+        <div className="relative h-5 bg-[#1a1a1a] rounded mb-3">
+          {entries.map((entry) => {
+            const left = dayOffset(entry.start_time, day);
+            const width = dayOffset(entry.end_time ?? nowIso, day) - left;
+            return (
+              <div
+                key={entry.id}
+                className="absolute top-0 h-full rounded-sm"
+                style={{ left: `${left}%`, width: `${Math.max(width, 0.2)}%`, backgroundColor: entry.project_color }}
+                title={`${entry.project_name}: ${formatTime(entry.start_time)}`}
+              />
+            );
+          })}
+        </div>
+        <div className="text-xs text-gray-400 mb-1">Activity</div>
+        <div className="relative h-5 bg-[#1a1a1a] rounded">
+          {segments.map((segment) => {
+            const left = dayOffset(segment.start_time, day);
+            const width = dayOffset(segment.end_time, day) - left;
+            return (
+              <div
+                key={segment.id}
+                className="absolute top-0 h-full rounded-sm bg-gray-500"
+                style={{ left: `${left}%`, width: `${Math.max(width, 0.2)}%` }}
+                title={`${segment.app}: ${segment.title}`}
+              />
+            );
+          })}
+        </div>
+        <div className="flex justify-between text-xs text-gray-500 mt-1">
+          {[0, 6, 12, 18, 24].map((hour) => (
+            <span key={hour}>{String(hour).padStart(2, "0")}:00</span>
+          ))}
+        </div>
+      </div>
+
+      {/* Samples */}
+      <div className="bg-[#252525] rounded-xl overflow-hidden">
+        {segments.length === 0 ? (
+          <div className="text-gray-400 text-center py-8">
+            No activity recorded. Enable the activity log in Settings.
+          </div>
+        ) : (
+          segments.map((segment, index) => (
+            <div
+              key={segment.id}
+              className={`flex items-center gap-4 px-5 py-3 ${
+                index !== segments.length - 1 ? "border-b border-white/5" : ""
+              }`}
+            >
+              <span className="text-sm text-gray-400 tabular-nums w-28 shrink-0">
+                {formatTime(segment.start_time)} - {formatTime(segment.end_time)}
+              </span>
+              <span className="text-sm font-medium w-32 shrink-0 truncate">{segment.app}</span>
+              <span className="flex-1 text-sm text-gray-400 truncate" title={segment.title}>
+                {segment.title}
+              </span>
+              <span className="text-sm tabular-nums">{formatDuration(segment.duration)}</span>
+            </div>
+          ))
+        )}
+      </div>
+    </div>
+  );
+}
@@ -1,6 +1,8 @@
 import { useEffect, useState } from "react";
-import { MoreVertical, Trash2, Pencil, AlertTriangle, X } from "lucide-react";
-import { getTimeEntries, getProjects, deleteTimeEntry, updateTimeEntry, TimeEntry, Project } from "../../lib/db";
+import { MoreVertical, Trash2, Pencil, AlertTriangle, X, GitCommit, Clock, Scissors, Copy, Merge, Lock } from "lucide-react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { getTimeEntries, getProjects, deleteTimeEntry, updateTimeEntry, TimeEntry, Project, Commit, Gap, FillAction, EntryConflict, EntrySummary, ConflictResolution, EntryHistoryRecord } from "../../lib/db";
 
 interface EntryWithProject extends TimeEntry {
   project_name: string;
@@ -55,6 +57,36 @@ function formatTime(dateStr: string): string {
   return date.toLocaleTimeString([], { hour: "2-digit", minute: "2-digit" });
 }
 
+function formatUnixTime(seconds: number): string {
+  return new Date(seconds * 1000).toLocaleTimeString([], { hour: "2-digit", minute: "2-digit" });
+}
+
+// Local calendar date of a DB timestamp, as the backend expects it
+function toLocalDateKey(dateStr: string): string {
+  const date = parseDbDate(dateStr);
+  const month = String(date.getMonth() + 1).padStart(2, "0");
+  const day = String(date.getDate()).padStart(2, "0");
+  return `${date.getFullYear()}-${month}-${day}`;
+}
+
+function fillActionValue(action: FillAction): string {
+  switch (action.action) {
+    case "extend":
+      return `extend:${action.entry_id}`;
+    case "assign":
+      return `assign:${action.project_id}`;
+    default:
+      return action.action;
+  }
+}
+
+function parseFillAction(value: string): FillAction {
+  const [action, id] = value.split(":");
+  if (action === "extend") return { action: "extend", entry_id: Number(id) };
+  if (action === "assign") return { action: "assign", project_id: Number(id) };
+  return { action: action as "break" | "skip" };
+}
+
 function getDateKey(dateStr: string): string {
   // Handle both formats
   if (dateStr.includes("T")) {
@@ -203,9 +235,29 @@ export default function HistoryPage() {
   const [editProjectId, setEditProjectId] = useState<number>(0);
   const [editStartTime, setEditStartTime] = useState("");
   const [editEndTime, setEditEndTime] = useState("");
+  const [commits, setCommits] = useState<Record<number, Commit[]>>({});
+  const [gapDate, setGapDate] = useState<string | null>(null);
+  const [gaps, setGaps] = useState<Gap[]>([]);
+  const [gapActions, setGapActions] = useState<FillAction[]>([]);
+  const [gapError, setGapError] = useState<string | null>(null);
+  const [gapsFilled, setGapsFilled] = useState(false);
+  const [editError, setEditError] = useState<string | null>(null);
+  const [editHistory, setEditHistory] = useState<EntryHistoryRecord[]>([]);
+  const [conflicts, setConflicts] = useState<EntryConflict[]>([]);
+  const [showConflicts, setShowConflicts] = useState(false);
+  const [splitEntry, setSplitEntry] = useState<EntryWithProject | null>(null);
+  const [splitTime, setSplitTime] = useState("");
+  const [splitProjectId, setSplitProjectId] = useState<number>(0);
+  const [duplicateEntry, setDuplicateEntry] = useState<EntryWithProject | null>(null);
+  const [duplicateDate, setDuplicateDate] = useState("");
+  const [actionError, setActionError] = useState<string | null>(null);
 
   useEffect(() => {
     loadData();
+    const unlisten = listen("entries-changed", () => loadData());
+    return () => {
+      unlisten.then((fn) => fn());
+    };
   }, []);
 
   async function loadData() {
@@ -216,6 +268,12 @@ export default function HistoryPage() {
       ]);
       setEntries(entriesData);
       setProjects(projectsData);
+      invoke<Record<number, Commit[]>>("get_entry_commits", { entryIds: entriesData.map((e) => e.id) })
+        .then(setCommits)
+        .catch((error) => console.error("Failed to load commits:", error));
+      invoke<EntryConflict[]>("list_entry_conflicts")
+        .then(setConflicts)
+        .catch((error) => console.error("Failed to load conflicts:", error));
     } catch (error) {
       console.error("Failed to load data:", error);
     } finally {
@@ -236,10 +294,120 @@ export default function HistoryPage() {
 
   function openEditModal(entry: EntryWithProject) {
     setEditEntry(entry);
+    setEditError(null);
     setEditProjectId(entry.project_id);
     setEditStartTime(toLocalDateTimeInput(entry.start_time));
     setEditEndTime(entry.end_time ? toLocalDateTimeInput(entry.end_time) : "");
     setMenuOpen(null);
+    setEditHistory([]);
+    invoke<EntryHistoryRecord[]>("get_entry_history", { entryId: entry.id })
+      .then(setEditHistory)
+      .catch((error) => console.error("Failed to load entry history:", error));
+  }
+
+  async function openGapFiller(dateStr: string) {
+    const date = toLocalDateKey(dateStr);
+    try {
+      const found = await invoke<Gap[]>("find_gaps", { date });
+      setGapDate(date);
+      setGaps(found);
+      setGapActions(found.map((gap) => gap.suggestion));
+      setGapError(null);
+    } catch (error) {
+      console.error("Failed to find gaps:", error);
+    }
+  }
+
+  async function handleApplyGaps() {
+    if (!gapDate) return;
+    try {
+      const fills = gaps.map((gap, index) => ({ start: gap.start, end: gap.end, ...gapActions[index] }));
+      await invoke("apply_gap_fills", { fills });
+      setGapsFilled(true);
+      setGapDate(null);
+      await loadData();
+    } catch (error) {
+      setGapError(String(error));
+    }
+  }
+
+  async function handleUndoGaps() {
+    if (!gapsFilled) return;
+    try {
+      await invoke("undo");
+      setGapsFilled(false);
+      await loadData();
+    } catch (error) {
+      console.error("Failed to undo gap fill:", error);
+    }
+  }
+
+  async function handleResolve(conflict: EntryConflict, resolution: ConflictResolution) {
+    try {
+      await invoke("resolve_entry_conflict", {
+        firstId: conflict.first.id,
+        secondId: conflict.second?.id ?? null,
+        resolution,
+      });
+      await loadData();
+    } catch (error) {
+      console.error("Failed to resolve conflict:", error);
+    }
+  }
+
+  function openSplitModal(entry: EntryWithProject) {
+    const start = parseDbDate(entry.start_time).getTime();
+    const end = parseDbDate(entry.end_time ?? entry.start_time).getTime();
+    const middle = new Date((start + end) / 2).toISOString();
+    setSplitEntry(entry);
+    setSplitTime(toLocalDateTimeInput(middle));
+    setSplitProjectId(entry.project_id);
+    setActionError(null);
+    setMenuOpen(null);
+  }
+
+  function openDuplicateModal(entry: EntryWithProject) {
+    setDuplicateEntry(entry);
+    setDuplicateDate(toLocalDateKey(new Date().toISOString()));
+    setActionError(null);
+    setMenuOpen(null);
+  }
+
+  async function handleSplit() {
+    if (!splitEntry || !splitTime) return;
+    try {
+      await invoke("split_time_entry", {
+        id: splitEntry.id,
+        at: fromLocalDateTimeInput(splitTime),
+        projectId: splitProjectId,
+      });
+      setSplitEntry(null);
+      await loadData();
+    } catch (error) {
+      setActionError(String(error));
+    }
+  }
+
+  async function handleDuplicate() {
+    if (!duplicateEntry || !duplicateDate) return;
+    try {
+      await invoke("duplicate_time_entry", { id: duplicateEntry.id, date: duplicateDate });
+      setDuplicateEntry(null);
+      await loadData();
+    } catch (error) {
+      setActionError(String(error));
+    }
+  }
+
+  async function handleMerge(first: EntryWithProject, second: EntryWithProject) {
+    setMenuOpen(null);
+    try {
+      await invoke("merge_time_entries", { firstId: first.id, secondId: second.id });
+      await loadData();
+    } catch (error) {
+      console.error("Failed to merge entries:", error);
+      setActionError(String(error));
+    }
   }
 
   async function handleEdit() {
@@ -254,7 +422,7 @@ export default function HistoryPage() {
       setEditEntry(null);
       await loadData();
     } catch (error) {
-      console.error("Failed to update entry:", error);
+      setEditError(String(error));
     }
   }
 
@@ -275,6 +443,27 @@ export default function HistoryPage() {
         <p className="text-gray-400 text-sm mt-1">View your tracked time entries</p>
       </header>
 
+      {conflicts.length > 0 && (
+        <div className="mb-6 flex items-center justify-between bg-amber-500/10 border border-amber-500/30 rounded-xl px-5 py-3 text-sm">
+          <span className="flex items-center gap-2 text-amber-300">
+            <AlertTriangle size={16} />
+            {conflicts.length} {conflicts.length === 1 ? "entry conflicts" : "entries conflict"} with others
+          </span>
+          <button onClick={() => setShowConflicts(true)} className="text-amber-300 hover:text-amber-200">
+            Review
+          </button>
+        </div>
+      )}
+
+      {gapsFilled && (
+        <div className="mb-6 flex items-center justify-between bg-[#252525] rounded-xl px-5 py-3 text-sm">
+          <span className="text-gray-300">Gaps filled</span>
+          <button onClick={handleUndoGaps} className="text-blue-400 hover:text-blue-300">
+            Undo
+          </button>
+        </div>
+      )}
+
       <div className="space-y-6">
         {dayGroups.length === 0 ? (
           <div className="text-center text-gray-400 py-12 bg-[#252525] rounded-xl">
@@ -288,9 +477,18 @@ export default function HistoryPage() {
                 <span className="text-sm font-medium text-gray-300">
                   {group.displayDate}
                 </span>
-                <span className="text-sm text-gray-500">
-                  {formatDuration(group.totalDuration)}
-                </span>
+                <div className="flex items-center gap-3">
+                  <button
+                    onClick={() => openGapFiller(group.entries[0].start_time)}
+                    className="flex items-center gap-1 text-xs text-gray-400 hover:text-white"
+                  >
+                    <Clock size={12} />
+                    Fill gaps
+                  </button>
+                  <span className="text-sm text-gray-500">
+                    {formatDuration(group.totalDuration)}
+                  </span>
+                </div>
               </div>
 
               {/* Summary bar */}
@@ -312,6 +510,15 @@ export default function HistoryPage() {
                         style={{ backgroundColor: entry.project_color }}
                       />
                       <span className="font-medium truncate">{entry.project_name}</span>
+                      {commits[entry.id] && (
+                        <span
+                          className="flex items-center gap-1 text-xs text-gray-400 shrink-0"
+                          title={commits[entry.id].map((c) => `${c.hash.slice(0, 7)} ${c.message}`).join("\n")}
+                        >
+                          <GitCommit size={14} />
+                          {commits[entry.id].length}
+                        </span>
+                      )}
                     </div>
 
                     {/* Time range */}
@@ -325,37 +532,66 @@ export default function HistoryPage() {
                       {formatEntryDuration(entry.duration || 0)}
                     </div>
 
-                    {/* Menu button */}
-                    <div className="relative">
-                      <button
-                        onClick={() => setMenuOpen(menuOpen === entry.id ? null : entry.id)}
-                        className="p-2 rounded hover:bg-white/10 opacity-0 group-hover:opacity-100 transition-opacity"
-                      >
-                        <MoreVertical size={16} className="text-gray-400" />
-                      </button>
-
-                      {menuOpen === entry.id && (
-                        <div className="absolute right-0 top-full mt-1 w-36 bg-[#333] rounded-lg shadow-xl border border-white/10 py-1 z-50">
-                          <button
-                            onClick={() => openEditModal(entry)}
-                            className="w-full flex items-center gap-2 px-4 py-2 text-sm hover:bg-white/10"
-                          >
-                            <Pencil size={14} />
-                            Edit
-                          </button>
-                          <button
-                            onClick={() => {
-                              setDeleteConfirm(entry);
-                              setMenuOpen(null);
-                            }}
-                            className="w-full flex items-center gap-2 px-4 py-2 text-sm hover:bg-white/10 text-red-400"
-                          >
-                            <Trash2 size={14} />
-                            Delete
-                          </button>
-                        </div>
-                      )}
-                    </div>
+                    {/* Menu button; entries of a submitted week can't be changed */}
+                    {entry.locked_by ? (
+                      <div className="p-2" title="Submitted. Unlock the week on the Reports page to change it.">
+                        <Lock size={16} className="text-gray-500" />
+                      </div>
+                    ) : (
+                      <div className="relative">
+                        <button
+                          onClick={() => setMenuOpen(menuOpen === entry.id ? null : entry.id)}
+                          className="p-2 rounded hover:bg-white/10 opacity-0 group-hover:opacity-100 transition-opacity"
+                        >
+                          <MoreVertical size={16} className="text-gray-400" />
+                        </button>
+
+                        {menuOpen === entry.id && (
+                          <div className="absolute right-0 top-full mt-1 w-48 bg-[#333] rounded-lg shadow-xl border border-white/10 py-1 z-50">
+                            <button
+                              onClick={() => openEditModal(entry)}
+                              className="w-full flex items-center gap-2 px-4 py-2 text-sm hover:bg-white/10"
+                            >
+                              <Pencil size={14} />
+                              Edit
+                            </button>
+                            <button
+                              onClick={() => openSplitModal(entry)}
+                              className="w-full flex items-center gap-2 px-4 py-2 text-sm hover:bg-white/10"
+                            >
+                              <Scissors size={14} />
+                              Split
+                            </button>
+                            <button
+                              onClick={() => openDuplicateModal(entry)}
+                              className="w-full flex items-center gap-2 px-4 py-2 text-sm hover:bg-white/10"
+                            >
+                              <Copy size={14} />
+                              Duplicate
+                            </button>
+                            {group.entries[index + 1]?.project_id === entry.project_id && (
+                              <button
+                                onClick={() => handleMerge(group.entries[index + 1], entry)}
+                                className="w-full flex items-center gap-2 px-4 py-2 text-sm hover:bg-white/10"
+                              >
+                                <Merge size={14} />
+                                Merge with previous
+                              </button>
+                            )}
+                            <button
+                              onClick={() => {
+                                setDeleteConfirm(entry);
+                                setMenuOpen(null);
+                              }}
+                              className="w-full flex items-center gap-2 px-4 py-2 text-sm hover:bg-white/10 text-red-400"
+                            >
+                              <Trash2 size={14} />
+                              Delete
+                            </button>
+                          </div>
+                        )}
+                      </div>
+                    )}
                   </div>
                 ))}
               </div>
@@ -398,6 +634,234 @@ export default function HistoryPage() {
         </div>
       )}
 
+      {/* Conflicts modal */}
+      {showConflicts && (
+        <div className="fixed inset-0 bg-black/60 flex items-center justify-center z-50">
+          <div className="bg-[#252525] rounded-xl p-6 max-w-lg w-full mx-4 shadow-2xl max-h-[80vh] overflow-auto">
+            <div className="flex items-center justify-between mb-6">
+              <h3 className="text-lg font-semibold">Conflicting Entries</h3>
+              <button onClick={() => setShowConflicts(false)} className="p-1 hover:bg-white/10 rounded">
+                <X size={20} className="text-gray-400" />
+              </button>
+            </div>
+
+            {conflicts.length === 0 ? (
+              <p className="text-gray-400 text-sm">All conflicts are resolved.</p>
+            ) : (
+              <div className="space-y-4">
+                {conflicts.map((conflict) => {
+                  const describe = (entry: EntrySummary) =>
+                    `${entry.project_name} ${formatUnixTime(entry.start)} - ${entry.end !== null ? formatUnixTime(entry.end) : "..."}`;
+                  const actions: { resolution: ConflictResolution; label: string }[] =
+                    conflict.kind === "negative_duration"
+                      ? [{ resolution: "swap", label: "Swap start and end" }]
+                      : [
+                          { resolution: "trim_first", label: "Trim first" },
+                          { resolution: "trim_second", label: "Trim second" },
+                          { resolution: "split", label: "Split" },
+                          { resolution: "merge", label: "Merge" },
+                        ];
+                  return (
+                    <div
+                      key={`${conflict.first.id}-${conflict.second?.id ?? ""}`}
+                      className="bg-[#1a1a1a] rounded-lg p-4"
+                    >
+                      <div className="text-sm text-gray-300">
+                        {new Date(conflict.first.start * 1000).toLocaleDateString()}: {describe(conflict.first)}
+                      </div>
+                      {conflict.second ? (
+                        <div className="text-sm text-gray-300">
+                          overlaps {describe(conflict.second)} by {formatEntryDuration(conflict.overlap_secs)}
+                        </div>
+                      ) : (
+                        <div className="text-sm text-gray-400">ends before it starts</div>
+                      )}
+                      <div className="flex flex-wrap gap-2 mt-3">
+                        {actions.map((action) => (
+                          <button
+                            key={action.resolution}
+                            onClick={() => handleResolve(conflict, action.resolution)}
+                            className="px-3 py-1.5 text-xs rounded-lg bg-white/10 hover:bg-white/15"
+                          >
+                            {action.label}
+                          </button>
+                        ))}
+                      </div>
+                    </div>
+                  );
+                })}
+              </div>
+            )}
+          </div>
+        </div>
+      )}
+
+      {/* Split modal */}
+      {splitEntry && (
+        <div className="fixed inset-0 bg-black/60 flex items-center justify-center z-50">
+          <div className="bg-[#252525] rounded-xl p-6 max-w-md w-full mx-4 shadow-2xl">
+            <div className="flex items-center justify-between mb-6">
+              <h3 className="text-lg font-semibold">Split Time Entry</h3>
+              <button onClick={() => setSplitEntry(null)} className="p-1 hover:bg-white/10 rounded">
+                <X size={20} className="text-gray-400" />
+              </button>
+            </div>
+
+            <div className="space-y-4">
+              <div>
+                <label className="block text-sm text-gray-400 mb-2">Split at</label>
+                <input
+                  type="datetime-local"
+                  value={splitTime}
+                  onChange={(e) => setSplitTime(e.target.value)}
+                  className="w-full bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2.5 text-sm focus:outline-none focus:border-blue-500"
+                />
+              </div>
+              <div>
+                <label className="block text-sm text-gray-400 mb-2">Project after the split</label>
+                <select
+                  value={splitProjectId}
+                  onChange={(e) => setSplitProjectId(Number(e.target.value))}
+                  className="w-full bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2.5 text-sm focus:outline-none focus:border-blue-500"
+                >
+                  {projects.map((project) => (
+                    <option key={project.id} value={project.id}>
+                      {project.name}
+                    </option>
+                  ))}
+                </select>
+              </div>
+            </div>
+            {actionError && <p className="text-sm text-red-400 mt-4">{actionError}</p>}
+
+            <div className="flex gap-3 justify-end mt-6">
+              <button
+                onClick={() => setSplitEntry(null)}
+                className="px-4 py-2 rounded-lg bg-[#1a1a1a] hover:bg-[#303030] text-gray-300 transition-colors"
+              >
+                Cancel
+              </button>
+              <button
+                onClick={handleSplit}
+                disabled={!splitTime}
+                className="px-4 py-2 rounded-lg bg-blue-600 hover:bg-blue-700 text-white transition-colors disabled:opacity-50 disabled:cursor-not-allowed"
+              >
+                Split
+              </button>
+            </div>
+          </div>
+        </div>
+      )}
+
+      {/* Duplicate modal */}
+      {duplicateEntry && (
+        <div className="fixed inset-0 bg-black/60 flex items-center justify-center z-50">
+          <div className="bg-[#252525] rounded-xl p-6 max-w-md w-full mx-4 shadow-2xl">
+            <div className="flex items-center justify-between mb-6">
+              <h3 className="text-lg font-semibold">Duplicate Time Entry</h3>
+              <button onClick={() => setDuplicateEntry(null)} className="p-1 hover:bg-white/10 rounded">
+                <X size={20} className="text-gray-400" />
+              </button>
+            </div>
+
+            <p className="text-gray-400 text-sm mb-4">
+              <strong>{duplicateEntry.project_name}</strong> - {formatTime(duplicateEntry.start_time)} to{" "}
+              {duplicateEntry.end_time ? formatTime(duplicateEntry.end_time) : "..."}, at the same time on
+            </p>
+            <input
+              type="date"
+              value={duplicateDate}
+              onChange={(e) => setDuplicateDate(e.target.value)}
+              className="w-full bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2.5 text-sm focus:outline-none focus:border-blue-500"
+            />
+            {actionError && <p className="text-sm text-red-400 mt-4">{actionError}</p>}
+
+            <div className="flex gap-3 justify-end mt-6">
+              <button
+                onClick={() => setDuplicateEntry(null)}
+                className="px-4 py-2 rounded-lg bg-[#1a1a1a] hover:bg-[#303030] text-gray-300 transition-colors"
+              >
+                Cancel
+              </button>
+              <button
+                onClick={handleDuplicate}
+                disabled={!duplicateDate}
+                className="px-4 py-2 rounded-lg bg-blue-600 hover:bg-blue-700 text-white transition-colors disabled:opacity-50 disabled:cursor-not-allowed"
+              >
+                Duplicate
+              </button>
+            </div>
+          </div>
+        </div>
+      )}
+
+      {/* Gap filler modal */}
+      {gapDate && (
+        <div className="fixed inset-0 bg-black/60 flex items-center justify-center z-50">
+          <div className="bg-[#252525] rounded-xl p-6 max-w-lg w-full mx-4 shadow-2xl">
+            <div className="flex items-center justify-between mb-6">
+              <h3 className="text-lg font-semibold">Untracked Gaps</h3>
+              <button onClick={() => setGapDate(null)} className="p-1 hover:bg-white/10 rounded">
+                <X size={20} className="text-gray-400" />
+              </button>
+            </div>
+
+            {gaps.length === 0 ? (
+              <p className="text-gray-400 text-sm">No gaps in your working hours on this day.</p>
+            ) : (
+              <div className="space-y-3">
+                {gaps.map((gap, index) => (
+                  <div key={gap.start} className="flex items-center gap-3">
+                    <span className="text-sm text-gray-300 tabular-nums w-32 shrink-0">
+                      {formatUnixTime(gap.start)} - {formatUnixTime(gap.end)}
+                    </span>
+                    <span className="text-sm text-gray-500 w-16 shrink-0">{formatEntryDuration(gap.duration)}</span>
+                    <select
+                      value={fillActionValue(gapActions[index])}
+                      onChange={(e) =>
+                        setGapActions(gapActions.map((a, i) => (i === index ? parseFillAction(e.target.value) : a)))
+                      }
+                      className="flex-1 bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500"
+                    >
+                      {gap.previous && (
+                        <option value={`extend:${gap.previous.entry_id}`}>Extend {gap.previous.project_name} (before)</option>
+                      )}
+                      {gap.next && (
+                        <option value={`extend:${gap.next.entry_id}`}>Extend {gap.next.project_name} (after)</option>
+                      )}
+                      {projects.map((project) => (
+                        <option key={project.id} value={`assign:${project.id}`}>
+                          {project.name}
+                        </option>
+                      ))}
+                      <option value="break">Break</option>
+                      <option value="skip">Leave untracked</option>
+                    </select>
+                  </div>
+                ))}
+              </div>
+            )}
+            {gapError && <p className="text-sm text-red-400 mt-4">{gapError}</p>}
+
+            <div className="flex gap-3 justify-end mt-6">
+              <button
+                onClick={() => setGapDate(null)}
+                className="px-4 py-2 rounded-lg bg-[#1a1a1a] hover:bg-[#303030] text-gray-300 transition-colors"
+              >
+                Cancel
+              </button>
+              <button
+                onClick={handleApplyGaps}
+                disabled={gaps.length === 0}
+                className="px-4 py-2 rounded-lg bg-blue-600 hover:bg-blue-700 text-white transition-colors disabled:opacity-50 disabled:cursor-not-allowed"
+              >
+                Apply
+              </button>
+            </div>
+          </div>
+        </div>
+      )}
+
       {/* Edit modal */}
       {editEntry && (
         <div className="fixed inset-0 bg-black/60 flex items-center justify-center z-50">
@@ -452,6 +916,29 @@ export default function HistoryPage() {
               </div>
             </div>
 
+            {/* Audit trail */}
+            {editHistory.length > 0 && (
+              <div className="mt-4">
+                <div className="text-sm text-gray-400 mb-2">Changes</div>
+                <ul className="space-y-1 max-h-32 overflow-auto text-xs text-gray-400">
+                  {editHistory.map((record) => (
+                    <li key={record.id} className="flex justify-between gap-3">
+                      <span>
+                        {parseDbDate(record.changed_at).toLocaleString([], { dateStyle: "short", timeStyle: "short" })} · {record.label} ({record.source})
+                      </span>
+                      {record.old?.duration != null && record.new?.duration != null && record.old.duration !== record.new.duration && (
+                        <span className="shrink-0">
+                          {formatEntryDuration(record.old.duration)} → {formatEntryDuration(record.new.duration)}
+                        </span>
+                      )}
+                    </li>
+                  ))}
+                </ul>
+              </div>
+            )}
+
+            {editError && <p className="text-sm text-red-400 mt-4">{editError}</p>}
+
             <div className="flex gap-3 justify-end mt-6">
               <button
                 onClick={() => setEditEntry(null)}
@@ -1,5 +1,6 @@
 import { useEffect, useState } from "react";
-import { getDb } from "../../lib/db";
+import { invoke } from "@tauri-apps/api/core";
+import { getProjects, getSettings, updateSetting, runReport, AppSettings, EditedEntry, Heatmap, Period, PeriodKind, Project, ReportDimension, ReportRow, Timesheet } from "../../lib/db";
 import {
   BarChart,
   Bar,
@@ -9,20 +10,7 @@ import {
   ResponsiveContainer,
   Legend,
 } from "recharts";
-import { ChevronLeft, ChevronRight } from "lucide-react";
-
-interface ProjectInfo {
-  id: number;
-  name: string;
-  color: string;
-}
-
-interface ProjectStats {
-  project_id: number;
-  project_name: string;
-  project_color: string;
-  total_duration: number;
-}
+import { ChevronLeft, ChevronRight, Lock } from "lucide-react";
 
 interface DailyChartData {
   date: string;
@@ -30,7 +18,27 @@ interface DailyChartData {
   [key: string]: number | string; // project hours by name
 }
 
-type ViewMode = "week" | "month";
+type ViewMode = Extract<PeriodKind, "week" | "month" | "fiscal_period" | "pay_period">;
+
+const VIEW_MODES: { id: ViewMode; label: string; noun: string }[] = [
+  { id: "week", label: "Week", noun: "week" },
+  { id: "month", label: "Month", noun: "month" },
+  { id: "fiscal_period", label: "Fiscal", noun: "fiscal period" },
+  { id: "pay_period", label: "Pay period", noun: "pay period" },
+];
+
+type BreakdownDimension = Extract<ReportDimension, "project" | "client" | "tag">;
+
+const BREAKDOWNS: { id: BreakdownDimension; label: string }[] = [
+  { id: "project", label: "By Project" },
+  { id: "client", label: "By Client" },
+  { id: "tag", label: "By Tag" },
+];
+
+const WEEKDAY_LABELS = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+// Minutes to round each entry up to; 0 keeps exact durations
+const ROUNDING_OPTIONS = [0, 6, 15, 30];
 
 function formatDuration(seconds: number): string {
   const h = Math.floor(seconds / 3600);
@@ -46,28 +54,10 @@ function formatHours(seconds: number): string {
   return hours.toFixed(1) + "h";
 }
 
-function getWeekRange(date: Date): { start: Date; end: Date; label: string } {
-  const d = new Date(date);
-  const day = d.getDay();
-  const diff = d.getDate() - day + (day === 0 ? -6 : 1); // Monday start
-  const start = new Date(d.setDate(diff));
-  start.setHours(0, 0, 0, 0);
-  const end = new Date(start);
-  end.setDate(end.getDate() + 6);
-  end.setHours(23, 59, 59, 999);
-
-  const startStr = start.toLocaleDateString("en-US", { month: "short", day: "numeric" });
-  const endStr = end.toLocaleDateString("en-US", { month: "short", day: "numeric" });
-  const label = `${startStr} - ${endStr}, ${start.getFullYear()}`;
-
-  return { start, end, label };
-}
-
-function getMonthRange(date: Date): { start: Date; end: Date; label: string } {
-  const start = new Date(date.getFullYear(), date.getMonth(), 1);
-  const end = new Date(date.getFullYear(), date.getMonth() + 1, 0, 23, 59, 59, 999);
-  const label = start.toLocaleDateString("en-US", { month: "long", year: "numeric" });
-  return { start, end, label };
+// "YYYY-MM-DD" as a local date
+function parseDateKey(key: string): Date {
+  const [year, month, day] = key.split("-").map(Number);
+  return new Date(year, month - 1, day);
 }
 
 function toLocalDateString(date: Date): string {
@@ -80,54 +70,101 @@ function toLocalDateString(date: Date): string {
 
 export default function ReportsPage() {
   const [viewMode, setViewMode] = useState<ViewMode>("week");
-  const [currentDate, setCurrentDate] = useState(new Date());
-  const [projects, setProjects] = useState<ProjectInfo[]>([]);
+  // Any date inside the shown period; periods themselves come from the backend
+  const [anchorDate, setAnchorDate] = useState(toLocalDateString(new Date()));
+  const [range, setRange] = useState<Period | null>(null);
+  const [projects, setProjects] = useState<Project[]>([]);
   const [chartData, setChartData] = useState<DailyChartData[]>([]);
-  const [periodStats, setPeriodStats] = useState<ProjectStats[]>([]);
+  const [breakdown, setBreakdown] = useState<BreakdownDimension>("project");
+  const [roundingMinutes, setRoundingMinutes] = useState(0);
+  const [periodStats, setPeriodStats] = useState<ReportRow[]>([]);
   const [periodTotal, setPeriodTotal] = useState(0);
+  const [editedEntries, setEditedEntries] = useState<EditedEntry[]>([]);
+  const [heatmap, setHeatmap] = useState<Heatmap | null>(null);
+  const [timesheets, setTimesheets] = useState<Timesheet[]>([]);
+  const [timesheetFolder, setTimesheetFolder] = useState("");
+  const [timesheetFormat, setTimesheetFormat] = useState<AppSettings["timesheet_format"]>("pdf");
+  // Open form: "submit" asks for the folder, "unlock" for a reason
+  const [timesheetForm, setTimesheetForm] = useState<"submit" | "unlock" | null>(null);
+  const [unlockReason, setUnlockReason] = useState("");
+  const [timesheetError, setTimesheetError] = useState<string | null>(null);
   const [isLoading, setIsLoading] = useState(true);
 
-  const range = viewMode === "week" ? getWeekRange(currentDate) : getMonthRange(currentDate);
-
   useEffect(() => {
     loadProjects();
+    loadTimesheets();
+    getSettings()
+      .then((settings) => {
+        setTimesheetFolder(settings.timesheet_folder);
+        setTimesheetFormat(settings.timesheet_format);
+      })
+      .catch((error) => console.error("Failed to load settings:", error));
   }, []);
 
   useEffect(() => {
-    if (projects.length > 0) {
-      loadStats();
+    invoke<Period>("get_period", { kind: viewMode, date: anchorDate })
+      .then(setRange)
+      .catch((error) => console.error("Failed to load period:", error));
+  }, [viewMode, anchorDate]);
+
+  useEffect(() => {
+    if (projects.length > 0 && range) {
+      loadStats(range);
     }
-  }, [projects, viewMode, currentDate]);
+  }, [projects, range, breakdown, roundingMinutes]);
 
   async function loadProjects() {
-    const db = await getDb();
-    const result = await db.select<ProjectInfo[]>("SELECT id, name, color FROM projects ORDER BY name");
-    setProjects(result);
+    setProjects(await getProjects());
+  }
+
+  async function loadTimesheets() {
+    try {
+      setTimesheets(await invoke<Timesheet[]>("list_timesheets"));
+    } catch (error) {
+      console.error("Failed to load timesheets:", error);
+    }
+  }
+
+  async function handleSubmitWeek() {
+    if (!range) return;
+    try {
+      await updateSetting("timesheet_folder", timesheetFolder);
+      await updateSetting("timesheet_format", timesheetFormat);
+      await invoke<Timesheet>("submit_week", { date: range.start, folder: timesheetFolder, format: timesheetFormat });
+      setTimesheetForm(null);
+      setTimesheetError(null);
+      await loadTimesheets();
+    } catch (error) {
+      setTimesheetError(String(error));
+    }
   }
 
-  async function loadStats() {
+  async function handleUnlockWeek(id: number) {
+    try {
+      await invoke("unlock_week", { id, reason: unlockReason });
+      setTimesheetForm(null);
+      setUnlockReason("");
+      setTimesheetError(null);
+      await loadTimesheets();
+    } catch (error) {
+      setTimesheetError(String(error));
+    }
+  }
+
+  async function loadStats(range: Period) {
     setIsLoading(true);
     try {
-      const db = await getDb();
-      const startDate = toLocalDateString(range.start);
-      const endDate = toLocalDateString(range.end);
-
-      // Get daily breakdown for chart
-      // Use 'localtime' modifier to convert UTC timestamps to local timezone for grouping
-      const dailyRaw = await db.select<{ date: string; project_id: number; project_name: string; total: number }[]>(
-        `SELECT date(start_time, 'localtime') as date, te.project_id, p.name as project_name, SUM(te.duration) as total
-         FROM time_entries te
-         JOIN projects p ON te.project_id = p.id
-         WHERE date(te.start_time, 'localtime') >= ? AND date(te.start_time, 'localtime') <= ? AND te.end_time IS NOT NULL
-         GROUP BY date(te.start_time, 'localtime'), te.project_id
-         ORDER BY date(te.start_time, 'localtime')`,
-        [startDate, endDate]
-      );
+      const startDate = range.start;
+      const endDate = range.end;
+
+      // Days are bucketed by the backend in the report time zone
+      const daily = await runReport({ start_date: startDate, end_date: endDate, group_by: ["day", "project"] });
 
       // Build chart data with all days in range
       const chartMap = new Map<string, DailyChartData>();
-      const current = new Date(range.start);
-      while (current <= range.end) {
+      const current = parseDateKey(range.start);
+      const last = parseDateKey(range.end);
+      while (current <= last) {
         const dateStr = toLocalDateString(current);
         const dayLabel = current.toLocaleDateString("en-US", {
           weekday: "short",
@@ -143,31 +180,27 @@ export default function ReportsPage() {
       }
 
       // Fill in actual data
-      for (const row of dailyRaw) {
-        const entry = chartMap.get(row.date);
-        if (entry) {
-          entry[row.project_name] = row.total / 3600; // Convert to hours
+      for (const row of daily.rows) {
+        const entry = row.day ? chartMap.get(row.day) : undefined;
+        if (entry && row.project_name) {
+          entry[row.project_name] = row.duration / 3600; // Convert to hours
         }
       }
 
       setChartData(Array.from(chartMap.values()));
 
-      // Get period totals by project
-      const periodRaw = await db.select<ProjectStats[]>(
-        `SELECT te.project_id, p.name as project_name, p.color as project_color,
-                SUM(te.duration) as total_duration
-         FROM time_entries te
-         JOIN projects p ON te.project_id = p.id
-         WHERE date(te.start_time, 'localtime') >= ? AND date(te.start_time, 'localtime') <= ? AND te.end_time IS NOT NULL
-         GROUP BY te.project_id
-         ORDER BY total_duration DESC`,
-        [startDate, endDate]
-      );
-      setPeriodStats(periodRaw);
-
-      // Calculate total
-      const total = periodRaw.reduce((sum, p) => sum + p.total_duration, 0);
-      setPeriodTotal(total);
+      // Period totals for the chosen breakdown, rounded per entry
+      const totals = await runReport({
+        start_date: startDate,
+        end_date: endDate,
+        group_by: [breakdown],
+        rounding: roundingMinutes > 0 ? { minutes: roundingMinutes, mode: "up", scope: "entry" } : null,
+      });
+      setPeriodStats(totals.rows.sort((a, b) => b.rounded_duration - a.rounded_duration));
+      setPeriodTotal(totals.rounded_duration);
+
+      setEditedEntries(await invoke<EditedEntry[]>("get_edited_entries", { startDate, endDate }));
+      setHeatmap(await invoke<Heatmap>("get_heatmap", { startDate, endDate }));
     } catch (error) {
       console.error("Failed to load stats:", error);
     } finally {
@@ -175,25 +208,28 @@ export default function ReportsPage() {
     }
   }
 
-  function navigate(direction: -1 | 1) {
-    const newDate = new Date(currentDate);
-    if (viewMode === "week") {
-      newDate.setDate(newDate.getDate() + direction * 7);
-    } else {
-      newDate.setMonth(newDate.getMonth() + direction);
+  async function navigate(direction: -1 | 1) {
+    if (!range) return;
+    try {
+      const next = await invoke<Period>("get_period", { kind: viewMode, date: range.start, offset: direction });
+      setAnchorDate(next.start);
+    } catch (error) {
+      console.error("Failed to load period:", error);
     }
-    setCurrentDate(newDate);
   }
 
   function goToToday() {
-    setCurrentDate(new Date());
+    setAnchorDate(toLocalDateString(new Date()));
   }
 
-  const isCurrentPeriod = viewMode === "week"
-    ? getWeekRange(new Date()).label === range.label
-    : getMonthRange(new Date()).label === range.label;
+  const submitted = viewMode === "week" && range
+    ? timesheets.find((sheet) => sheet.start_date === range.start && !sheet.unlocked_at)
+    : undefined;
+
+  const today = toLocalDateString(new Date());
+  const isCurrentPeriod = !range || (range.start <= today && today <= range.end);
 
-  if (isLoading && projects.length === 0) {
+  if ((isLoading && projects.length === 0) || !range) {
     return (
       <div className="flex items-center justify-center h-full">
         <span className="text-gray-400">Loading...</span>
@@ -211,26 +247,19 @@ export default function ReportsPage() {
       {/* View mode toggle and navigation */}
       <div className="flex items-center justify-between mb-6">
         <div className="flex items-center gap-2">
-          <button
-            onClick={() => setViewMode("week")}
-            className={`px-4 py-1.5 rounded-lg text-sm font-medium transition-colors ${
-              viewMode === "week"
-                ? "bg-[#5BA4C4] text-white"
-                : "bg-[#252525] text-gray-300 hover:bg-[#303030]"
-            }`}
-          >
-            Week
-          </button>
-          <button
-            onClick={() => setViewMode("month")}
-            className={`px-4 py-1.5 rounded-lg text-sm font-medium transition-colors ${
-              viewMode === "month"
-                ? "bg-[#5BA4C4] text-white"
-                : "bg-[#252525] text-gray-300 hover:bg-[#303030]"
-            }`}
-          >
-            Month
-          </button>
+          {VIEW_MODES.map((mode) => (
+            <button
+              key={mode.id}
+              onClick={() => setViewMode(mode.id)}
+              className={`px-4 py-1.5 rounded-lg text-sm font-medium transition-colors ${
+                viewMode === mode.id
+                  ? "bg-[#5BA4C4] text-white"
+                  : "bg-[#252525] text-gray-300 hover:bg-[#303030]"
+              }`}
+            >
+              {mode.label}
+            </button>
+          ))}
         </div>
 
         <div className="flex items-center gap-3">
@@ -261,9 +290,85 @@ export default function ReportsPage() {
       {/* Summary card */}
       <div className="bg-[#252525] rounded-xl p-5 mb-6">
         <div className="text-sm text-gray-400 mb-1">
-          Total {viewMode === "week" ? "this week" : "this month"}
+          Total this {VIEW_MODES.find((mode) => mode.id === viewMode)?.noun}
         </div>
         <div className="text-3xl font-semibold">{formatHours(periodTotal)}</div>
+        {viewMode === "week" && (
+          <div className="mt-4 pt-4 border-t border-white/5 text-sm">
+            {submitted ? (
+              <div className="flex items-center gap-3">
+                <Lock size={14} className="text-gray-400" />
+                <span className="flex-1 text-gray-400">
+                  Submitted {new Date(submitted.submitted_at + "Z").toLocaleString()}
+                  {submitted.file_path && <span className="text-gray-500"> to {submitted.file_path}</span>}
+                </span>
+                {timesheetForm !== "unlock" && (
+                  <button
+                    onClick={() => setTimesheetForm("unlock")}
+                    className="px-3 py-1.5 rounded-lg bg-[#1a1a1a] text-gray-300 hover:bg-[#303030] transition-colors"
+                  >
+                    Unlock
+                  </button>
+                )}
+              </div>
+            ) : timesheetForm !== "submit" ? (
+              <button
+                onClick={() => setTimesheetForm("submit")}
+                className="px-3 py-1.5 rounded-lg bg-[#1a1a1a] text-gray-300 hover:bg-[#303030] transition-colors"
+              >
+                Submit week
+              </button>
+            ) : null}
+            {timesheetForm === "submit" && !submitted && (
+              <div className="flex items-center gap-2">
+                <input
+                  type="text"
+                  value={timesheetFolder}
+                  onChange={(e) => setTimesheetFolder(e.target.value)}
+                  placeholder="/path/to/timesheets"
+                  className="flex-1 bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-1.5 focus:outline-none focus:border-blue-500"
+                />
+                <select
+                  value={timesheetFormat}
+                  onChange={(e) => setTimesheetFormat(e.target.value as AppSettings["timesheet_format"])}
+                  className="bg-[#1a1a1a] border border-white/10 rounded-lg px-2 py-1.5 focus:outline-none focus:border-blue-500"
+                >
+                  <option value="pdf">PDF</option>
+                  <option value="html">HTML</option>
+                  <option value="markdown">Markdown</option>
+                  <option value="csv">CSV</option>
+                </select>
+                <button onClick={handleSubmitWeek} className="px-3 py-1.5 rounded-lg bg-[#5BA4C4] text-white">
+                  Submit and lock
+                </button>
+                <button onClick={() => setTimesheetForm(null)} className="px-3 py-1.5 rounded-lg text-gray-400 hover:bg-white/10">
+                  Cancel
+                </button>
+              </div>
+            )}
+            {timesheetForm === "unlock" && submitted && (
+              <div className="flex items-center gap-2 mt-3">
+                <input
+                  type="text"
+                  value={unlockReason}
+                  onChange={(e) => setUnlockReason(e.target.value)}
+                  placeholder="Reason for unlocking"
+                  className="flex-1 bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-1.5 focus:outline-none focus:border-blue-500"
+                />
+                <button
+                  onClick={() => handleUnlockWeek(submitted.id)}
+                  className="px-3 py-1.5 rounded-lg bg-red-600 hover:bg-red-700 text-white"
+                >
+                  Unlock
+                </button>
+                <button onClick={() => setTimesheetForm(null)} className="px-3 py-1.5 rounded-lg text-gray-400 hover:bg-white/10">
+                  Cancel
+                </button>
+              </div>
+            )}
+            {timesheetError && <div className="text-red-400 mt-2">{timesheetError}</div>}
+          </div>
+        )}
       </div>
 
       {/* Stacked bar chart */}
@@ -317,17 +422,43 @@ export default function ReportsPage() {
         )}
       </div>
 
-      {/* Project breakdown for period */}
+      {/* Breakdown for period */}
       <div className="bg-[#252525] rounded-xl overflow-hidden">
-        <h2 className="text-lg font-medium px-5 pt-5 pb-3">By Project</h2>
+        <div className="flex items-center justify-between px-5 pt-5 pb-3">
+          <div className="flex items-center gap-2">
+            {BREAKDOWNS.map((option) => (
+              <button
+                key={option.id}
+                onClick={() => setBreakdown(option.id)}
+                className={`text-lg font-medium transition-colors ${
+                  breakdown === option.id ? "text-white" : "text-gray-500 hover:text-gray-300"
+                }`}
+              >
+                {option.label}
+              </button>
+            ))}
+          </div>
+          <select
+            value={roundingMinutes}
+            onChange={(e) => setRoundingMinutes(Number(e.target.value))}
+            className="bg-[#1a1a1a] border border-white/10 rounded-lg px-2 py-1 text-sm focus:outline-none focus:border-blue-500"
+          >
+            {ROUNDING_OPTIONS.map((minutes) => (
+              <option key={minutes} value={minutes}>
+                {minutes === 0 ? "Exact" : `Round up to ${minutes} min`}
+              </option>
+            ))}
+          </select>
+        </div>
         {periodStats.length === 0 ? (
           <div className="text-gray-400 text-center py-8">No tracked time for this period</div>
         ) : (
           periodStats.map((proj, index) => {
-            const percentage = periodTotal > 0 ? (proj.total_duration / periodTotal) * 100 : 0;
+            const percentage = periodTotal > 0 ? (proj.rounded_duration / periodTotal) * 100 : 0;
+            const color = proj.project_color ?? "#6b7280";
             return (
               <div
-                key={proj.project_id}
+                key={proj.labels.join("/")}
                 className={`px-5 py-4 ${
                   index !== periodStats.length - 1 ? "border-b border-white/5" : ""
                 }`}
@@ -335,17 +466,17 @@ export default function ReportsPage() {
                 <div className="flex items-center gap-3 mb-2">
                   <span
                     className="w-3 h-3 rounded-full shrink-0"
-                    style={{ backgroundColor: proj.project_color }}
+                    style={{ backgroundColor: color }}
                   />
-                  <span className="flex-1 font-medium">{proj.project_name}</span>
+                  <span className="flex-1 font-medium">{proj.labels.join(" / ")}</span>
                   <span className="text-gray-400 text-sm">{percentage.toFixed(0)}%</span>
-                  <span className="font-medium w-20 text-right">{formatDuration(proj.total_duration)}</span>
+                  <span className="font-medium w-20 text-right">{formatDuration(proj.rounded_duration)}</span>
                 </div>
                 <div className="h-1.5 bg-[#1a1a1a] rounded-full overflow-hidden ml-6">
                   <div
                     className="h-full rounded-full transition-all"
                     style={{
-                      backgroundColor: proj.project_color,
+                      backgroundColor: color,
                       width: `${percentage}%`,
                     }}
                   />
@@ -355,6 +486,109 @@ export default function ReportsPage() {
           })
         )}
       </div>
+
+      {/* When time was tracked */}
+      {heatmap && heatmap.total > 0 && (
+        <div className="bg-[#252525] rounded-xl p-5 mt-6">
+          <h2 className="text-lg font-medium mb-4">When You Work</h2>
+          <div className="space-y-1">
+            {Array.from({ length: 7 }, (_, i) => (heatmap.week_start + i) % 7).map((weekday) => (
+              <div key={weekday} className="flex items-center gap-1">
+                <span className="w-10 text-xs text-gray-400">{WEEKDAY_LABELS[weekday]}</span>
+                {heatmap.matrix[weekday].map((minutes, hour) => (
+                  <div
+                    key={hour}
+                    className="flex-1 h-5 rounded-sm bg-[#5BA4C4]"
+                    style={{ opacity: minutes > 0 ? 0.15 + 0.85 * (minutes / heatmap.max) : 0.05 }}
+                    title={`${WEEKDAY_LABELS[weekday]} ${String(hour).padStart(2, "0")}:00 - ${formatDuration(minutes * 60)}`}
+                  />
+                ))}
+              </div>
+            ))}
+            <div className="flex items-center gap-1 text-xs text-gray-500">
+              <span className="w-10" />
+              {Array.from({ length: 24 }, (_, hour) => (
+                <span key={hour} className="flex-1 text-center">{hour % 3 === 0 ? hour : ""}</span>
+              ))}
+            </div>
+          </div>
+          <h3 className="text-sm font-medium text-gray-400 mt-6 mb-2">Hour of day by project</h3>
+          <div className="h-48">
+            <ResponsiveContainer width="100%" height="100%">
+              <BarChart
+                data={Array.from({ length: 24 }, (_, hour) => {
+                  const point: { [key: string]: number | string } = { hour: String(hour).padStart(2, "0") };
+                  for (const project of heatmap.projects) {
+                    point[project.project_name] = project.hours[hour] / 60;
+                  }
+                  return point;
+                })}
+                margin={{ top: 10, right: 10, left: -10, bottom: 0 }}
+              >
+                <XAxis dataKey="hour" tick={{ fill: "#9ca3af", fontSize: 12 }} axisLine={{ stroke: "#374151" }} tickLine={false} />
+                <YAxis
+                  tick={{ fill: "#9ca3af", fontSize: 12 }}
+                  axisLine={{ stroke: "#374151" }}
+                  tickLine={false}
+                  tickFormatter={(value) => `${value}h`}
+                />
+                <Tooltip
+                  contentStyle={{
+                    backgroundColor: "#1a1a1a",
+                    border: "1px solid #374151",
+                    borderRadius: "8px",
+                    fontSize: "13px",
+                  }}
+                  labelStyle={{ color: "#fff", marginBottom: "4px" }}
+                  formatter={(value: number, name: string) => [`${value.toFixed(1)}h`, name]}
+                />
+                {heatmap.projects.map((project) => (
+                  <Bar key={project.project_id} dataKey={project.project_name} stackId="a" fill={project.project_color} />
+                ))}
+              </BarChart>
+            </ResponsiveContainer>
+          </div>
+        </div>
+      )}
+
+      {/* Entries changed after they were tracked */}
+      {editedEntries.length > 0 && (
+        <div className="bg-[#252525] rounded-xl overflow-hidden mt-6">
+          <h2 className="text-lg font-medium px-5 pt-5 pb-3">Edited Entries</h2>
+          <div className="px-5 pb-3 text-sm text-gray-400">
+            Tracked {formatDuration(editedEntries.reduce((sum, e) => sum + e.original_duration, 0))}, now{" "}
+            {formatDuration(editedEntries.reduce((sum, e) => sum + e.current_duration, 0))}
+          </div>
+          {editedEntries.map((entry, index) => {
+            const difference = entry.current_duration - entry.original_duration;
+            return (
+              <div
+                key={entry.entry_id}
+                className={`px-5 py-3 flex items-center gap-3 text-sm ${
+                  index !== editedEntries.length - 1 ? "border-b border-white/5" : ""
+                }`}
+              >
+                <span
+                  className="w-3 h-3 rounded-full shrink-0"
+                  style={{ backgroundColor: entry.project_color }}
+                />
+                <span className="flex-1">
+                  {entry.project_name}
+                  <span className="text-gray-500 ml-2">
+                    {new Date(entry.start_time + "Z").toLocaleDateString("en-US", { weekday: "short", day: "numeric", month: "short" })}
+                  </span>
+                </span>
+                <span className="text-gray-500">{entry.edits} {entry.edits === 1 ? "edit" : "edits"}</span>
+                <span className="text-gray-400 w-20 text-right line-through">{formatDuration(entry.original_duration)}</span>
+                <span className="font-medium w-20 text-right">{formatDuration(entry.current_duration)}</span>
+                <span className={`w-16 text-right ${difference > 0 ? "text-amber-400" : "text-gray-400"}`}>
+                  {difference >= 0 ? "+" : "-"}{formatDuration(Math.abs(difference))}
+                </span>
+              </div>
+            );
+          })}
+        </div>
+      )}
     </div>
   );
 }
@@ -1,7 +1,7 @@
 import { useEffect, useState } from "react";
-import { Plus, Pencil, Trash2, X, Check, AlertTriangle, FolderKanban, Settings2, Bell, Pipette } from "lucide-react";
+import { Plus, Pencil, Trash2, X, Check, AlertTriangle, FolderKanban, Settings2, Bell, Pipette, Wand2, GitBranch, FileText, Play, Archive, Lock, RefreshCw, RotateCcw, ShieldCheck } from "lucide-react";
 import { invoke } from "@tauri-apps/api/core";
-import { getProjects, createProject, updateProject, deleteProject, Project, getSettings, updateSetting, AppSettings } from "../../lib/db";
+import { getProjects, createProject, updateProject, deleteProject, Project, getSettings, updateSetting, exportSettings, importSettings, AppSettings, BreakStats, DayOff, ReminderSchedule, ActivityRule, ProjectRepo, TimeZones, ReportJob, ReportDimension, BackupFile, SyncStatus, SyncSummary, SyncedRow, DatabaseLock, closeDb } from "../../lib/db";
 
 const WEEKDAYS = [
   { value: 1, label: "Mon" },
@@ -13,6 +13,9 @@ const WEEKDAYS = [
   { value: 0, label: "Sun" },
 ];
 
+// Indexed like the week_start setting: 0 = Sunday
+const WEEK_START_DAYS = ["Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday"];
+
 const PRESET_COLORS = [
   "#3B82F6", // blue
   "#22C55E", // green
@@ -24,7 +27,38 @@ const PRESET_COLORS = [
   "#F97316", // orange
 ];
 
-type TabId = "projects" | "general" | "reminders";
+type TabId = "projects" | "general" | "reminders" | "rules" | "scheduled" | "backups" | "sync" | "encryption";
+
+// Groupings offered for scheduled reports, keyed by their joined dimensions
+const REPORT_GROUPINGS: { value: ReportDimension[]; label: string }[] = [
+  { value: ["project"], label: "Project" },
+  { value: ["client", "project"], label: "Client and project" },
+  { value: ["day", "project"], label: "Day and project" },
+  { value: ["tag"], label: "Tag" },
+  { value: ["weekday"], label: "Weekday" },
+];
+
+const EMPTY_JOB: ReportJob = {
+  name: "",
+  spec: { group_by: ["project"] },
+  format: "markdown",
+  schedule: "weekly",
+  weekday: 5,
+  time: "17:00",
+  folder: "",
+  enabled: true,
+};
+
+const EMPTY_RULE: ActivityRule = {
+  field: "title",
+  operator: "contains",
+  pattern: "",
+  project_id: null,
+  tag: null,
+  mode: "suggest",
+  enabled: true,
+  priority: 0,
+};
 
 interface ToggleProps {
   checked: boolean;
@@ -58,10 +92,35 @@ export default function SettingsPage() {
   const [editingId, setEditingId] = useState<number | null>(null);
   const [editName, setEditName] = useState("");
   const [editColor, setEditColor] = useState("");
+  const [editClient, setEditClient] = useState("");
   const [isAdding, setIsAdding] = useState(false);
   const [newName, setNewName] = useState("");
   const [newColor, setNewColor] = useState(PRESET_COLORS[0]);
   const [deleteConfirm, setDeleteConfirm] = useState<Project | null>(null);
+  const [breakStats, setBreakStats] = useState<BreakStats | null>(null);
+  const [daysOff, setDaysOff] = useState<DayOff[]>([]);
+  const [icsPath, setIcsPath] = useState("");
+  const [rules, setRules] = useState<ActivityRule[]>([]);
+  const [newRule, setNewRule] = useState<ActivityRule>(EMPTY_RULE);
+  const [repos, setRepos] = useState<ProjectRepo[]>([]);
+  const [repoPath, setRepoPath] = useState("");
+  const [repoError, setRepoError] = useState<string | null>(null);
+  const [timeZones, setTimeZones] = useState<TimeZones | null>(null);
+  const [jobs, setJobs] = useState<ReportJob[]>([]);
+  const [newJob, setNewJob] = useState<ReportJob>(EMPTY_JOB);
+  const [jobStatus, setJobStatus] = useState<string | null>(null);
+  const [backups, setBackups] = useState<BackupFile[]>([]);
+  const [backupStatus, setBackupStatus] = useState<string | null>(null);
+  const [restoreTarget, setRestoreTarget] = useState<BackupFile | null>(null);
+  const [restorePassphrase, setRestorePassphrase] = useState("");
+  const [syncStatus, setSyncStatus] = useState<SyncStatus | null>(null);
+  const [syncMessage, setSyncMessage] = useState<string | null>(null);
+  const [databaseLock, setDatabaseLock] = useState<DatabaseLock | null>(null);
+  const [newPassphrase, setNewPassphrase] = useState("");
+  const [confirmPassphrase, setConfirmPassphrase] = useState("");
+  const [rememberPassphrase, setRememberPassphrase] = useState(true);
+  const [encryptionMessage, setEncryptionMessage] = useState<string | null>(null);
+  const [settingsFileMessage, setSettingsFileMessage] = useState<string | null>(null);
 
   useEffect(() => {
     loadData();
@@ -75,6 +134,33 @@ export default function SettingsPage() {
       ]);
       setProjects(projectsData);
       setSettings(settingsData);
+      invoke<BreakStats>("get_break_stats", { days: 7 })
+        .then(setBreakStats)
+        .catch((error) => console.error("Failed to load break stats:", error));
+      invoke<DayOff[]>("list_days_off")
+        .then(setDaysOff)
+        .catch((error) => console.error("Failed to load days off:", error));
+      invoke<ActivityRule[]>("list_activity_rules")
+        .then(setRules)
+        .catch((error) => console.error("Failed to load rules:", error));
+      invoke<ProjectRepo[]>("list_project_repos")
+        .then(setRepos)
+        .catch((error) => console.error("Failed to load repositories:", error));
+      invoke<TimeZones>("list_timezones")
+        .then(setTimeZones)
+        .catch((error) => console.error("Failed to load time zones:", error));
+      invoke<ReportJob[]>("list_report_jobs")
+        .then(setJobs)
+        .catch((error) => console.error("Failed to load scheduled reports:", error));
+      invoke<BackupFile[]>("list_backups")
+        .then(setBackups)
+        .catch((error) => console.error("Failed to load backups:", error));
+      invoke<SyncStatus>("get_sync_status")
+        .then(setSyncStatus)
+        .catch((error) => console.error("Failed to load sync status:", error));
+      invoke<DatabaseLock>("get_database_lock")
+        .then(setDatabaseLock)
+        .catch((error) => console.error("Failed to load encryption state:", error));
     } catch (error) {
       console.error("Failed to load data:", error);
     } finally {
@@ -98,7 +184,7 @@ export default function SettingsPage() {
   async function handleUpdate() {
     if (!editingId || !editName.trim()) return;
     try {
-      await updateProject(editingId, editName.trim(), editColor);
+      await updateProject(editingId, editName.trim(), editColor, editClient.trim());
       setEditingId(null);
       await loadData();
     } catch (error) {
@@ -106,6 +192,28 @@ export default function SettingsPage() {
     }
   }
 
+  async function handleAddRepo(projectId: number) {
+    if (!repoPath.trim()) return;
+    try {
+      const repo = await invoke<ProjectRepo>("add_project_repo", { projectId, path: repoPath.trim() });
+      setRepos([...repos, repo]);
+      setRepoPath("");
+      setRepoError(null);
+      invoke("sync_git_commits").catch((error) => console.error("Failed to attach commits:", error));
+    } catch (error) {
+      setRepoError(String(error));
+    }
+  }
+
+  async function handleRemoveRepo(id: number) {
+    try {
+      await invoke("remove_project_repo", { id });
+      setRepos(repos.filter((r) => r.id !== id));
+    } catch (error) {
+      console.error("Failed to remove repository:", error);
+    }
+  }
+
   async function handleDelete() {
     if (!deleteConfirm) return;
     try {
@@ -117,35 +225,208 @@ export default function SettingsPage() {
     }
   }
 
-  async function handleSettingChange(key: keyof AppSettings, value: boolean | number | string) {
+  // Reminder settings restart the reminders on the Rust side
+  async function handleSettingChange(key: keyof AppSettings, value: AppSettings[keyof AppSettings]) {
     if (!settings) return;
     try {
-      await updateSetting(key, String(value));
-      const newSettings = { ...settings, [key]: value };
-      setSettings(newSettings);
-
-      // If it's a reminder-related setting, update the reminder system
-      if (key.startsWith("reminder_")) {
-        await updateReminderSystem(newSettings);
-      }
+      setSettings(await updateSetting(key, value));
     } catch (error) {
       console.error("Failed to update setting:", error);
     }
   }
 
-  async function updateReminderSystem(newSettings: AppSettings) {
+  async function updateSchedules(schedules: ReminderSchedule[]) {
+    if (!settings) return;
+    try {
+      setSettings(await updateSetting("reminder_schedules", schedules));
+    } catch (error) {
+      console.error("Failed to update schedules:", error);
+    }
+  }
+
+  function updateSchedule(index: number, changes: Partial<ReminderSchedule>) {
+    if (!settings) return;
+    updateSchedules(
+      settings.reminder_schedules.map((schedule, i) => (i === index ? { ...schedule, ...changes } : schedule))
+    );
+  }
+
+  async function handleSaveRule(rule: ActivityRule) {
+    try {
+      await invoke<number>("save_activity_rule", { rule });
+      setRules(await invoke<ActivityRule[]>("list_activity_rules"));
+      if (!rule.id) setNewRule(EMPTY_RULE);
+    } catch (error) {
+      console.error("Failed to save rule:", error);
+    }
+  }
+
+  async function handleDeleteRule(id: number) {
+    try {
+      await invoke("delete_activity_rule", { id });
+      setRules(rules.filter((r) => r.id !== id));
+    } catch (error) {
+      console.error("Failed to delete rule:", error);
+    }
+  }
+
+  async function handleSaveJob(job: ReportJob) {
+    try {
+      await invoke<number>("save_report_job", { job });
+      setJobs(await invoke<ReportJob[]>("list_report_jobs"));
+      if (!job.id) setNewJob(EMPTY_JOB);
+      setJobStatus(null);
+    } catch (error) {
+      setJobStatus(String(error));
+    }
+  }
+
+  async function handleDeleteJob(id: number) {
+    try {
+      await invoke("delete_report_job", { id });
+      setJobs(jobs.filter((j) => j.id !== id));
+    } catch (error) {
+      console.error("Failed to delete scheduled report:", error);
+    }
+  }
+
+  async function handleRunJob(id: number) {
+    try {
+      const path = await invoke<string>("run_report_job", { id });
+      setJobStatus(`Saved to ${path}`);
+    } catch (error) {
+      setJobStatus(String(error));
+    }
+  }
+
+  async function handleBackupFolderChange(folder: string) {
+    await handleSettingChange("backup_folder", folder);
+    try {
+      setBackups(await invoke<BackupFile[]>("list_backups"));
+    } catch (error) {
+      console.error("Failed to load backups:", error);
+    }
+  }
+
+  async function handleBackupNow() {
+    try {
+      const path = await invoke<string>("backup_now");
+      setBackupStatus(`Saved to ${path}`);
+      setBackups(await invoke<BackupFile[]>("list_backups"));
+    } catch (error) {
+      setBackupStatus(String(error));
+    }
+  }
+
+  async function handleRestore() {
+    if (!restoreTarget) return;
+    try {
+      await invoke("restore_backup", { path: restoreTarget.path, passphrase: restorePassphrase || null });
+      // Everything on screen came from the old data
+      window.location.reload();
+    } catch (error) {
+      setBackupStatus(String(error));
+    }
+  }
+
+  async function handleExportSettings() {
+    try {
+      setSettingsFileMessage(`Saved to ${await exportSettings()}`);
+    } catch (error) {
+      setSettingsFileMessage(String(error));
+    }
+  }
+
+  async function handleImportSettings() {
+    try {
+      setSettings(await importSettings());
+      setSettingsFileMessage("Settings file applied");
+    } catch (error) {
+      setSettingsFileMessage(String(error));
+    }
+  }
+
+  async function handleSyncNow() {
+    try {
+      const summary = await invoke<SyncSummary>("sync_now");
+      setSyncMessage(
+        `Sent ${summary.sent}, received ${summary.received}` +
+          (summary.conflicts ? `, ${summary.conflicts} conflict${summary.conflicts === 1 ? "" : "s"}` : "")
+      );
+      setSyncStatus(await invoke<SyncStatus>("get_sync_status"));
+    } catch (error) {
+      setSyncMessage(String(error));
+    }
+  }
+
+  async function handleResolveConflict(id: number, restore: boolean) {
+    try {
+      await invoke("resolve_sync_conflict", { id, restore });
+      setSyncStatus(await invoke<SyncStatus>("get_sync_status"));
+    } catch (error) {
+      setSyncMessage(String(error));
+    }
+  }
+
+  async function handleEncrypt() {
+    if (newPassphrase !== confirmPassphrase) {
+      setEncryptionMessage("The passphrases don't match");
+      return;
+    }
+    setEncryptionMessage("Encrypting…");
+    try {
+      // The file is replaced, so no connection may stay open on the old one
+      await closeDb();
+      await invoke("encrypt_database", { passphrase: newPassphrase, rememberPassphrase });
+      // Every window reloads once the database-lock-changed event arrives
+    } catch (error) {
+      setEncryptionMessage(String(error));
+    }
+  }
+
+  async function handleLock() {
+    try {
+      await invoke("lock_database");
+    } catch (error) {
+      setEncryptionMessage(String(error));
+    }
+  }
+
+  async function handleForgetPassphrase() {
     try {
-      await invoke("start_reminder", {
-        config: {
-          enabled: newSettings.reminder_enabled,
-          interval_minutes: newSettings.reminder_interval_minutes,
-          start_time: newSettings.reminder_start_time,
-          end_time: newSettings.reminder_end_time,
-          weekdays: newSettings.reminder_weekdays,
-        },
-      });
+      await invoke("forget_database_passphrase");
+      setDatabaseLock(await invoke<DatabaseLock>("get_database_lock"));
     } catch (error) {
-      console.error("Failed to update reminder:", error);
+      setEncryptionMessage(String(error));
+    }
+  }
+
+  async function handleMuteToday() {
+    try {
+      await invoke("mute_reminders_today");
+      setDaysOff(await invoke<DayOff[]>("list_days_off"));
+    } catch (error) {
+      console.error("Failed to mute reminders:", error);
+    }
+  }
+
+  async function handleImportIcs() {
+    if (!icsPath.trim()) return;
+    try {
+      await invoke<number>("import_days_off_ics", { path: icsPath.trim() });
+      setIcsPath("");
+      setDaysOff(await invoke<DayOff[]>("list_days_off"));
+    } catch (error) {
+      console.error("Failed to import calendar:", error);
+    }
+  }
+
+  async function handleRemoveDayOff(date: string) {
+    try {
+      await invoke("remove_day_off", { date });
+      setDaysOff(daysOff.filter((d) => d.date !== date));
+    } catch (error) {
+      console.error("Failed to remove day off:", error);
     }
   }
 
@@ -153,6 +434,9 @@ export default function SettingsPage() {
     setEditingId(project.id);
     setEditName(project.name);
     setEditColor(project.color);
+    setEditClient(project.client ?? "");
+    setRepoPath("");
+    setRepoError(null);
   }
 
   if (isLoading) {
@@ -167,8 +451,26 @@ export default function SettingsPage() {
     { id: "projects" as const, label: "Projects", icon: FolderKanban },
     { id: "general" as const, label: "General", icon: Settings2 },
     { id: "reminders" as const, label: "Reminders", icon: Bell },
+    { id: "rules" as const, label: "Rules", icon: Wand2 },
+    { id: "scheduled" as const, label: "Scheduled Reports", icon: FileText },
+    { id: "backups" as const, label: "Backups", icon: Archive },
+    { id: "sync" as const, label: "Sync", icon: RefreshCw },
+    { id: "encryption" as const, label: "Encryption", icon: ShieldCheck },
   ];
 
+  async function handleWorkdayToggle(dayValue: number) {
+    if (!settings) return;
+    const currentDays = settings.workday_weekdays;
+    const newDays = currentDays.includes(dayValue)
+      ? currentDays.filter((d) => d !== dayValue)
+      : [...currentDays, dayValue].sort((a, b) => a - b);
+    try {
+      setSettings(await updateSetting("workday_weekdays", newDays));
+    } catch (error) {
+      console.error("Failed to update working days:", error);
+    }
+  }
+
   async function handleWeekdayToggle(dayValue: number) {
     if (!settings) return;
     const currentDays = settings.reminder_weekdays;
@@ -178,12 +480,8 @@ export default function SettingsPage() {
     } else {
       newDays = [...currentDays, dayValue].sort((a, b) => a - b);
     }
-    // Save to DB as string
     try {
-      await updateSetting("reminder_weekdays", newDays.join(","));
-      const newSettings = { ...settings, reminder_weekdays: newDays };
-      setSettings(newSettings);
-      await updateReminderSystem(newSettings);
+      setSettings(await updateSetting("reminder_weekdays", newDays));
     } catch (error) {
       console.error("Failed to update weekdays:", error);
     }
@@ -343,6 +641,20 @@ export default function SettingsPage() {
                           <X size={18} />
                         </button>
                       </div>
+                      {/* Client, for grouping reports */}
+                      <div className="mt-3 ml-12">
+                        <input
+                          type="text"
+                          value={editClient}
+                          onChange={(e) => setEditClient(e.target.value)}
+                          onKeyDown={(e) => {
+                            if (e.key === "Enter") handleUpdate();
+                            if (e.key === "Escape") setEditingId(null);
+                          }}
+                          placeholder="Client (optional)"
+                          className="w-full bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-1.5 text-sm focus:outline-none focus:border-blue-500"
+                        />
+                      </div>
                       {/* Color presets */}
                       <div className="flex items-center gap-2 mt-3 ml-12">
                         {PRESET_COLORS.map((color) => (
@@ -376,6 +688,42 @@ export default function SettingsPage() {
                           </button>
                         </div>
                       </div>
+                      {/* Git repositories */}
+                      <div className="mt-3 ml-12 space-y-2">
+                        {repos
+                          .filter((repo) => repo.project_id === project.id)
+                          .map((repo) => (
+                            <div key={repo.id} className="flex items-center gap-2 text-sm text-gray-400">
+                              <GitBranch size={14} className="shrink-0" />
+                              <span className="flex-1 truncate" title={repo.path}>{repo.path}</span>
+                              <button
+                                onClick={() => handleRemoveRepo(repo.id)}
+                                className="p-1 hover:bg-white/10 rounded text-red-400"
+                              >
+                                <X size={14} />
+                              </button>
+                            </div>
+                          ))}
+                        <div className="flex items-center gap-2">
+                          <input
+                            type="text"
+                            value={repoPath}
+                            onChange={(e) => setRepoPath(e.target.value)}
+                            onKeyDown={(e) => {
+                              if (e.key === "Enter") handleAddRepo(project.id);
+                            }}
+                            placeholder="/path/to/repository"
+                            className="flex-1 bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-1.5 text-sm focus:outline-none focus:border-blue-500"
+                          />
+                          <button
+                            onClick={() => handleAddRepo(project.id)}
+                            className="px-3 py-1.5 text-sm bg-white/10 hover:bg-white/15 rounded-lg"
+                          >
+                            Add repository
+                          </button>
+                        </div>
+                        {repoError && <div className="text-xs text-red-400">{repoError}</div>}
+                      </div>
                     </div>
                   ) : (
                     // View mode
@@ -384,7 +732,10 @@ export default function SettingsPage() {
                         className="w-4 h-4 rounded-full shrink-0"
                         style={{ backgroundColor: project.color }}
                       />
-                      <span className="flex-1 font-medium">{project.name}</span>
+                      <span className="flex-1 font-medium">
+                        {project.name}
+                        {project.client && <span className="ml-2 text-sm font-normal text-gray-500">{project.client}</span>}
+                      </span>
                       <button
                         onClick={() => startEditing(project)}
                         className="p-2 hover:bg-white/10 rounded-lg text-gray-400 opacity-0 group-hover:opacity-100 transition-opacity"
@@ -439,7 +790,7 @@ export default function SettingsPage() {
             </div>
 
             {/* Stop timer when idle */}
-            <div className="flex items-center justify-between px-5 py-4">
+            <div className="flex items-center justify-between px-5 py-4 border-b border-white/5">
               <div>
                 <div className="font-medium">Stop timer when idle</div>
                 <div className="text-sm text-gray-400 mt-0.5">
@@ -451,124 +802,1123 @@ export default function SettingsPage() {
                 onChange={(checked) => handleSettingChange("stop_timer_when_idle", checked)}
               />
             </div>
-          </div>
-        </div>
-      )}
 
-      {/* Reminders Tab */}
-      {activeTab === "reminders" && settings && (
-        <div className="space-y-6">
-          <div className="bg-[#252525] rounded-xl overflow-hidden">
-            {/* Enable reminders */}
+            {/* Git watcher */}
             <div className="flex items-center justify-between px-5 py-4 border-b border-white/5">
               <div>
-                <div className="font-medium">Enable tracking reminders</div>
+                <div className="font-medium">Follow git repositories</div>
                 <div className="text-sm text-gray-400 mt-0.5">
-                  Get notified when you're not tracking time during work hours
+                  Switch the running timer to a project when you commit or check out in one of its repositories
                 </div>
               </div>
               <Toggle
-                checked={settings.reminder_enabled}
-                onChange={(checked) => handleSettingChange("reminder_enabled", checked)}
+                checked={settings.git_watch_enabled}
+                onChange={(checked) => handleSettingChange("git_watch_enabled", checked)}
               />
             </div>
 
-            {/* Reminder interval */}
+            {/* Activity log */}
             <div className="flex items-center justify-between px-5 py-4 border-b border-white/5">
               <div>
-                <div className="font-medium">Reminder interval</div>
+                <div className="font-medium">Record activity log</div>
                 <div className="text-sm text-gray-400 mt-0.5">
-                  How often to remind (in minutes)
+                  Sample the active application and window title to help fill gaps (X11 on Linux only)
+                </div>
+              </div>
+              <Toggle
+                checked={settings.activity_tracking_enabled}
+                onChange={(checked) => handleSettingChange("activity_tracking_enabled", checked)}
+              />
+            </div>
+
+            {/* Activity retention */}
+            <div className="flex items-center justify-between px-5 py-4 border-b border-white/5">
+              <div>
+                <div className="font-medium">Keep activity for</div>
+                <div className="text-sm text-gray-400 mt-0.5">
+                  Older samples are deleted automatically
                 </div>
               </div>
               <select
-                value={settings.reminder_interval_minutes}
-                onChange={(e) => {
-                  const value = parseInt(e.target.value, 10);
-                  handleSettingChange("reminder_interval_minutes", value);
-                  setSettings({ ...settings, reminder_interval_minutes: value });
-                }}
-                disabled={!settings.reminder_enabled}
+                value={settings.activity_retention_days}
+                onChange={(e) => handleSettingChange("activity_retention_days", parseInt(e.target.value, 10))}
+                disabled={!settings.activity_tracking_enabled}
                 className={`bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500 ${
-                  !settings.reminder_enabled ? "opacity-50 cursor-not-allowed" : ""
+                  !settings.activity_tracking_enabled ? "opacity-50 cursor-not-allowed" : ""
                 }`}
               >
-                <option value={5}>5 min</option>
-                <option value={10}>10 min</option>
-                <option value={15}>15 min</option>
-                <option value={20}>20 min</option>
-                <option value={30}>30 min</option>
-                <option value={45}>45 min</option>
-                <option value={60}>1 hour</option>
-                <option value={90}>1.5 hours</option>
-                <option value={120}>2 hours</option>
+                <option value={7}>1 week</option>
+                <option value={30}>30 days</option>
+                <option value={90}>90 days</option>
+                <option value={365}>1 year</option>
               </select>
             </div>
 
-            {/* Active hours */}
-            <div className="px-5 py-4 border-b border-white/5">
-              <div className="flex items-center justify-between mb-3">
-                <div>
-                  <div className="font-medium">Active hours</div>
-                  <div className="text-sm text-gray-400 mt-0.5">
-                    Only remind during these hours
-                  </div>
+            {/* Activity exclusions */}
+            <div className="px-5 py-4">
+              <div className="mb-3">
+                <div className="font-medium">Never record</div>
+                <div className="text-sm text-gray-400 mt-0.5">
+                  Applications or window titles containing any of these lines are skipped
                 </div>
               </div>
-              <div className="flex items-center gap-3">
-                <input
-                  type="time"
-                  value={settings.reminder_start_time}
-                  onChange={(e) => {
-                    handleSettingChange("reminder_start_time", e.target.value);
-                    setSettings({ ...settings, reminder_start_time: e.target.value });
-                  }}
-                  disabled={!settings.reminder_enabled}
-                  className={`bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500 ${
-                    !settings.reminder_enabled ? "opacity-50 cursor-not-allowed" : ""
-                  }`}
-                />
-                <span className="text-gray-400">to</span>
-                <input
-                  type="time"
-                  value={settings.reminder_end_time}
-                  onChange={(e) => {
-                    handleSettingChange("reminder_end_time", e.target.value);
-                    setSettings({ ...settings, reminder_end_time: e.target.value });
-                  }}
-                  disabled={!settings.reminder_enabled}
-                  className={`bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500 ${
-                    !settings.reminder_enabled ? "opacity-50 cursor-not-allowed" : ""
-                  }`}
-                />
-              </div>
+              <textarea
+                defaultValue={settings.activity_exclusions}
+                onBlur={(e) => handleSettingChange("activity_exclusions", e.target.value)}
+                disabled={!settings.activity_tracking_enabled}
+                rows={3}
+                placeholder={"KeePassXC\nPrivate Browsing"}
+                className={`w-full bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500 ${
+                  !settings.activity_tracking_enabled ? "opacity-50 cursor-not-allowed" : ""
+                }`}
+              />
             </div>
+          </div>
 
-            {/* Active days */}
-            <div className="px-5 py-4">
-              <div className="mb-3">
-                <div className="font-medium">Active days</div>
+          <div className="bg-[#252525] rounded-xl overflow-hidden">
+            {/* Working hours */}
+            <div className="flex items-center justify-between px-5 py-4">
+              <div>
+                <div className="font-medium">Working hours</div>
                 <div className="text-sm text-gray-400 mt-0.5">
-                  Only remind on these days
+                  Used to find untracked gaps in your day
                 </div>
               </div>
-              <div className="flex gap-2">
-                {WEEKDAYS.map((day) => (
-                  <button
-                    key={day.value}
-                    onClick={() => handleWeekdayToggle(day.value)}
-                    disabled={!settings.reminder_enabled}
-                    className={`px-3 py-1.5 rounded-lg text-sm font-medium transition-colors ${
-                      settings.reminder_weekdays.includes(day.value)
-                        ? "bg-blue-600 text-white"
-                        : "bg-[#1a1a1a] text-gray-400 hover:bg-[#303030]"
-                    } ${!settings.reminder_enabled ? "opacity-50 cursor-not-allowed" : ""}`}
-                  >
-                    {day.label}
-                  </button>
-                ))}
-              </div>
+              <select
+                value={settings.workday_source}
+                onChange={(e) => handleSettingChange("workday_source", e.target.value)}
+                className="bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500"
+              >
+                <option value="reminders">Same as reminder hours</option>
+                <option value="custom">Custom</option>
+              </select>
             </div>
+            {settings.workday_source === "custom" && (
+              <div className="flex items-center justify-between px-5 py-4 border-t border-white/5">
+                <div className="flex items-center gap-2">
+                  <input
+                    type="time"
+                    value={settings.workday_start_time}
+                    onChange={(e) => handleSettingChange("workday_start_time", e.target.value)}
+                    className="bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500"
+                  />
+                  <span className="text-gray-400">to</span>
+                  <input
+                    type="time"
+                    value={settings.workday_end_time}
+                    onChange={(e) => handleSettingChange("workday_end_time", e.target.value)}
+                    className="bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500"
+                  />
+                </div>
+                <div className="flex gap-2">
+                  {WEEKDAYS.map((day) => (
+                    <button
+                      key={day.value}
+                      onClick={() => handleWorkdayToggle(day.value)}
+                      className={`px-3 py-1.5 rounded-lg text-sm font-medium transition-colors ${
+                        settings.workday_weekdays.includes(day.value)
+                          ? "bg-blue-600 text-white"
+                          : "bg-[#1a1a1a] text-gray-400 hover:bg-[#303030]"
+                      }`}
+                    >
+                      {day.label}
+                    </button>
+                  ))}
+                </div>
+              </div>
+            )}
+          </div>
+
+          <div className="bg-[#252525] rounded-xl overflow-hidden">
+            {/* Report time zone */}
+            <div className="flex items-center justify-between px-5 py-4 border-b border-white/5">
+              <div>
+                <div className="font-medium">Report time zone</div>
+                <div className="text-sm text-gray-400 mt-0.5">
+                  Which calendar day an entry counts towards in reports
+                </div>
+              </div>
+              <select
+                value={settings.report_timezone}
+                onChange={(e) => handleSettingChange("report_timezone", e.target.value)}
+                className="bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500 max-w-[240px]"
+              >
+                <option value="">System ({timeZones?.system ?? "local"})</option>
+                <option value="entry">Where each entry was recorded</option>
+                {timeZones?.zones.map((zone) => (
+                  <option key={zone} value={zone}>
+                    {zone}
+                  </option>
+                ))}
+              </select>
+            </div>
+
+            {/* Week start */}
+            <div className="flex items-center justify-between px-5 py-4 border-b border-white/5">
+              <div>
+                <div className="font-medium">Week starts on</div>
+                <div className="text-sm text-gray-400 mt-0.5">
+                  Used for weekly totals and reports
+                </div>
+              </div>
+              <select
+                value={settings.week_start}
+                onChange={(e) => handleSettingChange("week_start", Number(e.target.value))}
+                className="bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500"
+              >
+                {WEEK_START_DAYS.map((day, index) => (
+                  <option key={day} value={index}>
+                    {day}
+                  </option>
+                ))}
+              </select>
+            </div>
+
+            {/* Fiscal calendar */}
+            <div className="flex items-center justify-between px-5 py-4 border-b border-white/5">
+              <div>
+                <div className="font-medium">Fiscal calendar</div>
+                <div className="text-sm text-gray-400 mt-0.5">
+                  Weeks per period in each quarter, and when the fiscal year starts
+                </div>
+              </div>
+              <div className="flex items-center gap-2">
+                <select
+                  value={settings.fiscal_pattern}
+                  onChange={(e) => handleSettingChange("fiscal_pattern", e.target.value)}
+                  className="bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500"
+                >
+                  <option value="4-4-5">4-4-5</option>
+                  <option value="4-5-4">4-5-4</option>
+                  <option value="5-4-4">5-4-4</option>
+                </select>
+                <input
+                  type="text"
+                  value={settings.fiscal_year_start}
+                  onChange={(e) => handleSettingChange("fiscal_year_start", e.target.value)}
+                  placeholder="MM-DD"
+                  className="w-20 bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500"
+                />
+              </div>
+            </div>
+
+            {/* Pay periods */}
+            <div className="flex items-center justify-between px-5 py-4">
+              <div>
+                <div className="font-medium">Pay periods</div>
+                <div className="text-sm text-gray-400 mt-0.5">
+                  Weekly and biweekly periods count from the start date
+                </div>
+              </div>
+              <div className="flex items-center gap-2">
+                <select
+                  value={settings.pay_period}
+                  onChange={(e) => handleSettingChange("pay_period", e.target.value)}
+                  className="bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500"
+                >
+                  <option value="weekly">Weekly</option>
+                  <option value="biweekly">Biweekly</option>
+                  <option value="semimonthly">Twice a month</option>
+                  <option value="monthly">Monthly</option>
+                </select>
+                {(settings.pay_period === "weekly" || settings.pay_period === "biweekly") && (
+                  <input
+                    type="date"
+                    value={settings.pay_period_anchor}
+                    onChange={(e) => handleSettingChange("pay_period_anchor", e.target.value)}
+                    className="bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500"
+                  />
+                )}
+              </div>
+            </div>
+          </div>
+
+          <div className="bg-[#252525] rounded-xl px-5 py-4 space-y-3">
+            <div>
+              <div className="font-medium">Settings file</div>
+              <div className="text-sm text-gray-400 mt-0.5">
+                Keep these settings in settings.toml, e.g. in your dotfiles. Once it exists it is applied on every launch
+                and updated when you change a setting here. Passphrases and tokens are left out.
+              </div>
+            </div>
+            <div className="flex items-center gap-3">
+              <button
+                onClick={handleExportSettings}
+                className="px-4 py-2 rounded-lg bg-white/10 hover:bg-white/15 text-white text-sm transition-colors"
+              >
+                Export
+              </button>
+              <button
+                onClick={handleImportSettings}
+                className="px-4 py-2 rounded-lg bg-white/10 hover:bg-white/15 text-white text-sm transition-colors"
+              >
+                Reload from file
+              </button>
+              {settingsFileMessage && <div className="text-sm text-gray-400">{settingsFileMessage}</div>}
+            </div>
+          </div>
+        </div>
+      )}
+
+      {/* Reminders Tab */}
+      {activeTab === "reminders" && settings && (
+        <div className="space-y-6">
+          <div className="bg-[#252525] rounded-xl overflow-hidden">
+            {/* Enable reminders */}
+            <div className="flex items-center justify-between px-5 py-4 border-b border-white/5">
+              <div>
+                <div className="font-medium">Enable tracking reminders</div>
+                <div className="text-sm text-gray-400 mt-0.5">
+                  Get notified when you're not tracking time during work hours
+                </div>
+              </div>
+              <Toggle
+                checked={settings.reminder_enabled}
+                onChange={(checked) => handleSettingChange("reminder_enabled", checked)}
+              />
+            </div>
+
+            {/* Smart reminders */}
+            <div className="flex items-center justify-between px-5 py-4 border-b border-white/5">
+              <div>
+                <div className="font-medium">Only when active</div>
+                <div className="text-sm text-gray-400 mt-0.5">
+                  Skip reminders while you're away from the computer
+                </div>
+              </div>
+              <Toggle
+                checked={settings.reminder_only_when_active}
+                onChange={(checked) => handleSettingChange("reminder_only_when_active", checked)}
+                disabled={!settings.reminder_enabled}
+              />
+            </div>
+
+            {/* Reminder interval */}
+            <div className="flex items-center justify-between px-5 py-4 border-b border-white/5">
+              <div>
+                <div className="font-medium">Reminder interval</div>
+                <div className="text-sm text-gray-400 mt-0.5">
+                  How often to remind (in minutes)
+                </div>
+              </div>
+              <select
+                value={settings.reminder_interval_minutes}
+                onChange={(e) => {
+                  const value = parseInt(e.target.value, 10);
+                  handleSettingChange("reminder_interval_minutes", value);
+                  setSettings({ ...settings, reminder_interval_minutes: value });
+                }}
+                disabled={!settings.reminder_enabled}
+                className={`bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500 ${
+                  !settings.reminder_enabled ? "opacity-50 cursor-not-allowed" : ""
+                }`}
+              >
+                <option value={5}>5 min</option>
+                <option value={10}>10 min</option>
+                <option value={15}>15 min</option>
+                <option value={20}>20 min</option>
+                <option value={30}>30 min</option>
+                <option value={45}>45 min</option>
+                <option value={60}>1 hour</option>
+                <option value={90}>1.5 hours</option>
+                <option value={120}>2 hours</option>
+              </select>
+            </div>
+
+            {/* Active hours */}
+            <div className="px-5 py-4 border-b border-white/5">
+              <div className="flex items-center justify-between mb-3">
+                <div>
+                  <div className="font-medium">Active hours</div>
+                  <div className="text-sm text-gray-400 mt-0.5">
+                    Only remind during these hours
+                  </div>
+                </div>
+              </div>
+              <div className="flex items-center gap-3">
+                <input
+                  type="time"
+                  value={settings.reminder_start_time}
+                  onChange={(e) => {
+                    handleSettingChange("reminder_start_time", e.target.value);
+                    setSettings({ ...settings, reminder_start_time: e.target.value });
+                  }}
+                  disabled={!settings.reminder_enabled}
+                  className={`bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500 ${
+                    !settings.reminder_enabled ? "opacity-50 cursor-not-allowed" : ""
+                  }`}
+                />
+                <span className="text-gray-400">to</span>
+                <input
+                  type="time"
+                  value={settings.reminder_end_time}
+                  onChange={(e) => {
+                    handleSettingChange("reminder_end_time", e.target.value);
+                    setSettings({ ...settings, reminder_end_time: e.target.value });
+                  }}
+                  disabled={!settings.reminder_enabled}
+                  className={`bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500 ${
+                    !settings.reminder_enabled ? "opacity-50 cursor-not-allowed" : ""
+                  }`}
+                />
+              </div>
+            </div>
+
+            {/* Active days */}
+            <div className="px-5 py-4">
+              <div className="mb-3">
+                <div className="font-medium">Active days</div>
+                <div className="text-sm text-gray-400 mt-0.5">
+                  Only remind on these days
+                </div>
+              </div>
+              <div className="flex gap-2">
+                {WEEKDAYS.map((day) => (
+                  <button
+                    key={day.value}
+                    onClick={() => handleWeekdayToggle(day.value)}
+                    disabled={!settings.reminder_enabled}
+                    className={`px-3 py-1.5 rounded-lg text-sm font-medium transition-colors ${
+                      settings.reminder_weekdays.includes(day.value)
+                        ? "bg-blue-600 text-white"
+                        : "bg-[#1a1a1a] text-gray-400 hover:bg-[#303030]"
+                    } ${!settings.reminder_enabled ? "opacity-50 cursor-not-allowed" : ""}`}
+                  >
+                    {day.label}
+                  </button>
+                ))}
+              </div>
+            </div>
+
+            {/* Additional windows */}
+            <div className="px-5 py-4 border-t border-white/5">
+              <div className="flex items-center justify-between mb-3">
+                <div>
+                  <div className="font-medium">Additional windows</div>
+                  <div className="text-sm text-gray-400 mt-0.5">
+                    Extra reminder hours, e.g. an evening shift. An end before the start runs past midnight.
+                  </div>
+                </div>
+                <button
+                  onClick={() =>
+                    updateSchedules([
+                      ...settings.reminder_schedules,
+                      { weekdays: [1, 2, 3, 4, 5], start_time: "22:00", end_time: "02:00", timezone: null },
+                    ])
+                  }
+                  disabled={!settings.reminder_enabled}
+                  className={`flex items-center gap-2 px-3 py-1.5 bg-[#1a1a1a] hover:bg-[#303030] rounded-lg text-sm font-medium transition-colors ${
+                    !settings.reminder_enabled ? "opacity-50 cursor-not-allowed" : ""
+                  }`}
+                >
+                  <Plus size={16} />
+                  Add window
+                </button>
+              </div>
+              {settings.reminder_schedules.map((schedule, index) => (
+                <div key={index} className="py-3 border-t border-white/5 first:border-t-0">
+                  <div className="flex items-center gap-3 mb-2">
+                    <input
+                      type="time"
+                      value={schedule.start_time}
+                      onChange={(e) => updateSchedule(index, { start_time: e.target.value })}
+                      className="bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500"
+                    />
+                    <span className="text-gray-400">to</span>
+                    <input
+                      type="time"
+                      value={schedule.end_time}
+                      onChange={(e) => updateSchedule(index, { end_time: e.target.value })}
+                      className="bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500"
+                    />
+                    <input
+                      type="text"
+                      value={schedule.timezone ?? ""}
+                      onChange={(e) => updateSchedule(index, { timezone: e.target.value || null })}
+                      placeholder="Time zone (e.g. Europe/Oslo)"
+                      className="flex-1 bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500"
+                    />
+                    <button
+                      onClick={() => updateSchedules(settings.reminder_schedules.filter((_, i) => i !== index))}
+                      className="p-2 hover:bg-white/10 rounded-lg text-red-400"
+                    >
+                      <Trash2 size={16} />
+                    </button>
+                  </div>
+                  <div className="flex gap-2">
+                    {WEEKDAYS.map((day) => (
+                      <button
+                        key={day.value}
+                        onClick={() =>
+                          updateSchedule(index, {
+                            weekdays: schedule.weekdays.includes(day.value)
+                              ? schedule.weekdays.filter((d) => d !== day.value)
+                              : [...schedule.weekdays, day.value].sort((a, b) => a - b),
+                          })
+                        }
+                        className={`px-3 py-1.5 rounded-lg text-sm font-medium transition-colors ${
+                          schedule.weekdays.includes(day.value)
+                            ? "bg-blue-600 text-white"
+                            : "bg-[#1a1a1a] text-gray-400 hover:bg-[#303030]"
+                        }`}
+                      >
+                        {day.label}
+                      </button>
+                    ))}
+                  </div>
+                </div>
+              ))}
+            </div>
+          </div>
+
+          <div className="bg-[#252525] rounded-xl overflow-hidden">
+            {/* Mute for today */}
+            <div className="flex items-center justify-between px-5 py-4 border-b border-white/5">
+              <div>
+                <div className="font-medium">Days off</div>
+                <div className="text-sm text-gray-400 mt-0.5">
+                  No reminders on vacations, holidays or muted days
+                </div>
+              </div>
+              <button
+                onClick={handleMuteToday}
+                className="px-3 py-1.5 bg-[#1a1a1a] hover:bg-[#303030] rounded-lg text-sm font-medium transition-colors"
+              >
+                Not working today
+              </button>
+            </div>
+
+            {/* Import vacation calendar */}
+            <div className="flex items-center gap-3 px-5 py-4 border-b border-white/5">
+              <input
+                type="text"
+                value={icsPath}
+                onChange={(e) => setIcsPath(e.target.value)}
+                placeholder="Path to .ics calendar file"
+                className="flex-1 bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500"
+                onKeyDown={(e) => {
+                  if (e.key === "Enter") handleImportIcs();
+                }}
+              />
+              <button
+                onClick={handleImportIcs}
+                className="px-3 py-1.5 bg-blue-600 hover:bg-blue-700 rounded-lg text-sm font-medium transition-colors"
+              >
+                Import
+              </button>
+            </div>
+
+            {/* Upcoming days off */}
+            {daysOff.length === 0 ? (
+              <div className="text-gray-400 text-sm px-5 py-4">No upcoming days off</div>
+            ) : (
+              daysOff.map((day) => (
+                <div key={day.date} className="flex items-center gap-4 px-5 py-2 group">
+                  <span className="text-sm tabular-nums">{day.date}</span>
+                  <span className="flex-1 text-sm text-gray-400">{day.label}</span>
+                  <button
+                    onClick={() => handleRemoveDayOff(day.date)}
+                    className="p-1.5 hover:bg-white/10 rounded-lg text-red-400 opacity-0 group-hover:opacity-100 transition-opacity"
+                  >
+                    <X size={14} />
+                  </button>
+                </div>
+              ))
+            )}
+          </div>
+
+          <div className="bg-[#252525] rounded-xl overflow-hidden">
+            {/* Enable break reminders */}
+            <div className="flex items-center justify-between px-5 py-4 border-b border-white/5">
+              <div>
+                <div className="font-medium">Enable break reminders</div>
+                <div className="text-sm text-gray-400 mt-0.5">
+                  Get notified after a long stretch of continuous work
+                </div>
+              </div>
+              <Toggle
+                checked={settings.reminder_break_enabled}
+                onChange={(checked) => handleSettingChange("reminder_break_enabled", checked)}
+              />
+            </div>
+
+            {/* Break reminder trigger */}
+            <div className="flex items-center justify-between px-5 py-4 border-b border-white/5">
+              <div>
+                <div className="font-medium">Suggest a break after</div>
+                <div className="text-sm text-gray-400 mt-0.5">
+                  Continuous time before you get a break reminder
+                </div>
+              </div>
+              <div className="flex items-center gap-3">
+                <select
+                  value={settings.reminder_break_mode}
+                  onChange={(e) => handleSettingChange("reminder_break_mode", e.target.value)}
+                  disabled={!settings.reminder_break_enabled}
+                  className={`bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500 ${
+                    !settings.reminder_break_enabled ? "opacity-50 cursor-not-allowed" : ""
+                  }`}
+                >
+                  <option value="tracked">Tracking</option>
+                  <option value="activity">Activity</option>
+                </select>
+                <select
+                  value={settings.reminder_break_after_minutes}
+                  onChange={(e) => handleSettingChange("reminder_break_after_minutes", parseInt(e.target.value, 10))}
+                  disabled={!settings.reminder_break_enabled}
+                  className={`bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500 ${
+                    !settings.reminder_break_enabled ? "opacity-50 cursor-not-allowed" : ""
+                  }`}
+                >
+                  <option value={25}>25 min</option>
+                  <option value={45}>45 min</option>
+                  <option value={50}>50 min</option>
+                  <option value={60}>1 hour</option>
+                  <option value={90}>1.5 hours</option>
+                  <option value={120}>2 hours</option>
+                </select>
+              </div>
+            </div>
+
+            {/* Break snooze */}
+            <div className="flex items-center justify-between px-5 py-4">
+              <div>
+                <div className="font-medium">Snooze duration</div>
+                <div className="text-sm text-gray-400 mt-0.5">
+                  {breakStats
+                    ? `${breakStats.breaks_taken} breaks taken, ${breakStats.reminders_snoozed} snoozed in the last 7 days`
+                    : "How long to wait before reminding again"}
+                </div>
+              </div>
+              <select
+                value={settings.reminder_break_snooze_minutes}
+                onChange={(e) => handleSettingChange("reminder_break_snooze_minutes", parseInt(e.target.value, 10))}
+                disabled={!settings.reminder_break_enabled}
+                className={`bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500 ${
+                  !settings.reminder_break_enabled ? "opacity-50 cursor-not-allowed" : ""
+                }`}
+              >
+                <option value={5}>5 min</option>
+                <option value={10}>10 min</option>
+                <option value={15}>15 min</option>
+                <option value={30}>30 min</option>
+              </select>
+            </div>
+          </div>
+        </div>
+      )}
+
+      {/* Rules Tab */}
+      {activeTab === "rules" && (
+        <div className="space-y-6">
+          <p className="text-sm text-gray-400">
+            Rules match the activity log (enable it under General) and suggest or switch projects, or tag the running entry.
+          </p>
+          <div className="bg-[#252525] rounded-xl overflow-hidden">
+            {[...rules, newRule].map((rule) => {
+              const isNew = !rule.id;
+              const update = (changes: Partial<ActivityRule>) => {
+                const updated = { ...rule, ...changes };
+                if (isNew) {
+                  setNewRule(updated);
+                } else {
+                  setRules(rules.map((r) => (r.id === rule.id ? updated : r)));
+                }
+              };
+              const selectClass =
+                "bg-[#1a1a1a] border border-white/10 rounded-lg px-2 py-2 text-sm focus:outline-none focus:border-blue-500";
+              return (
+                <div key={rule.id ?? "new"} className="flex items-center gap-2 px-5 py-3 border-b border-white/5 last:border-b-0">
+                  <select value={rule.field} onChange={(e) => update({ field: e.target.value as ActivityRule["field"] })} className={selectClass}>
+                    <option value="title">Title</option>
+                    <option value="app">App</option>
+                  </select>
+                  <select value={rule.operator} onChange={(e) => update({ operator: e.target.value as ActivityRule["operator"] })} className={selectClass}>
+                    <option value="contains">contains</option>
+                    <option value="equals">is</option>
+                    <option value="starts_with">starts with</option>
+                  </select>
+                  <input
+                    type="text"
+                    value={rule.pattern}
+                    onChange={(e) => update({ pattern: e.target.value })}
+                    placeholder="Text"
+                    className="flex-1 min-w-0 bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500"
+                  />
+                  <select
+                    value={rule.project_id ?? ""}
+                    onChange={(e) => update({ project_id: e.target.value ? Number(e.target.value) : null })}
+                    className={selectClass}
+                  >
+                    <option value="">No project</option>
+                    {projects.map((project) => (
+                      <option key={project.id} value={project.id}>{project.name}</option>
+                    ))}
+                  </select>
+                  <input
+                    type="text"
+                    value={rule.tag ?? ""}
+                    onChange={(e) => update({ tag: e.target.value || null })}
+                    placeholder="Tag"
+                    className="w-24 bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500"
+                  />
+                  <select value={rule.mode} onChange={(e) => update({ mode: e.target.value as ActivityRule["mode"] })} className={selectClass}>
+                    <option value="suggest">Suggest</option>
+                    <option value="auto">Switch</option>
+                  </select>
+                  <button onClick={() => handleSaveRule(rule)} className="p-2 hover:bg-white/10 rounded-lg text-green-500">
+                    {isNew ? <Plus size={16} /> : <Check size={16} />}
+                  </button>
+                  {!isNew && (
+                    <button onClick={() => handleDeleteRule(rule.id!)} className="p-2 hover:bg-white/10 rounded-lg text-red-400">
+                      <Trash2 size={16} />
+                    </button>
+                  )}
+                </div>
+              );
+            })}
+          </div>
+        </div>
+      )}
+
+      {/* Scheduled Reports Tab */}
+      {activeTab === "scheduled" && (
+        <div className="space-y-6">
+          <p className="text-sm text-gray-400">
+            Write a report to a folder on a schedule. Daily reports cover the day, weekly reports the week and monthly
+            reports, written on the last day of the month, the month.
+          </p>
+          <div className="bg-[#252525] rounded-xl overflow-hidden">
+            {[...jobs, newJob].map((job) => {
+              const isNew = !job.id;
+              const update = (changes: Partial<ReportJob>) => {
+                const updated = { ...job, ...changes };
+                if (isNew) {
+                  setNewJob(updated);
+                } else {
+                  setJobs(jobs.map((j) => (j.id === job.id ? updated : j)));
+                }
+              };
+              const selectClass =
+                "bg-[#1a1a1a] border border-white/10 rounded-lg px-2 py-2 text-sm focus:outline-none focus:border-blue-500";
+              const inputClass =
+                "bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500";
+              return (
+                <div key={job.id ?? "new"} className="px-5 py-3 space-y-2 border-b border-white/5 last:border-b-0">
+                  <div className="flex items-center gap-2">
+                    <input
+                      type="text"
+                      value={job.name}
+                      onChange={(e) => update({ name: e.target.value })}
+                      placeholder="Name"
+                      className={`flex-1 min-w-0 ${inputClass}`}
+                    />
+                    <select
+                      value={job.schedule}
+                      onChange={(e) => update({ schedule: e.target.value as ReportJob["schedule"] })}
+                      className={selectClass}
+                    >
+                      <option value="daily">Every day</option>
+                      <option value="weekly">Every week</option>
+                      <option value="monthly">Every month</option>
+                    </select>
+                    {job.schedule === "weekly" && (
+                      <select value={job.weekday} onChange={(e) => update({ weekday: Number(e.target.value) })} className={selectClass}>
+                        {WEEK_START_DAYS.map((day, index) => (
+                          <option key={day} value={index}>{day}</option>
+                        ))}
+                      </select>
+                    )}
+                    <input
+                      type="time"
+                      value={job.time}
+                      onChange={(e) => update({ time: e.target.value })}
+                      className={inputClass}
+                    />
+                    {!isNew && <Toggle checked={job.enabled} onChange={(enabled) => handleSaveJob({ ...job, enabled })} />}
+                  </div>
+                  <div className="flex items-center gap-2">
+                    <select
+                      value={(job.spec.group_by ?? []).join(",")}
+                      onChange={(e) => update({ spec: { ...job.spec, group_by: e.target.value.split(",") as ReportDimension[] } })}
+                      className={selectClass}
+                    >
+                      {REPORT_GROUPINGS.map((grouping) => (
+                        <option key={grouping.label} value={grouping.value.join(",")}>{grouping.label}</option>
+                      ))}
+                    </select>
+                    <select
+                      value={job.format}
+                      onChange={(e) => update({ format: e.target.value as ReportJob["format"] })}
+                      className={selectClass}
+                    >
+                      <option value="markdown">Markdown</option>
+                      <option value="html">HTML</option>
+                      <option value="csv">CSV</option>
+                      <option value="pdf">PDF</option>
+                    </select>
+                    <input
+                      type="text"
+                      value={job.folder}
+                      onChange={(e) => update({ folder: e.target.value })}
+                      placeholder="/path/to/folder"
+                      className={`flex-1 min-w-0 ${inputClass}`}
+                    />
+                    <button onClick={() => handleSaveJob(job)} className="p-2 hover:bg-white/10 rounded-lg text-green-500">
+                      {isNew ? <Plus size={16} /> : <Check size={16} />}
+                    </button>
+                    {!isNew && (
+                      <>
+                        <button onClick={() => handleRunJob(job.id!)} className="p-2 hover:bg-white/10 rounded-lg text-gray-400" title="Write now">
+                          <Play size={16} />
+                        </button>
+                        <button onClick={() => handleDeleteJob(job.id!)} className="p-2 hover:bg-white/10 rounded-lg text-red-400">
+                          <Trash2 size={16} />
+                        </button>
+                      </>
+                    )}
+                  </div>
+                </div>
+              );
+            })}
+          </div>
+          {jobStatus && <div className="text-sm text-gray-400">{jobStatus}</div>}
+        </div>
+      )}
+
+      {/* Backups Tab */}
+      {activeTab === "backups" && settings && (
+        <div className="space-y-6">
+          <div className="bg-[#252525] rounded-xl overflow-hidden">
+            <div className="flex items-center justify-between px-5 py-4 border-b border-white/5">
+              <div>
+                <div className="font-medium">Back up daily</div>
+                <div className="text-sm text-gray-400">Copy the database to a folder once a day</div>
+              </div>
+              <Toggle
+                checked={settings.backup_enabled}
+                onChange={(checked) => handleSettingChange("backup_enabled", checked)}
+              />
+            </div>
+
+            <div className="px-5 py-4 border-b border-white/5 space-y-2">
+              <div className="font-medium">Folder</div>
+              <input
+                type="text"
+                defaultValue={settings.backup_folder}
+                onBlur={(e) => handleBackupFolderChange(e.target.value.trim())}
+                placeholder="/path/to/backups"
+                className="w-full bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500"
+              />
+            </div>
+
+            <div className="px-5 py-4 border-b border-white/5 space-y-2">
+              <div>
+                <div className="font-medium">Passphrase</div>
+                <div className="text-sm text-gray-400">
+                  Encrypts new backups. Without it they can't be restored, so keep it somewhere safe.
+                </div>
+              </div>
+              <input
+                type="password"
+                defaultValue={settings.backup_passphrase}
+                onBlur={(e) => handleSettingChange("backup_passphrase", e.target.value)}
+                placeholder="Leave empty for unencrypted backups"
+                className="w-full bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500"
+              />
+            </div>
+
+            <div className="flex items-center justify-between px-5 py-4">
+              <div>
+                <div className="font-medium">Keep</div>
+                <div className="text-sm text-gray-400">The newest backup of each day, week and month; 0 keeps none, but the latest backup always stays</div>
+              </div>
+              <div className="flex items-center gap-2 text-sm text-gray-400">
+                {([
+                  ["backup_keep_daily", "days"],
+                  ["backup_keep_weekly", "weeks"],
+                  ["backup_keep_monthly", "months"],
+                ] as const).map(([key, label]) => (
+                  <label key={key} className="flex items-center gap-1">
+                    <input
+                      type="number"
+                      min={0}
+                      defaultValue={settings[key]}
+                      onBlur={(e) => handleSettingChange(key, Math.max(0, parseInt(e.target.value, 10) || 0))}
+                      className="w-16 bg-[#1a1a1a] border border-white/10 rounded-lg px-2 py-2 text-sm text-white focus:outline-none focus:border-blue-500"
+                    />
+                    {label}
+                  </label>
+                ))}
+              </div>
+            </div>
+          </div>
+
+          <div className="flex items-center gap-3">
+            <button
+              onClick={handleBackupNow}
+              className="px-4 py-2 rounded-lg bg-blue-600 hover:bg-blue-700 text-white text-sm transition-colors"
+            >
+              Back up now
+            </button>
+            {backupStatus && <div className="text-sm text-gray-400">{backupStatus}</div>}
+          </div>
+
+          {backups.length > 0 && (
+            <div className="bg-[#252525] rounded-xl overflow-hidden">
+              {backups.map((backup) => (
+                <div key={backup.path} className="px-5 py-3 border-b border-white/5 last:border-b-0">
+                  <div className="flex items-center gap-3">
+                    {backup.encrypted && <Lock size={14} className="text-gray-400" />}
+                    <span className="flex-1 text-sm">{backup.created_at}</span>
+                    <span className="text-sm text-gray-400">{(backup.size / 1024 / 1024).toFixed(1)} MB</span>
+                    <button
+                      onClick={() => {
+                        setRestoreTarget(backup);
+                        setRestorePassphrase("");
+                      }}
+                      className="px-3 py-1.5 rounded-lg bg-[#1a1a1a] hover:bg-[#303030] text-gray-300 text-sm transition-colors"
+                    >
+                      Restore
+                    </button>
+                  </div>
+                  {restoreTarget?.path === backup.path && (
+                    <div className="flex items-center gap-2 mt-3">
+                      <span className="flex-1 text-sm text-yellow-400">
+                        Replace all current data with this backup? The current data is backed up first.
+                      </span>
+                      {backup.encrypted && (
+                        <input
+                          type="password"
+                          value={restorePassphrase}
+                          onChange={(e) => setRestorePassphrase(e.target.value)}
+                          placeholder="Passphrase"
+                          className="w-40 bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-1.5 text-sm focus:outline-none focus:border-blue-500"
+                        />
+                      )}
+                      <button onClick={handleRestore} className="p-2 hover:bg-white/10 rounded-lg text-green-500">
+                        <Check size={16} />
+                      </button>
+                      <button onClick={() => setRestoreTarget(null)} className="p-2 hover:bg-white/10 rounded-lg text-gray-400">
+                        <X size={16} />
+                      </button>
+                    </div>
+                  )}
+                </div>
+              ))}
+            </div>
+          )}
+        </div>
+      )}
+
+      {/* Sync Tab */}
+      {activeTab === "sync" && settings && (
+        <div className="space-y-6">
+          <p className="text-sm text-gray-400">
+            Share projects and entries with your other computers through a folder that Syncthing, Dropbox or a network
+            drive keeps in sync, or through a sync server. Each computer sends its own changes; the database itself is
+            never copied. A timer started on one computer stops the one running on another.
+          </p>
+          <div className="bg-[#252525] rounded-xl overflow-hidden">
+            <div className="flex items-center justify-between px-5 py-4 border-b border-white/5">
+              <div>
+                <div className="font-medium">Sync every 30 seconds</div>
+                <div className="text-sm text-gray-400">
+                  This computer: <span className="font-mono">{syncStatus?.device.slice(0, 8) ?? "…"}</span>
+                </div>
+              </div>
+              <Toggle
+                checked={settings.sync_enabled}
+                onChange={(checked) => handleSettingChange("sync_enabled", checked)}
+              />
+            </div>
+            <div className="px-5 py-4 space-y-2">
+              <div className="font-medium">Shared folder</div>
+              <input
+                type="text"
+                defaultValue={settings.sync_folder}
+                onBlur={(e) => handleSettingChange("sync_folder", e.target.value.trim())}
+                placeholder="/path/to/shared/folder"
+                className="w-full bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500"
+              />
+            </div>
+            <div className="px-5 py-4 space-y-2 border-t border-white/5">
+              <div className="font-medium">Sync server</div>
+              <div className="text-sm text-gray-400">
+                Any server speaking the protocol in docs/sync-protocol.md
+                {syncStatus?.server_synced_at ? `; last synced ${syncStatus.server_synced_at} UTC` : ""}
+              </div>
+              <input
+                type="text"
+                defaultValue={settings.sync_server_url}
+                onBlur={(e) => handleSettingChange("sync_server_url", e.target.value.trim())}
+                placeholder="https://sync.example.com"
+                className="w-full bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500"
+              />
+              <input
+                type="password"
+                defaultValue={settings.sync_server_token}
+                onBlur={(e) => handleSettingChange("sync_server_token", e.target.value.trim())}
+                placeholder="Token (optional)"
+                className="w-full bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500"
+              />
+            </div>
+          </div>
+
+          <div className="flex items-center gap-3">
+            <button
+              onClick={handleSyncNow}
+              className="px-4 py-2 rounded-lg bg-blue-600 hover:bg-blue-700 text-white text-sm transition-colors"
+            >
+              Sync now
+            </button>
+            {syncMessage && <div className="text-sm text-gray-400">{syncMessage}</div>}
+          </div>
+
+          {syncStatus && syncStatus.peers.length > 0 && (
+            <div className="bg-[#252525] rounded-xl overflow-hidden">
+              {syncStatus.peers.map((peer) => (
+                <div key={peer.device} className="flex items-center justify-between px-5 py-3 border-b border-white/5 last:border-b-0 text-sm">
+                  <span className="font-mono">{peer.device.slice(0, 8)}</span>
+                  <span className="text-gray-400">
+                    {peer.seq} changes{peer.synced_at ? `, last read ${peer.synced_at} UTC` : ""}
+                  </span>
+                </div>
+              ))}
+            </div>
+          )}
+
+          {syncStatus && syncStatus.conflicts.length > 0 && (
+            <div className="space-y-2">
+              <div className="font-medium">Conflicts</div>
+              <p className="text-sm text-gray-400">
+                These were changed here and on another computer at the same time. The newer change was kept.
+              </p>
+              <div className="bg-[#252525] rounded-xl overflow-hidden">
+                {syncStatus.conflicts.map((conflict) => {
+                  const describe = (row: SyncedRow | null) => {
+                    if (!row) return "Deleted";
+                    if (conflict.table === "projects") return String(row.name);
+                    return `${row.start_time} – ${row.end_time ?? "running"}`;
+                  };
+                  return (
+                    <div key={conflict.id} className="flex items-center gap-3 px-5 py-3 border-b border-white/5 last:border-b-0 text-sm">
+                      <div className="flex-1 min-w-0">
+                        <div>
+                          {conflict.table === "projects" ? "Project" : "Entry"}: {describe(conflict.kept)}
+                        </div>
+                        <div className="text-gray-400">Discarded: {describe(conflict.discarded)}</div>
+                      </div>
+                      <button
+                        onClick={() => handleResolveConflict(conflict.id, true)}
+                        className="p-2 hover:bg-white/10 rounded-lg text-gray-400"
+                        title="Restore the discarded version"
+                      >
+                        <RotateCcw size={16} />
+                      </button>
+                      <button
+                        onClick={() => handleResolveConflict(conflict.id, false)}
+                        className="p-2 hover:bg-white/10 rounded-lg text-green-500"
+                        title="Keep"
+                      >
+                        <Check size={16} />
+                      </button>
+                    </div>
+                  );
+                })}
+              </div>
+            </div>
+          )}
+        </div>
+      )}
+
+      {/* Encryption Tab */}
+      {activeTab === "encryption" && databaseLock && (
+        <div className="space-y-6">
+          <p className="text-sm text-gray-400">
+            Encrypt the database with a passphrase so project names and entries can't be read from a lost or stolen
+            computer. Until the passphrase is entered the timer window asks for it and the tray shows no project
+            names.
+          </p>
+
+          {databaseLock.encrypted ? (
+            <div className="bg-[#252525] rounded-xl overflow-hidden">
+              <div className="flex items-center justify-between px-5 py-4 border-b border-white/5">
+                <div>
+                  <div className="font-medium">The database is encrypted</div>
+                  <div className="text-sm text-gray-400">Backups made from now on are encrypted with it too</div>
+                </div>
+                <button
+                  onClick={handleLock}
+                  className="px-4 py-2 rounded-lg bg-[#1a1a1a] hover:bg-[#303030] text-gray-300 text-sm transition-colors"
+                >
+                  Lock now
+                </button>
+              </div>
+              <div className="flex items-center justify-between px-5 py-4">
+                <div>
+                  <div className="font-medium">Unlock on start</div>
+                  <div className="text-sm text-gray-400">
+                    {databaseLock.remembered
+                      ? "The passphrase is kept in the system keychain"
+                      : "The passphrase is asked for on every start"}
+                  </div>
+                </div>
+                {databaseLock.remembered && (
+                  <button
+                    onClick={handleForgetPassphrase}
+                    className="px-4 py-2 rounded-lg bg-[#1a1a1a] hover:bg-[#303030] text-gray-300 text-sm transition-colors"
+                  >
+                    Forget passphrase
+                  </button>
+                )}
+              </div>
+            </div>
+          ) : (
+            <div className="bg-[#252525] rounded-xl overflow-hidden">
+              <div className="px-5 py-4 border-b border-white/5 space-y-2">
+                <div>
+                  <div className="font-medium">Passphrase</div>
+                  <div className="text-sm text-gray-400">
+                    At least 8 characters. Without it the data can't be recovered, so keep it somewhere safe.
+                  </div>
+                </div>
+                <input
+                  type="password"
+                  value={newPassphrase}
+                  onChange={(e) => setNewPassphrase(e.target.value)}
+                  placeholder="Passphrase"
+                  className="w-full bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500"
+                />
+                <input
+                  type="password"
+                  value={confirmPassphrase}
+                  onChange={(e) => setConfirmPassphrase(e.target.value)}
+                  placeholder="Repeat passphrase"
+                  className="w-full bg-[#1a1a1a] border border-white/10 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500"
+                />
+              </div>
+              <div className="flex items-center justify-between px-5 py-4">
+                <div>
+                  <div className="font-medium">Remember in the system keychain</div>
+                  <div className="text-sm text-gray-400">Unlocks on start without asking</div>
+                </div>
+                <Toggle checked={rememberPassphrase} onChange={setRememberPassphrase} />
+              </div>
+            </div>
+          )}
+
+          <div className="flex items-center gap-3">
+            {!databaseLock.encrypted && (
+              <button
+                onClick={handleEncrypt}
+                disabled={!newPassphrase}
+                className="px-4 py-2 rounded-lg bg-blue-600 hover:bg-blue-700 disabled:opacity-50 text-white text-sm transition-colors"
+              >
+                Encrypt database
+              </button>
+            )}
+            {encryptionMessage && <div className="text-sm text-gray-400">{encryptionMessage}</div>}
           </div>
         </div>
       )}
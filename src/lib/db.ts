@@ -1,4 +1,5 @@
 import Database from "@tauri-apps/plugin-sql";
+import { invoke } from "@tauri-apps/api/core";
 
 let db: Database | null = null;
 
@@ -10,6 +11,18 @@ export async function getDb(): Promise<Database> {
   return db;
 }
 
+// Close the connection, e.g. before the database is encrypted or after it was locked
+export async function closeDb() {
+  const current = db;
+  db = null;
+  try {
+    await current?.close();
+  } catch (e) {
+    // Another window may have closed the shared connection already
+    console.error("Failed to close database:", e);
+  }
+}
+
 async function initSchema() {
   if (!db) return;
 
@@ -41,25 +54,6 @@ async function initSchema() {
     )
   `);
 
-  // Insert default settings if they don't exist
-  const defaultSettings = [
-    ["show_window_on_startup", "true"],
-    ["show_timer_in_tray", "true"],
-    ["stop_timer_when_idle", "false"],
-    ["idle_timeout_minutes", "5"],
-    ["reminder_enabled", "false"],
-    ["reminder_interval_minutes", "30"],
-    ["reminder_start_time", "09:00"],
-    ["reminder_end_time", "18:00"],
-    ["reminder_weekdays", "1,2,3,4,5"], // Mon-Fri (0=Sun, 1=Mon, etc.)
-  ];
-  for (const [key, value] of defaultSettings) {
-    await db.execute(
-      "INSERT OR IGNORE INTO settings (key, value) VALUES (?, ?)",
-      [key, value]
-    );
-  }
-
   // Insert default projects if none exist
   const projects = await db.select<{ count: number }[]>(
     "SELECT COUNT(*) as count FROM projects"
@@ -77,6 +71,7 @@ export interface Project {
   id: number;
   name: string;
   color: string;
+  client: string | null;
   created_at: string;
 }
 
@@ -87,6 +82,8 @@ export interface TimeEntry {
   end_time: string | null;
   duration: number | null;
   created_at: string;
+  timezone: string | null; // IANA zone the entry was recorded in
+  locked_by: number | null; // Submitted timesheet the entry belongs to
 }
 
 export async function getProjects(): Promise<Project[]> {
@@ -95,46 +92,25 @@ export async function getProjects(): Promise<Project[]> {
 }
 
 export async function createProject(name: string, color: string): Promise<number> {
-  const db = await getDb();
-  const result = await db.execute(
-    "INSERT INTO projects (name, color) VALUES (?, ?)",
-    [name, color]
-  );
-  return result.lastInsertId ?? 0;
+  return invoke<number>("create_project", { name, color });
 }
 
-export async function updateProject(id: number, name: string, color: string): Promise<void> {
-  const db = await getDb();
-  await db.execute(
-    "UPDATE projects SET name = ?, color = ? WHERE id = ?",
-    [name, color, id]
-  );
+// `client` is left unchanged when undefined and cleared when empty
+export async function updateProject(id: number, name: string, color: string, client?: string): Promise<void> {
+  await invoke("update_project", { id, name, color, client: client ?? null });
 }
 
+// Also deletes the project's time entries; can be undone
 export async function deleteProject(id: number): Promise<void> {
-  const db = await getDb();
-  await db.execute("DELETE FROM time_entries WHERE project_id = ?", [id]);
-  await db.execute("DELETE FROM projects WHERE id = ?", [id]);
+  await invoke("delete_project", { id });
 }
 
-export async function startTimeEntry(projectId: number): Promise<number> {
-  const db = await getDb();
-  const result = await db.execute(
-    "INSERT INTO time_entries (project_id, start_time) VALUES (?, datetime('now'))",
-    [projectId]
-  );
-  return result.lastInsertId ?? 0;
+export async function startTimeEntry(projectId: number, source: ChangeSource = "ui"): Promise<number> {
+  return invoke<number>("start_time_entry", { projectId, source });
 }
 
-export async function stopTimeEntry(entryId: number): Promise<void> {
-  const db = await getDb();
-  await db.execute(
-    `UPDATE time_entries
-     SET end_time = datetime('now'),
-         duration = CAST((julianday(datetime('now')) - julianday(start_time)) * 86400 AS INTEGER)
-     WHERE id = ?`,
-    [entryId]
-  );
+export async function stopTimeEntry(entryId: number, source: ChangeSource = "ui"): Promise<void> {
+  await invoke("stop_time_entry", { id: entryId, source });
 }
 
 export async function getRunningEntry(): Promise<(TimeEntry & { project_name: string; project_color: string }) | null> {
@@ -163,23 +139,26 @@ export async function getTimeEntries(limit = 50): Promise<(TimeEntry & { project
 }
 
 export async function getTodayTotal(): Promise<number> {
-  const db = await getDb();
-  const result = await db.select<{ total: number | null }[]>(
-    `SELECT SUM(duration) as total
-     FROM time_entries
-     WHERE date(start_time, 'localtime') = date('now', 'localtime') AND end_time IS NOT NULL`
-  );
-  return result[0]?.total || 0;
+  return invoke<number>("get_period_total", { kind: "day" });
 }
 
+// Uses the configured first day of the week
 export async function getWeekTotal(): Promise<number> {
-  const db = await getDb();
-  const result = await db.select<{ total: number | null }[]>(
-    `SELECT SUM(duration) as total
-     FROM time_entries
-     WHERE date(start_time, 'localtime') >= date('now', 'localtime', '-7 days') AND end_time IS NOT NULL`
-  );
-  return result[0]?.total || 0;
+  return invoke<number>("get_period_total", { kind: "week" });
+}
+
+// Entries that count on a date in the report time zone, as in reports
+export interface DayEntry {
+  id: number;
+  project_id: number;
+  project_name: string;
+  project_color: string;
+  start_time: string;
+  end_time: string | null;
+}
+
+export async function getDayEntries(date: string): Promise<DayEntry[]> {
+  return invoke<DayEntry[]>("get_day_entries", { date });
 }
 
 export async function getLastUsedProjectId(): Promise<number | null> {
@@ -191,30 +170,269 @@ export async function getLastUsedProjectId(): Promise<number | null> {
 }
 
 export async function deleteTimeEntry(id: number): Promise<void> {
-  const db = await getDb();
-  await db.execute("DELETE FROM time_entries WHERE id = ?", [id]);
+  await invoke("delete_time_entry", { id });
 }
 
+// Validated in the backend: rejects entries that end before they start or
+// overlap another entry
 export async function updateTimeEntry(
   id: number,
   projectId: number,
   startTime: string,
   endTime: string
 ): Promise<void> {
-  const db = await getDb();
-  await db.execute(
-    `UPDATE time_entries
-     SET project_id = ?,
-         start_time = ?,
-         end_time = ?,
-         duration = CAST((julianday(?) - julianday(?)) * 86400 AS INTEGER)
-     WHERE id = ?`,
-    [projectId, startTime, endTime, endTime, startTime, id]
-  );
+  await invoke("update_time_entry", { id, projectId, startTime, endTime });
 }
 
+export interface EntrySummary {
+  id: number;
+  project_id: number;
+  project_name: string;
+  project_color: string;
+  start: number; // Unix seconds
+  end: number | null;
+}
+
+export interface EntryConflict {
+  kind: "overlap" | "negative_duration";
+  first: EntrySummary;
+  second: EntrySummary | null;
+  overlap_secs: number;
+}
+
+export interface JournalState {
+  undo: string | null; // Label of the operation undo would revert
+  redo: string | null;
+}
+
+export interface TimeZones {
+  system: string;
+  zones: string[];
+}
+
+export type PeriodKind =
+  | "day"
+  | "week"
+  | "iso_week"
+  | "month"
+  | "quarter"
+  | "year"
+  | "fiscal_period"
+  | "fiscal_quarter"
+  | "fiscal_year"
+  | "pay_period";
+
+export interface Period {
+  kind: PeriodKind;
+  start: string; // YYYY-MM-DD
+  end: string; // Inclusive
+  label: string;
+}
+
+export type ReportDimension =
+  | "project"
+  | "client"
+  | "tag"
+  | "day"
+  | "week"
+  | "month"
+  | "fiscal_period"
+  | "pay_period"
+  | "weekday"
+  | "hour";
+
+// What a report shows, independent of its dates
+export interface ReportSpec {
+  group_by?: ReportDimension[];
+  filters?: {
+    project_ids?: number[];
+    clients?: string[];
+    tags?: string[];
+    weekdays?: number[]; // 0 = Sunday
+  };
+  rounding?: {
+    minutes: number;
+    mode?: "nearest" | "up" | "down";
+    scope?: "entry" | "row";
+  } | null;
+}
+
+export interface ReportQuery extends ReportSpec {
+  start_date: string; // YYYY-MM-DD, inclusive
+  end_date: string;
+}
+
+// Only the fields of the grouped dimensions are set
+export interface ReportRow {
+  project_id: number | null;
+  project_name: string | null;
+  project_color: string | null;
+  client: string | null;
+  tag: string | null;
+  day: string | null; // YYYY-MM-DD in the report time zone
+  week: string | null; // First day of the week
+  month: string | null; // YYYY-MM
+  fiscal_period: string | null;
+  pay_period: string | null;
+  weekday: number | null;
+  hour: number | null;
+  labels: string[];
+  duration: number;
+  rounded_duration: number;
+  entries: number;
+}
+
+// Totals count every entry once, even when it sits in several tag rows
+export interface Report {
+  rows: ReportRow[];
+  duration: number;
+  rounded_duration: number;
+  entries: number;
+}
+
+export async function runReport(query: ReportQuery): Promise<Report> {
+  return invoke<Report>("report", { query });
+}
+
+export interface ProjectDistribution {
+  project_id: number;
+  project_name: string;
+  project_color: string;
+  hours: number[]; // Minutes per local hour
+  weekdays: number[]; // Minutes per weekday, 0 = Sunday
+  total: number;
+}
+
+// Tracked minutes by weekday and hour of day
+export interface Heatmap {
+  week_start: number;
+  matrix: number[][]; // [weekday][hour], weekday 0 = Sunday
+  hours: number[];
+  weekdays: number[];
+  max: number;
+  total: number;
+  projects: ProjectDistribution[];
+}
+
+export interface TimesheetRow {
+  project_id: number;
+  project_name: string;
+  days: number[]; // Seconds per day of the week
+  total: number;
+}
+
+// A submitted week; its entries stay locked until it is unlocked
+export interface Timesheet {
+  id: number;
+  start_date: string;
+  end_date: string;
+  submitted_at: string;
+  rows: TimesheetRow[];
+  total: number;
+  entries: number;
+  file_path: string | null;
+  unlocked_at: string | null;
+  unlock_reason: string | null;
+}
+
+// A report written to a folder on a schedule
+export interface ReportJob {
+  id?: number | null;
+  name: string;
+  spec: ReportSpec;
+  format: "markdown" | "html" | "csv" | "pdf";
+  schedule: "daily" | "weekly" | "monthly"; // Monthly runs on the last day of the month
+  weekday: number; // 0 = Sunday, weekly jobs only
+  time: string; // HH:MM
+  folder: string;
+  enabled: boolean;
+  last_run_at?: string | null;
+}
+
+// A copy of the database in the backup folder
+export interface BackupFile {
+  path: string;
+  file_name: string;
+  created_at: string; // Local time
+  size: number; // Bytes
+  encrypted: boolean;
+}
+
+// A project or entry as synced between devices; entries name their project by uid
+export type SyncedRow = Record<string, string | number | null>;
+
+export interface SyncConflict {
+  id: number;
+  table: "projects" | "time_entries";
+  device: string; // The other device involved
+  kept: SyncedRow | null; // null when the row was deleted
+  discarded: SyncedRow | null;
+  detected_at: string;
+}
+
+export interface SyncStatus {
+  device: string;
+  server_synced_at: string | null;
+  peers: { device: string; seq: number; synced_at: string | null }[];
+  conflicts: SyncConflict[];
+}
+
+export interface DatabaseLock {
+  encrypted: boolean;
+  locked: boolean; // Encrypted and no passphrase entered yet
+  remembered: boolean; // Passphrase kept in the system keyring
+}
+
+export interface SyncSummary {
+  sent: number;
+  received: number;
+  conflicts: number;
+}
+
+// Where a change to a time entry was made, for the audit trail
+export type ChangeSource = "ui" | "tray" | "cli" | "import" | "automatic";
+
+export interface EntryVersion {
+  project_id: number | null;
+  start_time: string | null;
+  end_time: string | null;
+  duration: number | null;
+}
+
+export interface EntryHistoryRecord {
+  id: number;
+  entry_id: number;
+  changed_at: string;
+  source: ChangeSource;
+  action: "insert" | "update" | "delete";
+  label: string;
+  old: EntryVersion | null;
+  new: EntryVersion | null;
+}
+
+export interface EditedEntry {
+  entry_id: number;
+  project_name: string;
+  project_color: string;
+  start_time: string;
+  original_duration: number;
+  current_duration: number;
+  edits: number;
+  last_edited_at: string;
+}
+
+export type ConflictResolution = "trim_first" | "trim_second" | "split" | "merge" | "swap";
+
 // Settings functions
+export interface ReminderSchedule {
+  weekdays: number[]; // 0=Sun, 1=Mon, ..., 6=Sat
+  start_time: string; // HH:MM
+  end_time: string; // HH:MM, at or before start_time means it ends the next day
+  timezone?: string | null; // IANA name, system time zone when unset
+}
+
 export interface AppSettings {
+  version: number;
   show_window_on_startup: boolean;
   show_timer_in_tray: boolean;
   stop_timer_when_idle: boolean;
@@ -224,34 +442,129 @@ export interface AppSettings {
   reminder_start_time: string;
   reminder_end_time: string;
   reminder_weekdays: number[]; // 0=Sun, 1=Mon, ..., 6=Sat
+  reminder_schedules: ReminderSchedule[];
+  reminder_only_when_active: boolean;
+  reminder_active_threshold_minutes: number;
+  reminder_break_enabled: boolean;
+  reminder_break_mode: "tracked" | "activity";
+  reminder_break_after_minutes: number;
+  reminder_break_snooze_minutes: number;
+  reminder_break_min_minutes: number;
+  activity_tracking_enabled: boolean;
+  activity_sample_seconds: number;
+  activity_retention_days: number;
+  activity_exclusions: string;
+  git_watch_enabled: boolean;
+  workday_source: "reminders" | "custom";
+  workday_start_time: string;
+  workday_end_time: string;
+  workday_weekdays: number[];
+  report_timezone: string;
+  week_start: number;
+  fiscal_pattern: "4-4-5" | "4-5-4" | "5-4-4";
+  fiscal_year_start: string;
+  pay_period: "weekly" | "biweekly" | "semimonthly" | "monthly";
+  pay_period_anchor: string;
+  timesheet_folder: string;
+  timesheet_format: "markdown" | "html" | "csv" | "pdf";
+  backup_enabled: boolean;
+  backup_folder: string;
+  backup_passphrase: string; // Empty for unencrypted backups
+  backup_keep_daily: number;
+  backup_keep_weekly: number;
+  backup_keep_monthly: number;
+  sync_enabled: boolean;
+  sync_folder: string;
+  sync_server_url: string;
+  sync_server_token: string;
 }
 
+// Defaults and validation live in settings.rs
 export async function getSettings(): Promise<AppSettings> {
-  const db = await getDb();
-  const rows = await db.select<{ key: string; value: string }[]>(
-    "SELECT key, value FROM settings"
-  );
-  const settings: Record<string, string> = {};
-  for (const row of rows) {
-    settings[row.key] = row.value;
-  }
-  return {
-    show_window_on_startup: settings.show_window_on_startup === "true",
-    show_timer_in_tray: settings.show_timer_in_tray === "true",
-    stop_timer_when_idle: settings.stop_timer_when_idle === "true",
-    idle_timeout_minutes: parseInt(settings.idle_timeout_minutes || "5", 10),
-    reminder_enabled: settings.reminder_enabled === "true",
-    reminder_interval_minutes: parseInt(settings.reminder_interval_minutes || "30", 10),
-    reminder_start_time: settings.reminder_start_time || "09:00",
-    reminder_end_time: settings.reminder_end_time || "18:00",
-    reminder_weekdays: (settings.reminder_weekdays || "1,2,3,4,5").split(",").map(Number),
-  };
+  return invoke<AppSettings>("get_settings");
 }
 
-export async function updateSetting(key: string, value: string): Promise<void> {
-  const db = await getDb();
-  await db.execute(
-    "INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)",
-    [key, value]
-  );
+export interface ActivitySegment {
+  id: number;
+  start_time: string; // UTC, SQLite format
+  end_time: string;
+  duration: number;
+  app: string;
+  title: string;
+}
+
+export interface ActivityRule {
+  id?: number | null;
+  field: "app" | "title";
+  operator: "contains" | "equals" | "starts_with";
+  pattern: string;
+  project_id: number | null;
+  tag: string | null;
+  mode: "suggest" | "auto";
+  enabled: boolean;
+  priority: number;
+}
+
+export interface ProjectRepo {
+  id: number;
+  project_id: number;
+  path: string;
+}
+
+export interface Commit {
+  hash: string;
+  message: string;
+  committed_at: number;
+  repo_path: string;
+}
+
+export interface GapNeighbor {
+  entry_id: number;
+  project_id: number;
+  project_name: string;
+  project_color: string;
+}
+
+export type FillAction =
+  | { action: "extend"; entry_id: number }
+  | { action: "assign"; project_id: number }
+  | { action: "break" }
+  | { action: "skip" };
+
+export interface Gap {
+  start: number; // Unix seconds
+  end: number;
+  duration: number;
+  previous: GapNeighbor | null;
+  next: GapNeighbor | null;
+  suggestion: FillAction;
+}
+
+export interface DayOff {
+  date: string; // YYYY-MM-DD
+  label: string;
+  source: "manual" | "ics" | "mute";
+}
+
+export interface BreakStats {
+  breaks_taken: number;
+  total_break_seconds: number;
+  longest_break_seconds: number;
+  reminders_sent: number;
+  reminders_snoozed: number;
+}
+
+// Rejects invalid values; resolves to the settings after the change
+export async function updateSetting<K extends keyof AppSettings>(key: K, value: AppSettings[K]): Promise<AppSettings> {
+  return invoke<AppSettings>("update_setting", { key, value });
+}
+
+// Writes settings.toml to the config folder and resolves to its path
+export async function exportSettings(): Promise<string> {
+  return invoke<string>("export_settings");
+}
+
+// Applies settings.toml now instead of on the next launch
+export async function importSettings(): Promise<AppSettings> {
+  return invoke<AppSettings>("import_settings");
 }
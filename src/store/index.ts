@@ -13,6 +13,7 @@ import {
   getLastUsedProjectId,
   getSettings,
   AppSettings,
+  ChangeSource,
 } from "../lib/db";
 
 interface TimerState {
@@ -30,12 +31,12 @@ interface TimerState {
   loadSettings: () => Promise<AppSettings | null>;
   selectProject: (project: Project) => void;
   startTimer: () => Promise<void>;
-  stopTimer: () => Promise<void>;
-  startTimerForProject: (projectId: number) => Promise<void>;
+  stopTimer: (source?: ChangeSource) => Promise<void>;
+  startTimerForProject: (projectId: number, source?: ChangeSource) => Promise<void>;
   tick: () => void;
   loadCurrentEntry: () => Promise<void>;
   addProject: (name: string, color: string) => Promise<void>;
-  editProject: (id: number, name: string, color: string) => Promise<void>;
+  editProject: (id: number, name: string, color: string, client?: string) => Promise<void>;
   removeProject: (id: number) => Promise<void>;
 }
 
@@ -97,23 +98,8 @@ export const useTimerStore = create<TimerState>((set, get) => ({
   loadSettings: async () => {
     try {
       const settings = await getSettings();
+      // Reminders are started by the backend on launch and on every change
       set({ settings });
-
-      // Initialize reminder system with settings
-      try {
-        await invoke("start_reminder", {
-          config: {
-            enabled: settings.reminder_enabled,
-            interval_minutes: settings.reminder_interval_minutes,
-            start_time: settings.reminder_start_time,
-            end_time: settings.reminder_end_time,
-            weekdays: settings.reminder_weekdays,
-          },
-        });
-      } catch (e) {
-        console.error("Failed to start reminder:", e);
-      }
-
       return settings;
     } catch (error) {
       console.error("Failed to load settings:", error);
@@ -158,11 +144,11 @@ export const useTimerStore = create<TimerState>((set, get) => ({
     }
   },
 
-  stopTimer: async () => {
+  stopTimer: async (source?: ChangeSource) => {
     const { currentEntry, projects } = get();
     if (!currentEntry) return;
 
-    await stopTimeEntry(currentEntry.id);
+    await stopTimeEntry(currentEntry.id, source);
     set({
       currentEntry: null,
       isRunning: false,
@@ -182,12 +168,12 @@ export const useTimerStore = create<TimerState>((set, get) => ({
     }
   },
 
-  startTimerForProject: async (projectId: number) => {
+  startTimerForProject: async (projectId: number, source?: ChangeSource) => {
     const { currentEntry, projects, settings } = get();
 
     // Stop any running timer first
     if (currentEntry) {
-      await stopTimeEntry(currentEntry.id);
+      await stopTimeEntry(currentEntry.id, source);
     }
 
     // Find the project
@@ -195,7 +181,7 @@ export const useTimerStore = create<TimerState>((set, get) => ({
     if (!project) return;
 
     // Start new timer
-    await startTimeEntry(projectId);
+    await startTimeEntry(projectId, source);
     const entry = await getRunningEntry();
     set({
       currentEntry: entry,
@@ -264,6 +250,15 @@ export const useTimerStore = create<TimerState>((set, get) => ({
         } catch (e) {
           console.error("Failed to set tray icon color:", e);
         }
+      } else if (get().isRunning) {
+        // Stopped elsewhere, e.g. by a timer started on another device
+        set({ currentEntry: null, isRunning: false, elapsedSeconds: 0 });
+        try {
+          await invoke("stop_tray_timer");
+          await invoke("reset_tray_icon");
+        } catch (e) {
+          console.error("Failed to clear tray:", e);
+        }
       }
     } catch (error) {
       console.error("Failed to load current entry:", error);
@@ -277,8 +272,8 @@ export const useTimerStore = create<TimerState>((set, get) => ({
     await get().loadProjects();
   },
 
-  editProject: async (id, name, color) => {
-    await updateProject(id, name, color);
+  editProject: async (id, name, color, client) => {
+    await updateProject(id, name, color, client);
     await get().loadProjects();
   },
 